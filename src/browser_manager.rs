@@ -6,8 +6,13 @@ use std::sync::Arc;
 use tokio::sync::{Mutex, MutexGuard};
 use tracing::{debug, error, info, warn};
 
-use crate::types::{ElementInfo, InspectionDepth, ViewportSize};
-use crate::webdriver::{Browser, BrowserType};
+use crate::keys::KeyChord;
+use crate::types::{
+    ClickOptions, DragMode, DragTarget, ElementInfo, FormAction, InspectionDepth, LayoutInfo,
+    PseudoState, ScrollPosition, ScrollUntil, TextSearchResult, ViewportSize,
+};
+use crate::validation::ValidationConfig;
+use crate::webdriver::{Browser, BrowserType, STABLE_TIMEOUT_MS};
 
 /// State of a browser tab
 #[derive(Debug, Clone)]
@@ -28,20 +33,129 @@ pub struct TabContext<'a> {
     tab_name: String,
     /// Lock guard that ensures exclusive access to this tab
     _guard: MutexGuard<'a, ()>,
+    /// Layout shifts recorded per tab, across navigations
+    shift_log: &'a DashMap<String, Vec<serde_json::Value>>,
+    /// Whether CSS transitions and animations are kept off in this tab
+    animations_disabled: bool,
 }
 
+/// Most layout shift entries kept per tab (oldest are dropped first)
+const MAX_SHIFT_LOG: usize = 1000;
+
 impl<'a> TabContext<'a> {
     /// Click an element (safe operation that can't switch tabs)
-    pub async fn click_element(&self, selector: &str, index: Option<usize>) -> Result<()> {
-        self.browser.click_element("", selector, index).await
+    pub async fn click_element(
+        &self,
+        selector: &str,
+        index: Option<usize>,
+        options: &ClickOptions,
+    ) -> Result<()> {
+        let phase = format!("click {}", selector);
+        self.begin_shift_phase(&phase).await;
+        let result = if options.is_plain() {
+            self.browser.click_element("", selector, index).await
+        } else {
+            self.browser.pointer_click(selector, index, options).await
+        };
+        self.mark_shift_phase(&phase).await;
+        result
+    }
+
+    /// Move the mouse over an element
+    pub async fn hover(
+        &self,
+        selector: &str,
+        index: Option<usize>,
+        offset: Option<(i64, i64)>,
+    ) -> Result<()> {
+        let phase = format!("hover {}", selector);
+        self.begin_shift_phase(&phase).await;
+        let result = self.browser.hover(selector, index, offset).await;
+        self.mark_shift_phase(&phase).await;
+        result
+    }
+
+    /// Drag an element onto another element or a viewport point
+    pub async fn drag(
+        &self,
+        source: &str,
+        target: &DragTarget,
+        steps: u32,
+        mode: DragMode,
+    ) -> Result<serde_json::Value> {
+        let phase = format!("drag {}", source);
+        self.begin_shift_phase(&phase).await;
+        let result = self.browser.drag(source, target, steps, mode).await;
+        self.mark_shift_phase(&phase).await;
+        result
     }
 
     /// Type text into an element
-    pub async fn type_text(&self, selector: &str, text: &str, clear: bool) -> Result<()> {
-        self.browser.type_text("", selector, text, clear).await
+    pub async fn type_text(
+        &self,
+        selector: &str,
+        text: &str,
+        clear: bool,
+        key_delay: u64,
+        submit: bool,
+    ) -> Result<()> {
+        let phase = format!("type {}", selector);
+        self.begin_shift_phase(&phase).await;
+        let result = self
+            .browser
+            .type_text_with_delay("", selector, text, clear, key_delay, submit)
+            .await;
+        self.mark_shift_phase(&phase).await;
+        result
     }
 
-    /// Inspect an element
+    /// Press keys in the focused element (or `selector`, focused first)
+    pub async fn press(&self, selector: Option<&str>, keys: &[KeyChord], delay: u64) -> Result<()> {
+        let phase = selector.map_or("press".to_string(), |s| format!("press {}", s));
+        self.begin_shift_phase(&phase).await;
+        let result = self.browser.press(selector, keys, delay).await;
+        self.mark_shift_phase(&phase).await;
+        result
+    }
+
+    /// Select, check, set or upload into a form control
+    pub async fn set_control(
+        &self,
+        selector: &str,
+        action: &FormAction,
+    ) -> Result<serde_json::Value> {
+        let phase = format!("{} {}", action.name(), selector);
+        self.begin_shift_phase(&phase).await;
+        let result = match action {
+            FormAction::Select { values, by, index } => {
+                self.browser
+                    .select_options(selector, *index, values, *by)
+                    .await
+            }
+            FormAction::Check { checked } => self.browser.set_checked(selector, *checked).await,
+            FormAction::SetRange { value } => self.browser.set_range(selector, *value).await,
+            FormAction::Upload { files } => self.browser.upload_files(selector, files).await,
+        };
+        self.mark_shift_phase(&phase).await;
+        result
+    }
+
+    /// Fill a form from a key/value map, optionally submitting it
+    pub async fn fill_form(
+        &self,
+        form: Option<&str>,
+        data: &serde_json::Map<String, serde_json::Value>,
+        submit: bool,
+        files_base: Option<&std::path::Path>,
+    ) -> Result<serde_json::Value> {
+        let phase = format!("fill-form {}", form.unwrap_or("form"));
+        self.begin_shift_phase(&phase).await;
+        let result = self.browser.fill_form(form, data, submit, files_base).await;
+        self.mark_shift_phase(&phase).await;
+        result
+    }
+
+    /// Inspect an element, optionally with pseudo-classes forced on it
     pub async fn inspect_element(
         &self,
         selector: &str,
@@ -49,15 +163,87 @@ impl<'a> TabContext<'a> {
         all: bool,
         index: Option<usize>,
         expect_one: bool,
+        states: &[PseudoState],
     ) -> Result<Vec<ElementInfo>> {
-        self.browser
+        let forced = self
+            .browser
+            .force_states(selector, all, index, states)
+            .await?;
+        let result = self
+            .browser
             .inspect_element("", selector, depth, all, index, expect_one)
-            .await
+            .await;
+        self.browser.clear_states(forced).await;
+
+        let mut elements = result?;
+        for element in &mut elements {
+            element.forced_states = states.to_vec();
+        }
+        Ok(elements)
     }
 
     /// Navigate to a URL
     pub async fn goto(&self, url: &str) -> Result<()> {
-        self.browser.goto(url).await
+        self.collect_layout_shifts().await;
+        self.browser.goto(url).await?;
+        if self.animations_disabled
+            && let Err(e) = self.browser.set_animations(true).await
+        {
+            warn!("Failed to disable animations after navigation: {}", e);
+        }
+        self.mark_shift_phase(&format!("load {}", url)).await;
+        Ok(())
+    }
+
+    /// Layout shifts recorded in this tab so far, oldest first
+    pub async fn layout_shifts(&self, clear: bool) -> Vec<serde_json::Value> {
+        // Make sure the current document is observed, then pick up what it has
+        self.mark_shift_phase("idle").await;
+        self.collect_layout_shifts().await;
+
+        if clear {
+            self.shift_log
+                .remove(&self.tab_name)
+                .map(|(_, log)| log)
+                .unwrap_or_default()
+        } else {
+            self.shift_log
+                .get(&self.tab_name)
+                .map(|log| log.clone())
+                .unwrap_or_default()
+        }
+    }
+
+    /// Move shifts recorded so far into the log and attribute new ones to `phase`
+    async fn begin_shift_phase(&self, phase: &str) {
+        self.collect_layout_shifts().await;
+        self.mark_shift_phase(phase).await;
+    }
+
+    /// Shift recording is best-effort and never fails the command itself
+    async fn mark_shift_phase(&self, phase: &str) {
+        if let Err(e) = self.browser.mark_layout_shifts(phase).await {
+            debug!(
+                "Layout shift recording unavailable in tab '{}': {}",
+                self.tab_name, e
+            );
+        }
+    }
+
+    async fn collect_layout_shifts(&self) {
+        match self.browser.drain_layout_shifts().await {
+            Ok(entries) if !entries.is_empty() => {
+                let mut log = self.shift_log.entry(self.tab_name.clone()).or_default();
+                log.extend(entries);
+                let excess = log.len().saturating_sub(MAX_SHIFT_LOG);
+                log.drain(..excess);
+            }
+            Ok(_) => {}
+            Err(e) => debug!(
+                "Failed to collect layout shifts in tab '{}': {}",
+                self.tab_name, e
+            ),
+        }
     }
 
     /// Execute JavaScript
@@ -65,11 +251,69 @@ impl<'a> TabContext<'a> {
         self.browser.execute_javascript(Some(""), code).await
     }
 
+    /// Execute a raw WebDriver script body (use `return` and `arguments`)
+    pub async fn execute(
+        &self,
+        script: &str,
+        args: Vec<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.browser.execute(script, args).await
+    }
+
     /// Execute JavaScript (alias for compatibility)
     pub async fn execute_script(&self, code: &str) -> Result<serde_json::Value> {
         self.browser.execute_javascript(Some(""), code).await
     }
 
+    /// Build the accessibility tree under an element (or the body)
+    pub async fn accessibility_tree(
+        &self,
+        selector: Option<&str>,
+        max_nodes: usize,
+        interactive_only: bool,
+    ) -> Result<serde_json::Value> {
+        self.browser
+            .accessibility_tree("", selector, max_nodes, interactive_only)
+            .await
+    }
+
+    /// Load a URL (or measure the current page) and collect Web Vitals
+    pub async fn performance_metrics(
+        &self,
+        url: &str,
+        interact: Option<&str>,
+        settle_ms: u64,
+    ) -> Result<serde_json::Value> {
+        self.browser
+            .performance_metrics(url, interact, settle_ms)
+            .await
+    }
+
+    /// Tab through the page and audit focus order, indicators and traps
+    pub async fn keyboard_audit(&self, max_tabs: usize) -> Result<serde_json::Value> {
+        self.browser.keyboard_audit("", max_tabs).await
+    }
+
+    /// Explain which rules set a property on an element (cascade, inheritance, variables)
+    pub async fn explain_cascade(
+        &self,
+        selector: &str,
+        index: Option<usize>,
+        property: &str,
+    ) -> Result<serde_json::Value> {
+        let mut styles = self
+            .browser
+            .matched_styles("", selector, index, property)
+            .await?;
+        self.browser.map_to_sources(&mut styles).await;
+        Ok(crate::cascade::explain(&styles, property))
+    }
+
+    /// Audit image sizes, srcset, lazy loading, distortion and broken images
+    pub async fn image_audit(&self, tolerance: f64) -> Result<serde_json::Value> {
+        self.browser.image_audit("", tolerance).await
+    }
+
     /// Detect smart elements on the page
     pub async fn detect_smart_elements(&self, context: Option<&str>) -> Result<serde_json::Value> {
         self.browser.detect_smart_elements("", context).await
@@ -95,6 +339,121 @@ impl<'a> TabContext<'a> {
         self.browser.scroll("", selector, by_x, by_y, to).await
     }
 
+    /// Keep scrolling to the bottom until `until` is met, reporting each step
+    pub async fn scroll_until(
+        &self,
+        selector: Option<&str>,
+        until: &ScrollUntil,
+    ) -> Result<serde_json::Value> {
+        self.browser.scroll_until(selector, until).await
+    }
+
+    /// Scroll through `positions` and record an element's viewport rect at each
+    pub async fn scroll_probe(
+        &self,
+        selector: &str,
+        positions: &[ScrollPosition],
+        wait_stable_frames: Option<u32>,
+    ) -> Result<serde_json::Value> {
+        self.browser
+            .scroll_probe(selector, positions, wait_stable_frames)
+            .await
+    }
+
+    /// Wait for the elements matching `selector` to hold still for `frames` frames
+    pub async fn wait_for_stable(&self, selector: &str, frames: u32) -> Result<()> {
+        self.browser
+            .wait_for_stable(selector, frames, STABLE_TIMEOUT_MS)
+            .await?;
+        Ok(())
+    }
+
+    /// Analyze the context around an element, optionally with pseudo-classes
+    /// forced on it
+    pub async fn analyze_context(
+        &self,
+        selector: &str,
+        focus: &str,
+        proximity: u32,
+        index: Option<usize>,
+        states: &[PseudoState],
+    ) -> Result<serde_json::Value> {
+        let forced = self
+            .browser
+            .force_states(selector, false, index, states)
+            .await?;
+        let result = self
+            .browser
+            .analyze_context("", selector, focus, proximity, index)
+            .await;
+        self.browser.clear_states(forced).await;
+
+        let mut analysis = result?;
+        if !states.is_empty() {
+            analysis["forced_states"] = serde_json::to_value(states)?;
+        }
+        Ok(analysis)
+    }
+
+    /// Analyze the box model layout of an element tree
+    pub async fn analyze_layout(
+        &self,
+        selector: &str,
+        depth: u8,
+        max_elements: usize,
+        wait_stable: u64,
+        detect_shadow: bool,
+    ) -> Result<LayoutInfo> {
+        self.browser
+            .analyze_layout(
+                "",
+                selector,
+                depth,
+                max_elements,
+                wait_stable,
+                detect_shadow,
+            )
+            .await
+    }
+
+    /// Find elements by their text content
+    pub async fn find_by_text(
+        &self,
+        text: &str,
+        element_type: Option<&str>,
+        fuzzy: bool,
+        case_sensitive: bool,
+    ) -> Result<Vec<TextSearchResult>> {
+        self.browser
+            .find_by_text("", text, element_type, fuzzy, case_sensitive)
+            .await
+    }
+
+    /// Wait for an element to meet a condition
+    pub async fn wait_for_element(
+        &self,
+        selector: &str,
+        timeout_secs: u64,
+        condition: &str,
+    ) -> Result<bool> {
+        self.browser
+            .wait_for_element("", selector, timeout_secs, condition)
+            .await
+    }
+
+    /// Take a screenshot of the page, or of a single element
+    pub async fn screenshot(&self, selector: Option<&str>, path: Option<&str>) -> Result<Vec<u8>> {
+        match selector {
+            Some(sel) => self.browser.screenshot_element(sel, path).await,
+            None => self.browser.screenshot(path).await,
+        }
+    }
+
+    /// Get the current URL of the tab
+    pub async fn current_url(&self) -> Result<String> {
+        self.browser.get_current_url().await
+    }
+
     /// Get the tab name
     pub fn tab_name(&self) -> &str {
         &self.tab_name
@@ -132,13 +491,17 @@ impl<'a> TabContext<'a> {
             viewport.width, viewport.height
         );
 
-        self.browser.execute_javascript(Some(""), &script).await?;
+        self.browser.execute(&script, vec![]).await?;
         Ok(())
     }
 
     /// Validate the page for accessibility, SEO, and performance issues
-    pub async fn validate_page(&self, check_type: &str) -> Result<serde_json::Value> {
-        self.browser.validate_page(check_type).await
+    pub async fn validate_page(
+        &self,
+        check_type: &str,
+        config: &ValidationConfig,
+    ) -> Result<serde_json::Value> {
+        self.browser.validate_page(check_type, config).await
     }
 
     /// Compare two pages
@@ -148,8 +511,11 @@ impl<'a> TabContext<'a> {
         url2: &str,
         mode: &str,
         selector: Option<&str>,
+        wait_stable_frames: Option<u32>,
     ) -> Result<serde_json::Value> {
-        self.browser.compare_pages(url1, url2, mode, selector).await
+        self.browser
+            .compare_pages(url1, url2, mode, selector, wait_stable_frames)
+            .await
     }
 }
 
@@ -174,6 +540,14 @@ pub struct BrowserManager {
     window_creation_lock: Arc<Mutex<()>>,
     /// Per-tab viewport settings (using JavaScript emulation)
     tab_viewports: Arc<DashMap<String, ViewportSize>>,
+    /// Per-tab layout shift log (see `TabContext::layout_shifts`)
+    tab_shifts: Arc<DashMap<String, Vec<serde_json::Value>>>,
+    /// Whether tabs start with CSS transitions and animations off
+    animations_disabled: bool,
+    /// Per-tab overrides of `animations_disabled`
+    tab_animations: Arc<DashMap<String, bool>>,
+    /// Chrome scripts that keep animations off in newly loaded documents, per tab
+    animation_scripts: Arc<DashMap<String, String>>,
 }
 
 impl BrowserManager {
@@ -212,6 +586,10 @@ impl BrowserManager {
             tab_states,
             window_creation_lock: Arc::new(Mutex::new(())),
             tab_viewports: Arc::new(DashMap::new()),
+            tab_shifts: Arc::new(DashMap::new()),
+            animations_disabled: false,
+            tab_animations: Arc::new(DashMap::new()),
+            animation_scripts: Arc::new(DashMap::new()),
         })
     }
 
@@ -329,11 +707,13 @@ impl BrowserManager {
                         "#,
                         viewport.width, viewport.height
                     );
-                    if let Err(e) = self.browser.execute_javascript(Some(""), &script).await {
+                    if let Err(e) = self.browser.execute(&script, vec![]).await {
                         warn!("Failed to restore viewport for tab '{}': {}", name, e);
                     }
                 }
 
+                self.apply_animations(name).await;
+
                 Ok(())
             }
             Err(e) => {
@@ -345,6 +725,67 @@ impl BrowserManager {
         }
     }
 
+    /// Turn CSS transitions and animations off (or on) in every tab of this
+    /// browser that doesn't have its own setting
+    pub fn set_animations_disabled(&mut self, disabled: bool) {
+        self.animations_disabled = disabled;
+    }
+
+    /// Turn CSS transitions and animations off (or back on) in one tab; it
+    /// takes effect now and in documents the tab loads later
+    pub async fn set_tab_animations(&self, tab_name: &str, disabled: bool) -> Result<()> {
+        self.get_or_create_tab(tab_name).await?;
+        self.tab_animations.insert(tab_name.to_string(), disabled);
+        // Switching to the tab applies the new setting
+        self.with_tab(tab_name, |_| Box::pin(async { Ok(()) }))
+            .await
+    }
+
+    fn animations_disabled_for(&self, tab_name: &str) -> bool {
+        self.tab_animations
+            .get(tab_name)
+            .map(|disabled| *disabled)
+            .unwrap_or(self.animations_disabled)
+    }
+
+    /// Bring the current document and (on Chrome) future loads in line with
+    /// the tab's animation setting (internal use - assumes caller holds tab lock)
+    async fn apply_animations(&self, name: &str) {
+        let disabled = self.animations_disabled_for(name);
+        if (disabled || self.tab_animations.contains_key(name))
+            && let Err(e) = self.browser.set_animations(disabled).await
+        {
+            warn!("Failed to toggle animations in tab '{}': {}", name, e);
+        }
+
+        if !matches!(self.browser_type, BrowserType::Chrome) {
+            return;
+        }
+        let registered = self.animation_scripts.get(name).map(|id| id.clone());
+        match (disabled, registered) {
+            (true, None) => match self.browser.disable_animations_on_load().await {
+                Ok(id) => {
+                    self.animation_scripts.insert(name.to_string(), id);
+                }
+                Err(e) => warn!("Failed to keep animations off in tab '{}': {}", name, e),
+            },
+            (false, Some(id)) => {
+                if let Err(e) = self
+                    .browser
+                    .cdp(
+                        "Page.removeScriptToEvaluateOnNewDocument",
+                        serde_json::json!({ "identifier": id }),
+                    )
+                    .await
+                {
+                    warn!("Failed to turn animations back on in tab '{}': {}", name, e);
+                }
+                self.animation_scripts.remove(name);
+            }
+            _ => {}
+        }
+    }
+
     /// Public method to switch tabs (acquires lock first)
     pub async fn switch_to_tab(&self, name: &str) -> Result<()> {
         // Get the tab's lock
@@ -417,6 +858,9 @@ impl BrowserManager {
         // Step 5: Clean up tracking structures LAST
         self.tab_locks.remove(name);
         self.tab_states.remove(name);
+        self.tab_shifts.remove(name);
+        self.tab_animations.remove(name);
+        self.animation_scripts.remove(name);
 
         let mut temp_tabs = self.temporary_tabs.lock().await;
         temp_tabs.remove(name);
@@ -486,7 +930,7 @@ impl BrowserManager {
                 "#,
                 viewport.width, viewport.height
             );
-            self.browser.execute_javascript(Some(""), &script).await?;
+            self.browser.execute(&script, vec![]).await?;
         }
 
         Ok(())
@@ -546,6 +990,8 @@ impl BrowserManager {
             browser: &self.browser,
             tab_name: tab_name.to_string(),
             _guard: guard,
+            shift_log: &self.tab_shifts,
+            animations_disabled: self.animations_disabled_for(tab_name),
         };
 
         // Execute the operation with safe context
@@ -37,11 +37,14 @@ mod tests {
             _browser: &'a crate::webdriver::Browser,
             tab_name: String,
             _guard: tokio::sync::MutexGuard<'a, ()>,
+            shift_log: &'a dashmap::DashMap<String, Vec<serde_json::Value>>,
         ) -> TabContext<'a> {
             TabContext {
                 browser: _browser,
                 tab_name,
                 _guard,
+                shift_log,
+                animations_disabled: false,
             }
         }
     }
@@ -0,0 +1,641 @@
+//! CSS cascade explanation for `why`
+//!
+//! The page script walks `document.styleSheets` and returns every declaration
+//! that could set the requested property on the element or one of its
+//! ancestors. In Chrome, DevTools adds the user-agent rules and rules from
+//! cross-origin stylesheets the page can't read. This module sorts those
+//! declarations the way the cascade does (origin and importance, inline
+//! style, layers, specificity, source order), picks the winner, follows
+//! inheritance when the element declares nothing itself, and resolves the
+//! custom properties the winning value refers to.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Properties that inherit by default (custom properties always do)
+const INHERITED: &[&str] = &[
+    "border-collapse",
+    "border-spacing",
+    "caption-side",
+    "color",
+    "color-scheme",
+    "cursor",
+    "direction",
+    "empty-cells",
+    "font",
+    "font-family",
+    "font-feature-settings",
+    "font-kerning",
+    "font-size",
+    "font-size-adjust",
+    "font-stretch",
+    "font-style",
+    "font-variant",
+    "font-variant-caps",
+    "font-variant-east-asian",
+    "font-variant-ligatures",
+    "font-variant-numeric",
+    "font-variation-settings",
+    "font-weight",
+    "hyphens",
+    "letter-spacing",
+    "line-height",
+    "list-style",
+    "list-style-image",
+    "list-style-position",
+    "list-style-type",
+    "orphans",
+    "overflow-wrap",
+    "pointer-events",
+    "quotes",
+    "tab-size",
+    "text-align",
+    "text-align-last",
+    "text-indent",
+    "text-justify",
+    "text-shadow",
+    "text-transform",
+    "text-underline-position",
+    "visibility",
+    "white-space",
+    "widows",
+    "word-break",
+    "word-spacing",
+    "word-wrap",
+    "writing-mode",
+];
+
+/// Where a declaration comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Origin {
+    /// Browser default stylesheet (only visible through DevTools)
+    UserAgent,
+    /// A rule in a page stylesheet
+    Author,
+    /// The element's `style` attribute
+    Inline,
+}
+
+/// One declaration of a property, on the element or an ancestor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Declaration {
+    pub property: String,
+    pub value: String,
+    #[serde(default)]
+    pub important: bool,
+    pub origin: Origin,
+    /// Full selector text of the rule (None for inline styles)
+    #[serde(default)]
+    pub selector: Option<String>,
+    /// The selectors in the rule's list that match the element
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched_selectors: Vec<String>,
+    #[serde(default)]
+    pub stylesheet: Option<String>,
+    /// Enclosing @media / @supports / @container conditions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+    /// Position of the layer in layer order (None when unlayered)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer_order: Option<usize>,
+    /// Shorthand the value was declared through (e.g. `margin` for `margin-top`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shorthand: Option<String>,
+    /// Source order across all stylesheets
+    #[serde(default)]
+    pub order: usize,
+    /// 0 for the element itself, 1 for its parent, ...
+    #[serde(default)]
+    pub distance: usize,
+    /// Selector of the element that carries the declaration
+    #[serde(default)]
+    pub element: Option<String>,
+    /// 1-based position of the declaration in the stylesheet's text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    /// Where the declaration was authored, through the stylesheet's source map
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original: Option<crate::sourcemap::SourceLocation>,
+}
+
+/// Everything the page (and DevTools) know about a property on one element
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MatchedStyles {
+    #[serde(default)]
+    pub url: String,
+    /// Selector of the inspected element
+    pub element: String,
+    /// Selector per ancestor distance (element first)
+    #[serde(default)]
+    pub chain: Vec<String>,
+    /// Longhands the requested property expands to
+    pub longhands: Vec<String>,
+    /// Computed values on the element, for the longhands and referenced custom properties
+    #[serde(default)]
+    pub computed: BTreeMap<String, String>,
+    #[serde(default)]
+    pub declarations: Vec<Declaration>,
+    /// Stylesheets whose rules the page can't read (cross-origin)
+    #[serde(default)]
+    pub inaccessible_sheets: Vec<String>,
+    /// Whether DevTools rules (user-agent, cross-origin) were merged in
+    #[serde(default)]
+    pub devtools: bool,
+}
+
+/// Specificity as (ids, classes/attributes/pseudo-classes, types/pseudo-elements)
+pub fn specificity(selector: &str) -> [u32; 3] {
+    let chars: Vec<char> = selector.chars().collect();
+    let mut spec = [0u32; 3];
+    let mut i = 0;
+
+    let ident_end = |mut j: usize| {
+        while j < chars.len()
+            && (chars[j].is_alphanumeric()
+                || chars[j] == '-'
+                || chars[j] == '_'
+                || chars[j] == '\\')
+        {
+            j += if chars[j] == '\\' { 2 } else { 1 };
+        }
+        j.min(chars.len())
+    };
+    // Index just past the parenthesised group starting at `open`
+    let group_end = |open: usize| {
+        let mut depth = 0;
+        let mut j = open;
+        while j < chars.len() {
+            match chars[j] {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return j + 1;
+                    }
+                }
+                _ => {}
+            }
+            j += 1;
+        }
+        chars.len()
+    };
+    let add = |spec: &mut [u32; 3], other: [u32; 3]| {
+        for k in 0..3 {
+            spec[k] += other[k];
+        }
+    };
+
+    while i < chars.len() {
+        match chars[i] {
+            '#' => {
+                spec[0] += 1;
+                i = ident_end(i + 1);
+            }
+            '.' => {
+                spec[1] += 1;
+                i = ident_end(i + 1);
+            }
+            '[' => {
+                spec[1] += 1;
+                while i < chars.len() && chars[i] != ']' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            ':' => {
+                let element = chars.get(i + 1) == Some(&':');
+                let start = if element { i + 2 } else { i + 1 };
+                let end = ident_end(start);
+                let name: String = chars[start..end].iter().collect::<String>().to_lowercase();
+                let args = if chars.get(end) == Some(&'(') {
+                    let close = group_end(end);
+                    let inner: String = chars[end + 1..close.saturating_sub(1)].iter().collect();
+                    i = close;
+                    Some(inner)
+                } else {
+                    i = end;
+                    None
+                };
+
+                let legacy_element = matches!(
+                    name.as_str(),
+                    "before" | "after" | "first-line" | "first-letter"
+                );
+                match (name.as_str(), args) {
+                    _ if element || legacy_element => spec[2] += 1,
+                    ("where", _) => {}
+                    ("is" | "not" | "has" | "matches", Some(list)) => {
+                        add(&mut spec, max_specificity(&list));
+                    }
+                    ("nth-child" | "nth-last-child", Some(arg)) => {
+                        spec[1] += 1;
+                        if let Some((_, list)) = arg.split_once(" of ") {
+                            add(&mut spec, max_specificity(list));
+                        }
+                    }
+                    _ => spec[1] += 1,
+                }
+            }
+            c if c.is_alphabetic() || c == '_' || c == '-' => {
+                // Type selector (ignoring any namespace prefix)
+                spec[2] += 1;
+                i = ident_end(i);
+                if chars.get(i) == Some(&'|') {
+                    i = ident_end(i + 1);
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    spec
+}
+
+/// Split a selector list on top-level commas
+pub fn split_selectors(list: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut current = String::new();
+    for c in list.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        parts.push(current.trim().to_string());
+    }
+    parts
+}
+
+fn max_specificity(list: &str) -> [u32; 3] {
+    split_selectors(list)
+        .iter()
+        .map(|s| specificity(s))
+        .max()
+        .unwrap_or([0, 0, 0])
+}
+
+impl Declaration {
+    /// Specificity of the most specific selector that matched (None for inline styles)
+    pub fn specificity(&self) -> Option<[u32; 3]> {
+        if self.origin == Origin::Inline {
+            return None;
+        }
+        let selector = self.selector.as_deref()?;
+        Some(if self.matched_selectors.is_empty() {
+            max_specificity(selector)
+        } else {
+            self.matched_selectors
+                .iter()
+                .map(|s| specificity(s))
+                .max()
+                .unwrap_or([0, 0, 0])
+        })
+    }
+
+    /// Sort key: a greater key wins the cascade
+    fn precedence(&self) -> (u8, bool, usize, [u32; 3], usize) {
+        let origin = match (self.origin, self.important) {
+            (Origin::UserAgent, false) => 0,
+            (Origin::Author | Origin::Inline, false) => 1,
+            (Origin::Author | Origin::Inline, true) => 2,
+            (Origin::UserAgent, true) => 3,
+        };
+        // Unlayered styles beat layered ones, unless !important reverses it
+        let layer = match (self.layer_order, self.important) {
+            (None, false) => usize::MAX,
+            (Some(order), false) => order,
+            (None, true) => 0,
+            (Some(order), true) => usize::MAX - order,
+        };
+        (
+            origin,
+            self.origin == Origin::Inline,
+            layer,
+            self.specificity().unwrap_or([0, 0, 0]),
+            self.order,
+        )
+    }
+}
+
+/// Declarations sorted by cascade precedence, winner first
+pub fn cascade<'a>(
+    declarations: impl IntoIterator<Item = &'a Declaration>,
+) -> Vec<&'a Declaration> {
+    let mut sorted: Vec<&Declaration> = declarations.into_iter().collect();
+    sorted.sort_by(|a, b| match b.precedence().cmp(&a.precedence()) {
+        Ordering::Equal => b.order.cmp(&a.order),
+        other => other,
+    });
+    sorted
+}
+
+pub fn is_inherited(property: &str) -> bool {
+    property.starts_with("--") || INHERITED.contains(&property)
+}
+
+/// Custom properties referenced through `var()` in a value
+pub fn var_references(value: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("var(") {
+        rest = rest[start + 4..].trim_start();
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+        if rest.starts_with("--") && !names.contains(&rest[..end].to_string()) {
+            names.push(rest[..end].to_string());
+        }
+        rest = &rest[end..];
+    }
+    names
+}
+
+fn describe(declaration: &Declaration, status: &str) -> Value {
+    let mut value = serde_json::to_value(declaration).unwrap_or(Value::Null);
+    value["specificity"] = json!(declaration.specificity());
+    value["status"] = json!(status);
+    value
+}
+
+fn describe_cascade(sorted: &[&Declaration]) -> Vec<Value> {
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(i, d)| describe(d, if i == 0 { "applied" } else { "overridden" }))
+        .collect()
+}
+
+/// Where a property's value comes from: the element's own winning
+/// declaration, or the nearest ancestor's for inherited properties
+fn resolve<'a>(
+    styles: &'a MatchedStyles,
+    property: &str,
+) -> (Vec<&'a Declaration>, Option<(usize, Vec<&'a Declaration>)>) {
+    let at = |distance: usize| {
+        cascade(
+            styles
+                .declarations
+                .iter()
+                .filter(|d| d.property == property && d.distance == distance),
+        )
+    };
+
+    let own = at(0);
+    let explicit_inherit = own.first().is_some_and(|d| d.value == "inherit");
+    if !own.is_empty() && !explicit_inherit {
+        return (own, None);
+    }
+    if !(explicit_inherit || own.is_empty() && is_inherited(property)) {
+        return (own, None);
+    }
+
+    let deepest = styles
+        .declarations
+        .iter()
+        .map(|d| d.distance)
+        .max()
+        .unwrap_or(0);
+    for distance in 1..=deepest {
+        let sorted = at(distance);
+        match sorted.first() {
+            Some(winner) if winner.value != "inherit" => return (own, Some((distance, sorted))),
+            // `inherit` keeps walking up, as does an ancestor with no declaration
+            _ => continue,
+        }
+    }
+    (own, None)
+}
+
+fn explain_property(styles: &MatchedStyles, property: &str) -> Value {
+    let (own, inherited) = resolve(styles, property);
+    let effective = match &inherited {
+        Some((_, sorted)) => sorted.first().copied(),
+        None => own.first().copied(),
+    };
+
+    let source = match (&inherited, own.first()) {
+        (Some(_), _) => "inherited",
+        (None, Some(winner)) => match winner.origin {
+            Origin::UserAgent => "user-agent",
+            Origin::Author => "author",
+            Origin::Inline => "inline",
+        },
+        (None, None) => "initial",
+    };
+
+    let mut report = json!({
+        "property": property,
+        "computed": styles.computed.get(property),
+        "source": source,
+        "winner": effective.map(|d| describe(d, "applied")),
+        "cascade": describe_cascade(&own),
+    });
+    if let Some((distance, sorted)) = &inherited {
+        report["inherited_from"] = json!({
+            "element": styles.chain.get(*distance).or(sorted[0].element.as_ref()),
+            "distance": distance,
+            "cascade": describe_cascade(sorted),
+        });
+    }
+    if let Some(winner) = effective {
+        report["variables"] = json!(resolve_variables(styles, &winner.value));
+    }
+    report
+}
+
+/// Follow `var()` references (and the references in their values) to the
+/// declarations that set them
+fn resolve_variables(styles: &MatchedStyles, value: &str) -> Vec<Value> {
+    let mut seen = BTreeSet::new();
+    let mut pending = var_references(value);
+    let mut resolved = Vec::new();
+
+    while let Some(name) = pending.pop() {
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+        let (own, inherited) = resolve(styles, &name);
+        let (winner, distance) = match &inherited {
+            Some((distance, sorted)) => (sorted.first().copied(), *distance),
+            None => (own.first().copied(), 0),
+        };
+        if let Some(winner) = winner {
+            pending.extend(var_references(&winner.value));
+        }
+        resolved.push(json!({
+            "name": name,
+            "computed": styles.computed.get(&name),
+            "declared_by": winner.map(|d| describe(d, "applied")),
+            "element": styles.chain.get(distance),
+            "inherited": inherited.is_some(),
+        }));
+    }
+    resolved
+}
+
+/// The `why` report: every longhand with its cascade, winner, inheritance
+/// and custom-property resolution
+pub fn explain(styles: &MatchedStyles, property: &str) -> Value {
+    json!({
+        "url": styles.url,
+        "element": styles.element,
+        "property": property,
+        "properties": styles
+            .longhands
+            .iter()
+            .map(|longhand| explain_property(styles, longhand))
+            .collect::<Vec<_>>(),
+        "inaccessible_sheets": styles.inaccessible_sheets,
+        "devtools": styles.devtools,
+    })
+}
+
+/// Declarations from DevTools `CSS.getMatchedStylesForNode`, limited to `wanted`
+/// properties. Ancestors are labelled from `chain`; rules from unreadable
+/// sheets are attributed to `fallback_sheet`.
+pub fn from_devtools(
+    matched: &Value,
+    wanted: &BTreeSet<String>,
+    chain: &[String],
+    fallback_sheet: Option<&str>,
+) -> Vec<Declaration> {
+    let mut declarations = Vec::new();
+    let mut order = 0;
+
+    let mut add_rules = |rules: &Value, distance: usize, declarations: &mut Vec<Declaration>| {
+        for entry in rules.as_array().into_iter().flatten() {
+            let rule = &entry["rule"];
+            let origin = match rule["origin"].as_str() {
+                Some("user-agent") => Origin::UserAgent,
+                Some("regular") => Origin::Author,
+                // Injected and inspector rules aren't part of the page
+                _ => continue,
+            };
+            let selectors: Vec<String> = rule["selectorList"]["selectors"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|s| s["text"].as_str().map(String::from))
+                .collect();
+            let matched_selectors: Vec<String> = entry["matchingSelectors"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|i| selectors.get(i.as_u64()? as usize).cloned())
+                .collect();
+            let conditions = rule["media"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|m| m["text"].as_str().map(|t| format!("@media {}", t)))
+                .collect::<Vec<_>>();
+
+            order += 1;
+            for property in rule["style"]["cssProperties"]
+                .as_array()
+                .into_iter()
+                .flatten()
+            {
+                let Some(name) = property["name"].as_str() else {
+                    continue;
+                };
+                if !wanted.contains(name)
+                    || property["disabled"] == true
+                    || property["parsedOk"] == false
+                {
+                    continue;
+                }
+                let value = property["value"].as_str().unwrap_or("");
+                let important = property["important"] == true;
+                declarations.push(Declaration {
+                    property: name.to_string(),
+                    value: value.trim_end_matches("!important").trim().to_string(),
+                    important,
+                    origin,
+                    selector: rule["selectorList"]["text"].as_str().map(String::from),
+                    matched_selectors: matched_selectors.clone(),
+                    stylesheet: match origin {
+                        Origin::UserAgent => Some("user agent stylesheet".to_string()),
+                        _ => Some(
+                            fallback_sheet
+                                .unwrap_or("cross-origin stylesheet")
+                                .to_string(),
+                        ),
+                    },
+                    conditions: conditions.clone(),
+                    layer: None,
+                    layer_order: None,
+                    shorthand: None,
+                    order,
+                    distance,
+                    element: chain.get(distance).cloned(),
+                    line: None,
+                    column: None,
+                    original: None,
+                });
+            }
+        }
+    };
+
+    add_rules(&matched["matchedCSSRules"], 0, &mut declarations);
+    for (i, ancestor) in matched["inherited"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        add_rules(&ancestor["matchedCSSRules"], i + 1, &mut declarations);
+    }
+    declarations
+}
+
+impl MatchedStyles {
+    /// Properties DevTools declarations are needed for
+    pub fn wanted(&self) -> BTreeSet<String> {
+        self.longhands
+            .iter()
+            .chain(self.computed.keys())
+            .cloned()
+            .collect()
+    }
+
+    /// Add DevTools declarations the page script couldn't see (user-agent
+    /// rules, cross-origin sheets); ones it already has are skipped
+    pub fn merge_devtools(&mut self, declarations: Vec<Declaration>) {
+        for declaration in declarations {
+            // Values aren't compared: DevTools has the source text, the page
+            // the serialized value
+            let known = self.declarations.iter().any(|d| {
+                d.property == declaration.property
+                    && d.distance == declaration.distance
+                    && d.selector == declaration.selector
+            });
+            // Author rules the page could read are already there with better detail
+            let readable =
+                declaration.origin == Origin::Author && self.inaccessible_sheets.is_empty();
+            if !known && !readable {
+                self.declarations.push(declaration);
+            }
+        }
+        self.devtools = true;
+    }
+}
+
+#[cfg(test)]
+#[path = "cascade_test.rs"]
+mod cascade_test;
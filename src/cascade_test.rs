@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod tests {
+    use crate::cascade::*;
+    use serde_json::json;
+    use std::collections::BTreeSet;
+
+    fn rule(property: &str, value: &str, selector: &str, order: usize) -> Declaration {
+        Declaration {
+            property: property.to_string(),
+            value: value.to_string(),
+            important: false,
+            origin: Origin::Author,
+            selector: Some(selector.to_string()),
+            matched_selectors: vec![],
+            stylesheet: Some("http://localhost:3000/app.css".to_string()),
+            conditions: vec![],
+            layer: None,
+            layer_order: None,
+            shorthand: None,
+            order,
+            distance: 0,
+            element: None,
+            line: None,
+            column: None,
+            original: None,
+        }
+    }
+
+    fn winner(declarations: &[Declaration]) -> &str {
+        cascade(declarations)[0].value.as_str()
+    }
+
+    #[test]
+    fn test_specificity() {
+        assert_eq!(specificity("#nav .item a"), [1, 1, 1]);
+        assert_eq!(specificity("a:hover::before"), [0, 1, 2]);
+        assert_eq!(specificity("p:first-line"), [0, 0, 2]);
+        assert_eq!(specificity("input[type=\"text\"]:not(.wide)"), [0, 2, 1]);
+        assert_eq!(specificity(":is(#hero, .card) p"), [1, 0, 1]);
+        assert_eq!(specificity(":where(#hero) p"), [0, 0, 1]);
+        assert_eq!(specificity("li:nth-child(2n of .item)"), [0, 2, 1]);
+        assert_eq!(specificity("*"), [0, 0, 0]);
+        assert_eq!(specificity("svg|rect"), [0, 0, 1]);
+    }
+
+    #[test]
+    fn test_split_selectors() {
+        assert_eq!(
+            split_selectors(".a, :is(.b, .c) > p,[data-x=\"1,2\"]"),
+            vec![".a", ":is(.b, .c) > p", "[data-x=\"1,2\"]"]
+        );
+    }
+
+    #[test]
+    fn test_cascade_order() {
+        // Specificity beats source order
+        let decls = [
+            rule("margin-top", "8px", "#main p", 1),
+            rule("margin-top", "24px", "p", 2),
+        ];
+        assert_eq!(winner(&decls), "8px");
+
+        // Equal specificity: later wins
+        let decls = [
+            rule("margin-top", "8px", ".a", 1),
+            rule("margin-top", "24px", ".b", 2),
+        ];
+        assert_eq!(winner(&decls), "24px");
+
+        // Inline beats any selector, !important beats inline
+        let mut inline = rule("margin-top", "4px", "", 3);
+        inline.origin = Origin::Inline;
+        inline.selector = None;
+        let mut important = rule("margin-top", "0", "p", 0);
+        important.important = true;
+        let decls = [rule("margin-top", "8px", "#main p", 1), inline.clone()];
+        assert_eq!(winner(&decls), "4px");
+        let decls = [inline, important];
+        assert_eq!(winner(&decls), "0");
+
+        // Unlayered beats layered, but layered !important beats unlayered !important
+        let mut layered = rule("color", "red", "#id", 1);
+        layered.layer = Some("base".to_string());
+        layered.layer_order = Some(0);
+        let decls = [layered.clone(), rule("color", "blue", "p", 2)];
+        assert_eq!(winner(&decls), "blue");
+        let mut unlayered = rule("color", "blue", "p", 2);
+        unlayered.important = true;
+        layered.important = true;
+        let decls = [unlayered, layered];
+        assert_eq!(winner(&decls), "red");
+
+        // The most specific matching selector of a list counts
+        let mut list = rule("color", "green", "p, #main p", 1);
+        list.matched_selectors = vec!["p".to_string()];
+        assert_eq!(list.specificity(), Some([0, 0, 1]));
+    }
+
+    #[test]
+    fn test_explain_inheritance_and_variables() {
+        let mut on_body = rule("color", "var(--text)", "body", 1);
+        on_body.distance = 2;
+        let mut text_var = rule("--text", "var(--brand)", ":root", 0);
+        text_var.distance = 3;
+        let mut brand_var = rule("--brand", "#0a66c2", ":root", 0);
+        brand_var.distance = 3;
+
+        let styles = MatchedStyles {
+            url: "http://localhost:3000/".to_string(),
+            element: "main > p".to_string(),
+            chain: vec!["main > p", "main", "body", "html"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            longhands: vec!["color".to_string()],
+            computed: [
+                ("color".to_string(), "rgb(10, 102, 194)".to_string()),
+                ("--text".to_string(), "#0a66c2".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+            declarations: vec![on_body, text_var, brand_var],
+            ..Default::default()
+        };
+
+        let report = explain(&styles, "color");
+        let color = &report["properties"][0];
+        assert_eq!(color["source"], "inherited");
+        assert_eq!(color["inherited_from"]["element"], "body");
+        assert_eq!(color["inherited_from"]["distance"], 2);
+        assert_eq!(color["cascade"], json!([]));
+
+        let variables = color["variables"].as_array().unwrap();
+        assert_eq!(variables[0]["name"], "--text");
+        assert_eq!(variables[0]["element"], "html");
+        assert_eq!(variables[1]["name"], "--brand");
+        assert_eq!(variables[1]["declared_by"]["value"], "#0a66c2");
+    }
+
+    #[test]
+    fn test_explain_own_and_initial() {
+        let mut parent = rule("margin-top", "40px", "main", 1);
+        parent.distance = 1;
+        let styles = MatchedStyles {
+            element: "p".to_string(),
+            longhands: vec!["margin-top".to_string(), "margin-bottom".to_string()],
+            declarations: vec![
+                rule("margin-top", "8px", ".a", 2),
+                rule("margin-top", "24px", "p", 3),
+                parent,
+            ],
+            ..Default::default()
+        };
+
+        let report = explain(&styles, "margin");
+        let top = &report["properties"][0];
+        assert_eq!(top["source"], "author");
+        assert_eq!(top["winner"]["value"], "8px");
+        assert_eq!(top["winner"]["specificity"], json!([0, 1, 0]));
+        assert_eq!(top["cascade"][1]["status"], "overridden");
+
+        // margin doesn't inherit, so the parent's value is irrelevant
+        let bottom = &report["properties"][1];
+        assert_eq!(bottom["source"], "initial");
+        assert!(bottom["winner"].is_null());
+        assert!(bottom.get("inherited_from").is_none());
+
+        assert_eq!(
+            var_references("calc(var(--gap) * var( --scale, 1))"),
+            vec!["--gap", "--scale"]
+        );
+    }
+
+    #[test]
+    fn test_devtools_declarations() {
+        let matched = json!({
+            "matchedCSSRules": [
+                {
+                    "rule": {
+                        "origin": "user-agent",
+                        "selectorList": {"text": "p", "selectors": [{"text": "p"}]},
+                        "style": {"cssProperties": [
+                            {"name": "margin-top", "value": "1em"},
+                            {"name": "display", "value": "block"}
+                        ]}
+                    },
+                    "matchingSelectors": [0]
+                },
+                {
+                    "rule": {
+                        "origin": "regular",
+                        "selectorList": {"text": ".x, p", "selectors": [{"text": ".x"}, {"text": "p"}]},
+                        "style": {"cssProperties": [{"name": "margin-top", "value": "2px !important", "important": true}]}
+                    },
+                    "matchingSelectors": [1]
+                }
+            ],
+            "inherited": []
+        });
+        let wanted: BTreeSet<String> = ["margin-top".to_string()].into_iter().collect();
+        let chain = vec!["p".to_string()];
+        let decls = from_devtools(
+            &matched,
+            &wanted,
+            &chain,
+            Some("https://cdn.example.com/x.css"),
+        );
+
+        assert_eq!(decls.len(), 2);
+        assert_eq!(decls[0].origin, Origin::UserAgent);
+        assert_eq!(decls[1].value, "2px");
+        assert!(decls[1].important);
+        assert_eq!(decls[1].matched_selectors, vec!["p"]);
+        assert_eq!(
+            decls[1].stylesheet.as_deref(),
+            Some("https://cdn.example.com/x.css")
+        );
+
+        // With every sheet readable, only the user-agent rule is new
+        let mut styles = MatchedStyles {
+            element: "p".to_string(),
+            longhands: vec!["margin-top".to_string()],
+            ..Default::default()
+        };
+        styles.merge_devtools(decls);
+        assert_eq!(styles.declarations.len(), 1);
+        assert!(styles.devtools);
+    }
+}
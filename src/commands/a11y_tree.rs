@@ -0,0 +1,97 @@
+use anyhow::Result;
+use serde_json::Value;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::OutputFormat;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_a11y_tree(
+    url: String,
+    selector: Option<String>,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+    max_nodes: usize,
+    interactive_only: bool,
+) -> Result<()> {
+    info!("Building accessibility tree for {}", url);
+    utils::require_daemon()?;
+
+    let request = DaemonRequest::A11yTree {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        url,
+        selector,
+        max_nodes,
+        interactive_only,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::A11yTreeResult(result) => {
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&result)?),
+                OutputFormat::Simple => {
+                    print!("{}", format_outline(&result["tree"]));
+                    if result["truncated"] == true {
+                        println!("… truncated after {} nodes (raise --max-nodes)", max_nodes);
+                    }
+                }
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+/// Render tree nodes as an indented outline: `- role "name" [state, level=2]`
+pub fn format_outline(nodes: &Value) -> String {
+    fn render(nodes: &Value, depth: usize, out: &mut String) {
+        for node in nodes.as_array().into_iter().flatten() {
+            let role = node["role"].as_str().unwrap_or("unknown");
+            out.push_str(&"  ".repeat(depth));
+            if role == "text" {
+                out.push_str(&format!(
+                    "- text: {}\n",
+                    node["name"].as_str().unwrap_or("")
+                ));
+                continue;
+            }
+
+            out.push_str("- ");
+            out.push_str(role);
+            if let Some(name) = node["name"].as_str() {
+                out.push_str(&format!(" {:?}", name));
+            }
+
+            let mut attrs = Vec::new();
+            if let Some(level) = node["level"].as_u64() {
+                attrs.push(format!("level={}", level));
+            }
+            for (state, value) in node["states"].as_object().into_iter().flatten() {
+                match value {
+                    Value::Bool(true) => attrs.push(state.clone()),
+                    Value::Bool(false) => attrs.push(format!("{}=false", state)),
+                    Value::String(s) => attrs.push(format!("{}={}", state, s)),
+                    other => attrs.push(format!("{}={}", state, other)),
+                }
+            }
+            if !attrs.is_empty() {
+                out.push_str(&format!(" [{}]", attrs.join(", ")));
+            }
+            if let Some(value) = node["value"].as_str() {
+                out.push_str(&format!(": {}", value));
+            }
+            out.push('\n');
+
+            render(&node["children"], depth + 1, out);
+        }
+    }
+
+    let mut out = String::new();
+    render(nodes, 0, &mut out);
+    out
+}
@@ -3,7 +3,37 @@ use tracing::info;
 
 use crate::commands::utils;
 use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
-use crate::types::{DiagnosticResult, OutputFormat};
+use crate::types::{DiagnosticResult, OutputFormat, PseudoState};
+
+/// "Only 60% visible, covered by #cookie-banner (40%)" for an element that
+/// isn't fully on screen, `None` when all of it can be seen
+pub fn describe_occlusion(occlusion: &serde_json::Value) -> Option<String> {
+    let visible = occlusion["visible_percent"].as_f64()?;
+    if visible >= 100.0 {
+        return None;
+    }
+    let mut reasons: Vec<String> = occlusion["covered_by"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|o| {
+            format!(
+                "covered by {} ({}%)",
+                o["selector"].as_str().unwrap_or("?"),
+                o["percent"].as_f64().unwrap_or(0.0)
+            )
+        })
+        .collect();
+    let in_viewport = occlusion["in_viewport_percent"].as_f64().unwrap_or(100.0);
+    if in_viewport < 100.0 {
+        reasons.push(format!("{}% outside the viewport", 100.0 - in_viewport));
+    }
+    let clipped = occlusion["clipped_percent"].as_f64().unwrap_or(0.0);
+    if clipped > 0.0 {
+        reasons.push(format!("{}% clipped by overflow", clipped));
+    }
+    Some(format!("Only {}% visible, {}", visible, reasons.join(", ")))
+}
 
 // Helper function to convert raw analysis data to diagnostic result
 fn analyze_to_diagnostic(focus: &str, raw_data: serde_json::Value) -> DiagnosticResult {
@@ -183,6 +213,38 @@ fn analyze_to_diagnostic(focus: &str, raw_data: serde_json::Value) -> Diagnostic
                 }
             }
 
+            // Buttons, links and text something else is painted over
+            if let Some(conflicts) = raw_data["anomalies"]["interaction_conflicts"].as_array()
+                && !conflicts.is_empty()
+            {
+                anomalies.push("covered interactive elements");
+                for conflict in conflicts {
+                    evidence.push(format!(
+                        "{} is covered by {} ({}% visible)",
+                        conflict["clickable_element"].as_str().unwrap_or("?"),
+                        conflict["blocking_element"].as_str().unwrap_or("?"),
+                        conflict["visible_percent"].as_f64().unwrap_or(0.0)
+                    ));
+                }
+                suggested_fix = Some(
+                    "Check sticky headers, overlays and modal backdrops painted over these elements"
+                        .to_string(),
+                );
+            }
+            if let Some(covered) = raw_data["anomalies"]["covered_elements"].as_array()
+                && !covered.is_empty()
+            {
+                anomalies.push("covered text");
+                for element in covered {
+                    evidence.push(format!(
+                        "{} is covered by {} ({}% visible)",
+                        element["selector"].as_str().unwrap_or("?"),
+                        element["covered_by"][0]["selector"].as_str().unwrap_or("?"),
+                        element["visible_percent"].as_f64().unwrap_or(0.0)
+                    ));
+                }
+            }
+
             confidence = if anomalies.is_empty() { 0.95 } else { 0.90 };
 
             if anomalies.is_empty() {
@@ -273,6 +335,11 @@ fn analyze_to_diagnostic(focus: &str, raw_data: serde_json::Value) -> Diagnostic
                 all_issues.push("anomalies");
             }
 
+            if let Some(hidden) = describe_occlusion(&raw_data["element"]["occlusion"]) {
+                all_issues.push("visibility");
+                evidence.push(hidden);
+            }
+
             confidence = if all_issues.is_empty() { 0.90 } else { 0.80 };
 
             if all_issues.is_empty() {
@@ -292,12 +359,15 @@ fn analyze_to_diagnostic(focus: &str, raw_data: serde_json::Value) -> Diagnostic
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_analyze(
     url: String,
     selector: String,
     focus: String,
     proximity: u32,
     index: Option<usize>,
+    state: Option<String>,
+    wait_stable_frames: Option<u32>,
     _browser: String,
     profile: Option<String>,
     _no_headless: bool,
@@ -305,6 +375,11 @@ pub async fn handle_analyze(
     tab: Option<String>,
 ) -> Result<()> {
     info!("Analyzing {} on {} with focus: {}", selector, url, focus);
+    let states = state
+        .as_deref()
+        .map(PseudoState::parse_list)
+        .transpose()?
+        .unwrap_or_default();
 
     // Require daemon for all operations
     utils::require_daemon()?;
@@ -316,6 +391,8 @@ pub async fn handle_analyze(
         focus: focus.clone(),
         proximity: Some(proximity),
         index,
+        states,
+        wait_stable_frames,
         profile: profile.clone(),
     };
 
@@ -331,6 +408,17 @@ pub async fn handle_analyze(
                 OutputFormat::Simple => {
                     println!("Diagnosis: {}", diagnostic.diagnosis);
                     println!("Confidence: {:.0}%", diagnostic.confidence * 100.0);
+                    if let Some(states) = diagnostic
+                        .raw_data
+                        .as_ref()
+                        .and_then(|raw| raw["forced_states"].as_array())
+                    {
+                        let states: Vec<String> = states
+                            .iter()
+                            .map(|s| format!(":{}", s.as_str().unwrap_or("")))
+                            .collect();
+                        println!("State: {}", states.join(", "));
+                    }
                     if !diagnostic.evidence.is_empty() {
                         println!("\nEvidence:");
                         for evidence in &diagnostic.evidence {
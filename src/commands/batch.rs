@@ -1,46 +1,121 @@
 use anyhow::{Context, Result};
+use serde_json::{Value, json};
 use tracing::info;
 
 use crate::commands::utils;
+use crate::daemon::flow::{self, Flow};
 use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::{OutputFormat, ViewportSize};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_batch(
     commands: String,
     tab: Option<String>,
     _browser: String,
-    _stop_on_error: bool,
-    _headless: bool,
+    stop_on_error: bool,
+    headless: bool,
+    no_headless: bool,
     profile: Option<String>,
-    _viewport: Option<String>,
+    viewport: Option<String>,
+    format: OutputFormat,
+    unsafe_eval: bool,
 ) -> Result<()> {
     info!("Executing batch commands");
 
     // Require daemon for all operations
     utils::require_daemon()?;
-    // Parse commands if needed (for validation purposes)
-    let commands_to_send = if let Some(file_path) = commands.strip_prefix('@') {
-        // Read from file if starts with @
-
-        std::fs::read_to_string(file_path)
-            .context(format!("Failed to read commands from file: {}", file_path))?
+    // Read from file if the argument starts with @
+    let (text, yaml) = if let Some(file_path) = commands.strip_prefix('@') {
+        let text = std::fs::read_to_string(file_path)
+            .context(format!("Failed to read commands from file: {}", file_path))?;
+        let yaml = file_path.ends_with(".yaml") || file_path.ends_with(".yml");
+        (text, yaml)
     } else {
-        commands.clone()
+        (commands.clone(), false)
     };
 
+    // Environment references are resolved here, in the caller's shell, not in the daemon
+    let mut value = flow::expand_env(&Flow::parse_text(&text, yaml)?)?;
+    flow::resolve_upload_paths(&mut value, &std::env::current_dir()?);
+
+    // Validate locally so obvious mistakes don't need a round trip to the daemon
+    let flow = Flow::from_value(value.clone())?;
+    if !unsafe_eval && flow.contains_step_type("eval") {
+        anyhow::bail!(
+            "Batch contains eval steps. Pass --unsafe-eval to acknowledge running JavaScript"
+        );
+    }
+    if let Some(viewport) = &viewport {
+        ViewportSize::parse(viewport)?;
+    }
+    if headless {
+        eprintln!(
+            "Warning: --headless is deprecated and has no effect; batches run headless by default."
+        );
+    }
+    if no_headless {
+        eprintln!(
+            "Note: headless mode is fixed when a profile's browser starts. \
+             Use 'webprobe profile create <name> --no-headless' to watch a batch run."
+        );
+    }
+
     let request = DaemonRequest::Batch {
         tab_name: utils::resolve_tab_name(&profile, tab)?,
-        commands: commands_to_send,
+        commands: serde_json::to_string(&value)?,
+        stop_on_error,
+        viewport,
+        unsafe_eval,
         profile: profile.clone(),
     };
 
     match DaemonClient::send_request(request)? {
         DaemonResponse::BatchResult(results) => {
-            println!("\nBatch execution complete:");
-            let successes = results.iter().filter(|r| !r["error"].is_string()).count();
-            let failures = results.len() - successes;
-            println!("  ✓ {} commands succeeded", successes);
-            if failures > 0 {
-                println!("  ✗ {} commands failed", failures);
+            let failures = results.iter().filter(|r| r["error"].is_string()).count();
+            let skipped = results.iter().filter(|r| r["skipped"] == true).count();
+            let successes = results.len() - failures - skipped;
+            let not_run = flow.steps.len().saturating_sub(results.len());
+
+            match format {
+                OutputFormat::Json => {
+                    let output = json!({
+                        "name": flow.name,
+                        "total": flow.steps.len(),
+                        "succeeded": successes,
+                        "failed": failures,
+                        "skipped": skipped,
+                        "not_run": not_run,
+                        "steps": results,
+                    });
+                    println!("{}", serde_json::to_string_pretty(&output)?);
+                }
+                OutputFormat::Simple => {
+                    for result in &results {
+                        print_step(result);
+                    }
+                    println!("\nBatch execution complete:");
+                    println!("  ✓ {} commands succeeded", successes);
+                    if failures > 0 {
+                        println!("  ✗ {} commands failed", failures);
+                    }
+                    if skipped > 0 {
+                        println!("  - {} commands skipped by condition", skipped);
+                    }
+                    if not_run > 0 {
+                        println!("  - {} commands not run after error", not_run);
+                    }
+                }
+            }
+
+            if (stop_on_error || flow.stop_on_error)
+                && let Some(failed) = results.iter().find(|r| r["error"].is_string())
+            {
+                return Err(anyhow::anyhow!(
+                    "Batch stopped at step {} ({}): {}",
+                    failed["step"],
+                    failed["type"].as_str().unwrap_or("unknown"),
+                    failed["error"].as_str().unwrap_or("")
+                ));
             }
             Ok(())
         }
@@ -48,3 +123,75 @@ pub async fn handle_batch(
         _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
     }
 }
+
+/// Print one step result in the simple format
+fn print_step(result: &Value) {
+    let step = &result["step"];
+    let step_type = result["type"].as_str().unwrap_or("unknown");
+
+    if let Some(error) = result["error"].as_str() {
+        println!("✗ [{}] {}: {}", step, step_type, error);
+        return;
+    }
+    if result["skipped"] == true {
+        println!("- [{}] {} skipped (condition not met)", step, step_type);
+        return;
+    }
+
+    let output = &result["result"];
+    match step_type {
+        "goto" | "navigate" => {
+            println!("✓ [{}] goto {}", step, output["url"].as_str().unwrap_or(""))
+        }
+        "click" | "hover" | "type" | "select" | "check" | "uncheck" | "set_range" | "upload" => {
+            println!(
+                "✓ [{}] {} {}",
+                step,
+                step_type,
+                output["selector"].as_str().unwrap_or("")
+            );
+        }
+        "press" => println!(
+            "✓ [{}] press {}",
+            step,
+            output["keys"].as_str().unwrap_or("")
+        ),
+        "wait" => println!(
+            "✓ [{}] wait {} ({}) after {}ms",
+            step,
+            output["selector"].as_str().unwrap_or(""),
+            output["condition"].as_str().unwrap_or(""),
+            output["elapsed_ms"]
+        ),
+        "sleep" => println!("✓ [{}] sleep {}ms", step, output["milliseconds"]),
+        "screenshot" => println!(
+            "✓ [{}] screenshot saved to {} ({} bytes)",
+            step,
+            output["saved_to"].as_str().unwrap_or(""),
+            output["bytes"]
+        ),
+        "scroll" if output["reason"].is_string() => println!(
+            "✓ [{}] scroll x{} until {} (height {}px)",
+            step,
+            output["steps"].as_array().map_or(0, |s| s.len()),
+            output["reason"].as_str().unwrap_or(""),
+            output["height"]
+        ),
+        "scroll" => println!("✓ [{}] scroll to ({}, {})", step, output["x"], output["y"]),
+        "assert" => println!("✓ [{}] assert passed", step),
+        "for_each" => println!(
+            "✓ [{}] for_each {} ({} iterations)",
+            step,
+            output["selector"].as_str().unwrap_or(""),
+            output["iterations"].as_array().map_or(0, |i| i.len())
+        ),
+        _ => {
+            println!("✓ [{}] {}", step, step_type);
+            match output {
+                Value::String(s) => println!("  {}", s),
+                Value::Null => {}
+                other => println!("  {}", other),
+            }
+        }
+    }
+}
@@ -2,13 +2,19 @@ use anyhow::Result;
 use tracing::info;
 
 use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::ClickOptions;
 
 use super::utils::{self, require_daemon};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_click(
     url: String,
     selector: String,
     index: Option<usize>,
+    button: String,
+    double: bool,
+    offset: Option<String>,
+    modifiers: Option<String>,
     _browser: String, // Daemon uses its configured browser
     profile: Option<String>,
     _viewport: Option<String>, // Not used in daemon mode
@@ -17,6 +23,20 @@ pub async fn handle_click(
 ) -> Result<()> {
     info!("Clicking {} on {}", selector, url);
 
+    let options = ClickOptions {
+        button: button.parse()?,
+        double,
+        offset: offset
+            .as_deref()
+            .map(ClickOptions::parse_offset)
+            .transpose()?,
+        modifiers: modifiers
+            .as_deref()
+            .map(ClickOptions::parse_modifiers)
+            .transpose()?
+            .unwrap_or_default(),
+    };
+
     // Ensure daemon is running (daemon-only architecture)
     require_daemon()?;
 
@@ -29,6 +49,7 @@ pub async fn handle_click(
         url: if url.is_empty() { None } else { Some(url) },
         selector: selector.clone(),
         index,
+        options,
         profile,
     };
 
@@ -5,11 +5,13 @@ use crate::commands::utils;
 use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
 use crate::types::OutputFormat;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_compare(
     url1: String,
     url2: String,
     mode: String,
     selector: Option<String>,
+    wait_stable_frames: Option<u32>,
     _browser: String,
     profile: Option<String>,
     viewport: Option<String>,
@@ -31,6 +33,7 @@ pub async fn handle_compare(
         url2,
         mode: mode.clone(),
         selector,
+        wait_stable_frames,
         profile,
     };
 
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use serde_json::Value;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::{DragMode, DragTarget, OutputFormat};
+
+pub async fn handle_drag(
+    source: String,
+    target: String,
+    url: String,
+    steps: u32,
+    mode: String,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    info!("Dragging {} to {}", source, target);
+    let mode: DragMode = mode.parse()?;
+    utils::require_daemon()?;
+
+    let request = DaemonRequest::Drag {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        url,
+        source,
+        target: DragTarget::parse(&target),
+        steps,
+        mode,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::DragResult(drag) => {
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&drag)?),
+                OutputFormat::Simple => print_simple(&drag),
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+fn position(rect: &Value) -> String {
+    if rect.is_null() {
+        return "gone".to_string();
+    }
+    format!(
+        "({:.0}, {:.0})",
+        rect["x"].as_f64().unwrap_or(0.0),
+        rect["y"].as_f64().unwrap_or(0.0)
+    )
+}
+
+fn print_simple(drag: &Value) {
+    println!(
+        "Dragged {} ({}): {} -> {}{}",
+        drag["source"].as_str().unwrap_or(""),
+        drag["method"].as_str().unwrap_or(""),
+        position(&drag["before"]),
+        position(&drag["after"]),
+        if drag["moved"] == true {
+            ""
+        } else {
+            "  (did not move)"
+        }
+    );
+    if drag["dropped"] == false {
+        println!("  The drop target did not accept the drop (no dragover preventDefault)");
+    }
+}
@@ -0,0 +1,342 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::{FormAction, OptionMatch, OutputFormat};
+
+pub async fn handle_select(
+    selector: String,
+    values: Vec<String>,
+    labels: Vec<String>,
+    index: Option<usize>,
+    url: String,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    let action = match (values.is_empty(), labels.is_empty()) {
+        (false, true) => FormAction::Select {
+            values,
+            by: OptionMatch::Value,
+            index,
+        },
+        (true, false) => FormAction::Select {
+            values: labels,
+            by: OptionMatch::Label,
+            index,
+        },
+        _ => anyhow::bail!("Pass either --value or --label (repeat it for <select multiple>)"),
+    };
+    send(selector, action, url, tab, profile, format).await
+}
+
+pub async fn handle_check(
+    selector: String,
+    checked: bool,
+    url: String,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    send(
+        selector,
+        FormAction::Check { checked },
+        url,
+        tab,
+        profile,
+        format,
+    )
+    .await
+}
+
+pub async fn handle_set_range(
+    selector: String,
+    value: f64,
+    url: String,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    send(
+        selector,
+        FormAction::SetRange { value },
+        url,
+        tab,
+        profile,
+        format,
+    )
+    .await
+}
+
+pub async fn handle_upload(
+    selector: String,
+    files: Vec<String>,
+    url: String,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    // The daemon doesn't share our working directory
+    let files = files
+        .iter()
+        .map(|file| {
+            std::fs::canonicalize(file)
+                .with_context(|| format!("File not found: {}", file))
+                .map(|path| path.to_string_lossy().into_owned())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    send(
+        selector,
+        FormAction::Upload { files },
+        url,
+        tab,
+        profile,
+        format,
+    )
+    .await
+}
+
+pub async fn handle_fill_form(
+    url: String,
+    form: Option<String>,
+    data: String,
+    submit: bool,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    info!(
+        "Filling form {} on {}",
+        form.as_deref().unwrap_or("(auto)"),
+        url
+    );
+    utils::require_daemon()?;
+
+    // Read from file if the argument starts with @
+    let text = if let Some(file_path) = data.strip_prefix('@') {
+        std::fs::read_to_string(file_path)
+            .context(format!("Failed to read form data from file: {}", file_path))?
+    } else {
+        data
+    };
+    let data = match serde_json::from_str(&text).context("Invalid JSON in --data")? {
+        Value::Object(data) => data,
+        _ => anyhow::bail!("--data must be a JSON object mapping fields to values"),
+    };
+
+    let request = DaemonRequest::FillForm {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        url: if url.is_empty() { None } else { Some(url) },
+        form,
+        data,
+        submit,
+        // The daemon doesn't share our working directory
+        files_base: Some(std::env::current_dir()?.to_string_lossy().into_owned()),
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::FormResult(filled) => {
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&filled)?),
+                OutputFormat::Simple => print_filled(&filled),
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+async fn send(
+    selector: String,
+    action: FormAction,
+    url: String,
+    tab: Option<String>,
+    profile: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    info!("{} {} on {}", action.name(), selector, url);
+    utils::require_daemon()?;
+
+    let request = DaemonRequest::Form {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        url: if url.is_empty() { None } else { Some(url) },
+        selector,
+        action: action.clone(),
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::FormResult(control) => {
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&control)?),
+                OutputFormat::Simple => print_simple(&action, &control),
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+fn print_simple(action: &FormAction, control: &Value) {
+    let selector = control["selector"].as_str().unwrap_or("");
+    let unchanged = if control["changed"] == false {
+        " (unchanged)"
+    } else {
+        ""
+    };
+    match action {
+        FormAction::Select { .. } => {
+            let chosen: Vec<String> = control["labels"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .zip(control["values"].as_array().into_iter().flatten())
+                .map(|(label, value)| {
+                    format!(
+                        "'{}' ({})",
+                        label.as_str().unwrap_or(""),
+                        value.as_str().unwrap_or("")
+                    )
+                })
+                .collect();
+            println!(
+                "Selected {} in {}{}",
+                chosen.join(", "),
+                selector,
+                unchanged
+            );
+        }
+        FormAction::Check { checked } => println!(
+            "{} {} {}{}",
+            if *checked { "Checked" } else { "Unchecked" },
+            control["kind"].as_str().unwrap_or(""),
+            selector,
+            unchanged
+        ),
+        FormAction::SetRange { value } => {
+            println!(
+                "Set {} to {} (min {}, max {}, step {}){}",
+                selector,
+                control["value"],
+                control["min"],
+                control["max"],
+                control["step"].as_str().unwrap_or("1"),
+                unchanged
+            );
+            if control["value"].as_f64() != Some(*value) {
+                println!("  Requested {}, which the input clamped or snapped", value);
+            }
+        }
+        FormAction::Upload { .. } => {
+            let files = control["files"].as_array().cloned().unwrap_or_default();
+            println!("Chose {} file(s) in {}", files.len(), selector);
+            for file in files {
+                println!(
+                    "  {} ({} bytes{})",
+                    file["name"].as_str().unwrap_or(""),
+                    file["size"],
+                    file["type"]
+                        .as_str()
+                        .filter(|t| !t.is_empty())
+                        .map(|t| format!(", {}", t))
+                        .unwrap_or_default()
+                );
+            }
+        }
+    }
+}
+
+fn print_filled(filled: &Value) {
+    let list = |key: &str| filled[key].as_array().cloned().unwrap_or_default();
+    let name_of = |field: &Value| {
+        field["label"]
+            .as_str()
+            .or(field["name"].as_str())
+            .map(|name| format!(" ({})", name))
+            .unwrap_or_default()
+    };
+
+    let fields = list("filled");
+    println!(
+        "Filled {} field(s) in {}",
+        fields.len(),
+        filled["form"].as_str().unwrap_or("")
+    );
+    for field in &fields {
+        println!(
+            "  {} -> {} (by {}): {}",
+            field["key"].as_str().unwrap_or(""),
+            field["selector"].as_str().unwrap_or(""),
+            field["matched_by"].as_str().unwrap_or(""),
+            field["value"]
+        );
+    }
+
+    let failed = list("failed");
+    if !failed.is_empty() {
+        println!("Failed:");
+        for field in &failed {
+            println!(
+                "  {} -> {}: {}",
+                field["key"].as_str().unwrap_or(""),
+                field["selector"].as_str().unwrap_or(""),
+                field["error"].as_str().unwrap_or("")
+            );
+        }
+    }
+
+    let unmatched: Vec<String> = list("unmatched")
+        .iter()
+        .filter_map(|key| key.as_str().map(str::to_string))
+        .collect();
+    if !unmatched.is_empty() {
+        println!("No field for: {}", unmatched.join(", "));
+    }
+
+    let missing = list("missing_required");
+    if !missing.is_empty() {
+        println!("Required but empty:");
+        for field in &missing {
+            println!(
+                "  {}{}",
+                field["selector"].as_str().unwrap_or(""),
+                name_of(field)
+            );
+        }
+    }
+
+    if filled.get("submitted").is_some() {
+        if filled["navigated"] == true {
+            println!(
+                "Submitted, navigated to {}",
+                filled["url"].as_str().unwrap_or("")
+            );
+        } else if filled["submitted"] == true {
+            println!("Submitted");
+        } else {
+            println!("Not submitted");
+        }
+        let validation = list("validation");
+        if !validation.is_empty() {
+            println!("Validation messages:");
+            for field in &validation {
+                println!(
+                    "  {}{}: {}",
+                    field["selector"].as_str().unwrap_or(""),
+                    name_of(field),
+                    field["message"].as_str().unwrap_or("")
+                );
+            }
+        }
+    }
+}
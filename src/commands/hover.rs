@@ -0,0 +1,43 @@
+use anyhow::Result;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::ClickOptions;
+
+pub async fn handle_hover(
+    url: String,
+    selector: String,
+    index: Option<usize>,
+    offset: Option<String>,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+) -> Result<()> {
+    info!("Hovering over {} on {}", selector, url);
+
+    let offset = offset
+        .as_deref()
+        .map(ClickOptions::parse_offset)
+        .transpose()?;
+    utils::require_daemon()?;
+
+    let tab_name = utils::resolve_tab_name(&profile, tab)?;
+    let request = DaemonRequest::Hover {
+        tab_name,
+        url: if url.is_empty() { None } else { Some(url) },
+        selector,
+        index,
+        offset,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::Success(msg) => {
+            println!("{}", msg);
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use serde_json::Value;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::OutputFormat;
+
+pub async fn handle_images(
+    url: String,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+    tolerance: f64,
+) -> Result<()> {
+    info!("Auditing images on {}", url);
+    utils::require_daemon()?;
+
+    if tolerance < 1.0 {
+        anyhow::bail!("--tolerance must be at least 1.0");
+    }
+
+    let request = DaemonRequest::Images {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        url,
+        tolerance,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::ImagesResult(audit) => {
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&audit)?),
+                OutputFormat::Simple => print_simple(&audit),
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+/// Human-readable size, e.g. 1536 -> "1.5 KB"
+pub fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    }
+}
+
+fn print_simple(audit: &Value) {
+    let summary = &audit["summary"];
+    println!(
+        "{} image(s) at {}x device pixel ratio",
+        summary["images"], audit["device_pixel_ratio"]
+    );
+
+    for image in audit["images"].as_array().into_iter().flatten() {
+        let issues = image["issues"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+        if issues.is_empty() {
+            continue;
+        }
+        println!(
+            "\n{}  {}x{} natural, {}x{} rendered{}",
+            image["selector"].as_str().unwrap_or(""),
+            image["natural"]["width"],
+            image["natural"]["height"],
+            image["rendered"]["width"],
+            image["rendered"]["height"],
+            image["bytes"]
+                .as_u64()
+                .map(|b| format!(", {}", format_bytes(b)))
+                .unwrap_or_default()
+        );
+        println!("  {}", image["src"].as_str().unwrap_or(""));
+        for issue in issues {
+            println!(
+                "  - {}: {}",
+                issue["type"].as_str().unwrap_or(""),
+                issue["message"].as_str().unwrap_or("")
+            );
+        }
+    }
+
+    println!(
+        "\nBroken {}, oversized {}, undersized {}, missing srcset {}, missing sizes {}, lazy above fold {}, distorted {}",
+        summary["broken"],
+        summary["oversized"],
+        summary["undersized"],
+        summary["missing_srcset"],
+        summary["missing_sizes"],
+        summary["lazy_above_fold"],
+        summary["distorted"]
+    );
+    if let Some(wasted) = summary["wasted_bytes"].as_u64().filter(|w| *w > 0) {
+        println!(
+            "About {} could be saved by serving appropriately sized images",
+            format_bytes(wasted)
+        );
+    }
+}
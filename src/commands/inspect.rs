@@ -3,7 +3,7 @@ use anyhow::Result;
 use tracing::info;
 
 use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
-use crate::types::{InspectionDepth, OutputFormat};
+use crate::types::{InspectionDepth, OutputFormat, PseudoState};
 
 #[allow(clippy::too_many_arguments)]
 pub async fn handle_inspect(
@@ -15,12 +15,19 @@ pub async fn handle_inspect(
     all: bool,
     index: Option<usize>,
     expect_one: bool,
+    state: Option<String>,
+    wait_stable_frames: Option<u32>,
     _viewport: Option<String>,
     tab: Option<String>,
     _console: bool,
 ) -> Result<()> {
     // Don't log browser type here as it may be different when using daemon
     info!("Inspecting {} on {}", selector, url);
+    let states = state
+        .as_deref()
+        .map(PseudoState::parse_list)
+        .transpose()?
+        .unwrap_or_default();
 
     // Check if daemon is running
     if DaemonClient::is_daemon_running() {
@@ -40,6 +47,8 @@ pub async fn handle_inspect(
             all,
             index,
             expect_one,
+            states,
+            wait_stable_frames,
             profile: profile.clone(),
         };
 
@@ -91,6 +100,31 @@ pub async fn handle_inspect(
                             if result.children_count > 0 {
                                 println!("  Children: {}", result.children_count);
                             }
+                            if !result.forced_states.is_empty() {
+                                let states: Vec<String> = result
+                                    .forced_states
+                                    .iter()
+                                    .map(|s| format!(":{}", s.as_str()))
+                                    .collect();
+                                println!("  State: {}", states.join(", "));
+                            }
+                            if let Some(occlusion) = &result.occlusion
+                                && occlusion.visible_percent < 100.0
+                            {
+                                println!(
+                                    "  Visible: {}% ({}% in viewport)",
+                                    occlusion.visible_percent, occlusion.in_viewport_percent
+                                );
+                                for occluder in &occlusion.covered_by {
+                                    println!(
+                                        "    covered by {} ({}, z-index {}): {}%",
+                                        occluder.selector,
+                                        occluder.position,
+                                        occluder.z_index,
+                                        occluder.percent
+                                    );
+                                }
+                            }
                         }
                     }
                 }
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use serde_json::Value;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::OutputFormat;
+
+pub async fn handle_keyboard_audit(
+    url: String,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+    max_tabs: usize,
+) -> Result<()> {
+    info!("Auditing keyboard navigation for {}", url);
+    utils::require_daemon()?;
+
+    let request = DaemonRequest::KeyboardAudit {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        url,
+        max_tabs,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::KeyboardAuditResult(audit) => {
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&audit)?),
+                OutputFormat::Simple => print_simple(&audit, max_tabs),
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+fn print_simple(audit: &Value, max_tabs: usize) {
+    println!("Focus order:");
+    for stop in audit["focus_order"].as_array().into_iter().flatten() {
+        let marker = if stop["indicator"] == true { " " } else { "!" };
+        println!(
+            "{} {:>3}. {} {:?}{}",
+            marker,
+            stop["order"],
+            stop["selector"].as_str().unwrap_or(""),
+            stop["name"].as_str().unwrap_or(""),
+            match stop["tabindex"].as_i64() {
+                Some(t) if t > 0 => format!(" (tabindex={})", t),
+                _ => String::new(),
+            }
+        );
+    }
+    if audit["completed"] != true && audit["traps"].as_array().is_none_or(|t| t.is_empty()) {
+        println!(
+            "… stopped after {} Tab presses (raise --max-tabs)",
+            max_tabs
+        );
+    }
+
+    for trap in audit["traps"].as_array().into_iter().flatten() {
+        let elements: Vec<&str> = trap["elements"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.as_str())
+            .collect();
+        println!(
+            "\n{} focus cycles between: {}",
+            if trap["modal"] == true {
+                "Modal"
+            } else {
+                "Focus trap:"
+            },
+            elements.join(" → ")
+        );
+    }
+
+    let unreachable = audit["unreachable"].as_array();
+    if let Some(items) = unreachable.filter(|u| !u.is_empty()) {
+        println!("\nClickable but not keyboard reachable:");
+        for item in items {
+            println!(
+                "  {} - {}",
+                item["selector"].as_str().unwrap_or(""),
+                item["reason"].as_str().unwrap_or("")
+            );
+        }
+    }
+
+    let summary = &audit["summary"];
+    println!(
+        "\n{} tab stops, {} without a visible focus indicator (!), {} traps, {} unreachable",
+        summary["tab_stops"],
+        summary["without_focus_indicator"],
+        summary["traps"],
+        summary["unreachable"]
+    );
+}
@@ -1,3 +1,4 @@
+pub mod a11y_tree;
 pub mod analyze;
 pub mod batch;
 pub mod click;
@@ -5,15 +6,26 @@ pub mod compare;
 pub mod daemon;
 pub mod detect;
 pub mod diagnose;
+pub mod drag;
 pub mod eval;
 pub mod find_text;
+pub mod form;
+pub mod hover;
 pub mod iframe;
+pub mod images;
 pub mod inspect;
+pub mod keyboard_audit;
 pub mod layout;
+pub mod perf;
+pub mod press;
 pub mod profile;
+pub mod record;
+pub mod route;
 pub mod screenshot;
 pub mod scroll;
+pub mod scroll_probe;
 pub mod session;
+pub mod shifts;
 pub mod status;
 pub mod tab;
 pub mod r#type;
@@ -21,8 +33,10 @@ pub mod update;
 pub mod utils;
 pub mod validate;
 pub mod version;
+pub mod wait;
 pub mod wait_idle;
 pub mod wait_navigation;
+pub mod why;
 
 #[cfg(test)]
 #[path = "../commands_test.rs"]
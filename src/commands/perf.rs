@@ -0,0 +1,163 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::OutputFormat;
+
+/// Web Vitals "good" / "poor" boundaries (values at or below `good` are good,
+/// above `poor` are poor)
+const THRESHOLDS: &[(&str, f64, f64)] = &[
+    ("ttfb", 800.0, 1800.0),
+    ("fcp", 1800.0, 3000.0),
+    ("lcp", 2500.0, 4000.0),
+    ("cls", 0.1, 0.25),
+    ("inp", 200.0, 500.0),
+    ("fid", 100.0, 300.0),
+    ("total_blocking_time", 200.0, 600.0),
+];
+
+pub async fn handle_perf(
+    url: String,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+    interact: Option<String>,
+    settle_ms: u64,
+) -> Result<()> {
+    info!("Measuring performance of {}", url);
+    utils::require_daemon()?;
+
+    let request = DaemonRequest::Perf {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        url,
+        interact,
+        settle_ms,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::PerfResult(mut metrics) => {
+            if metrics.is_null() {
+                anyhow::bail!("Performance observers are not available on this page");
+            }
+            rate_metrics(&mut metrics);
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&metrics)?),
+                OutputFormat::Simple => print_simple(&metrics),
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+/// good / needs-improvement / poor for a metric value
+pub fn rating(metric: &str, value: f64) -> Option<&'static str> {
+    let (_, good, poor) = THRESHOLDS.iter().find(|(name, _, _)| *name == metric)?;
+    Some(if value <= *good {
+        "good"
+    } else if value <= *poor {
+        "needs-improvement"
+    } else {
+        "poor"
+    })
+}
+
+/// Add a `ratings` object for every metric that was measured
+pub fn rate_metrics(metrics: &mut Value) {
+    let value_of = |metric: &str| -> Option<f64> {
+        match metric {
+            "ttfb" | "fcp" => metrics[metric].as_f64(),
+            "total_blocking_time" => metrics["long_tasks"][metric].as_f64(),
+            _ => metrics[metric]["value"].as_f64(),
+        }
+    };
+
+    let mut ratings = serde_json::Map::new();
+    for (metric, _, _) in THRESHOLDS {
+        if let Some(value) = value_of(metric)
+            && let Some(rating) = rating(metric, value)
+        {
+            ratings.insert(metric.to_string(), json!(rating));
+        }
+    }
+    metrics["ratings"] = Value::Object(ratings);
+}
+
+fn print_simple(metrics: &Value) {
+    let ratings = &metrics["ratings"];
+    let line =
+        |label: &str, metric: &str, value: Option<String>, detail: Option<String>| match value {
+            Some(value) => println!(
+                "{:<6} {:>10}  {:<18}{}",
+                label,
+                value,
+                ratings[metric].as_str().unwrap_or(""),
+                detail.unwrap_or_default()
+            ),
+            None => println!("{:<6} {:>10}  {}", label, "n/a", detail.unwrap_or_default()),
+        };
+    let millis = |v: &Value| v.as_f64().map(|v| format!("{:.0} ms", v));
+
+    line("TTFB", "ttfb", millis(&metrics["ttfb"]), None);
+    line("FCP", "fcp", millis(&metrics["fcp"]), None);
+    line(
+        "LCP",
+        "lcp",
+        millis(&metrics["lcp"]["value"]),
+        metrics["lcp"]["element"].as_str().map(String::from),
+    );
+    line(
+        "CLS",
+        "cls",
+        metrics["cls"]["value"]
+            .as_f64()
+            .map(|v| format!("{:.3}", v)),
+        metrics["cls"]["sources"][0]["selector"]
+            .as_str()
+            .map(|s| format!("largest shift: {}", s)),
+    );
+    line(
+        "INP",
+        "inp",
+        millis(&metrics["inp"]["value"]),
+        Some(match metrics["inp"]["target"].as_str() {
+            Some(target) => format!(
+                "{} on {}",
+                metrics["inp"]["event"].as_str().unwrap_or(""),
+                target
+            ),
+            None if metrics["inp"].is_null() => {
+                "no interaction (use --interact <selector>)".to_string()
+            }
+            None => String::new(),
+        }),
+    );
+    line("FID", "fid", millis(&metrics["fid"]["value"]), None);
+    line(
+        "TBT",
+        "total_blocking_time",
+        millis(&metrics["long_tasks"]["total_blocking_time"]),
+        Some(format!("{} long tasks", metrics["long_tasks"]["count"])),
+    );
+
+    let nav = &metrics["navigation"];
+    if !nav.is_null() {
+        println!(
+            "\nDOMContentLoaded {} ms, load {} ms (dns {} / connect {} / request {} / response {})",
+            nav["dom_content_loaded"],
+            nav["load"],
+            nav["dns"],
+            nav["connect"],
+            nav["request"],
+            nav["response"]
+        );
+    }
+    if metrics["observers"] == "buffered" {
+        println!("(observers registered after load: long tasks before that are not included)");
+    }
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::keys::KeyChord;
+
+pub async fn handle_press(
+    keys: Vec<String>,
+    selector: Option<String>,
+    url: String,
+    delay: u64,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+) -> Result<()> {
+    info!("Pressing {}", keys.join(" "));
+
+    // Catch unknown key names before anything is sent
+    KeyChord::parse_sequence(&keys.join(" "))?;
+    utils::require_daemon()?;
+
+    let request = DaemonRequest::Press {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        url: if url.is_empty() { None } else { Some(url) },
+        selector,
+        keys,
+        delay,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::Success(msg) => {
+            println!("{}", msg);
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
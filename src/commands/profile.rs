@@ -26,6 +26,10 @@ pub enum ProfileCommands {
         /// Run in visible mode (not headless)
         #[arg(short = 'n', long)]
         no_headless: bool,
+
+        /// Turn off CSS transitions and animations in every tab
+        #[arg(long)]
+        disable_animations: bool,
     },
 
     /// Delete a profile
@@ -82,6 +86,7 @@ pub async fn handle_profile(command: ProfileCommands) -> Result<()> {
             browser,
             viewport,
             no_headless,
+            disable_animations,
         } => {
             info!("Creating profile: {} for {}", name, browser);
 
@@ -101,6 +106,7 @@ pub async fn handle_profile(command: ProfileCommands) -> Result<()> {
                 headless: !no_headless,
                 persist_cookies: true,
                 persist_storage: true,
+                disable_animations,
             };
 
             let request = DaemonRequest::CreateProfile {
@@ -240,6 +246,10 @@ pub async fn handle_profile(command: ProfileCommands) -> Result<()> {
                                 println!("Viewport: default");
                             }
 
+                            if metadata.disable_animations {
+                                println!("Animations: disabled");
+                            }
+
                             if let Some(locked) = metadata.locked_until {
                                 println!("Locked until: {}", locked.format("%Y-%m-%d %H:%M:%S"));
                             } else {
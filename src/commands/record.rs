@@ -0,0 +1,183 @@
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{debug, info};
+
+use crate::commands::utils;
+use crate::daemon::record::{self, SECRET_VAR};
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse, ProfileConfig};
+use crate::webdriver::BrowserType;
+
+/// Profile used when none is given, so the default profile isn't touched
+const RECORD_PROFILE: &str = "record";
+
+/// Give up after this many failed polls in a row
+const MAX_POLL_FAILURES: u32 = 20;
+
+pub async fn handle_record(
+    url: Option<String>,
+    tab: Option<String>,
+    profile: Option<String>,
+    browser: String,
+    no_headless: bool,
+    output: String,
+) -> Result<()> {
+    info!("Recording interactions to {}", output);
+
+    // Require daemon for all operations
+    utils::require_daemon()?;
+
+    let profile = profile.unwrap_or_else(|| RECORD_PROFILE.to_string());
+    ensure_profile(&profile, &browser, no_headless)?;
+    let tab_name = utils::resolve_tab_name(&Some(profile.clone()), tab)?;
+
+    let mut events = match DaemonClient::send_request(DaemonRequest::StartRecording {
+        tab_name: tab_name.clone(),
+        url,
+        profile: Some(profile.clone()),
+    })? {
+        DaemonResponse::RecordedEvents(events) => events,
+        DaemonResponse::Error(e) => return Err(anyhow::anyhow!(e)),
+        _ => return Err(anyhow::anyhow!("Unexpected response from daemon")),
+    };
+
+    println!(
+        "Recording in tab '{}' of profile '{}'. Interact with the page, then press Enter (or Ctrl+C) to stop.",
+        tab_name, profile
+    );
+    for event in &events {
+        print_event(event);
+    }
+
+    // Enter on stdin stops the recording; EOF (no terminal) leaves only Ctrl+C
+    let (stop_tx, mut stop_rx) = tokio::sync::mpsc::channel::<()>(1);
+    std::thread::spawn(move || {
+        let mut line = String::new();
+        if matches!(std::io::stdin().read_line(&mut line), Ok(n) if n > 0) {
+            let _ = stop_tx.blocking_send(());
+        }
+    });
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    let mut failures = 0;
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            Some(()) = stop_rx.recv() => break,
+            _ = tokio::time::sleep(Duration::from_millis(500)) => {
+                let request = DaemonRequest::PollRecording {
+                    tab_name: tab_name.clone(),
+                    profile: Some(profile.clone()),
+                };
+                match DaemonClient::send_request(request) {
+                    Ok(DaemonResponse::RecordedEvents(new_events)) => {
+                        failures = 0;
+                        for event in &new_events {
+                            print_event(event);
+                        }
+                        events.extend(new_events);
+                    }
+                    // Polls fail while a page is mid-navigation; keep going
+                    Ok(DaemonResponse::Error(e)) => {
+                        debug!("Recording poll failed: {}", e);
+                        failures += 1;
+                    }
+                    Ok(_) => failures += 1,
+                    Err(e) => {
+                        debug!("Recording poll failed: {}", e);
+                        failures += 1;
+                    }
+                }
+                if failures >= MAX_POLL_FAILURES {
+                    eprintln!("Lost contact with the recording tab, saving what was captured");
+                    break;
+                }
+            }
+        }
+    }
+
+    match DaemonClient::send_request(DaemonRequest::StopRecording {
+        tab_name,
+        profile: Some(profile),
+    }) {
+        Ok(DaemonResponse::RecordedEvents(rest)) => events.extend(rest),
+        Ok(DaemonResponse::Error(e)) => eprintln!("Warning: failed to stop recorder: {}", e),
+        Ok(_) => eprintln!("Warning: unexpected response while stopping recorder"),
+        Err(e) => eprintln!("Warning: failed to stop recorder: {}", e),
+    }
+
+    let steps = record::events_to_steps(&events);
+    let flow = json!({ "name": "recording", "steps": steps });
+    let text = if output.ends_with(".yaml") || output.ends_with(".yml") {
+        serde_yaml::to_string(&flow)?
+    } else {
+        serde_json::to_string_pretty(&flow)? + "\n"
+    };
+    std::fs::write(&output, text).context(format!("Failed to write flow to {}", output))?;
+
+    println!("\n✓ Recorded {} steps to {}", steps.len(), output);
+    println!("  Replay with: webprobe batch @{}", output);
+    if flow.to_string().contains(SECRET_VAR) {
+        println!(
+            "  Password fields were recorded as ${{env.{}}}; set it before replaying",
+            SECRET_VAR
+        );
+    }
+    Ok(())
+}
+
+/// Make sure the recording profile exists, visible when asked for
+fn ensure_profile(profile: &str, browser: &str, no_headless: bool) -> Result<()> {
+    // Only the built-in recording profile is created on demand
+    if profile != RECORD_PROFILE && !no_headless {
+        return Ok(());
+    }
+
+    let config = ProfileConfig {
+        browser_type: BrowserType::from_str(browser)?,
+        headless: !no_headless,
+        ..Default::default()
+    };
+    match DaemonClient::send_request(DaemonRequest::CreateProfile {
+        name: profile.to_string(),
+        config,
+    })? {
+        DaemonResponse::Success(_) => Ok(()),
+        DaemonResponse::Error(e) if e.contains("already exists") => {
+            if no_headless {
+                eprintln!(
+                    "Note: profile '{}' already exists; its browser keeps the headless mode it was started with.",
+                    profile
+                );
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+/// Print a recorded event as it arrives
+fn print_event(event: &Value) {
+    let selector = event["selector"].as_str().unwrap_or("");
+    match event["kind"].as_str().unwrap_or("") {
+        "load" => println!("● page {}", event["url"].as_str().unwrap_or("")),
+        "navigation" => println!("● navigated {}", event["url"].as_str().unwrap_or("")),
+        "click" => println!("● click {}", selector),
+        "input" if event["sensitive"] == true => println!("● type {} (hidden)", selector),
+        "input" => println!("● type {}", selector),
+        "select" => println!(
+            "● select {} = {}",
+            selector,
+            event["value"].as_str().unwrap_or("")
+        ),
+        "key" => println!(
+            "● press {} in {}",
+            event["key"].as_str().unwrap_or(""),
+            selector
+        ),
+        _ => {}
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use tracing::{debug, info};
+
+use crate::commands::utils;
+use crate::daemon::flow::{self, Flow};
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::route::{Route, RouteStore};
+use crate::types::OutputFormat;
+
+#[derive(Subcommand)]
+pub enum RouteCommands {
+    /// Define a named navigation recipe
+    Define {
+        /// Route name
+        name: String,
+
+        /// Steps as batch JSON/YAML or a file (use @ prefix, e.g., @steps.json)
+        #[arg(long)]
+        steps: String,
+
+        /// Predicate that is true once the tab is on this screen,
+        /// e.g. "exists('.product-details')" or "url_contains('/products/')"
+        #[arg(long)]
+        check: String,
+
+        /// Replace an existing route with the same name
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// List defined routes
+    List {
+        /// Output format
+        #[arg(short, long, default_value = "simple")]
+        format: OutputFormat,
+    },
+
+    /// Show a route's predicate and steps
+    Show {
+        /// Route name
+        name: String,
+    },
+
+    /// Delete a route
+    Delete {
+        /// Route name
+        name: String,
+    },
+}
+
+pub async fn handle_route(command: RouteCommands) -> Result<()> {
+    let store = RouteStore::new()?;
+
+    match command {
+        RouteCommands::Define {
+            name,
+            steps,
+            check,
+            force,
+        } => {
+            info!("Defining route: {}", name);
+
+            let (text, yaml) = if let Some(file_path) = steps.strip_prefix('@') {
+                let text = std::fs::read_to_string(file_path)
+                    .context(format!("Failed to read steps from file: {}", file_path))?;
+                let yaml = file_path.ends_with(".yaml") || file_path.ends_with(".yml");
+                (text, yaml)
+            } else {
+                (steps, false)
+            };
+
+            // ${env.*} references are kept and resolved each time the route runs,
+            // but upload paths are relative to where the route was defined
+            let mut steps = Flow::parse_text(&text, yaml)?;
+            flow::resolve_upload_paths(&mut steps, &std::env::current_dir()?);
+            let route = Route::new(&name, &check, steps)?;
+            store.save(&route, force)?;
+            println!("✓ Route '{}' saved", name);
+            Ok(())
+        }
+
+        RouteCommands::List { format } => {
+            let routes = store.list()?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&routes)?),
+                OutputFormat::Simple => {
+                    if routes.is_empty() {
+                        println!("No routes defined");
+                        return Ok(());
+                    }
+                    println!("{:<24} {:<8} Check", "Name", "Steps");
+                    println!("{}", "-".repeat(70));
+                    for route in routes {
+                        let steps = Flow::from_value(route.steps.clone())
+                            .map(|f| f.steps.len())
+                            .unwrap_or(0);
+                        println!("{:<24} {:<8} {}", route.name, steps, route.check);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        RouteCommands::Show { name } => {
+            let route = store.load(&name)?;
+            println!("{}", serde_json::to_string_pretty(&route)?);
+            Ok(())
+        }
+
+        RouteCommands::Delete { name } => {
+            store.delete(&name)?;
+            println!("✓ Route '{}' deleted", name);
+            Ok(())
+        }
+    }
+}
+
+/// Bring a tab to a route's screen before running a command (`--ensure-route`)
+pub async fn ensure_route(name: &str, profile: &Option<String>, tab: Option<String>) -> Result<()> {
+    utils::require_daemon()?;
+
+    let tab_name = utils::resolve_tab_name(profile, tab)?;
+    if tab_name.is_empty() {
+        anyhow::bail!("--ensure-route needs a persistent tab (use --profile and --tab)");
+    }
+
+    let route = RouteStore::new()?.load(name)?;
+    let steps = flow::expand_env(&route.steps)?;
+
+    let request = DaemonRequest::EnsureRoute {
+        tab_name,
+        check: route.check.clone(),
+        steps: serde_json::to_string(&steps)?,
+        profile: profile.clone(),
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::RouteResult(result) => {
+            // Report on stderr so the command's own output stays parseable
+            if result["replayed"] == true {
+                eprintln!(
+                    "Route '{}' restored ({} steps replayed)",
+                    name,
+                    result["steps"].as_array().map_or(0, |s| s.len())
+                );
+            } else {
+                debug!("Already on route '{}'", name);
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!("Route '{}': {}", name, e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
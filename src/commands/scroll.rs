@@ -1,15 +1,22 @@
 use anyhow::Result;
+use serde_json::Value;
 use tracing::info;
 
 use crate::commands::utils;
 use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::{OutputFormat, ScrollUntil, SelectorCount};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_scroll(
     url: String,
     selector: Option<String>,
     by_x: i32,
     by_y: i32,
     to: Option<String>,
+    until_selector_count: Option<String>,
+    until_stable: Option<u64>,
+    max_steps: usize,
+    format: OutputFormat,
     _browser: String,
     profile: Option<String>,
     _no_headless: bool,
@@ -17,6 +24,20 @@ pub async fn handle_scroll(
 ) -> Result<()> {
     info!("Scrolling on {}", url);
 
+    let until = if until_selector_count.is_some() || until_stable.is_some() {
+        let defaults = ScrollUntil::default();
+        Some(ScrollUntil {
+            selector_count: until_selector_count
+                .as_deref()
+                .map(SelectorCount::parse)
+                .transpose()?,
+            stable_ms: until_stable.unwrap_or(defaults.stable_ms),
+            max_steps,
+        })
+    } else {
+        None
+    };
+
     // Require daemon for all operations
     utils::require_daemon()?;
     let request = DaemonRequest::Scroll {
@@ -26,6 +47,7 @@ pub async fn handle_scroll(
         by_x,
         by_y,
         to: to.clone(),
+        until,
         profile,
     };
 
@@ -38,7 +60,61 @@ pub async fn handle_scroll(
             }
             Ok(())
         }
+        DaemonResponse::ScrollResult(result) => {
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&result)?),
+                OutputFormat::Simple => print_steps(&result),
+            }
+            Ok(())
+        }
         DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
         _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
     }
 }
+
+fn print_steps(result: &Value) {
+    let steps = result["steps"].as_array().cloned().unwrap_or_default();
+    let reason = match result["reason"].as_str() {
+        Some("count") => format!(
+            "{} elements match {}",
+            result["count"],
+            result["target"]["selector"].as_str().unwrap_or("")
+        ),
+        Some("stable") => "the page stopped growing".to_string(),
+        _ => "--max-steps reached".to_string(),
+    };
+    println!("Scrolled {} time(s), stopped: {}", steps.len(), reason);
+
+    let items = |v: &Value| {
+        v["count"]
+            .as_u64()
+            .map(|count| format!(", {} items", count))
+            .unwrap_or_default()
+    };
+    println!(
+        "  Height {} -> {}px{}, {} request(s) in {}ms",
+        result["start"]["height"],
+        result["height"],
+        match (result["start"]["count"].as_u64(), result["count"].as_u64()) {
+            (Some(from), Some(to)) => format!(", items {} -> {}", from, to),
+            _ => String::new(),
+        },
+        result["requests"],
+        result["elapsed_ms"]
+    );
+    for step in &steps {
+        println!(
+            "  {}: height {}px{}{}, {} request(s), waited {}ms",
+            step["step"],
+            step["height"],
+            if step["grew"] == true {
+                ""
+            } else {
+                " (no growth)"
+            },
+            items(step),
+            step["requests"],
+            step["waited_ms"]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use serde_json::Value;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::{OutputFormat, ScrollPosition};
+
+pub async fn handle_scroll_probe(
+    selector: String,
+    positions: String,
+    wait_stable_frames: Option<u32>,
+    url: String,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    info!("Probing {} at scroll positions {}", selector, positions);
+    let positions = ScrollPosition::parse_list(&positions)?;
+    utils::require_daemon()?;
+
+    let request = DaemonRequest::ScrollProbe {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        url: if url.is_empty() { None } else { Some(url) },
+        selector,
+        positions,
+        wait_stable_frames,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::ScrollResult(probe) => {
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&probe)?),
+                OutputFormat::Simple => print_simple(&probe),
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+fn print_simple(probe: &Value) {
+    println!(
+        "{} (position: {})",
+        probe["selector"].as_str().unwrap_or(""),
+        probe["position"].as_str().unwrap_or("")
+    );
+    for sample in probe["samples"].as_array().into_iter().flatten() {
+        let rect = &sample["rect"];
+        println!(
+            "  at {:>6} (scrollY {}): {:.0}x{:.0} at ({:.0}, {:.0}){}{}{}",
+            sample["at"].as_str().unwrap_or(""),
+            sample["scroll_y"],
+            rect["width"].as_f64().unwrap_or(0.0),
+            rect["height"].as_f64().unwrap_or(0.0),
+            rect["x"].as_f64().unwrap_or(0.0),
+            rect["y"].as_f64().unwrap_or(0.0),
+            if sample["in_viewport"] == true {
+                ""
+            } else {
+                ", out of view"
+            },
+            match sample["stuck"].as_bool() {
+                Some(true) => ", stuck",
+                Some(false) => ", not stuck",
+                None => "",
+            },
+            if sample["stable"] == false {
+                ", still moving"
+            } else {
+                ""
+            }
+        );
+    }
+
+    let issues = probe["issues"].as_array().cloned().unwrap_or_default();
+    if issues.is_empty() {
+        println!("No issues found");
+    } else {
+        println!("Issues:");
+        for issue in issues {
+            println!(
+                "  [{}] {}",
+                issue["kind"].as_str().unwrap_or(""),
+                issue["message"].as_str().unwrap_or("")
+            );
+        }
+    }
+}
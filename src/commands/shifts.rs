@@ -0,0 +1,144 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::OutputFormat;
+
+pub async fn handle_shifts(
+    tab: Option<String>,
+    profile: Option<String>,
+    format: OutputFormat,
+    clear: bool,
+) -> Result<()> {
+    info!("Reading layout shifts");
+    utils::require_daemon()?;
+
+    let request = DaemonRequest::LayoutShifts {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        clear,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::LayoutShiftsResult(shifts) => {
+            let report = summarize(&shifts);
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+                OutputFormat::Simple => print_simple(&report),
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+/// Group recorded shifts by moved element
+///
+/// Shifts right after user input (`had_recent_input`) are kept in the list
+/// but, as in CLS, don't count towards `unexpected_score`.
+pub fn summarize(shifts: &[Value]) -> Value {
+    #[derive(Default)]
+    struct Moved {
+        shifts: usize,
+        score: f64,
+        max_dx: i64,
+        max_dy: i64,
+        phases: Vec<String>,
+    }
+
+    let mut elements: BTreeMap<String, Moved> = BTreeMap::new();
+    let mut unexpected_score = 0.0;
+
+    for shift in shifts {
+        let value = shift["value"].as_f64().unwrap_or(0.0);
+        if !shift["had_recent_input"].as_bool().unwrap_or(false) {
+            unexpected_score += value;
+        }
+        let phase = shift["phase"].as_str().unwrap_or("").to_string();
+
+        for source in shift["sources"].as_array().into_iter().flatten() {
+            let selector = source["selector"].as_str().unwrap_or("(removed node)");
+            let moved = elements.entry(selector.to_string()).or_default();
+            moved.shifts += 1;
+            moved.score += value;
+            let dx = source["delta"]["x"].as_i64().unwrap_or(0);
+            let dy = source["delta"]["y"].as_i64().unwrap_or(0);
+            if dx.abs() > moved.max_dx.abs() {
+                moved.max_dx = dx;
+            }
+            if dy.abs() > moved.max_dy.abs() {
+                moved.max_dy = dy;
+            }
+            if !moved.phases.contains(&phase) {
+                moved.phases.push(phase.clone());
+            }
+        }
+    }
+
+    let mut elements: Vec<(String, Moved)> = elements.into_iter().collect();
+    elements.sort_by(|a, b| b.1.score.total_cmp(&a.1.score));
+
+    json!({
+        "total_shifts": shifts.len(),
+        "unexpected_score": (unexpected_score * 10000.0).round() / 10000.0,
+        "elements": elements
+            .into_iter()
+            .map(|(selector, moved)| json!({
+                "selector": selector,
+                "shifts": moved.shifts,
+                "score": (moved.score * 10000.0).round() / 10000.0,
+                "max_move": {"x": moved.max_dx, "y": moved.max_dy},
+                "phases": moved.phases,
+            }))
+            .collect::<Vec<_>>(),
+        "shifts": shifts,
+    })
+}
+
+fn print_simple(report: &Value) {
+    let shifts = report["shifts"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or(&[]);
+    if shifts.is_empty() {
+        println!("No layout shifts recorded in this tab");
+        return;
+    }
+
+    println!(
+        "{} layout shift(s), unexpected score {}",
+        report["total_shifts"], report["unexpected_score"]
+    );
+    for shift in shifts {
+        println!(
+            "\n{:>7} ms  {:.4}{}  [{}]",
+            shift["time"],
+            shift["value"].as_f64().unwrap_or(0.0),
+            if shift["had_recent_input"] == true {
+                " (after input)"
+            } else {
+                ""
+            },
+            shift["phase"].as_str().unwrap_or("")
+        );
+        for source in shift["sources"].as_array().into_iter().flatten() {
+            let (before, after) = (&source["previous"], &source["current"]);
+            println!(
+                "    {}  ({}, {}) {}x{} -> ({}, {}) {}x{}",
+                source["selector"].as_str().unwrap_or("(removed node)"),
+                before["x"],
+                before["y"],
+                before["width"],
+                before["height"],
+                after["x"],
+                after["y"],
+                after["width"],
+                after["height"]
+            );
+        }
+    }
+}
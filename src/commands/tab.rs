@@ -20,6 +20,20 @@ pub enum TabCommands {
 
     /// Close all tabs
     CloseAll,
+
+    /// Turn CSS transitions and animations off (or back on) in a tab
+    Animations {
+        /// Tab name
+        name: String,
+
+        /// "off" disables transitions and animations, "on" restores them
+        #[arg(value_parser = ["on", "off"])]
+        state: String,
+
+        /// Profile that owns the tab
+        #[arg(short, long)]
+        profile: String,
+    },
 }
 
 pub async fn handle_tab(command: TabCommands) -> Result<()> {
@@ -90,6 +104,28 @@ pub async fn handle_tab(command: TabCommands) -> Result<()> {
                 }
             }
         }
+        TabCommands::Animations {
+            name,
+            state,
+            profile,
+        } => {
+            match DaemonClient::send_request(DaemonRequest::SetAnimations {
+                tab_name: name,
+                disabled: state == "off",
+                profile: Some(profile),
+            }) {
+                Ok(DaemonResponse::Success(msg)) => {
+                    println!("{}", msg);
+                }
+                Ok(DaemonResponse::Error(e)) => {
+                    eprintln!("Error: {}", e);
+                }
+                Err(e) => {
+                    eprintln!("Failed to communicate with daemon: {}", e);
+                }
+                _ => {}
+            }
+        }
     }
     Ok(())
 }
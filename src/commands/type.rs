@@ -9,6 +9,8 @@ pub async fn handle_type(
     selector: String,
     text: String,
     clear: bool,
+    key_delay: u64,
+    submit: bool,
     _browser: String,
     profile: Option<String>,
     _no_headless: bool,
@@ -28,6 +30,8 @@ pub async fn handle_type(
         selector: selector.clone(),
         text: text.clone(),
         clear,
+        key_delay,
+        submit,
         profile: profile.clone(),
     };
 
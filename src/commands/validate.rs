@@ -1,26 +1,36 @@
 use anyhow::Result;
+use serde_json::Value;
+use std::path::PathBuf;
 use tracing::info;
 
 use crate::commands::utils;
 use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
-use crate::types::OutputFormat;
+use crate::validation::{self, ReportFormat, ValidationConfig};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_validate(
     url: String,
     check: String,
     _browser: String,
     profile: Option<String>,
     _no_headless: bool,
-    format: OutputFormat,
+    format: ReportFormat,
     tab: Option<String>,
+    config: Option<PathBuf>,
 ) -> Result<()> {
     info!("Validating page {}", url);
+
+    // Catch config and --check mistakes before touching the browser
+    let config = ValidationConfig::discover(config.as_deref())?;
+    config.resolve(&check)?;
+
     utils::require_daemon()?;
 
     let request = DaemonRequest::Validate {
         tab_name: utils::resolve_tab_name(&profile, tab)?,
         url,
         check_type: check,
+        config,
         profile,
     };
 
@@ -38,39 +48,120 @@ pub async fn handle_validate(
     };
 
     match format {
-        OutputFormat::Json => {
+        ReportFormat::Json => {
             println!("{}", serde_json::to_string_pretty(&validation)?);
         }
-        OutputFormat::Simple => {
-            if let Some(summary) = validation.get("summary") {
-                println!("Validation Results:");
-                println!(
-                    "Score: {}/100",
-                    summary.get("score").and_then(|v| v.as_i64()).unwrap_or(0)
-                );
-                println!(
-                    "Accessibility issues: {}",
-                    summary
-                        .get("accessibility_issues")
-                        .and_then(|v| v.as_i64())
-                        .unwrap_or(0)
-                );
-                println!(
-                    "SEO issues: {}",
-                    summary
-                        .get("seo_issues")
-                        .and_then(|v| v.as_i64())
-                        .unwrap_or(0)
-                );
+        ReportFormat::Sarif => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&validation::to_sarif(&validation))?
+            );
+        }
+        ReportFormat::Junit => {
+            print!("{}", validation::to_junit(&validation));
+        }
+        ReportFormat::Simple => print_simple(&validation),
+    }
+    Ok(())
+}
+
+fn print_simple(validation: &Value) {
+    if let Some(summary) = validation.get("summary") {
+        println!("Validation Results:");
+        println!(
+            "Score: {}/100",
+            summary.get("score").and_then(|v| v.as_i64()).unwrap_or(0)
+        );
+        println!(
+            "Accessibility issues: {}",
+            summary
+                .get("accessibility_issues")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0)
+        );
+        println!(
+            "SEO issues: {}",
+            summary
+                .get("seo_issues")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0)
+        );
+        println!(
+            "Performance issues: {}",
+            summary
+                .get("performance_issues")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0)
+        );
+    }
+
+    for category in ["accessibility", "seo", "performance"] {
+        for issue in validation[category].as_array().into_iter().flatten() {
+            print!(
+                "  [{}] {}: {}",
+                issue["severity"].as_str().unwrap_or("?"),
+                issue["rule"].as_str().unwrap_or("?"),
+                issue["message"].as_str().unwrap_or("")
+            );
+            match issue["selector"].as_str() {
+                Some(selector) => println!(" ({})", selector),
+                None => println!(),
+            }
+        }
+    }
+
+    if let Some(contrast) = validation.get("contrast") {
+        println!(
+            "Contrast: {} text elements checked, {} fail AA, {} fail AAA, {} unknown",
+            contrast["checked"].as_u64().unwrap_or(0),
+            contrast["aa"]["failed"].as_u64().unwrap_or(0),
+            contrast["aaa"]["failed"].as_u64().unwrap_or(0),
+            contrast["unknown"].as_u64().unwrap_or(0)
+        );
+    }
+}
+
+/// Print the rule registry (`validate --list-rules`)
+pub fn list_rules(format: ReportFormat) -> Result<()> {
+    match format {
+        ReportFormat::Simple => {
+            println!(
+                "{:<26} {:<14} {:<8} {:<6} Description",
+                "Rule", "Category", "Severity", "WCAG"
+            );
+            println!("{}", "-".repeat(100));
+            for rule in validation::RULES {
                 println!(
-                    "Performance issues: {}",
-                    summary
-                        .get("performance_issues")
-                        .and_then(|v| v.as_i64())
-                        .unwrap_or(0)
+                    "{:<26} {:<14} {:<8} {:<6} {}{}",
+                    rule.id,
+                    rule.category.as_str(),
+                    rule.severity.as_str(),
+                    rule.wcag.unwrap_or("-"),
+                    rule.description,
+                    rule.threshold
+                        .map(|t| format!(" [threshold {}]", t))
+                        .unwrap_or_default()
                 );
             }
         }
+        _ => {
+            let rules: Vec<Value> = validation::RULES
+                .iter()
+                .map(|rule| {
+                    serde_json::json!({
+                        "id": rule.id,
+                        "category": rule.category,
+                        "severity": rule.severity,
+                        "wcag": rule.wcag,
+                        "description": rule.description,
+                        "help_url": rule.help_url,
+                        "threshold": rule.threshold,
+                        "tags": rule.tags,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&rules)?);
+        }
     }
     Ok(())
 }
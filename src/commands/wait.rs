@@ -0,0 +1,81 @@
+use anyhow::Result;
+use serde_json::Value;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::errors::WebprobeError;
+use crate::types::{OutputFormat, WaitCondition};
+
+pub async fn handle_wait(
+    target: Vec<String>,
+    condition: String,
+    timeout: u64,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    // "[url] <selector>": a lone argument is the selector
+    let (url, selector) = match target.as_slice() {
+        [selector] => (String::new(), selector.clone()),
+        [url, selector] => (url.clone(), selector.clone()),
+        _ => anyhow::bail!("Expected [url] <selector>"),
+    };
+    // Reject bad conditions before anything is loaded
+    let parsed = WaitCondition::parse(&condition)?;
+    info!(
+        "Waiting up to {}s for {} to be {}",
+        timeout, selector, parsed
+    );
+
+    utils::require_daemon()?;
+    let tab_name = utils::resolve_tab_name(&profile, tab)?;
+    if url.is_empty() && tab_name.is_empty() {
+        return Err(anyhow::anyhow!("URL is required for one-shot operations"));
+    }
+
+    let request = DaemonRequest::Wait {
+        tab_name,
+        url,
+        selector,
+        timeout,
+        condition,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::WaitResult(outcome) => {
+            if outcome["met"] != true {
+                // The error JSON is the only stdout document, so it carries the outcome
+                return Err(WebprobeError::Timeout(format!(
+                    "waited {}s for '{}' to be {} ({} matching after {} polls)",
+                    timeout,
+                    outcome["selector"].as_str().unwrap_or(""),
+                    outcome["condition"].as_str().unwrap_or(""),
+                    outcome["count"],
+                    outcome["polls"]
+                ))
+                .into());
+            }
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&outcome)?),
+                OutputFormat::Simple => print_simple(&outcome),
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+fn print_simple(outcome: &Value) {
+    println!(
+        "✓ '{}' is {} after {}ms ({} polls, {} matching)",
+        outcome["selector"].as_str().unwrap_or(""),
+        outcome["condition"].as_str().unwrap_or(""),
+        outcome["elapsed_ms"],
+        outcome["polls"],
+        outcome["count"]
+    );
+}
@@ -0,0 +1,151 @@
+use anyhow::Result;
+use serde_json::Value;
+use tracing::info;
+
+use crate::commands::utils;
+use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
+use crate::types::OutputFormat;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_why(
+    url: String,
+    selector: String,
+    property: String,
+    index: Option<usize>,
+    tab: Option<String>,
+    _browser: String,
+    profile: Option<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    info!("Explaining {} on {}", property, selector);
+    utils::require_daemon()?;
+
+    let request = DaemonRequest::Why {
+        tab_name: utils::resolve_tab_name(&profile, tab)?,
+        url,
+        selector,
+        index,
+        property,
+        profile,
+    };
+
+    match DaemonClient::send_request(request)? {
+        DaemonResponse::WhyResult(explanation) => {
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&explanation)?),
+                OutputFormat::Simple => print_simple(&explanation),
+            }
+            Ok(())
+        }
+        DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+        _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+    }
+}
+
+/// One line per declaration: value, where it is declared and its specificity
+fn describe(declaration: &Value) -> String {
+    let mut line = format!(
+        "{}{}",
+        declaration["value"].as_str().unwrap_or(""),
+        if declaration["important"] == true {
+            " !important"
+        } else {
+            ""
+        }
+    );
+    match declaration["origin"].as_str() {
+        Some("inline") => line.push_str("  style attribute"),
+        _ => {
+            line.push_str(&format!(
+                "  {}  {}",
+                declaration["selector"].as_str().unwrap_or(""),
+                location(declaration)
+            ));
+            if let Some(spec) = declaration["specificity"].as_array() {
+                let spec: Vec<String> = spec.iter().map(|n| n.to_string()).collect();
+                line.push_str(&format!("  ({})", spec.join(",")));
+            }
+        }
+    }
+    if let Some(shorthand) = declaration["shorthand"].as_str() {
+        line.push_str(&format!("  via {}", shorthand));
+    }
+    if let Some(layer) = declaration["layer"].as_str() {
+        line.push_str(&format!("  @layer {}", layer));
+    }
+    for condition in declaration["conditions"].as_array().into_iter().flatten() {
+        line.push_str(&format!("  {}", condition.as_str().unwrap_or("")));
+    }
+    line
+}
+
+/// `src/Card.scss:42` through the source map, else the stylesheet and line
+fn location(declaration: &Value) -> String {
+    if let Some(file) = declaration["original"]["file"].as_str() {
+        return format!("{}:{}", file, declaration["original"]["line"]);
+    }
+    let sheet = declaration["stylesheet"].as_str().unwrap_or("");
+    match declaration["line"].as_u64() {
+        Some(line) => format!("{}:{}", sheet, line),
+        None => sheet.to_string(),
+    }
+}
+
+fn marker(declaration: &Value) -> &'static str {
+    if declaration["status"] == "applied" {
+        "✓"
+    } else {
+        "✗"
+    }
+}
+
+fn print_simple(explanation: &Value) {
+    println!("{}", explanation["element"].as_str().unwrap_or(""));
+
+    for property in explanation["properties"].as_array().into_iter().flatten() {
+        println!(
+            "\n{}: {}  ({})",
+            property["property"].as_str().unwrap_or(""),
+            property["computed"].as_str().unwrap_or("?"),
+            property["source"].as_str().unwrap_or("")
+        );
+        for declaration in property["cascade"].as_array().into_iter().flatten() {
+            println!("  {} {}", marker(declaration), describe(declaration));
+        }
+        if let Some(inherited) = property.get("inherited_from") {
+            println!(
+                "  inherited from {}:",
+                inherited["element"].as_str().unwrap_or("")
+            );
+            for declaration in inherited["cascade"].as_array().into_iter().flatten() {
+                println!("    {} {}", marker(declaration), describe(declaration));
+            }
+        }
+        for variable in property["variables"].as_array().into_iter().flatten() {
+            println!(
+                "  {} = {}  {}",
+                variable["name"].as_str().unwrap_or(""),
+                variable["computed"].as_str().unwrap_or("?"),
+                if variable["declared_by"].is_null() {
+                    "(not declared)".to_string()
+                } else {
+                    format!(
+                        "from {} on {}",
+                        describe(&variable["declared_by"]),
+                        variable["element"].as_str().unwrap_or("")
+                    )
+                }
+            );
+        }
+    }
+
+    if let Some(sheets) = explanation["inaccessible_sheets"].as_array()
+        && !sheets.is_empty()
+        && explanation["devtools"] != true
+    {
+        println!(
+            "\nCould not read {} cross-origin stylesheet(s); their rules are missing (Chrome adds them via DevTools)",
+            sheets.len()
+        );
+    }
+}
@@ -45,6 +45,8 @@ mod tests {
             text_content: Some("Test content".to_string()),
             children_count: 0,
             metadata: None,
+            occlusion: None,
+            forced_states: Vec::new(),
         };
 
         assert_eq!(element.selector, ".test");
@@ -230,4 +232,150 @@ mod tests {
         let size_json = serde_json::to_string(&size).unwrap();
         assert!(size_json.contains("100"));
     }
+
+    #[test]
+    fn test_a11y_outline() {
+        use crate::commands::a11y_tree::format_outline;
+        use serde_json::json;
+
+        let tree = json!([
+            {"role": "navigation", "name": "Main", "selector": "nav", "children": [
+                {"role": "link", "name": "Home", "states": {"current": "page"}, "selector": "nav > a"}
+            ]},
+            {"role": "heading", "name": "Products", "level": 1, "selector": "h1"},
+            {"role": "checkbox", "name": "In stock", "states": {"checked": false, "disabled": true}, "selector": "#stock"},
+            {"role": "textbox", "name": "Search", "value": "shoes", "selector": "#q"},
+            {"role": "text", "name": "3 results"}
+        ]);
+
+        let outline = format_outline(&tree);
+        let lines: Vec<&str> = outline.lines().collect();
+        assert_eq!(lines[0], "- navigation \"Main\"");
+        assert_eq!(lines[1], "  - link \"Home\" [current=page]");
+        assert_eq!(lines[2], "- heading \"Products\" [level=1]");
+        assert_eq!(
+            lines[3],
+            "- checkbox \"In stock\" [checked=false, disabled]"
+        );
+        assert_eq!(lines[4], "- textbox \"Search\": shoes");
+        assert_eq!(lines[5], "- text: 3 results");
+    }
+
+    #[test]
+    fn test_perf_ratings() {
+        use crate::commands::perf::{rate_metrics, rating};
+        use serde_json::json;
+
+        assert_eq!(rating("lcp", 2500.0), Some("good"));
+        assert_eq!(rating("lcp", 2501.0), Some("needs-improvement"));
+        assert_eq!(rating("cls", 0.3), Some("poor"));
+        assert_eq!(rating("unknown", 1.0), None);
+
+        let mut metrics = json!({
+            "ttfb": 120.5,
+            "fcp": 900.0,
+            "lcp": {"value": 4200.0, "element": "img.hero"},
+            "cls": {"value": 0.05},
+            "inp": null,
+            "fid": null,
+            "long_tasks": {"count": 3, "total_blocking_time": 350.0}
+        });
+        rate_metrics(&mut metrics);
+
+        let ratings = &metrics["ratings"];
+        assert_eq!(ratings["ttfb"], "good");
+        assert_eq!(ratings["lcp"], "poor");
+        assert_eq!(ratings["cls"], "good");
+        assert_eq!(ratings["total_blocking_time"], "needs-improvement");
+        // Unmeasured metrics get no rating
+        assert!(ratings.get("inp").is_none());
+    }
+
+    #[test]
+    fn test_shifts_summary() {
+        use crate::commands::shifts::summarize;
+        use serde_json::json;
+
+        let source = |selector: &str, dy: i64| {
+            json!({
+                "selector": selector,
+                "previous": {"x": 0, "y": 100, "width": 300, "height": 40},
+                "current": {"x": 0, "y": 100 + dy, "width": 300, "height": 40},
+                "delta": {"x": 0, "y": dy, "width": 0, "height": 0}
+            })
+        };
+        let shifts = vec![
+            json!({
+                "phase": "load http://localhost:3000/",
+                "time": 850,
+                "value": 0.12,
+                "had_recent_input": false,
+                "sources": [source("main > p", 60), source("#banner", -20)]
+            }),
+            json!({
+                "phase": "click #more",
+                "time": 4200,
+                "value": 0.05,
+                "had_recent_input": true,
+                "sources": [source("main > p", 120)]
+            }),
+        ];
+
+        let report = summarize(&shifts);
+        assert_eq!(report["total_shifts"], 2);
+        // Shifts right after input don't count, as in CLS
+        assert_eq!(report["unexpected_score"], 0.12);
+
+        let top = &report["elements"][0];
+        assert_eq!(top["selector"], "main > p");
+        assert_eq!(top["shifts"], 2);
+        assert_eq!(top["max_move"]["y"], 120);
+        assert_eq!(
+            top["phases"],
+            json!(["load http://localhost:3000/", "click #more"])
+        );
+        assert_eq!(report["elements"][1]["max_move"]["y"], -20);
+
+        assert_eq!(summarize(&[])["elements"], json!([]));
+    }
+
+    #[test]
+    fn test_image_bytes_format() {
+        use crate::commands::images::format_bytes;
+
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MB");
+    }
+
+    #[test]
+    fn test_occlusion_description() {
+        use crate::commands::analyze::describe_occlusion;
+
+        let covered = serde_json::json!({
+            "in_viewport_percent": 100.0,
+            "visible_percent": 60.0,
+            "clipped_percent": 0.0,
+            "covered_by": [{"selector": "#bar", "percent": 40.0}]
+        });
+        assert_eq!(
+            describe_occlusion(&covered).unwrap(),
+            "Only 60% visible, covered by #bar (40%)"
+        );
+
+        let offscreen = serde_json::json!({
+            "in_viewport_percent": 50.0,
+            "visible_percent": 50.0,
+            "clipped_percent": 0.0,
+            "covered_by": []
+        });
+        assert_eq!(
+            describe_occlusion(&offscreen).unwrap(),
+            "Only 50% visible, 50% outside the viewport"
+        );
+
+        let visible = serde_json::json!({"visible_percent": 100.0, "covered_by": []});
+        assert!(describe_occlusion(&visible).is_none());
+        assert!(describe_occlusion(&serde_json::Value::Null).is_none());
+    }
 }
@@ -0,0 +1,994 @@
+//! Batch execution of browser operations inside a single tab
+//!
+//! A batch is a flow of steps tagged by `type` (see `flow` for ids, variables,
+//! conditions and loops). The whole batch runs under one tab lock, so no other
+//! command can interleave with its steps.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use tracing::{debug, info};
+
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::browser_manager::TabContext;
+use crate::daemon::flow::{Condition, Flow, Scope, StepControl, interpolate, split_step};
+use crate::keys::KeyChord;
+use crate::types::{
+    ClickOptions, DragMode, DragTarget, FormAction, InspectionDepth, OptionMatch, PseudoState,
+    ScrollUntil, SelectorCount, ViewportSize,
+};
+
+/// A single step in a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BatchStep {
+    /// Navigate to a URL
+    #[serde(alias = "navigate")]
+    Goto { url: String },
+    /// Click an element, optionally with another button, twice, at an
+    /// offset from its top-left corner or with modifier keys held
+    Click {
+        selector: String,
+        #[serde(default)]
+        index: Option<usize>,
+        #[serde(flatten)]
+        options: ClickOptions,
+    },
+    /// Move the mouse over an element
+    Hover {
+        selector: String,
+        #[serde(default)]
+        index: Option<usize>,
+        #[serde(default)]
+        offset: Option<(i64, i64)>,
+    },
+    /// Drag an element onto another element (selector) or a viewport point ("x,y")
+    Drag {
+        source: String,
+        target: String,
+        #[serde(default = "default_drag_steps")]
+        steps: u32,
+        #[serde(default)]
+        mode: DragMode,
+    },
+    /// Type text into an element
+    Type {
+        selector: String,
+        text: String,
+        #[serde(default)]
+        clear: bool,
+        /// Milliseconds between characters
+        #[serde(default)]
+        key_delay: u64,
+        /// Press Enter afterwards
+        #[serde(default)]
+        submit: bool,
+    },
+    /// Press keys, e.g. "Control+K" or "ArrowDown ArrowDown Enter"
+    Press {
+        keys: String,
+        /// Element to focus first
+        #[serde(default)]
+        selector: Option<String>,
+        /// Milliseconds between key presses
+        #[serde(default)]
+        delay: u64,
+    },
+    /// Choose an option in a select element by value (or visible text),
+    /// by label, or several values of a `<select multiple>`
+    Select {
+        selector: String,
+        #[serde(default)]
+        index: Option<usize>,
+        #[serde(default)]
+        value: Option<String>,
+        #[serde(default)]
+        label: Option<String>,
+        #[serde(default)]
+        values: Vec<String>,
+    },
+    /// Check a checkbox or radio button
+    Check { selector: String },
+    /// Uncheck a checkbox
+    Uncheck { selector: String },
+    /// Move a range input to a value
+    #[serde(alias = "set-range")]
+    SetRange { selector: String, value: f64 },
+    /// Choose files (absolute paths) in a file input
+    Upload {
+        selector: String,
+        files: Vec<String>,
+    },
+    /// Wait for an element (timeout in seconds)
+    Wait {
+        selector: String,
+        #[serde(default = "default_wait_timeout")]
+        timeout: u64,
+        #[serde(default = "default_wait_condition")]
+        condition: String,
+    },
+    /// Pause for a fixed amount of time
+    Sleep { milliseconds: u64 },
+    /// Inspect elements
+    Inspect {
+        selector: String,
+        #[serde(default)]
+        all: bool,
+        #[serde(default)]
+        index: Option<usize>,
+        #[serde(default)]
+        expect_one: bool,
+        /// Pseudo-classes to force, e.g. `["hover"]`
+        #[serde(default)]
+        state: Vec<PseudoState>,
+        /// Frames the element must hold still before it is measured
+        #[serde(default)]
+        wait_stable_frames: Option<u32>,
+    },
+    /// Analyze the context around an element
+    Analyze {
+        selector: String,
+        #[serde(default = "default_analyze_focus")]
+        focus: String,
+        #[serde(default)]
+        proximity: Option<u32>,
+        #[serde(default)]
+        index: Option<usize>,
+        #[serde(default)]
+        state: Vec<PseudoState>,
+        #[serde(default)]
+        wait_stable_frames: Option<u32>,
+    },
+    /// Get the box model layout of an element tree
+    Layout {
+        selector: String,
+        #[serde(default = "default_layout_depth")]
+        depth: u8,
+        #[serde(default = "default_layout_max_elements")]
+        max_elements: usize,
+        #[serde(default = "default_layout_wait_stable")]
+        wait_stable: u64,
+        #[serde(default)]
+        detect_shadow: bool,
+    },
+    /// Save a screenshot of the page or an element
+    Screenshot {
+        output: String,
+        #[serde(default)]
+        selector: Option<String>,
+    },
+    /// Find elements by text content
+    #[serde(alias = "find-text")]
+    FindText {
+        text: String,
+        #[serde(default)]
+        element_type: Option<String>,
+        #[serde(default)]
+        fuzzy: bool,
+        #[serde(default)]
+        case_sensitive: bool,
+        #[serde(default)]
+        index: Option<usize>,
+    },
+    /// Evaluate a JavaScript expression
+    Eval { code: String },
+    /// Scroll the page or an element
+    Scroll {
+        #[serde(default)]
+        selector: Option<String>,
+        #[serde(default)]
+        by_x: i32,
+        #[serde(default)]
+        by_y: i32,
+        #[serde(default)]
+        to: Option<String>,
+        /// Keep scrolling to the bottom until this many elements match ("selector:count")
+        #[serde(default)]
+        until_selector_count: Option<String>,
+        /// Keep scrolling to the bottom until the page stops growing for this long (ms)
+        #[serde(default)]
+        until_stable: Option<u64>,
+        #[serde(default)]
+        max_steps: Option<usize>,
+    },
+    /// Check the page state, failing the step if any expectation is not met
+    Assert {
+        #[serde(default)]
+        selector: Option<String>,
+        /// Whether at least one element should match (defaults to true with a selector)
+        #[serde(default)]
+        exists: Option<bool>,
+        /// Whether the first match should be visible
+        #[serde(default)]
+        visible: Option<bool>,
+        /// Text the first match should contain
+        #[serde(default)]
+        text: Option<String>,
+        /// Exact number of matches
+        #[serde(default)]
+        count: Option<usize>,
+        /// Text the current URL should contain
+        #[serde(default)]
+        url: Option<String>,
+    },
+}
+
+fn default_wait_timeout() -> u64 {
+    30
+}
+
+fn default_wait_condition() -> String {
+    "present".to_string()
+}
+
+fn default_analyze_focus() -> String {
+    "all".to_string()
+}
+
+fn default_layout_depth() -> u8 {
+    2
+}
+
+fn default_layout_max_elements() -> usize {
+    100
+}
+
+fn default_layout_wait_stable() -> u64 {
+    500
+}
+
+fn default_drag_steps() -> u32 {
+    10
+}
+
+/// Options that apply to a whole batch
+#[derive(Debug, Clone, Default)]
+pub struct BatchOptions {
+    /// Stop at the first failing step instead of running the rest
+    pub stop_on_error: bool,
+    /// Viewport to emulate, re-applied after every navigation
+    pub viewport: Option<ViewportSize>,
+    /// Run eval steps (the caller passed --unsafe-eval)
+    pub allow_eval: bool,
+}
+
+impl BatchStep {
+    /// The step type as written in the batch file
+    pub fn name(&self) -> &'static str {
+        match self {
+            BatchStep::Goto { .. } => "goto",
+            BatchStep::Click { .. } => "click",
+            BatchStep::Hover { .. } => "hover",
+            BatchStep::Drag { .. } => "drag",
+            BatchStep::Type { .. } => "type",
+            BatchStep::Press { .. } => "press",
+            BatchStep::Select { .. } => "select",
+            BatchStep::Check { .. } => "check",
+            BatchStep::Uncheck { .. } => "uncheck",
+            BatchStep::SetRange { .. } => "set_range",
+            BatchStep::Upload { .. } => "upload",
+            BatchStep::Wait { .. } => "wait",
+            BatchStep::Sleep { .. } => "sleep",
+            BatchStep::Inspect { .. } => "inspect",
+            BatchStep::Analyze { .. } => "analyze",
+            BatchStep::Layout { .. } => "layout",
+            BatchStep::Screenshot { .. } => "screenshot",
+            BatchStep::FindText { .. } => "find_text",
+            BatchStep::Eval { .. } => "eval",
+            BatchStep::Scroll { .. } => "scroll",
+            BatchStep::Assert { .. } => "assert",
+        }
+    }
+
+    /// Execute the step in the given tab, returning its output
+    pub async fn execute(&self, ctx: &TabContext<'_>, options: &BatchOptions) -> Result<Value> {
+        match self {
+            BatchStep::Goto { url } => {
+                ctx.goto(url).await?;
+                if let Some(viewport) = &options.viewport {
+                    ctx.set_viewport(viewport).await?;
+                }
+                Ok(json!({ "url": ctx.current_url().await? }))
+            }
+            BatchStep::Click {
+                selector,
+                index,
+                options,
+            } => {
+                ctx.click_element(selector, *index, options).await?;
+                Ok(json!({ "selector": selector, "index": index }))
+            }
+            BatchStep::Hover {
+                selector,
+                index,
+                offset,
+            } => {
+                ctx.hover(selector, *index, *offset).await?;
+                Ok(json!({ "selector": selector, "index": index }))
+            }
+            BatchStep::Drag {
+                source,
+                target,
+                steps,
+                mode,
+            } => {
+                ctx.drag(source, &DragTarget::parse(target), *steps, *mode)
+                    .await
+            }
+            BatchStep::Type {
+                selector,
+                text,
+                clear,
+                key_delay,
+                submit,
+            } => {
+                ctx.type_text(selector, text, *clear, *key_delay, *submit)
+                    .await?;
+                // Don't echo the text back, it may be a password
+                Ok(json!({ "selector": selector, "characters": text.chars().count() }))
+            }
+            BatchStep::Press {
+                keys,
+                selector,
+                delay,
+            } => {
+                let chords = KeyChord::parse_sequence(keys)?;
+                if chords.is_empty() {
+                    anyhow::bail!("No keys to press");
+                }
+                ctx.press(selector.as_deref(), &chords, *delay).await?;
+                Ok(json!({ "keys": keys, "selector": selector }))
+            }
+            BatchStep::Select {
+                selector,
+                index,
+                value,
+                label,
+                values,
+            } => {
+                let (values, by) = match (value, label) {
+                    (Some(value), None) if values.is_empty() => {
+                        (vec![value.clone()], OptionMatch::Any)
+                    }
+                    (None, Some(label)) if values.is_empty() => {
+                        (vec![label.clone()], OptionMatch::Label)
+                    }
+                    (None, None) if !values.is_empty() => (values.clone(), OptionMatch::Any),
+                    _ => anyhow::bail!("A select step takes one of value, label or values"),
+                };
+                let action = FormAction::Select {
+                    values,
+                    by,
+                    index: *index,
+                };
+                ctx.set_control(selector, &action).await
+            }
+            BatchStep::Check { selector } => {
+                ctx.set_control(selector, &FormAction::Check { checked: true })
+                    .await
+            }
+            BatchStep::Uncheck { selector } => {
+                ctx.set_control(selector, &FormAction::Check { checked: false })
+                    .await
+            }
+            BatchStep::SetRange { selector, value } => {
+                ctx.set_control(selector, &FormAction::SetRange { value: *value })
+                    .await
+            }
+            BatchStep::Upload { selector, files } => {
+                ctx.set_control(
+                    selector,
+                    &FormAction::Upload {
+                        files: files.clone(),
+                    },
+                )
+                .await
+            }
+            BatchStep::Wait {
+                selector,
+                timeout,
+                condition,
+            } => {
+                let start = std::time::Instant::now();
+                if !ctx.wait_for_element(selector, *timeout, condition).await? {
+                    anyhow::bail!(
+                        "Timed out after {}s waiting for '{}' to be {}",
+                        timeout,
+                        selector,
+                        condition
+                    );
+                }
+                Ok(json!({
+                    "selector": selector,
+                    "condition": condition,
+                    "elapsed_ms": start.elapsed().as_millis() as u64,
+                }))
+            }
+            BatchStep::Sleep { milliseconds } => {
+                tokio::time::sleep(tokio::time::Duration::from_millis(*milliseconds)).await;
+                Ok(json!({ "milliseconds": milliseconds }))
+            }
+            BatchStep::Inspect {
+                selector,
+                all,
+                index,
+                expect_one,
+                state,
+                wait_stable_frames,
+            } => {
+                if let Some(frames) = wait_stable_frames {
+                    ctx.wait_for_stable(selector, *frames).await?;
+                }
+                let elements = ctx
+                    .inspect_element(
+                        selector,
+                        InspectionDepth::Shallow,
+                        *all,
+                        *index,
+                        *expect_one,
+                        state,
+                    )
+                    .await?;
+                Ok(serde_json::to_value(elements)?)
+            }
+            BatchStep::Analyze {
+                selector,
+                focus,
+                proximity,
+                index,
+                state,
+                wait_stable_frames,
+            } => {
+                if let Some(frames) = wait_stable_frames {
+                    ctx.wait_for_stable(selector, *frames).await?;
+                }
+                ctx.analyze_context(selector, focus, proximity.unwrap_or(100), *index, state)
+                    .await
+            }
+            BatchStep::Layout {
+                selector,
+                depth,
+                max_elements,
+                wait_stable,
+                detect_shadow,
+            } => {
+                let layout = ctx
+                    .analyze_layout(
+                        selector,
+                        *depth,
+                        *max_elements,
+                        *wait_stable,
+                        *detect_shadow,
+                    )
+                    .await?;
+                Ok(serde_json::to_value(layout)?)
+            }
+            BatchStep::Screenshot { output, selector } => {
+                let data = ctx.screenshot(selector.as_deref(), Some(output)).await?;
+                Ok(json!({ "saved_to": output, "bytes": data.len() }))
+            }
+            BatchStep::FindText {
+                text,
+                element_type,
+                fuzzy,
+                case_sensitive,
+                index,
+            } => {
+                let results = ctx
+                    .find_by_text(text, element_type.as_deref(), *fuzzy, *case_sensitive)
+                    .await?;
+                if results.is_empty() {
+                    anyhow::bail!("No elements found containing text: {}", text);
+                }
+                match index {
+                    Some(idx) => {
+                        let result = results.get(*idx).ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "Index {} out of bounds. Found {} elements containing '{}'",
+                                idx,
+                                results.len(),
+                                text
+                            )
+                        })?;
+                        Ok(serde_json::to_value(result)?)
+                    }
+                    None => Ok(serde_json::to_value(results)?),
+                }
+            }
+            BatchStep::Eval { code } => {
+                if !options.allow_eval {
+                    anyhow::bail!(
+                        "eval steps are disabled. Pass --unsafe-eval to acknowledge running JavaScript"
+                    );
+                }
+                ctx.execute_javascript(code).await
+            }
+            BatchStep::Scroll {
+                until_selector_count,
+                until_stable,
+                max_steps,
+                selector,
+                ..
+            } if until_selector_count.is_some() || until_stable.is_some() => {
+                let defaults = ScrollUntil::default();
+                let until = ScrollUntil {
+                    selector_count: until_selector_count
+                        .as_deref()
+                        .map(SelectorCount::parse)
+                        .transpose()?,
+                    stable_ms: until_stable.unwrap_or(defaults.stable_ms),
+                    max_steps: max_steps.unwrap_or(defaults.max_steps),
+                };
+                ctx.scroll_until(selector.as_deref(), &until).await
+            }
+            BatchStep::Scroll {
+                selector,
+                by_x,
+                by_y,
+                to,
+                ..
+            } => {
+                ctx.scroll(selector.as_deref(), *by_x, *by_y, to.as_deref())
+                    .await?;
+                ctx.execute_javascript("({ x: window.scrollX, y: window.scrollY })")
+                    .await
+            }
+            BatchStep::Assert {
+                selector,
+                exists,
+                visible,
+                text,
+                count,
+                url,
+            } => {
+                let state = ctx
+                    .execute_javascript(&assert_state_script(selector.as_deref()))
+                    .await?;
+                check_assertions(
+                    &state,
+                    selector.as_deref(),
+                    *exists,
+                    *visible,
+                    text.as_deref(),
+                    *count,
+                    url.as_deref(),
+                )?;
+                Ok(state)
+            }
+        }
+    }
+}
+
+/// Build the expression that gathers the page state an `assert` step checks
+fn assert_state_script(selector: Option<&str>) -> String {
+    let selector = serde_json::to_string(&selector).unwrap_or_else(|_| "null".to_string());
+    format!(
+        r#"(() => {{
+            const selector = {selector};
+            const els = selector ? Array.from(document.querySelectorAll(selector)) : [];
+            const first = els[0];
+            let visible = false;
+            if (first) {{
+                const style = window.getComputedStyle(first);
+                visible = first.getClientRects().length > 0
+                    && style.visibility !== 'hidden'
+                    && style.display !== 'none';
+            }}
+            return {{
+                url: window.location.href,
+                count: els.length,
+                visible: visible,
+                text: first ? (first.innerText || first.textContent || '').trim() : null
+            }};
+        }})()"#
+    )
+}
+
+/// Compare the gathered page state against the expectations of an `assert` step
+fn check_assertions(
+    state: &Value,
+    selector: Option<&str>,
+    exists: Option<bool>,
+    visible: Option<bool>,
+    text: Option<&str>,
+    count: Option<usize>,
+    url: Option<&str>,
+) -> Result<()> {
+    let mut failures = Vec::new();
+    let found = state["count"].as_u64().unwrap_or(0) as usize;
+    let target = selector.unwrap_or("");
+
+    // A selector on its own means "this element should exist"
+    let exists = exists.or_else(|| {
+        (selector.is_some() && visible.is_none() && text.is_none() && count.is_none())
+            .then_some(true)
+    });
+
+    if let Some(expected) = exists
+        && (found > 0) != expected
+    {
+        if expected {
+            failures.push(format!("expected '{}' to exist, found none", target));
+        } else {
+            failures.push(format!(
+                "expected '{}' not to exist, found {}",
+                target, found
+            ));
+        }
+    }
+    if let Some(expected) = count
+        && found != expected
+    {
+        failures.push(format!(
+            "expected {} elements matching '{}', found {}",
+            expected, target, found
+        ));
+    }
+    if let Some(expected) = visible
+        && state["visible"].as_bool().unwrap_or(false) != expected
+    {
+        failures.push(format!(
+            "expected '{}' to be {}",
+            target,
+            if expected { "visible" } else { "hidden" }
+        ));
+    }
+    if let Some(expected) = text {
+        let actual = state["text"].as_str().unwrap_or("");
+        if !actual.contains(expected) {
+            failures.push(format!(
+                "expected '{}' to contain text {:?}, got {:?}",
+                target, expected, actual
+            ));
+        }
+    }
+    if let Some(expected) = url {
+        let actual = state["url"].as_str().unwrap_or("");
+        if !actual.contains(expected) {
+            failures.push(format!(
+                "expected URL to contain {:?}, got {:?}",
+                expected, actual
+            ));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Assertion failed: {}", failures.join("; ")))
+    }
+}
+
+/// Run a flow, returning one result object per top-level step that ran
+pub async fn run_flow(ctx: &TabContext<'_>, flow: Flow, options: &BatchOptions) -> Vec<Value> {
+    let options = BatchOptions {
+        stop_on_error: options.stop_on_error || flow.stop_on_error,
+        viewport: options.viewport,
+        allow_eval: options.allow_eval,
+    };
+    let mut scope = Scope {
+        vars: flow.vars,
+        ..Default::default()
+    };
+
+    if let Some(name) = &flow.name {
+        info!("Running flow '{}'", name);
+    }
+
+    let (results, _) = run_steps(ctx, &flow.steps, &mut scope, &options).await;
+    results
+}
+
+/// Run a list of steps, returning their results and whether the run was stopped
+fn run_steps<'a>(
+    ctx: &'a TabContext<'_>,
+    steps: &'a [Value],
+    scope: &'a mut Scope,
+    options: &'a BatchOptions,
+) -> Pin<Box<dyn Future<Output = (Vec<Value>, bool)> + Send + 'a>> {
+    Box::pin(async move {
+        let mut results = Vec::new();
+
+        for (index, raw) in steps.iter().enumerate() {
+            let (entry, failed) = run_step(ctx, index, raw, scope, options).await;
+            results.push(entry);
+
+            if failed && options.stop_on_error {
+                info!("Stopping batch after failed step {}", index);
+                return (results, true);
+            }
+        }
+
+        (results, false)
+    })
+}
+
+/// Run one raw step with its control keys applied
+async fn run_step(
+    ctx: &TabContext<'_>,
+    index: usize,
+    raw: &Value,
+    scope: &mut Scope,
+    options: &BatchOptions,
+) -> (Value, bool) {
+    let step_type = if raw.get("for_each").is_some() {
+        "for_each".to_string()
+    } else {
+        raw.get("type")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+
+    let (control, definition) = match split_step(raw) {
+        Ok(parts) => parts,
+        Err(e) => return (step_result(index, &step_type, Err(e)), true),
+    };
+
+    // Conditional steps are skipped, not failed, when the condition is false
+    if let Some(expr) = &control.condition {
+        match evaluate_condition(ctx, expr, scope).await {
+            Ok(true) => {}
+            Ok(false) => {
+                debug!(
+                    "Skipping batch step {}: condition '{}' is false",
+                    index, expr
+                );
+                let mut entry = json!({
+                    "step": index,
+                    "type": step_type,
+                    "success": true,
+                    "skipped": true,
+                });
+                if let Some(id) = &control.id {
+                    entry["id"] = json!(id);
+                }
+                return (entry, false);
+            }
+            Err(e) => {
+                let e = e.context(format!("Failed to evaluate condition '{}'", expr));
+                return (step_result(index, &step_type, Err(e)), true);
+            }
+        }
+    }
+
+    let (outcome, attempts) = if let Some(selector) = &control.for_each {
+        match run_loop(ctx, selector, &control, scope, options).await {
+            Ok(output) => {
+                let all_passed = output["iterations"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|iteration| iteration.as_array())
+                    .flatten()
+                    .all(|r| r["success"] == true);
+                if !all_passed {
+                    let mut entry = step_result(index, &step_type, Ok(output));
+                    entry["success"] = json!(false);
+                    entry["error"] = json!("One or more loop iterations failed");
+                    if let Some(id) = &control.id {
+                        entry["id"] = json!(id);
+                    }
+                    return (entry, true);
+                }
+                (Ok(output), 1)
+            }
+            Err(e) => (Err(e), 1),
+        }
+    } else {
+        match resolve_step(definition, scope) {
+            Ok(step) => {
+                debug!("Batch step {}: {}", index, step.name());
+                execute_with_policy(ctx, &step, &control, options).await
+            }
+            Err(e) => (Err(e), 1),
+        }
+    };
+
+    if let (Some(id), Ok(output)) = (&control.id, &outcome) {
+        scope.steps.insert(id.clone(), output.clone());
+    }
+
+    let failed = outcome.is_err();
+    let mut entry = step_result(index, &step_type, outcome);
+    if let Some(id) = &control.id {
+        entry["id"] = json!(id);
+    }
+    if attempts > 1 {
+        entry["attempts"] = json!(attempts);
+    }
+    (entry, failed)
+}
+
+/// Fill in a step's `${...}` references and parse it. The type decides what
+/// runs (eval is gated on it), so it is taken as written, never interpolated.
+pub fn resolve_step(mut definition: Map<String, Value>, scope: &Scope) -> Result<BatchStep> {
+    let kind = definition.remove("type");
+    if let Some(Value::String(kind)) = &kind
+        && kind.contains("${")
+    {
+        anyhow::bail!("Step type '{}' can't contain ${{...}} references", kind);
+    }
+
+    let mut resolved = interpolate(&Value::Object(definition), &|reference| {
+        Some(scope.lookup(reference))
+    })?;
+    if let (Some(kind), Some(object)) = (kind, resolved.as_object_mut()) {
+        object.insert("type".to_string(), kind);
+    }
+    serde_json::from_value::<BatchStep>(resolved)
+        .map_err(|e| anyhow::anyhow!("Invalid batch step: {}", e))
+}
+
+/// Execute a step, applying its timeout and retry policy
+async fn execute_with_policy(
+    ctx: &TabContext<'_>,
+    step: &BatchStep,
+    control: &StepControl,
+    options: &BatchOptions,
+) -> (Result<Value>, u32) {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let outcome = match control.timeout_ms {
+            Some(ms) => match tokio::time::timeout(
+                tokio::time::Duration::from_millis(ms),
+                step.execute(ctx, options),
+            )
+            .await
+            {
+                Ok(outcome) => outcome,
+                Err(_) => Err(anyhow::anyhow!("Step timed out after {}ms", ms)),
+            },
+            None => step.execute(ctx, options).await,
+        };
+
+        if outcome.is_ok() || attempt > control.retry {
+            return (outcome, attempt);
+        }
+
+        debug!(
+            "Batch step {} failed (attempt {}/{}), retrying in {}ms",
+            step.name(),
+            attempt,
+            control.retry + 1,
+            control.retry_delay
+        );
+        tokio::time::sleep(tokio::time::Duration::from_millis(control.retry_delay)).await;
+    }
+}
+
+/// Run a loop body once per element matching the selector
+async fn run_loop(
+    ctx: &TabContext<'_>,
+    selector: &str,
+    control: &StepControl,
+    scope: &mut Scope,
+    options: &BatchOptions,
+) -> Result<Value> {
+    let selector = match interpolate(&json!(selector), &|reference| Some(scope.lookup(reference)))?
+    {
+        Value::String(s) => s,
+        other => other.to_string(),
+    };
+    let literal = serde_json::to_string(&selector)?;
+    let texts = ctx
+        .execute_javascript(&format!(
+            "Array.from(document.querySelectorAll({literal})).map(el => (el.innerText || el.textContent || '').trim().slice(0, 200))"
+        ))
+        .await?;
+    let texts = texts.as_array().cloned().unwrap_or_default();
+    let count = texts.len();
+
+    let mut iterations = Vec::new();
+    for (index, text) in texts.into_iter().enumerate() {
+        scope.items.push((
+            control.item_name.clone(),
+            json!({ "index": index, "selector": selector, "text": text }),
+        ));
+        let (results, was_stopped) = run_steps(ctx, &control.body, scope, options).await;
+        scope.items.pop();
+
+        iterations.push(Value::Array(results));
+        if was_stopped {
+            break;
+        }
+    }
+
+    Ok(json!({ "selector": selector, "count": count, "iterations": iterations }))
+}
+
+/// Replay a route's steps unless `check` already holds
+///
+/// SPAs often render the target screen a moment after the last click, so the
+/// predicate is re-checked for a few seconds before the route is declared
+/// broken.
+pub async fn ensure_route(ctx: &TabContext<'_>, check: &str, flow: Flow) -> Result<Value> {
+    // Route files can be edited by hand, so don't trust that define rejected eval
+    if flow.contains_step_type("eval") {
+        anyhow::bail!("Routes can't contain eval steps");
+    }
+    let condition = Condition::parse(check)?;
+    if check_condition(ctx, &condition).await? {
+        return Ok(json!({ "replayed": false }));
+    }
+
+    let options = BatchOptions {
+        stop_on_error: true,
+        viewport: None,
+        allow_eval: false,
+    };
+    let results = run_flow(ctx, flow, &options).await;
+    if let Some(failed) = results.iter().find(|r| r["error"].is_string()) {
+        anyhow::bail!(
+            "Step {} ({}) failed: {}",
+            failed["step"],
+            failed["type"].as_str().unwrap_or("unknown"),
+            failed["error"].as_str().unwrap_or("")
+        );
+    }
+
+    for _ in 0..10 {
+        if check_condition(ctx, &condition).await? {
+            return Ok(json!({ "replayed": true, "steps": results }));
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    }
+    anyhow::bail!("Replayed all steps but '{}' is still false", check)
+}
+
+/// Evaluate an `if:` expression against the current page
+async fn evaluate_condition(ctx: &TabContext<'_>, expr: &str, scope: &Scope) -> Result<bool> {
+    let expr = match interpolate(&json!(expr), &|reference| Some(scope.lookup(reference)))? {
+        Value::String(s) => s,
+        other => other.to_string(),
+    };
+    check_condition(ctx, &Condition::parse(&expr)?).await
+}
+
+fn check_condition<'a>(
+    ctx: &'a TabContext<'_>,
+    condition: &'a Condition,
+) -> Pin<Box<dyn Future<Output = Result<bool>> + Send + 'a>> {
+    Box::pin(async move {
+        match condition {
+            Condition::Literal(value) => Ok(*value),
+            Condition::Not(inner) => Ok(!check_condition(ctx, inner).await?),
+            Condition::UrlContains(text) => Ok(ctx.current_url().await?.contains(text.as_str())),
+            Condition::Exists(selector) | Condition::Visible(selector) => {
+                let state = ctx
+                    .execute_javascript(&assert_state_script(Some(selector)))
+                    .await?;
+                if matches!(condition, Condition::Exists(_)) {
+                    Ok(state["count"].as_u64().unwrap_or(0) > 0)
+                } else {
+                    Ok(state["visible"].as_bool().unwrap_or(false))
+                }
+            }
+        }
+    })
+}
+
+/// Build the result object reported for one step
+pub fn step_result(index: usize, step_type: &str, outcome: Result<Value>) -> Value {
+    match outcome {
+        Ok(output) => json!({
+            "step": index,
+            "type": step_type,
+            "success": true,
+            "result": output,
+        }),
+        Err(e) => json!({
+            "step": index,
+            "type": step_type,
+            "success": false,
+            "error": e.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+#[path = "batch_test.rs"]
+mod batch_test;
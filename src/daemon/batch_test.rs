@@ -0,0 +1,249 @@
+#[cfg(test)]
+mod tests {
+    use crate::daemon::batch::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_batch_steps() {
+        let step: BatchStep =
+            serde_json::from_value(json!({"type": "navigate", "url": "http://localhost"})).unwrap();
+        assert!(matches!(step, BatchStep::Goto { ref url } if url == "http://localhost"));
+
+        let step: BatchStep =
+            serde_json::from_value(json!({"type": "find-text", "text": "Sign in"})).unwrap();
+        assert_eq!(step.name(), "find_text");
+
+        let step: BatchStep =
+            serde_json::from_value(json!({"type": "select", "selector": "#plan", "value": "pro"}))
+                .unwrap();
+        assert!(
+            matches!(step, BatchStep::Select { ref value, .. } if value.as_deref() == Some("pro"))
+        );
+
+        let step: BatchStep = serde_json::from_value(json!({
+            "type": "click",
+            "selector": "li",
+            "button": "right",
+            "offset": [10, 5],
+            "modifiers": ["shift"]
+        }))
+        .unwrap();
+        if let BatchStep::Click { options, .. } = step {
+            assert_eq!(options.button, crate::types::MouseButton::Right);
+            assert!(!options.double);
+            assert_eq!(options.offset, Some((10, 5)));
+            assert_eq!(options.modifiers, vec![crate::types::Modifier::Shift]);
+        } else {
+            panic!("Expected click step");
+        }
+
+        let step: BatchStep = serde_json::from_value(
+            json!({"type": "select", "selector": "#size", "label": "Large"}),
+        )
+        .unwrap();
+        assert!(matches!(
+            step,
+            BatchStep::Select {
+                value: None,
+                label: Some(_),
+                ..
+            }
+        ));
+        let step: BatchStep = serde_json::from_value(
+            json!({"type": "set-range", "selector": "#volume", "value": 40}),
+        )
+        .unwrap();
+        assert_eq!(step.name(), "set_range");
+
+        let step: BatchStep =
+            serde_json::from_value(json!({"type": "press", "keys": "Control+K"})).unwrap();
+        assert!(matches!(
+            step,
+            BatchStep::Press {
+                selector: None,
+                delay: 0,
+                ..
+            }
+        ));
+
+        let step: BatchStep = serde_json::from_value(
+            json!({"type": "inspect", "selector": ".menu", "state": ["hover", "focus-within"]}),
+        )
+        .unwrap();
+        if let BatchStep::Inspect { state, .. } = step {
+            assert_eq!(
+                state,
+                vec![
+                    crate::types::PseudoState::Hover,
+                    crate::types::PseudoState::FocusWithin
+                ]
+            );
+        } else {
+            panic!("Expected inspect step");
+        }
+
+        let step: BatchStep =
+            serde_json::from_value(json!({"type": "drag", "source": "#card-1", "target": "#done"}))
+                .unwrap();
+        assert!(matches!(step, BatchStep::Drag { steps: 10, .. }));
+
+        let step: BatchStep =
+            serde_json::from_value(json!({"type": "wait", "selector": "h1"})).unwrap();
+        if let BatchStep::Wait {
+            timeout, condition, ..
+        } = step
+        {
+            assert_eq!(timeout, 30);
+            assert_eq!(condition, "present");
+        } else {
+            panic!("Expected wait step");
+        }
+
+        let step: BatchStep = serde_json::from_value(json!({
+            "type": "scroll",
+            "until_selector_count": ".feed-item:30",
+            "max_steps": 20
+        }))
+        .unwrap();
+        if let BatchStep::Scroll {
+            until_selector_count,
+            until_stable,
+            max_steps,
+            by_y,
+            ..
+        } = step
+        {
+            assert_eq!(until_selector_count.as_deref(), Some(".feed-item:30"));
+            assert_eq!(until_stable, None);
+            assert_eq!(max_steps, Some(20));
+            assert_eq!(by_y, 0);
+        } else {
+            panic!("Expected scroll step");
+        }
+
+        let step: BatchStep = serde_json::from_value(json!({
+            "type": "inspect",
+            "selector": ".toast",
+            "wait_stable_frames": 5
+        }))
+        .unwrap();
+        if let BatchStep::Inspect {
+            wait_stable_frames,
+            all,
+            ..
+        } = step
+        {
+            assert_eq!(wait_stable_frames, Some(5));
+            assert!(!all);
+        } else {
+            panic!("Expected inspect step");
+        }
+
+        let step: BatchStep =
+            serde_json::from_value(json!({"type": "layout", "selector": ".card"})).unwrap();
+        if let BatchStep::Layout {
+            depth,
+            max_elements,
+            ..
+        } = step
+        {
+            assert_eq!(depth, 2);
+            assert_eq!(max_elements, 100);
+        } else {
+            panic!("Expected layout step");
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_steps() {
+        let err = serde_json::from_value::<BatchStep>(json!({"type": "dance"})).unwrap_err();
+        assert!(err.to_string().contains("unknown variant"));
+
+        let err = serde_json::from_value::<BatchStep>(json!({"type": "click"})).unwrap_err();
+        assert!(err.to_string().contains("selector"));
+    }
+
+    #[test]
+    fn test_resolve_step_type_is_literal() {
+        let scope = crate::daemon::flow::Scope {
+            vars: json!({"kind": "eval", "selector": "#save"})
+                .as_object()
+                .cloned()
+                .unwrap(),
+            ..Default::default()
+        };
+        let definition = |value: serde_json::Value| value.as_object().cloned().unwrap();
+
+        let step = resolve_step(
+            definition(json!({"type": "click", "selector": "${vars.selector}"})),
+            &scope,
+        )
+        .unwrap();
+        assert!(matches!(step, BatchStep::Click { ref selector, .. } if selector == "#save"));
+
+        // A templated type could turn any step into eval
+        let err = resolve_step(
+            definition(json!({"type": "${vars.kind}", "code": "1"})),
+            &scope,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("can't contain"));
+    }
+
+    #[test]
+    fn test_step_result_shape() {
+        let ok = step_result(0, "goto", Ok(json!({"url": "http://localhost/"})));
+        assert_eq!(ok["success"], true);
+        assert_eq!(ok["result"]["url"], "http://localhost/");
+        assert!(ok.get("error").is_none());
+
+        let failed = step_result(3, "click", Err(anyhow::anyhow!("Element not found")));
+        assert_eq!(failed["step"], 3);
+        assert_eq!(failed["success"], false);
+        assert_eq!(failed["error"], "Element not found");
+    }
+
+    #[test]
+    fn test_check_assertions() {
+        let state = json!({
+            "url": "http://localhost/dashboard",
+            "count": 2,
+            "visible": true,
+            "text": "Welcome back"
+        });
+
+        // A bare selector asserts existence
+        assert!(check_assertions(&state, Some(".card"), None, None, None, None, None).is_ok());
+        assert!(
+            check_assertions(
+                &state,
+                Some(".card"),
+                None,
+                Some(true),
+                Some("Welcome"),
+                Some(2),
+                Some("/dashboard")
+            )
+            .is_ok()
+        );
+
+        let err = check_assertions(
+            &state,
+            Some(".card"),
+            Some(false),
+            None,
+            None,
+            Some(3),
+            None,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.starts_with("Assertion failed"));
+        assert!(err.contains("not to exist"));
+        assert!(err.contains("expected 3 elements"));
+
+        let missing =
+            json!({"url": "http://localhost/", "count": 0, "visible": false, "text": null});
+        assert!(check_assertions(&missing, Some(".card"), None, None, None, None, None).is_err());
+    }
+}
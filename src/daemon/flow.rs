@@ -0,0 +1,366 @@
+//! Flow files: batches with step ids, variables, conditions and loops
+//!
+//! A flow is either a plain JSON array of steps or an object with `steps`
+//! and optional `vars`. Steps may carry control keys (`id`, `if`, `retry`,
+//! `retry_delay`, `timeout_ms`, `for_each`, `as`, `steps`) that the batch
+//! runner strips before parsing the step itself. String values can reference
+//! `${env.NAME}`, `${vars.name}`, `${steps.<id>.<field>}` and, inside loops,
+//! `${item.index}` / `${item.text}`.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Keys that control how a step runs rather than what it does
+const CONTROL_KEYS: &[&str] = &[
+    "id",
+    "if",
+    "retry",
+    "retry_delay",
+    "timeout_ms",
+    "for_each",
+    "as",
+    "steps",
+];
+
+/// A parsed flow
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Flow {
+    /// Optional human-readable name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Variables available as `${vars.<name>}`
+    #[serde(default)]
+    pub vars: Map<String, Value>,
+    /// Stop at the first failing step
+    #[serde(default)]
+    pub stop_on_error: bool,
+    /// Raw steps, parsed one at a time as they run
+    pub steps: Vec<Value>,
+}
+
+impl Flow {
+    /// Build a flow from a JSON value (a bare array of steps or a flow object)
+    pub fn from_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Array(steps) => Ok(Flow {
+                steps,
+                ..Default::default()
+            }),
+            Value::Object(_) => serde_json::from_value(value).context("Invalid flow"),
+            _ => anyhow::bail!("A batch must be an array of steps or an object with 'steps'"),
+        }
+    }
+
+    /// Parse flow text as JSON, falling back to YAML
+    pub fn parse_text(text: &str, yaml: bool) -> Result<Value> {
+        if !yaml && let Ok(value) = serde_json::from_str(text) {
+            return Ok(value);
+        }
+        serde_yaml::from_str(text).context("Failed to parse batch as JSON or YAML")
+    }
+
+    /// Whether any step (including loop bodies) has the given type
+    pub fn contains_step_type(&self, step_type: &str) -> bool {
+        fn walk(steps: &[Value], step_type: &str) -> bool {
+            steps.iter().any(|step| {
+                step["type"] == step_type
+                    || step["steps"]
+                        .as_array()
+                        .is_some_and(|body| walk(body, step_type))
+            })
+        }
+        walk(&self.steps, step_type)
+    }
+}
+
+/// How a single step should be run
+#[derive(Debug, Clone, Default)]
+pub struct StepControl {
+    pub id: Option<String>,
+    pub condition: Option<String>,
+    pub retry: u32,
+    pub retry_delay: u64,
+    pub timeout_ms: Option<u64>,
+    pub for_each: Option<String>,
+    pub item_name: String,
+    pub body: Vec<Value>,
+}
+
+/// Split a raw step into its control keys and the step definition itself
+pub fn split_step(raw: &Value) -> Result<(StepControl, Map<String, Value>)> {
+    let object = raw
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("Each step must be an object"))?;
+
+    let mut step = object.clone();
+    for key in CONTROL_KEYS {
+        step.remove(*key);
+    }
+
+    let as_string = |key: &str| -> Result<Option<String>> {
+        match object.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            Some(Value::Bool(b)) => Ok(Some(b.to_string())),
+            Some(other) => anyhow::bail!("'{}' must be a string, got {}", key, other),
+        }
+    };
+    let as_u64 = |key: &str| -> Result<Option<u64>> {
+        match object.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(v) => v
+                .as_u64()
+                .map(Some)
+                .ok_or_else(|| anyhow::anyhow!("'{}' must be a non-negative integer", key)),
+        }
+    };
+
+    let body = match object.get("steps") {
+        None => Vec::new(),
+        Some(Value::Array(steps)) => steps.clone(),
+        Some(_) => anyhow::bail!("'steps' must be an array"),
+    };
+    let for_each = as_string("for_each")?;
+    if for_each.is_none() && !body.is_empty() {
+        anyhow::bail!("Nested 'steps' are only allowed together with 'for_each'");
+    }
+
+    let control = StepControl {
+        id: as_string("id")?,
+        condition: as_string("if")?,
+        retry: as_u64("retry")?.unwrap_or(0) as u32,
+        retry_delay: as_u64("retry_delay")?.unwrap_or(500),
+        timeout_ms: as_u64("timeout_ms")?,
+        for_each,
+        item_name: as_string("as")?.unwrap_or_else(|| "item".to_string()),
+        body,
+    };
+
+    Ok((control, step))
+}
+
+/// Variables visible to a step
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    /// Flow-level variables
+    pub vars: Map<String, Value>,
+    /// Outputs of completed steps, by id
+    pub steps: Map<String, Value>,
+    /// Loop items, innermost last
+    pub items: Vec<(String, Value)>,
+}
+
+impl Scope {
+    /// Resolve a `${...}` reference
+    pub fn lookup(&self, reference: &str) -> Result<Value> {
+        let (namespace, path) = reference.split_once('.').unwrap_or((reference, ""));
+        match namespace {
+            // The client expands these with expand_env; reading them here would
+            // hand the daemon's own environment to the page
+            "env" => Err(anyhow::anyhow!(
+                "env references must be expanded by the client ('${{{}}}')",
+                reference
+            )),
+            "vars" => lookup_path(&self.vars, path)
+                .ok_or_else(|| anyhow::anyhow!("Unknown variable '${{{}}}'", reference)),
+            "steps" => lookup_path(&self.steps, path).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown step output '${{{}}}' (did that step run and succeed?)",
+                    reference
+                )
+            }),
+            name => {
+                let (_, item) = self
+                    .items
+                    .iter()
+                    .rev()
+                    .find(|(item_name, _)| item_name == name)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown variable '${{{}}}'", reference))?;
+                if path.is_empty() {
+                    Ok(item.clone())
+                } else {
+                    item.as_object()
+                        .and_then(|map| lookup_path(map, path))
+                        .ok_or_else(|| anyhow::anyhow!("Unknown variable '${{{}}}'", reference))
+                }
+            }
+        }
+    }
+}
+
+/// Follow a dotted path (`a.b.0.c`) through a JSON object
+fn lookup_path(root: &Map<String, Value>, path: &str) -> Option<Value> {
+    let mut segments = path.split('.');
+    let mut current = root.get(segments.next()?)?;
+    for segment in segments {
+        current = match current {
+            Value::Object(map) => map.get(segment)?,
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current.clone())
+}
+
+/// Replace `${...}` references throughout a JSON value
+///
+/// The resolver returns `None` to leave a reference untouched. A string that
+/// is exactly one reference takes the referenced value's JSON type, so
+/// `"index": "${item.index}"` stays a number.
+pub fn interpolate(
+    value: &Value,
+    resolve: &dyn Fn(&str) -> Option<Result<Value>>,
+) -> Result<Value> {
+    match value {
+        Value::String(s) => interpolate_str(s, resolve),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| interpolate(item, resolve))
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Array),
+        Value::Object(map) => {
+            let mut out = Map::new();
+            for (key, item) in map {
+                out.insert(key.clone(), interpolate(item, resolve)?);
+            }
+            Ok(Value::Object(out))
+        }
+        other => Ok(other.clone()),
+    }
+}
+
+fn interpolate_str(s: &str, resolve: &dyn Fn(&str) -> Option<Result<Value>>) -> Result<Value> {
+    if let Some(inner) = s.strip_prefix("${").and_then(|rest| rest.strip_suffix('}'))
+        && !inner.contains('}')
+    {
+        return match resolve(inner.trim()) {
+            Some(value) => value,
+            None => Ok(Value::String(s.to_string())),
+        };
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        match resolve(after[..end].trim()) {
+            Some(value) => match value? {
+                Value::String(text) => out.push_str(&text),
+                other => out.push_str(&other.to_string()),
+            },
+            None => out.push_str(&rest[start..start + end + 3]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(Value::String(out))
+}
+
+/// Expand `${env.NAME}` references, leaving all others for the daemon
+pub fn expand_env(value: &Value) -> Result<Value> {
+    interpolate(value, &|reference| {
+        reference.starts_with("env.").then(|| {
+            let name = &reference[4..];
+            std::env::var(name)
+                .map(Value::String)
+                .map_err(|_| anyhow::anyhow!("Environment variable '{}' is not set", name))
+        })
+    })
+}
+
+/// Make relative `upload` step paths absolute against `base`, the caller's
+/// directory, since the daemon resolves them from wherever it was started
+pub fn resolve_upload_paths(value: &mut Value, base: &std::path::Path) {
+    match value {
+        Value::Array(steps) => {
+            for step in steps {
+                resolve_upload_paths(step, base);
+            }
+        }
+        Value::Object(step) => {
+            if step.get("type").and_then(Value::as_str) == Some("upload")
+                && let Some(Value::Array(files)) = step.get_mut("files")
+            {
+                for file in files {
+                    // Interpolated paths are only known when the step runs
+                    if let Value::String(path) = file
+                        && !path.contains("${")
+                        && std::path::Path::new(path.as_str()).is_relative()
+                    {
+                        *path = base.join(path.as_str()).to_string_lossy().into_owned();
+                    }
+                }
+            }
+            if let Some(steps) = step.get_mut("steps") {
+                resolve_upload_paths(steps, base);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Condition for an `if:` key
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// `exists('.selector')`
+    Exists(String),
+    /// `visible('.selector')`
+    Visible(String),
+    /// `url_contains('/checkout')`
+    UrlContains(String),
+    /// `true` / `false`, usually produced by a variable
+    Literal(bool),
+    /// `!condition` or `not condition`
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    /// Parse a condition expression
+    pub fn parse(expr: &str) -> Result<Self> {
+        let expr = expr.trim();
+        if let Some(rest) = expr.strip_prefix('!') {
+            return Ok(Condition::Not(Box::new(Condition::parse(rest)?)));
+        }
+        if let Some(rest) = expr.strip_prefix("not ") {
+            return Ok(Condition::Not(Box::new(Condition::parse(rest)?)));
+        }
+        match expr {
+            "true" => return Ok(Condition::Literal(true)),
+            "false" | "" => return Ok(Condition::Literal(false)),
+            _ => {}
+        }
+
+        let (name, arg) = expr
+            .strip_suffix(')')
+            .and_then(|e| e.split_once('('))
+            .ok_or_else(|| anyhow::anyhow!("Invalid condition: {}", expr))?;
+        let arg = arg.trim();
+        let arg = arg
+            .strip_prefix('\'')
+            .and_then(|a| a.strip_suffix('\''))
+            .or_else(|| arg.strip_prefix('"').and_then(|a| a.strip_suffix('"')))
+            .unwrap_or(arg)
+            .to_string();
+
+        match name.trim() {
+            "exists" => Ok(Condition::Exists(arg)),
+            "visible" => Ok(Condition::Visible(arg)),
+            "url_contains" => Ok(Condition::UrlContains(arg)),
+            other => anyhow::bail!(
+                "Unknown condition '{}'. Use exists(), visible() or url_contains()",
+                other
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "flow_test.rs"]
+mod flow_test;
@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use crate::daemon::flow::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flow_from_array_and_object() {
+        let flow = Flow::from_value(json!([{"type": "goto", "url": "http://localhost"}])).unwrap();
+        assert_eq!(flow.steps.len(), 1);
+        assert!(flow.vars.is_empty());
+
+        let flow = Flow::from_value(json!({
+            "name": "checkout",
+            "vars": {"email": "dev@example.com"},
+            "stop_on_error": true,
+            "steps": [{"type": "click", "selector": ".buy"}]
+        }))
+        .unwrap();
+        assert_eq!(flow.name.as_deref(), Some("checkout"));
+        assert!(flow.stop_on_error);
+        assert_eq!(flow.vars["email"], "dev@example.com");
+
+        assert!(Flow::from_value(json!("goto")).is_err());
+    }
+
+    #[test]
+    fn test_parse_yaml_flow() {
+        let yaml = r#"
+name: login
+steps:
+  - id: open
+    type: goto
+    url: http://localhost:3000/login
+  - type: click
+    selector: .cookie-banner .accept
+    if: exists('.cookie-banner')
+  - for_each: .product-card
+    steps:
+      - type: eval
+        code: "1 + 1"
+"#;
+        let value = Flow::parse_text(yaml, true).unwrap();
+        let flow = Flow::from_value(value).unwrap();
+        assert_eq!(flow.steps.len(), 3);
+        assert_eq!(flow.steps[1]["if"], "exists('.cookie-banner')");
+        assert!(flow.contains_step_type("eval"));
+        assert!(!flow.contains_step_type("screenshot"));
+
+        // JSON is tried first when the file isn't known to be YAML
+        let value = Flow::parse_text(r#"[{"type": "sleep", "milliseconds": 10}]"#, false).unwrap();
+        assert!(value.is_array());
+    }
+
+    #[test]
+    fn test_split_step() {
+        let (control, step) = split_step(&json!({
+            "id": "login",
+            "type": "click",
+            "selector": "#submit",
+            "if": "visible('#submit')",
+            "retry": 2,
+            "timeout_ms": 5000
+        }))
+        .unwrap();
+        assert_eq!(control.id.as_deref(), Some("login"));
+        assert_eq!(control.condition.as_deref(), Some("visible('#submit')"));
+        assert_eq!(control.retry, 2);
+        assert_eq!(control.retry_delay, 500);
+        assert_eq!(control.timeout_ms, Some(5000));
+        assert_eq!(step.len(), 2);
+        assert!(step.contains_key("selector"));
+
+        let (control, _) = split_step(&json!({
+            "for_each": ".row",
+            "as": "row",
+            "steps": [{"type": "click", "selector": ".row"}]
+        }))
+        .unwrap();
+        assert_eq!(control.for_each.as_deref(), Some(".row"));
+        assert_eq!(control.item_name, "row");
+        assert_eq!(control.body.len(), 1);
+
+        assert!(split_step(&json!({"type": "click", "steps": [{}]})).is_err());
+        assert!(split_step(&json!({"type": "click", "retry": "twice"})).is_err());
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let scope = Scope {
+            vars: json!({"email": "dev@example.com"})
+                .as_object()
+                .unwrap()
+                .clone(),
+            steps: json!({"login": {"url": "http://localhost/home", "items": [{"id": 7}]}})
+                .as_object()
+                .unwrap()
+                .clone(),
+            items: vec![("item".to_string(), json!({"index": 2, "text": "Row"}))],
+        };
+        let resolve = |reference: &str| Some(scope.lookup(reference));
+
+        let step = json!({
+            "type": "type",
+            "text": "${vars.email}",
+            "url": "Back to ${steps.login.url}!",
+            "index": "${item.index}",
+            "id": "${steps.login.items.0.id}"
+        });
+        let resolved = interpolate(&step, &resolve).unwrap();
+        assert_eq!(resolved["text"], "dev@example.com");
+        assert_eq!(resolved["url"], "Back to http://localhost/home!");
+        assert_eq!(resolved["index"], 2);
+        assert_eq!(resolved["id"], 7);
+
+        let err = interpolate(&json!("${steps.missing.url}"), &resolve).unwrap_err();
+        assert!(err.to_string().contains("steps.missing.url"));
+
+        // The daemon never reads its own environment
+        let err = interpolate(&json!("${env.HOME}"), &resolve).unwrap_err();
+        assert!(err.to_string().contains("expanded by the client"));
+
+        // Unterminated references are left as-is
+        assert_eq!(
+            interpolate(&json!("cost ${5"), &resolve).unwrap(),
+            "cost ${5"
+        );
+    }
+
+    #[test]
+    fn test_expand_env_leaves_other_references() {
+        // SAFETY: test-only variable that no other test reads
+        unsafe { std::env::set_var("WEBPROBE_FLOW_TEST_USER", "alice") };
+        let value = expand_env(&json!({
+            "text": "${env.WEBPROBE_FLOW_TEST_USER}",
+            "url": "${steps.login.url}"
+        }))
+        .unwrap();
+        assert_eq!(value["text"], "alice");
+        assert_eq!(value["url"], "${steps.login.url}");
+
+        assert!(expand_env(&json!("${env.WEBPROBE_FLOW_TEST_UNSET}")).is_err());
+    }
+
+    #[test]
+    fn test_resolve_upload_paths() {
+        let mut value = json!({"steps": [
+            {"type": "upload", "selector": "#cv", "files": ["cv.pdf", "/tmp/a.png", "${vars.file}"]},
+            {"type": "click", "selector": "a", "files": ["x"]},
+            {"for_each": "li", "steps": [{"type": "upload", "selector": "#f", "files": ["b.txt"]}]}
+        ]});
+        resolve_upload_paths(&mut value, std::path::Path::new("/home/dev"));
+
+        assert_eq!(
+            value["steps"][0]["files"],
+            json!(["/home/dev/cv.pdf", "/tmp/a.png", "${vars.file}"])
+        );
+        assert_eq!(value["steps"][1]["files"], json!(["x"]));
+        assert_eq!(
+            value["steps"][2]["steps"][0]["files"],
+            json!(["/home/dev/b.txt"])
+        );
+    }
+
+    #[test]
+    fn test_parse_condition() {
+        assert_eq!(
+            Condition::parse("exists('.cookie-banner')").unwrap(),
+            Condition::Exists(".cookie-banner".to_string())
+        );
+        assert_eq!(
+            Condition::parse("!visible(\"#modal\")").unwrap(),
+            Condition::Not(Box::new(Condition::Visible("#modal".to_string())))
+        );
+        assert_eq!(
+            Condition::parse("not url_contains('/login')").unwrap(),
+            Condition::Not(Box::new(Condition::UrlContains("/login".to_string())))
+        );
+        assert_eq!(Condition::parse("true").unwrap(), Condition::Literal(true));
+        assert!(Condition::parse("hovered('.x')").is_err());
+        assert!(Condition::parse("exists('.x'").is_err());
+    }
+}
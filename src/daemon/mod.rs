@@ -1,4 +1,5 @@
 use crate::browser_manager::BrowserManager;
+use crate::keys::KeyChord;
 use crate::types::{CoreProfile, ElementInfo, InspectionDepth, LayoutInfo, Profile, ViewportSize};
 use crate::webdriver::{BrowserType, ConsoleMessage};
 use anyhow::{Context, Result};
@@ -7,7 +8,6 @@ use interprocess::local_socket::{
     traits::{ListenerExt, Stream as StreamTrait},
 };
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 use std::collections::HashMap;
 use std::fs;
 use std::io::{BufRead, BufReader, Write};
@@ -16,6 +16,10 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::{error, info, warn};
 
+pub mod batch;
+pub mod flow;
+pub mod record;
+
 /// Daemon that runs in the background and maintains browser profiles
 pub struct Daemon {
     _auth_token: String,
@@ -31,6 +35,7 @@ pub struct Daemon {
     last_cleanup: chrono::DateTime<chrono::Utc>,
 
     // Counter for generating unique temporary profile names
+    #[allow(dead_code)]
     temp_profile_counter: u64,
 }
 
@@ -65,6 +70,11 @@ pub enum DaemonRequest {
         all: bool,
         index: Option<usize>,
         expect_one: bool,
+        #[serde(default)]
+        states: Vec<crate::types::PseudoState>,
+        /// Frames the element must hold still before it is measured
+        #[serde(default)]
+        wait_stable_frames: Option<u32>,
         profile: Option<String>,
     },
     Type {
@@ -73,6 +83,21 @@ pub enum DaemonRequest {
         selector: String,
         text: String,
         clear: bool,
+        #[serde(default)]
+        key_delay: u64,
+        #[serde(default)]
+        submit: bool,
+        profile: Option<String>,
+    },
+    Press {
+        tab_name: String,
+        url: Option<String>,
+        /// Element to focus first; otherwise keys go to whatever has focus
+        selector: Option<String>,
+        /// Chords like "Control+K", pressed in order
+        keys: Vec<String>,
+        #[serde(default)]
+        delay: u64,
         profile: Option<String>,
     },
     Click {
@@ -80,6 +105,16 @@ pub enum DaemonRequest {
         url: Option<String>,
         selector: String,
         index: Option<usize>,
+        #[serde(default)]
+        options: crate::types::ClickOptions,
+        profile: Option<String>,
+    },
+    Hover {
+        tab_name: String,
+        url: Option<String>,
+        selector: String,
+        index: Option<usize>,
+        offset: Option<(i64, i64)>,
         profile: Option<String>,
     },
     Scroll {
@@ -89,6 +124,19 @@ pub enum DaemonRequest {
         by_x: i32,
         by_y: i32,
         to: Option<String>,
+        /// Keep scrolling to the bottom until this is met
+        #[serde(default)]
+        until: Option<crate::types::ScrollUntil>,
+        profile: Option<String>,
+    },
+    ScrollProbe {
+        tab_name: String,
+        url: Option<String>,
+        selector: String,
+        positions: Vec<crate::types::ScrollPosition>,
+        /// Frames the element must hold still before each measurement
+        #[serde(default)]
+        wait_stable_frames: Option<u32>,
         profile: Option<String>,
     },
     Analyze {
@@ -98,6 +146,11 @@ pub enum DaemonRequest {
         focus: String,
         proximity: Option<u32>,
         index: Option<usize>,
+        #[serde(default)]
+        states: Vec<crate::types::PseudoState>,
+        /// Frames the element must hold still before it is measured
+        #[serde(default)]
+        wait_stable_frames: Option<u32>,
         profile: Option<String>,
     },
     Layout {
@@ -136,6 +189,78 @@ pub enum DaemonRequest {
         context: Option<String>,
         profile: Option<String>,
     },
+    A11yTree {
+        tab_name: String,
+        url: String,
+        selector: Option<String>,
+        max_nodes: usize,
+        interactive_only: bool,
+        profile: Option<String>,
+    },
+    Perf {
+        tab_name: String,
+        url: String,
+        interact: Option<String>,
+        settle_ms: u64,
+        profile: Option<String>,
+    },
+    KeyboardAudit {
+        tab_name: String,
+        url: String,
+        max_tabs: usize,
+        profile: Option<String>,
+    },
+    Why {
+        tab_name: String,
+        url: String,
+        selector: String,
+        index: Option<usize>,
+        property: String,
+        profile: Option<String>,
+    },
+    Images {
+        tab_name: String,
+        url: String,
+        tolerance: f64,
+        profile: Option<String>,
+    },
+    Drag {
+        tab_name: String,
+        url: String,
+        source: String,
+        target: crate::types::DragTarget,
+        steps: u32,
+        #[serde(default)]
+        mode: crate::types::DragMode,
+        profile: Option<String>,
+    },
+    Form {
+        tab_name: String,
+        url: Option<String>,
+        selector: String,
+        action: crate::types::FormAction,
+        profile: Option<String>,
+    },
+    FillForm {
+        tab_name: String,
+        url: Option<String>,
+        form: Option<String>,
+        data: serde_json::Map<String, serde_json::Value>,
+        submit: bool,
+        /// Directory relative file paths are resolved against
+        files_base: Option<String>,
+        profile: Option<String>,
+    },
+    LayoutShifts {
+        tab_name: String,
+        clear: bool,
+        profile: Option<String>,
+    },
+    SetAnimations {
+        tab_name: String,
+        disabled: bool,
+        profile: Option<String>,
+    },
     FindText {
         tab_name: String,
         url: String,
@@ -168,6 +293,30 @@ pub enum DaemonRequest {
     Batch {
         tab_name: String,
         commands: String,
+        stop_on_error: bool,
+        viewport: Option<String>,
+        /// Run eval steps; without it they fail
+        #[serde(default)]
+        unsafe_eval: bool,
+        profile: Option<String>,
+    },
+    StartRecording {
+        tab_name: String,
+        url: Option<String>,
+        profile: Option<String>,
+    },
+    PollRecording {
+        tab_name: String,
+        profile: Option<String>,
+    },
+    StopRecording {
+        tab_name: String,
+        profile: Option<String>,
+    },
+    EnsureRoute {
+        tab_name: String,
+        check: String,
+        steps: String,
         profile: Option<String>,
     },
     Screenshot {
@@ -196,6 +345,8 @@ pub enum DaemonRequest {
         tab_name: String,
         url: String,
         check_type: String,
+        #[serde(default)]
+        config: crate::validation::ValidationConfig,
         profile: Option<String>,
     },
     Compare {
@@ -204,6 +355,9 @@ pub enum DaemonRequest {
         url2: String,
         mode: String,
         selector: Option<String>,
+        /// Frames the compared element must hold still before each capture
+        #[serde(default)]
+        wait_stable_frames: Option<u32>,
         profile: Option<String>,
     },
 
@@ -245,15 +399,26 @@ pub enum DaemonResponse {
     InspectResult(Vec<ElementInfo>, Option<Vec<ConsoleMessage>>),
     AnalyzeResult(serde_json::Value),
     LayoutResult(LayoutInfo),
-    WaitResult(bool),
+    WaitResult(serde_json::Value),
     HtmlResult(String),
     EvalResult(serde_json::Value),
     DetectResult(serde_json::Value),
+    A11yTreeResult(serde_json::Value),
+    KeyboardAuditResult(serde_json::Value),
+    PerfResult(serde_json::Value),
+    LayoutShiftsResult(Vec<serde_json::Value>),
+    ImagesResult(serde_json::Value),
+    DragResult(serde_json::Value),
+    ScrollResult(serde_json::Value),
+    FormResult(serde_json::Value),
+    WhyResult(serde_json::Value),
     FindTextResult(Vec<crate::types::TextSearchResult>),
     WaitIdleResult(Vec<String>),
     WaitNavigationResult(String),
     StatusResult(serde_json::Value),
     BatchResult(Vec<serde_json::Value>),
+    RecordedEvents(Vec<serde_json::Value>),
+    RouteResult(serde_json::Value),
     ScreenshotResult { saved_to: String, bytes: usize },
     IframeResult(Vec<ElementInfo>),
     DiagnoseResult(serde_json::Value),
@@ -287,6 +452,9 @@ pub struct ProfileMetadata {
     pub viewport: Option<ViewportSize>,
     pub last_accessed: Option<chrono::DateTime<chrono::Utc>>,
     pub tabs_count: usize,
+    /// Tabs start with CSS transitions and animations off
+    #[serde(default)]
+    pub disable_animations: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -296,6 +464,9 @@ pub struct ProfileConfig {
     pub headless: bool,
     pub persist_cookies: bool,
     pub persist_storage: bool,
+    /// Tabs start with CSS transitions and animations off
+    #[serde(default)]
+    pub disable_animations: bool,
 }
 
 impl Default for ProfileConfig {
@@ -306,12 +477,14 @@ impl Default for ProfileConfig {
             headless: true,
             persist_cookies: true,
             persist_storage: true,
+            disable_animations: false,
         }
     }
 }
 
 /// Represents the complete state of a profile including browser, tabs, and storage
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct ProfileState {
     /// The browser manager for this profile
     pub browser: BrowserManager,
@@ -328,13 +501,14 @@ pub struct ProfileState {
 impl ProfileState {
     /// Create a new profile state
     pub async fn new(config: &ProfileConfig, profile_name: Option<String>) -> Result<Self> {
-        let browser = BrowserManager::new(
+        let mut browser = BrowserManager::new(
             config.browser_type,
             profile_name,
             config.viewport,
             config.headless,
         )
         .await?;
+        browser.set_animations_disabled(config.disable_animations);
 
         Ok(Self {
             browser,
@@ -346,6 +520,7 @@ impl ProfileState {
     }
 
     /// Create a temporary profile state that will be cleaned up after use
+    #[allow(dead_code)]
     pub async fn new_temporary(browser_type: BrowserType) -> Result<Self> {
         let config = ProfileConfig {
             browser_type,
@@ -363,6 +538,7 @@ impl ProfileState {
 
 /// Simple tab metadata (without full TabInfo)
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct TabMetadata {
     pub url: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -389,11 +565,13 @@ impl Daemon {
     }
 
     /// Check if custom browser exists (compatibility layer)
+    #[allow(dead_code)]
     fn custom_browsers_contains(&self, name: &str) -> bool {
         self.profiles.contains_key(name) && name != "default" && name != "oneshot"
     }
 
     /// Get or create custom browser (compatibility layer)
+    #[allow(dead_code)]
     async fn get_or_create_custom_browser(&mut self, name: &str) -> Result<&mut BrowserManager> {
         if name == "default" || name == "oneshot" {
             return Err(anyhow::anyhow!("Cannot use reserved profile names"));
@@ -606,6 +784,7 @@ impl Daemon {
                 browser_type: metadata.browser_type,
                 viewport: metadata.viewport,
                 headless: true,
+                disable_animations: metadata.disable_animations,
                 ..Default::default()
             };
 
@@ -625,6 +804,7 @@ impl Daemon {
     }
 
     /// Create a temporary profile that will be cleaned up after use
+    #[allow(dead_code)]
     async fn create_temp_profile(&mut self) -> Result<String> {
         self.temp_profile_counter += 1;
         let name = format!("temp-{}", self.temp_profile_counter);
@@ -638,6 +818,7 @@ impl Daemon {
     }
 
     /// Clean up a temporary profile
+    #[allow(dead_code)]
     async fn cleanup_temp_profile(&mut self, name: &str) {
         if let Some(profile) = self.profiles.remove(name)
             && profile.is_temporary
@@ -782,6 +963,7 @@ impl Daemon {
                 url,
                 selector,
                 index,
+                options,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -807,6 +989,7 @@ impl Daemon {
                 // Capture variables needed in the closures
                 let url_clone = url.clone();
                 let selector_clone = selector.clone();
+                let options_clone = options.clone();
 
                 // Use the right method based on whether it's one-shot
                 let result = if is_oneshot {
@@ -819,7 +1002,8 @@ impl Daemon {
                                     ctx.goto(&url).await?;
                                 }
                                 // Perform the click
-                                ctx.click_element(&selector_clone, index).await
+                                ctx.click_element(&selector_clone, index, &options_clone)
+                                    .await
                             })
                         })
                         .await
@@ -832,6 +1016,7 @@ impl Daemon {
                     // Capture for the closure
                     let url_clone2 = url.clone();
                     let selector_clone2 = selector.clone();
+                    let options_clone2 = options.clone();
 
                     // Use with_tab for persistent operations
                     browser
@@ -842,7 +1027,8 @@ impl Daemon {
                                     ctx.goto(&url).await?;
                                 }
                                 // Perform the click
-                                ctx.click_element(&selector_clone2, index).await
+                                ctx.click_element(&selector_clone2, index, &options_clone2)
+                                    .await
                             })
                         })
                         .await
@@ -854,12 +1040,112 @@ impl Daemon {
                 }
             }
 
+            DaemonRequest::Hover {
+                tab_name,
+                url,
+                selector,
+                index,
+                offset,
+                profile,
+            } => {
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+                // The hover state only matters in a tab that stays open
+                if tab_name.is_empty() {
+                    return DaemonResponse::Error(
+                        "Hover requires a persistent tab (use --profile/--tab)".to_string(),
+                    );
+                }
+
+                let browser = match self.get_browser(profile).await {
+                    Ok(b) => b,
+                    Err(e) => {
+                        return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                    }
+                };
+                if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                    return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                }
+
+                let selector_clone = selector.clone();
+                let result = browser
+                    .with_tab(&tab_name, move |ctx| {
+                        Box::pin(async move {
+                            if let Some(url) = url {
+                                ctx.goto(&url).await?;
+                            }
+                            ctx.hover(&selector_clone, index, offset).await
+                        })
+                    })
+                    .await;
+
+                match result {
+                    Ok(_) => {
+                        DaemonResponse::Success(format!("Hovering over element: {}", selector))
+                    }
+                    Err(e) => DaemonResponse::Error(format!("Failed to hover: {}", e)),
+                }
+            }
+
+            DaemonRequest::Press {
+                tab_name,
+                url,
+                selector,
+                keys,
+                delay,
+                profile,
+            } => {
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+                // Keys go to whatever has focus, which only a kept tab remembers
+                if tab_name.is_empty() {
+                    return DaemonResponse::Error(
+                        "Press requires a persistent tab (use --profile/--tab)".to_string(),
+                    );
+                }
+                let chords = match KeyChord::parse_sequence(&keys.join(" ")) {
+                    Ok(chords) if !chords.is_empty() => chords,
+                    Ok(_) => return DaemonResponse::Error("No keys to press".to_string()),
+                    Err(e) => return DaemonResponse::Error(e.to_string()),
+                };
+
+                let browser = match self.get_browser(profile).await {
+                    Ok(b) => b,
+                    Err(e) => {
+                        return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                    }
+                };
+                if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                    return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                }
+
+                let result = browser
+                    .with_tab(&tab_name, move |ctx| {
+                        Box::pin(async move {
+                            if let Some(url) = url {
+                                ctx.goto(&url).await?;
+                            }
+                            ctx.press(selector.as_deref(), &chords, delay).await
+                        })
+                    })
+                    .await;
+
+                match result {
+                    Ok(_) => DaemonResponse::Success(format!("Pressed {}", keys.join(" "))),
+                    Err(e) => DaemonResponse::Error(format!("Failed to press keys: {}", e)),
+                }
+            }
+
             DaemonRequest::Type {
                 tab_name,
                 url,
                 selector,
                 text,
                 clear,
+                key_delay,
+                submit,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -898,7 +1184,14 @@ impl Daemon {
                                     ctx.goto(&url).await?;
                                 }
                                 // Type the text
-                                ctx.type_text(&selector_clone, &text_clone, clear).await
+                                ctx.type_text(
+                                    &selector_clone,
+                                    &text_clone,
+                                    clear,
+                                    key_delay,
+                                    submit,
+                                )
+                                .await
                             })
                         })
                         .await
@@ -922,7 +1215,14 @@ impl Daemon {
                                     ctx.goto(&url).await?;
                                 }
                                 // Type the text
-                                ctx.type_text(&selector_clone2, &text_clone2, clear).await
+                                ctx.type_text(
+                                    &selector_clone2,
+                                    &text_clone2,
+                                    clear,
+                                    key_delay,
+                                    submit,
+                                )
+                                .await
                             })
                         })
                         .await
@@ -941,6 +1241,8 @@ impl Daemon {
                 all,
                 index,
                 expect_one,
+                states,
+                wait_stable_frames,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -966,6 +1268,7 @@ impl Daemon {
                 // Capture variables for the async block
                 let url_clone = url.clone();
                 let selector_clone = selector.clone();
+                let states_clone = states.clone();
 
                 // Use the right method based on whether it's one-shot
                 let result = if is_oneshot {
@@ -977,6 +1280,9 @@ impl Daemon {
                                 if !url_clone.is_empty() {
                                     ctx.goto(&url_clone).await?;
                                 }
+                                if let Some(frames) = wait_stable_frames {
+                                    ctx.wait_for_stable(&selector_clone, frames).await?;
+                                }
                                 // Perform the inspection
                                 ctx.inspect_element(
                                     &selector_clone,
@@ -984,6 +1290,7 @@ impl Daemon {
                                     all,
                                     index,
                                     expect_one,
+                                    &states_clone,
                                 )
                                 .await
                             })
@@ -1001,6 +1308,7 @@ impl Daemon {
                     // Capture for the closure
                     let url_clone2 = url.clone();
                     let selector_clone2 = selector.clone();
+                    let states_clone2 = states.clone();
 
                     // Use with_tab for persistent operations
                     browser
@@ -1010,6 +1318,9 @@ impl Daemon {
                                 if !url_clone2.is_empty() {
                                     ctx.goto(&url_clone2).await?;
                                 }
+                                if let Some(frames) = wait_stable_frames {
+                                    ctx.wait_for_stable(&selector_clone2, frames).await?;
+                                }
                                 // Perform the inspection
                                 ctx.inspect_element(
                                     &selector_clone2,
@@ -1017,6 +1328,7 @@ impl Daemon {
                                     all,
                                     index,
                                     expect_one,
+                                    &states_clone2,
                                 )
                                 .await
                             })
@@ -1037,6 +1349,7 @@ impl Daemon {
                 by_x,
                 by_y,
                 to,
+                until,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -1072,27 +1385,37 @@ impl Daemon {
                 }
 
                 // Perform scroll
-                match browser
-                    .browser()
-                    .scroll("", selector.as_deref(), by_x, by_y, to.as_deref())
-                    .await
-                {
-                    Ok(_) => {
+                let result = match &until {
+                    Some(until) => browser
+                        .browser()
+                        .scroll_until(selector.as_deref(), until)
+                        .await
+                        .map(DaemonResponse::ScrollResult),
+                    None => browser
+                        .browser()
+                        .scroll("", selector.as_deref(), by_x, by_y, to.as_deref())
+                        .await
+                        .map(|_| DaemonResponse::Success("Scrolled successfully".to_string())),
+                };
+                match result {
+                    Ok(response) => {
                         if is_oneshot
                             && let Err(e) = browser.cleanup_if_temporary(&actual_tab_name).await
                         {
                             warn!("Failed to cleanup temporary tab: {}", e);
                         }
-                        DaemonResponse::Success("Scrolled successfully".to_string())
+                        response
                     }
                     Err(e) => DaemonResponse::Error(format!("Failed to scroll: {}", e)),
                 }
             }
 
-            DaemonRequest::Eval {
+            DaemonRequest::ScrollProbe {
                 tab_name,
                 url,
-                code,
+                selector,
+                positions,
+                wait_stable_frames,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -1102,11 +1425,6 @@ impl Daemon {
 
                 // Determine if this is a one-shot operation
                 let is_oneshot = tab_name.is_empty();
-                let actual_tab_name = if is_oneshot {
-                    format!("oneshot-{}", uuid::Uuid::new_v4())
-                } else {
-                    tab_name.clone()
-                };
 
                 // Get the appropriate browser manager
                 let browser = if is_oneshot {
@@ -1120,45 +1438,49 @@ impl Daemon {
                     }
                 };
 
-                // Get or create the tab
-                if let Err(e) = browser.get_or_create_tab(&actual_tab_name).await {
-                    return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
-                }
-
-                // Mark as temporary if needed
-                if is_oneshot {
-                    browser.mark_tab_temporary(&actual_tab_name).await;
-                }
+                // Scrolling and measuring happen under one tab lock, so nothing
+                // else moves the page in between
+                let result = if is_oneshot {
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                if let Some(url) = url {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.scroll_probe(&selector, &positions, wait_stable_frames)
+                                    .await
+                            })
+                        })
+                        .await
+                } else {
+                    // Create tab if needed
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
 
-                // Navigate if URL is provided
-                if let Some(url) = url
-                    && !url.is_empty()
-                    && let Err(e) = browser.goto(&url).await
-                {
-                    return DaemonResponse::Error(format!("Failed to navigate: {}", e));
-                }
+                    browser
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move {
+                                if let Some(url) = url {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.scroll_probe(&selector, &positions, wait_stable_frames)
+                                    .await
+                            })
+                        })
+                        .await
+                };
 
-                // Execute JavaScript
-                match browser.browser().execute_javascript(Some(""), &code).await {
-                    Ok(result) => {
-                        if is_oneshot
-                            && let Err(e) = browser.cleanup_if_temporary(&actual_tab_name).await
-                        {
-                            warn!("Failed to cleanup temporary tab: {}", e);
-                        }
-                        DaemonResponse::EvalResult(result)
-                    }
-                    Err(e) => DaemonResponse::Error(format!("Failed to execute script: {}", e)),
+                match result {
+                    Ok(probe) => DaemonResponse::ScrollResult(probe),
+                    Err(e) => DaemonResponse::Error(format!("Failed to probe scrolling: {}", e)),
                 }
             }
 
-            DaemonRequest::Analyze {
+            DaemonRequest::Eval {
                 tab_name,
                 url,
-                selector,
-                focus,
-                proximity,
-                index,
+                code,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -1197,38 +1519,36 @@ impl Daemon {
                 }
 
                 // Navigate if URL is provided
-                if !url.is_empty()
+                if let Some(url) = url
+                    && !url.is_empty()
                     && let Err(e) = browser.goto(&url).await
                 {
                     return DaemonResponse::Error(format!("Failed to navigate: {}", e));
                 }
 
-                // Perform analysis
-                match browser
-                    .browser()
-                    .analyze_context("", &selector, &focus, proximity.unwrap_or(100), index)
-                    .await
-                {
+                // Execute JavaScript
+                match browser.browser().execute_javascript(Some(""), &code).await {
                     Ok(result) => {
                         if is_oneshot
                             && let Err(e) = browser.cleanup_if_temporary(&actual_tab_name).await
                         {
                             warn!("Failed to cleanup temporary tab: {}", e);
                         }
-                        DaemonResponse::AnalyzeResult(result)
+                        DaemonResponse::EvalResult(result)
                     }
-                    Err(e) => DaemonResponse::Error(format!("Failed to analyze: {}", e)),
+                    Err(e) => DaemonResponse::Error(format!("Failed to execute script: {}", e)),
                 }
             }
 
-            DaemonRequest::Layout {
+            DaemonRequest::Analyze {
                 tab_name,
                 url,
                 selector,
-                depth,
-                max_elements,
-                wait_stable,
-                detect_shadow,
+                focus,
+                proximity,
+                index,
+                states,
+                wait_stable_frames,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -1238,11 +1558,6 @@ impl Daemon {
 
                 // Determine if this is a one-shot operation
                 let is_oneshot = tab_name.is_empty();
-                let actual_tab_name = if is_oneshot {
-                    format!("oneshot-{}", uuid::Uuid::new_v4())
-                } else {
-                    tab_name.clone()
-                };
 
                 // Get the appropriate browser manager
                 let browser = if is_oneshot {
@@ -1256,10 +1571,87 @@ impl Daemon {
                     }
                 };
 
-                // Get or create the tab
-                if let Err(e) = browser.get_or_create_tab(&actual_tab_name).await {
-                    return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
-                }
+                let proximity = proximity.unwrap_or(100);
+                let result = if is_oneshot {
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                if let Some(frames) = wait_stable_frames {
+                                    ctx.wait_for_stable(&selector, frames).await?;
+                                }
+                                ctx.analyze_context(&selector, &focus, proximity, index, &states)
+                                    .await
+                            })
+                        })
+                        .await
+                } else {
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
+                    browser
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move {
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                if let Some(frames) = wait_stable_frames {
+                                    ctx.wait_for_stable(&selector, frames).await?;
+                                }
+                                ctx.analyze_context(&selector, &focus, proximity, index, &states)
+                                    .await
+                            })
+                        })
+                        .await
+                };
+
+                match result {
+                    Ok(result) => DaemonResponse::AnalyzeResult(result),
+                    Err(e) => DaemonResponse::Error(format!("Failed to analyze: {}", e)),
+                }
+            }
+
+            DaemonRequest::Layout {
+                tab_name,
+                url,
+                selector,
+                depth,
+                max_elements,
+                wait_stable,
+                detect_shadow,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // Determine if this is a one-shot operation
+                let is_oneshot = tab_name.is_empty();
+                let actual_tab_name = if is_oneshot {
+                    format!("oneshot-{}", uuid::Uuid::new_v4())
+                } else {
+                    tab_name.clone()
+                };
+
+                // Get the appropriate browser manager
+                let browser = if is_oneshot {
+                    self.oneshot_browser()
+                } else {
+                    match self.get_browser(profile).await {
+                        Ok(b) => b,
+                        Err(e) => {
+                            return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                        }
+                    }
+                };
+
+                // Get or create the tab
+                if let Err(e) = browser.get_or_create_tab(&actual_tab_name).await {
+                    return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                }
 
                 // Mark as temporary if needed
                 if is_oneshot {
@@ -1471,10 +1863,629 @@ impl Daemon {
                 }
             }
 
-            DaemonRequest::Html {
+            DaemonRequest::Html {
+                tab_name,
+                url,
+                selector,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // Determine if this is a one-shot operation
+                let is_oneshot = tab_name.is_empty();
+
+                // Get the appropriate browser manager
+                let browser = if is_oneshot {
+                    self.oneshot_browser()
+                } else {
+                    match self.get_browser(profile).await {
+                        Ok(b) => b,
+                        Err(e) => {
+                            return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                        }
+                    }
+                };
+
+                // Use the right method based on whether it's one-shot
+                let result = if is_oneshot {
+                    // Create a temp tab name for one-shot
+                    let temp_tab = format!("oneshot-{}", uuid::Uuid::new_v4());
+                    browser.get_or_create_tab(&temp_tab).await.ok();
+                    browser.mark_tab_temporary(&temp_tab).await;
+
+                    // Navigate and get HTML
+                    if !url.is_empty()
+                        && let Err(e) = browser.goto(&url).await
+                    {
+                        return DaemonResponse::Error(format!("Failed to navigate: {}", e));
+                    }
+
+                    let html_result = browser
+                        .browser()
+                        .get_page_html("", selector.as_deref())
+                        .await;
+                    if let Err(e) = browser.cleanup_if_temporary(&temp_tab).await {
+                        warn!("Failed to cleanup temporary tab: {}", e);
+                    }
+                    html_result
+                } else {
+                    // Get or create the tab
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
+
+                    // Navigate if URL is provided
+                    if !url.is_empty()
+                        && let Err(e) = browser.goto(&url).await
+                    {
+                        return DaemonResponse::Error(format!("Failed to navigate: {}", e));
+                    }
+
+                    // Get HTML
+                    browser
+                        .browser()
+                        .get_page_html("", selector.as_deref())
+                        .await
+                };
+
+                match result {
+                    Ok(html) => DaemonResponse::HtmlResult(html),
+                    Err(e) => DaemonResponse::Error(format!("Failed to get HTML: {}", e)),
+                }
+            }
+
+            DaemonRequest::Wait {
+                tab_name,
+                url,
+                selector,
+                timeout,
+                condition,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                let condition = match crate::types::WaitCondition::parse(&condition) {
+                    Ok(condition) => condition,
+                    Err(e) => return DaemonResponse::Error(e.to_string()),
+                };
+
+                // Determine if this is a one-shot operation
+                let is_oneshot = tab_name.is_empty();
+
+                // Get the appropriate browser manager
+                let browser = if is_oneshot {
+                    self.oneshot_browser()
+                } else {
+                    match self.get_browser(profile).await {
+                        Ok(b) => b,
+                        Err(e) => {
+                            return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                        }
+                    }
+                };
+
+                // Use the right method based on whether it's one-shot
+                let result = if is_oneshot {
+                    // Create a temp tab name for one-shot
+                    let temp_tab = format!("oneshot-{}", uuid::Uuid::new_v4());
+                    browser.get_or_create_tab(&temp_tab).await.ok();
+                    browser.mark_tab_temporary(&temp_tab).await;
+
+                    // Navigate and wait
+                    if !url.is_empty()
+                        && let Err(e) = browser.goto(&url).await
+                    {
+                        return DaemonResponse::Error(format!("Failed to navigate: {}", e));
+                    }
+
+                    let wait_result = browser
+                        .browser()
+                        .wait_for(&selector, &condition, timeout)
+                        .await;
+                    if let Err(e) = browser.cleanup_if_temporary(&temp_tab).await {
+                        warn!("Failed to cleanup temporary tab: {}", e);
+                    }
+                    wait_result
+                } else {
+                    // Get or create the tab
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
+
+                    // Navigate if URL is provided
+                    if !url.is_empty()
+                        && let Err(e) = browser.goto(&url).await
+                    {
+                        return DaemonResponse::Error(format!("Failed to navigate: {}", e));
+                    }
+
+                    // Wait for element
+                    browser
+                        .browser()
+                        .wait_for(&selector, &condition, timeout)
+                        .await
+                };
+
+                match result {
+                    Ok(outcome) => DaemonResponse::WaitResult(outcome),
+                    Err(e) => DaemonResponse::Error(format!("Failed to wait for element: {}", e)),
+                }
+            }
+
+            DaemonRequest::A11yTree {
+                tab_name,
+                url,
+                selector,
+                max_nodes,
+                interactive_only,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // Determine if this is a one-shot operation
+                let is_oneshot = tab_name.is_empty();
+
+                // Get the appropriate browser manager
+                let browser = if is_oneshot {
+                    self.oneshot_browser()
+                } else {
+                    match self.get_browser(profile).await {
+                        Ok(b) => b,
+                        Err(e) => {
+                            return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                        }
+                    }
+                };
+
+                let result = if is_oneshot {
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                // Navigate if URL provided and not empty
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.accessibility_tree(
+                                    selector.as_deref(),
+                                    max_nodes,
+                                    interactive_only,
+                                )
+                                .await
+                            })
+                        })
+                        .await
+                } else {
+                    // Create tab if needed
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
+
+                    browser
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move {
+                                // Navigate if URL provided and not empty
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.accessibility_tree(
+                                    selector.as_deref(),
+                                    max_nodes,
+                                    interactive_only,
+                                )
+                                .await
+                            })
+                        })
+                        .await
+                };
+
+                match result {
+                    Ok(tree) => DaemonResponse::A11yTreeResult(tree),
+                    Err(e) => {
+                        DaemonResponse::Error(format!("Failed to build accessibility tree: {}", e))
+                    }
+                }
+            }
+
+            DaemonRequest::Perf {
+                tab_name,
+                url,
+                interact,
+                settle_ms,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // Determine if this is a one-shot operation
+                let is_oneshot = tab_name.is_empty();
+
+                // Get the appropriate browser manager
+                let browser = if is_oneshot {
+                    self.oneshot_browser()
+                } else {
+                    match self.get_browser(profile).await {
+                        Ok(b) => b,
+                        Err(e) => {
+                            return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                        }
+                    }
+                };
+
+                // Navigation happens inside the measurement so the observers
+                // can be registered first
+                let result = if is_oneshot {
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                ctx.performance_metrics(&url, interact.as_deref(), settle_ms)
+                                    .await
+                            })
+                        })
+                        .await
+                } else {
+                    // Create tab if needed
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
+
+                    browser
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move {
+                                ctx.performance_metrics(&url, interact.as_deref(), settle_ms)
+                                    .await
+                            })
+                        })
+                        .await
+                };
+
+                match result {
+                    Ok(metrics) => DaemonResponse::PerfResult(metrics),
+                    Err(e) => {
+                        DaemonResponse::Error(format!("Failed to measure performance: {}", e))
+                    }
+                }
+            }
+
+            DaemonRequest::LayoutShifts {
+                tab_name,
+                clear,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // Shifts are collected by earlier commands in the same tab
+                if tab_name.is_empty() {
+                    return DaemonResponse::Error(
+                        "Layout shifts are recorded per tab (use --profile/--tab)".to_string(),
+                    );
+                }
+
+                let browser = match self.get_browser(profile).await {
+                    Ok(b) => b,
+                    Err(e) => {
+                        return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                    }
+                };
+
+                match browser
+                    .with_tab(&tab_name, move |ctx| {
+                        Box::pin(async move { Ok(ctx.layout_shifts(clear).await) })
+                    })
+                    .await
+                {
+                    Ok(shifts) => DaemonResponse::LayoutShiftsResult(shifts),
+                    Err(e) => DaemonResponse::Error(format!("Failed to read layout shifts: {}", e)),
+                }
+            }
+
+            DaemonRequest::SetAnimations {
+                tab_name,
+                disabled,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // One-shot tabs are gone before the setting could matter
+                if tab_name.is_empty() {
+                    return DaemonResponse::Error(
+                        "Animations are set per tab (use --profile/--tab)".to_string(),
+                    );
+                }
+
+                let browser = match self.get_browser(profile).await {
+                    Ok(b) => b,
+                    Err(e) => {
+                        return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                    }
+                };
+
+                match browser.set_tab_animations(&tab_name, disabled).await {
+                    Ok(()) => DaemonResponse::Success(format!(
+                        "Animations {} in tab '{}'",
+                        if disabled { "disabled" } else { "enabled" },
+                        tab_name
+                    )),
+                    Err(e) => DaemonResponse::Error(format!("Failed to set animations: {}", e)),
+                }
+            }
+
+            DaemonRequest::KeyboardAudit {
+                tab_name,
+                url,
+                max_tabs,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // Determine if this is a one-shot operation
+                let is_oneshot = tab_name.is_empty();
+
+                // Get the appropriate browser manager
+                let browser = if is_oneshot {
+                    self.oneshot_browser()
+                } else {
+                    match self.get_browser(profile).await {
+                        Ok(b) => b,
+                        Err(e) => {
+                            return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                        }
+                    }
+                };
+
+                let result = if is_oneshot {
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                // Navigate if URL provided and not empty
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.keyboard_audit(max_tabs).await
+                            })
+                        })
+                        .await
+                } else {
+                    // Create tab if needed
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
+
+                    browser
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move {
+                                // Navigate if URL provided and not empty
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.keyboard_audit(max_tabs).await
+                            })
+                        })
+                        .await
+                };
+
+                match result {
+                    Ok(audit) => DaemonResponse::KeyboardAuditResult(audit),
+                    Err(e) => DaemonResponse::Error(format!("Failed to run keyboard audit: {}", e)),
+                }
+            }
+
+            DaemonRequest::Why {
+                tab_name,
+                url,
+                selector,
+                index,
+                property,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // Determine if this is a one-shot operation
+                let is_oneshot = tab_name.is_empty();
+
+                // Get the appropriate browser manager
+                let browser = if is_oneshot {
+                    self.oneshot_browser()
+                } else {
+                    match self.get_browser(profile).await {
+                        Ok(b) => b,
+                        Err(e) => {
+                            return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                        }
+                    }
+                };
+
+                let result = if is_oneshot {
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                // Navigate if URL provided and not empty
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.explain_cascade(&selector, index, &property).await
+                            })
+                        })
+                        .await
+                } else {
+                    // Create tab if needed
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
+
+                    browser
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move {
+                                // Navigate if URL provided and not empty
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.explain_cascade(&selector, index, &property).await
+                            })
+                        })
+                        .await
+                };
+
+                match result {
+                    Ok(explanation) => DaemonResponse::WhyResult(explanation),
+                    Err(e) => DaemonResponse::Error(format!("Failed to explain cascade: {}", e)),
+                }
+            }
+
+            DaemonRequest::Images {
+                tab_name,
+                url,
+                tolerance,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // Determine if this is a one-shot operation
+                let is_oneshot = tab_name.is_empty();
+
+                // Get the appropriate browser manager
+                let browser = if is_oneshot {
+                    self.oneshot_browser()
+                } else {
+                    match self.get_browser(profile).await {
+                        Ok(b) => b,
+                        Err(e) => {
+                            return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                        }
+                    }
+                };
+
+                let result = if is_oneshot {
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                // Navigate if URL provided and not empty
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.image_audit(tolerance).await
+                            })
+                        })
+                        .await
+                } else {
+                    // Create tab if needed
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
+
+                    browser
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move {
+                                // Navigate if URL provided and not empty
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.image_audit(tolerance).await
+                            })
+                        })
+                        .await
+                };
+
+                match result {
+                    Ok(audit) => DaemonResponse::ImagesResult(audit),
+                    Err(e) => DaemonResponse::Error(format!("Failed to audit images: {}", e)),
+                }
+            }
+
+            DaemonRequest::Drag {
+                tab_name,
+                url,
+                source,
+                target,
+                steps,
+                mode,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // Determine if this is a one-shot operation
+                let is_oneshot = tab_name.is_empty();
+
+                // Get the appropriate browser manager
+                let browser = if is_oneshot {
+                    self.oneshot_browser()
+                } else {
+                    match self.get_browser(profile).await {
+                        Ok(b) => b,
+                        Err(e) => {
+                            return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                        }
+                    }
+                };
+
+                let result = if is_oneshot {
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.drag(&source, &target, steps, mode).await
+                            })
+                        })
+                        .await
+                } else {
+                    // Create tab if needed
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
+
+                    browser
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move {
+                                if !url.is_empty() {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.drag(&source, &target, steps, mode).await
+                            })
+                        })
+                        .await
+                };
+
+                match result {
+                    Ok(drag) => DaemonResponse::DragResult(drag),
+                    Err(e) => DaemonResponse::Error(format!("Failed to drag: {}", e)),
+                }
+            }
+
+            DaemonRequest::Form {
                 tab_name,
                 url,
                 selector,
+                action,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -1484,6 +2495,7 @@ impl Daemon {
 
                 // Determine if this is a one-shot operation
                 let is_oneshot = tab_name.is_empty();
+                let name = action.name();
 
                 // Get the appropriate browser manager
                 let browser = if is_oneshot {
@@ -1497,60 +2509,48 @@ impl Daemon {
                     }
                 };
 
-                // Use the right method based on whether it's one-shot
                 let result = if is_oneshot {
-                    // Create a temp tab name for one-shot
-                    let temp_tab = format!("oneshot-{}", uuid::Uuid::new_v4());
-                    browser.get_or_create_tab(&temp_tab).await.ok();
-                    browser.mark_tab_temporary(&temp_tab).await;
-
-                    // Navigate and get HTML
-                    if !url.is_empty()
-                        && let Err(e) = browser.goto(&url).await
-                    {
-                        return DaemonResponse::Error(format!("Failed to navigate: {}", e));
-                    }
-
-                    let html_result = browser
-                        .browser()
-                        .get_page_html("", selector.as_deref())
-                        .await;
-                    if let Err(e) = browser.cleanup_if_temporary(&temp_tab).await {
-                        warn!("Failed to cleanup temporary tab: {}", e);
-                    }
-                    html_result
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                if let Some(url) = url {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.set_control(&selector, &action).await
+                            })
+                        })
+                        .await
                 } else {
-                    // Get or create the tab
+                    // Create tab if needed
                     if let Err(e) = browser.get_or_create_tab(&tab_name).await {
                         return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
                     }
 
-                    // Navigate if URL is provided
-                    if !url.is_empty()
-                        && let Err(e) = browser.goto(&url).await
-                    {
-                        return DaemonResponse::Error(format!("Failed to navigate: {}", e));
-                    }
-
-                    // Get HTML
                     browser
-                        .browser()
-                        .get_page_html("", selector.as_deref())
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move {
+                                if let Some(url) = url {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.set_control(&selector, &action).await
+                            })
+                        })
                         .await
                 };
 
                 match result {
-                    Ok(html) => DaemonResponse::HtmlResult(html),
-                    Err(e) => DaemonResponse::Error(format!("Failed to get HTML: {}", e)),
+                    Ok(control) => DaemonResponse::FormResult(control),
+                    Err(e) => DaemonResponse::Error(format!("Failed to {}: {}", name, e)),
                 }
             }
 
-            DaemonRequest::Wait {
+            DaemonRequest::FillForm {
                 tab_name,
                 url,
-                selector,
-                timeout,
-                condition,
+                form,
+                data,
+                submit,
+                files_base,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -1573,51 +2573,50 @@ impl Daemon {
                     }
                 };
 
-                // Use the right method based on whether it's one-shot
                 let result = if is_oneshot {
-                    // Create a temp tab name for one-shot
-                    let temp_tab = format!("oneshot-{}", uuid::Uuid::new_v4());
-                    browser.get_or_create_tab(&temp_tab).await.ok();
-                    browser.mark_tab_temporary(&temp_tab).await;
-
-                    // Navigate and wait
-                    if !url.is_empty()
-                        && let Err(e) = browser.goto(&url).await
-                    {
-                        return DaemonResponse::Error(format!("Failed to navigate: {}", e));
-                    }
-
-                    let wait_result = browser
-                        .browser()
-                        .wait_for_element("", &selector, timeout, &condition)
-                        .await;
-                    if let Err(e) = browser.cleanup_if_temporary(&temp_tab).await {
-                        warn!("Failed to cleanup temporary tab: {}", e);
-                    }
-                    wait_result
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                if let Some(url) = url {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.fill_form(
+                                    form.as_deref(),
+                                    &data,
+                                    submit,
+                                    files_base.as_deref().map(std::path::Path::new),
+                                )
+                                .await
+                            })
+                        })
+                        .await
                 } else {
-                    // Get or create the tab
+                    // Create tab if needed
                     if let Err(e) = browser.get_or_create_tab(&tab_name).await {
                         return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
                     }
 
-                    // Navigate if URL is provided
-                    if !url.is_empty()
-                        && let Err(e) = browser.goto(&url).await
-                    {
-                        return DaemonResponse::Error(format!("Failed to navigate: {}", e));
-                    }
-
-                    // Wait for element
                     browser
-                        .browser()
-                        .wait_for_element("", &selector, timeout, &condition)
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move {
+                                if let Some(url) = url {
+                                    ctx.goto(&url).await?;
+                                }
+                                ctx.fill_form(
+                                    form.as_deref(),
+                                    &data,
+                                    submit,
+                                    files_base.as_deref().map(std::path::Path::new),
+                                )
+                                .await
+                            })
+                        })
                         .await
                 };
 
                 match result {
-                    Ok(found) => DaemonResponse::WaitResult(found),
-                    Err(e) => DaemonResponse::Error(format!("Failed to wait for element: {}", e)),
+                    Ok(filled) => DaemonResponse::FormResult(filled),
+                    Err(e) => DaemonResponse::Error(format!("Failed to fill form: {}", e)),
                 }
             }
 
@@ -1859,6 +2858,9 @@ impl Daemon {
             DaemonRequest::Batch {
                 tab_name,
                 commands,
+                stop_on_error,
+                viewport,
+                unsafe_eval,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -1866,9 +2868,12 @@ impl Daemon {
                     return DaemonResponse::Error(e);
                 }
 
-                // Parse the commands JSON - expecting an array of command objects
-                let batch_commands: Vec<serde_json::Value> = match serde_json::from_str(&commands) {
-                    Ok(cmds) => cmds,
+                // Parse the commands JSON - an array of steps or a flow object
+                let flow = match serde_json::from_str(&commands)
+                    .map_err(anyhow::Error::from)
+                    .and_then(flow::Flow::from_value)
+                {
+                    Ok(flow) => flow,
                     Err(e) => {
                         return DaemonResponse::Error(format!(
                             "Failed to parse batch commands: {}",
@@ -1877,7 +2882,86 @@ impl Daemon {
                     }
                 };
 
+                let viewport = match viewport.as_deref().map(ViewportSize::parse).transpose() {
+                    Ok(v) => v,
+                    Err(e) => return DaemonResponse::Error(format!("Invalid viewport: {}", e)),
+                };
+                let options = batch::BatchOptions {
+                    stop_on_error,
+                    viewport,
+                    allow_eval: unsafe_eval,
+                };
+
+                // Determine if this is a one-shot operation
+                let is_oneshot = tab_name.is_empty();
+
                 // Get the appropriate browser manager
+                let browser = if is_oneshot {
+                    self.oneshot_browser()
+                } else {
+                    match self.get_browser(profile).await {
+                        Ok(b) => b,
+                        Err(e) => {
+                            return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                        }
+                    }
+                };
+
+                // Run every step under a single tab lock
+                let result = if is_oneshot {
+                    browser
+                        .with_temp_tab(move |ctx| {
+                            Box::pin(async move {
+                                if let Some(viewport) = &options.viewport {
+                                    ctx.set_viewport(viewport).await?;
+                                }
+                                Ok(batch::run_flow(&ctx, flow, &options).await)
+                            })
+                        })
+                        .await
+                } else {
+                    // Create tab if needed
+                    if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                        return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
+                    }
+
+                    // Stored viewports are restored when with_tab switches to the tab
+                    if let Some(viewport) = options.viewport
+                        && let Err(e) = browser.set_tab_viewport(&tab_name, viewport).await
+                    {
+                        warn!("Failed to set viewport for tab '{}': {}", tab_name, e);
+                    }
+
+                    browser
+                        .with_tab(&tab_name, move |ctx| {
+                            Box::pin(async move { Ok(batch::run_flow(&ctx, flow, &options).await) })
+                        })
+                        .await
+                };
+
+                match result {
+                    Ok(results) => DaemonResponse::BatchResult(results),
+                    Err(e) => DaemonResponse::Error(format!("Failed to run batch: {}", e)),
+                }
+            }
+
+            DaemonRequest::StartRecording {
+                tab_name,
+                url,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                // Recording needs a tab that outlives the request
+                if tab_name.is_empty() {
+                    return DaemonResponse::Error(
+                        "Recording requires a persistent tab (use --profile/--tab)".to_string(),
+                    );
+                }
+
                 let browser = match self.get_browser(profile).await {
                     Ok(b) => b,
                     Err(e) => {
@@ -1885,98 +2969,79 @@ impl Daemon {
                     }
                 };
 
-                // Get or create the tab
                 if let Err(e) = browser.get_or_create_tab(&tab_name).await {
                     return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
                 }
 
-                // Execute batch commands one by one
-                let mut results = Vec::new();
-                for cmd in batch_commands {
-                    let cmd_type = cmd.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                    let result = match cmd_type {
-                        "goto" | "navigate" => {
-                            if let Some(url) = cmd.get("url").and_then(|v| v.as_str()) {
-                                match browser.goto(url).await {
-                                    Ok(_) => {
-                                        json!({"success": true, "message": format!("Navigated to {}", url)})
-                                    }
-                                    Err(e) => json!({"error": e.to_string()}),
-                                }
-                            } else {
-                                json!({"error": "goto requires 'url' field"})
-                            }
-                        }
-                        "type" => {
-                            let selector = cmd.get("selector").and_then(|v| v.as_str());
-                            let text = cmd.get("text").and_then(|v| v.as_str());
-                            let clear = cmd.get("clear").and_then(|v| v.as_bool()).unwrap_or(false);
-
-                            if let (Some(selector), Some(text)) = (selector, text) {
-                                match browser.browser().type_text("", selector, text, clear).await {
-                                    Ok(_) => {
-                                        json!({"success": true, "message": format!("Typed text into {}", selector)})
-                                    }
-                                    Err(e) => json!({"error": e.to_string()}),
-                                }
-                            } else {
-                                json!({"error": "type requires 'selector' and 'text' fields"})
-                            }
-                        }
-                        "click" => {
-                            if let Some(selector) = cmd.get("selector").and_then(|v| v.as_str()) {
-                                let index = cmd
-                                    .get("index")
-                                    .and_then(|v| v.as_u64())
-                                    .map(|i| i as usize);
-                                match browser.browser().click_element("", selector, index).await {
-                                    Ok(_) => {
-                                        json!({"success": true, "message": format!("Clicked {}", selector)})
-                                    }
-                                    Err(e) => json!({"error": e.to_string()}),
-                                }
-                            } else {
-                                json!({"error": "click requires 'selector' field"})
-                            }
-                        }
-                        "wait" => {
-                            if let Some(selector) = cmd.get("selector").and_then(|v| v.as_str()) {
-                                let timeout =
-                                    cmd.get("timeout").and_then(|v| v.as_u64()).unwrap_or(30000);
-                                let condition = cmd
-                                    .get("condition")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("present");
-                                match browser
-                                    .browser()
-                                    .wait_for_element("", selector, timeout, condition)
-                                    .await
-                                {
-                                    Ok(_) => {
-                                        json!({"success": true, "message": format!("Found element {}", selector)})
-                                    }
-                                    Err(e) => json!({"error": e.to_string()}),
-                                }
-                            } else {
-                                json!({"error": "wait requires 'selector' field"})
-                            }
-                        }
-                        "sleep" => {
-                            if let Some(ms) = cmd.get("milliseconds").and_then(|v| v.as_u64()) {
-                                tokio::time::sleep(tokio::time::Duration::from_millis(ms)).await;
-                                json!({"success": true, "message": format!("Slept for {} ms", ms)})
-                            } else {
-                                json!({"error": "sleep requires 'milliseconds' field"})
-                            }
-                        }
-                        _ => {
-                            json!({"error": format!("Unknown command type: {}", cmd_type)})
-                        }
-                    };
-                    results.push(result);
+                match browser
+                    .with_tab(&tab_name, move |ctx| {
+                        Box::pin(async move { record::start(&ctx, url.as_deref()).await })
+                    })
+                    .await
+                {
+                    Ok(events) => DaemonResponse::RecordedEvents(events),
+                    Err(e) => DaemonResponse::Error(format!("Failed to start recording: {}", e)),
+                }
+            }
+
+            DaemonRequest::PollRecording { tab_name, profile } => {
+                self.read_recording(tab_name, profile, false).await
+            }
+
+            DaemonRequest::StopRecording { tab_name, profile } => {
+                self.read_recording(tab_name, profile, true).await
+            }
+
+            DaemonRequest::EnsureRoute {
+                tab_name,
+                check,
+                steps,
+                profile,
+            } => {
+                // Validate profile access if specified
+                if let Err(e) = self.validate_profile_access(&profile) {
+                    return DaemonResponse::Error(e);
+                }
+
+                if tab_name.is_empty() {
+                    return DaemonResponse::Error(
+                        "Routes need a persistent tab (use --profile/--tab)".to_string(),
+                    );
+                }
+
+                let flow = match serde_json::from_str(&steps)
+                    .map_err(anyhow::Error::from)
+                    .and_then(flow::Flow::from_value)
+                {
+                    Ok(flow) => flow,
+                    Err(e) => {
+                        return DaemonResponse::Error(format!(
+                            "Failed to parse route steps: {}",
+                            e
+                        ));
+                    }
+                };
+
+                let browser = match self.get_browser(profile).await {
+                    Ok(b) => b,
+                    Err(e) => {
+                        return DaemonResponse::Error(format!("Failed to get browser: {}", e));
+                    }
+                };
+
+                if let Err(e) = browser.get_or_create_tab(&tab_name).await {
+                    return DaemonResponse::Error(format!("Failed to get/create tab: {}", e));
                 }
 
-                DaemonResponse::BatchResult(results)
+                match browser
+                    .with_tab(&tab_name, move |ctx| {
+                        Box::pin(async move { batch::ensure_route(&ctx, &check, flow).await })
+                    })
+                    .await
+                {
+                    Ok(result) => DaemonResponse::RouteResult(result),
+                    Err(e) => DaemonResponse::Error(format!("Failed to restore route: {}", e)),
+                }
             }
 
             DaemonRequest::Screenshot {
@@ -2352,6 +3417,7 @@ impl Daemon {
                 url2,
                 mode,
                 selector,
+                wait_stable_frames,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -2386,7 +3452,7 @@ impl Daemon {
                     // Compare pages
                     let result = browser
                         .browser()
-                        .compare_pages(&url1, &url2, &mode, selector.as_deref())
+                        .compare_pages(&url1, &url2, &mode, selector.as_deref(), wait_stable_frames)
                         .await;
 
                     // Cleanup temp tab
@@ -2411,9 +3477,10 @@ impl Daemon {
                     let s = selector.clone();
                     let result = browser
                         .with_tab(&tab_name, move |ctx| {
-                            Box::pin(
-                                async move { ctx.compare_pages(&u1, &u2, &m, s.as_deref()).await },
-                            )
+                            Box::pin(async move {
+                                ctx.compare_pages(&u1, &u2, &m, s.as_deref(), wait_stable_frames)
+                                    .await
+                            })
                         })
                         .await;
 
@@ -2428,6 +3495,7 @@ impl Daemon {
                 tab_name,
                 url,
                 check_type,
+                config,
                 profile,
             } => {
                 // Validate profile access if specified
@@ -2464,7 +3532,7 @@ impl Daemon {
                                     ctx.goto(&url_clone).await?;
                                 }
                                 // Validate page
-                                ctx.validate_page(&check_clone).await
+                                ctx.validate_page(&check_clone, &config).await
                             })
                         })
                         .await;
@@ -2490,7 +3558,7 @@ impl Daemon {
                     let check = check_type.clone();
                     let result = browser
                         .with_tab(&tab_name, move |ctx| {
-                            Box::pin(async move { ctx.validate_page(&check).await })
+                            Box::pin(async move { ctx.validate_page(&check, &config).await })
                         })
                         .await;
 
@@ -2517,6 +3585,7 @@ impl Daemon {
                     viewport: config.viewport,
                     last_accessed: None,
                     tabs_count: 0,
+                    disable_animations: config.disable_animations,
                 };
 
                 // Add to registry
@@ -2628,6 +3697,44 @@ impl Daemon {
         }
     }
 
+    /// Drain the recorder in a tab, removing it when `stop` is set
+    async fn read_recording(
+        &mut self,
+        tab_name: String,
+        profile: Option<String>,
+        stop: bool,
+    ) -> DaemonResponse {
+        // Validate profile access if specified
+        if let Err(e) = self.validate_profile_access(&profile) {
+            return DaemonResponse::Error(e);
+        }
+
+        let browser = match self.get_browser(profile).await {
+            Ok(b) => b,
+            Err(e) => return DaemonResponse::Error(format!("Failed to get browser: {}", e)),
+        };
+
+        if !browser.has_tab(&tab_name).await {
+            return DaemonResponse::Error(format!("Tab '{}' not found", tab_name));
+        }
+
+        match browser
+            .with_tab(&tab_name, move |ctx| {
+                Box::pin(async move {
+                    if stop {
+                        record::stop(&ctx).await
+                    } else {
+                        record::poll(&ctx).await
+                    }
+                })
+            })
+            .await
+        {
+            Ok(events) => DaemonResponse::RecordedEvents(events),
+            Err(e) => DaemonResponse::Error(format!("Failed to read recording: {}", e)),
+        }
+    }
+
     /// Get the browser manager for the given profile
     async fn get_browser(&mut self, profile: Option<String>) -> Result<&mut BrowserManager> {
         let profile = Profile::from_optional_string(profile.clone());
@@ -0,0 +1,310 @@
+//! Recording user interactions as a replayable batch flow
+//!
+//! The recorder is a page script that listens for trusted clicks, text entry,
+//! select changes and SPA navigations, and buffers them in `sessionStorage`
+//! so events survive same-origin page loads. The daemon drains the buffer on
+//! every poll and re-installs the script when it finds a fresh document,
+//! adding a `load` event for the new page. `events_to_steps` turns the event
+//! log into batch steps.
+
+use anyhow::Result;
+use serde_json::{Value, json};
+
+use crate::browser_manager::TabContext;
+
+/// Install the recorder (`arguments[0]`: clear any previous buffer).
+/// Returns false if it was already installed.
+pub const INSTALL_SCRIPT: &str = r#"
+const KEY = '__webprobe_recording';
+const memory = [];
+const read = () => {
+    try { return JSON.parse(sessionStorage.getItem(KEY) || '[]'); } catch (e) { return memory.slice(); }
+};
+const write = (events) => {
+    try { sessionStorage.setItem(KEY, JSON.stringify(events)); } catch (e) { memory.length = 0; memory.push(...events); }
+};
+if (arguments[0]) write([]);
+if (window.__webprobeRecorder) return false;
+
+const INTERACTIVE = 'a, button, input, select, textarea, label, summary, [role=button], [role=link], ' +
+    '[role=tab], [role=menuitem], [role=option], [role=checkbox], [role=switch], [onclick]';
+const NON_TEXT_INPUTS = ['checkbox', 'radio', 'button', 'submit', 'reset', 'file', 'image', 'range', 'color'];
+
+const unique = (selector) => {
+    try { return document.querySelectorAll(selector).length === 1; } catch (e) { return false; }
+};
+const quote = (value) => '"' + String(value).replace(/\\/g, '\\\\').replace(/"/g, '\\"') + '"';
+// Generated ids (":r1:", "ember123", "input-4821") change between page loads
+const stableId = (id) => id && !/\d{3,}|^\d|:/.test(id);
+const stableClass = (name) => !/\d{3,}|^(css|sc|jsx|svelte|emotion)-/.test(name);
+
+const anchorFor = (el) => {
+    if (stableId(el.id)) {
+        const selector = '#' + CSS.escape(el.id);
+        if (unique(selector)) return selector;
+    }
+    for (const attr of ['data-testid', 'data-test', 'data-cy', 'data-qa']) {
+        const value = el.getAttribute(attr);
+        if (value) {
+            const selector = '[' + attr + '=' + quote(value) + ']';
+            if (unique(selector)) return selector;
+        }
+    }
+    return null;
+};
+
+const selectorFor = (el) => {
+    const anchor = anchorFor(el);
+    if (anchor) return anchor;
+    const tag = el.tagName.toLowerCase();
+    for (const attr of ['name', 'aria-label', 'placeholder', 'title', 'alt', 'href']) {
+        const value = el.getAttribute(attr);
+        if (value) {
+            const selector = tag + '[' + attr + '=' + quote(value) + ']';
+            if (unique(selector)) return selector;
+        }
+    }
+
+    // Walk up until the path is unique, preferably below a stable ancestor
+    const parts = [];
+    let node = el;
+    while (node && node.nodeType === 1 && node !== document.documentElement) {
+        let part = node.tagName.toLowerCase();
+        const classes = Array.from(node.classList).filter(stableClass).slice(0, 2);
+        if (classes.length) part += '.' + classes.map(c => CSS.escape(c)).join('.');
+        const parent = node.parentElement;
+        if (parent) {
+            const same = Array.from(parent.children).filter(c => c.tagName === node.tagName);
+            if (same.length > 1) part += ':nth-of-type(' + (same.indexOf(node) + 1) + ')';
+        }
+        parts.unshift(part);
+        const path = parts.join(' > ');
+        const parentAnchor = parent && parent !== document.documentElement ? anchorFor(parent) : null;
+        if (parentAnchor && unique(parentAnchor + ' > ' + path)) return parentAnchor + ' > ' + path;
+        if (unique(path)) return path;
+        node = parent;
+    }
+    return parts.join(' > ');
+};
+
+const textLike = (el) => el.isContentEditable || el.tagName === 'TEXTAREA' ||
+    (el.tagName === 'INPUT' && !NON_TEXT_INPUTS.includes((el.type || 'text').toLowerCase()));
+const sensitive = (el) => el.tagName === 'INPUT' && (el.type === 'password' ||
+    /cc-|one-time-code/.test(el.getAttribute('autocomplete') || ''));
+
+const push = (event) => {
+    event.time = Date.now();
+    event.url = location.href;
+    const events = read();
+    events.push(event);
+    write(events);
+};
+const pushInput = (el) => push({
+    kind: 'input',
+    selector: selectorFor(el),
+    value: sensitive(el) ? null : (el.isContentEditable ? el.innerText : el.value),
+    sensitive: sensitive(el),
+});
+
+const recorder = { lastClick: null, listeners: [] };
+const listen = (target, type, handler) => {
+    target.addEventListener(type, handler, true);
+    recorder.listeners.push([target, type, handler]);
+};
+
+listen(document, 'click', (e) => {
+    if (!e.isTrusted || !(e.target instanceof Element)) return;
+    const target = e.target.closest(INTERACTIVE) || e.target;
+    if (target.tagName === 'SELECT' || target.tagName === 'OPTION' || textLike(target)) return;
+    // A click on a <label> is forwarded to its control; only keep the label click
+    const last = recorder.lastClick;
+    recorder.lastClick = { el: target, time: Date.now() };
+    if (last && last.el.tagName === 'LABEL' && last.el.control === target && Date.now() - last.time < 100) return;
+    push({ kind: 'click', selector: selectorFor(target) });
+});
+listen(document, 'change', (e) => {
+    if (!e.isTrusted || !(e.target instanceof Element)) return;
+    const el = e.target;
+    if (el.tagName === 'SELECT') {
+        push({ kind: 'select', selector: selectorFor(el), value: el.value });
+    } else if (textLike(el)) {
+        pushInput(el);
+    }
+});
+listen(document, 'focusout', (e) => {
+    if (e.isTrusted && e.target instanceof Element && e.target.isContentEditable) pushInput(e.target);
+});
+listen(document, 'keydown', (e) => {
+    if (!e.isTrusted || e.key !== 'Enter' || !(e.target instanceof Element)) return;
+    if (!textLike(e.target) || e.target.tagName === 'TEXTAREA' || e.target.isContentEditable) return;
+    pushInput(e.target);
+    push({ kind: 'key', selector: selectorFor(e.target), key: 'Enter' });
+});
+listen(window, 'popstate', () => push({ kind: 'navigation', spa: true, back: true }));
+
+recorder.pushState = history.pushState;
+history.pushState = function (...args) {
+    const result = recorder.pushState.apply(this, args);
+    push({ kind: 'navigation', spa: true });
+    return result;
+};
+recorder.read = read;
+recorder.write = write;
+window.__webprobeRecorder = recorder;
+return true;
+"#;
+
+/// Drain buffered events, or return null if the recorder isn't installed
+/// (the tab has loaded a new document since the last poll)
+pub const DRAIN_SCRIPT: &str = r#"
+const recorder = window.__webprobeRecorder;
+if (!recorder) return null;
+const events = recorder.read();
+recorder.write([]);
+return events;
+"#;
+
+/// Drain remaining events and remove the recorder from the page
+pub const UNINSTALL_SCRIPT: &str = r#"
+const recorder = window.__webprobeRecorder;
+if (!recorder) return [];
+const events = recorder.read();
+recorder.write([]);
+for (const [target, type, handler] of recorder.listeners) target.removeEventListener(type, handler, true);
+history.pushState = recorder.pushState;
+delete window.__webprobeRecorder;
+return events;
+"#;
+
+/// Environment variable referenced in place of recorded password values
+pub const SECRET_VAR: &str = "WEBPROBE_RECORD_SECRET";
+
+/// A page-load event, added by the daemon when it finds a fresh document
+pub fn load_event(url: &str) -> Value {
+    json!({
+        "kind": "load",
+        "url": url,
+        "time": chrono::Utc::now().timestamp_millis(),
+    })
+}
+
+/// Start recording in the tab, optionally navigating first
+pub async fn start(ctx: &TabContext<'_>, url: Option<&str>) -> Result<Vec<Value>> {
+    if let Some(url) = url {
+        ctx.goto(url).await?;
+    }
+    ctx.execute(INSTALL_SCRIPT, vec![json!(true)]).await?;
+    Ok(vec![load_event(&ctx.current_url().await?)])
+}
+
+/// Collect events recorded since the last poll
+pub async fn poll(ctx: &TabContext<'_>) -> Result<Vec<Value>> {
+    let drained = ctx.execute(DRAIN_SCRIPT, vec![]).await?;
+    if let Value::Array(events) = drained {
+        return Ok(events);
+    }
+
+    // A new document: events from the previous page (same origin) are still
+    // buffered in sessionStorage, and come before the load
+    ctx.execute(INSTALL_SCRIPT, vec![json!(false)]).await?;
+    let mut events = match ctx.execute(DRAIN_SCRIPT, vec![]).await? {
+        Value::Array(events) => events,
+        _ => Vec::new(),
+    };
+    events.push(load_event(&ctx.current_url().await?));
+    Ok(events)
+}
+
+/// Collect the remaining events and remove the recorder
+pub async fn stop(ctx: &TabContext<'_>) -> Result<Vec<Value>> {
+    // Poll first so a page loaded since the last poll is still accounted for
+    let mut events = poll(ctx).await?;
+    if let Value::Array(rest) = ctx.execute(UNINSTALL_SCRIPT, vec![]).await? {
+        events.extend(rest);
+    }
+    Ok(events)
+}
+
+/// How long after a click or Enter a page load still counts as caused by it
+const INTERACTION_WINDOW_MS: i64 = 10_000;
+
+/// Convert recorded events into batch steps
+///
+/// Page loads that follow an interaction are not replayed as `goto` (the
+/// replayed click will navigate); instead the next step waits for its element
+/// to become visible. The same applies to SPA navigations.
+pub fn events_to_steps(events: &[Value]) -> Vec<Value> {
+    let mut steps: Vec<Value> = Vec::new();
+    let mut typed: Vec<(String, Value)> = Vec::new();
+    let mut last_interaction: Option<i64> = None;
+    let mut navigated = false;
+
+    for event in events {
+        let kind = event["kind"].as_str().unwrap_or("");
+        let time = event["time"].as_i64().unwrap_or(0);
+
+        if kind == "load" || kind == "navigation" {
+            let caused = last_interaction.is_some_and(|t| time - t <= INTERACTION_WINDOW_MS);
+            let back = event["back"] == true;
+            if caused && !back {
+                navigated = true;
+            } else if let Some(url) = event["url"].as_str() {
+                // Typed in the address bar, or the first page of the session
+                steps.push(json!({ "type": "goto", "url": url }));
+                navigated = false;
+            }
+            typed.clear();
+            continue;
+        }
+
+        let Some(selector) = event["selector"].as_str() else {
+            continue;
+        };
+
+        let step = match kind {
+            "click" => json!({ "type": "click", "selector": selector }),
+            "select" => json!({
+                "type": "select",
+                "selector": selector,
+                "value": event["value"].as_str().unwrap_or(""),
+            }),
+            "input" => {
+                let text = if event["sensitive"] == true {
+                    Value::String(format!("${{env.{}}}", SECRET_VAR))
+                } else {
+                    event["value"].clone()
+                };
+                // Skip repeats (change after Enter, blur after change)
+                if typed.iter().any(|(s, t)| s == selector && *t == text) {
+                    continue;
+                }
+                typed.retain(|(s, _)| s != selector);
+                typed.push((selector.to_string(), text.clone()));
+                json!({ "type": "type", "selector": selector, "text": text, "clear": true })
+            }
+            // WebDriver's Enter key
+            "key" => json!({ "type": "type", "selector": selector, "text": "\u{E007}" }),
+            _ => continue,
+        };
+
+        if matches!(kind, "click" | "key") {
+            last_interaction = Some(time);
+        }
+        if navigated {
+            steps.push(json!({
+                "type": "wait",
+                "selector": selector,
+                "condition": "visible",
+                "timeout": 10,
+            }));
+            navigated = false;
+        }
+        steps.push(step);
+    }
+
+    steps
+}
+
+#[cfg(test)]
+#[path = "record_test.rs"]
+mod record_test;
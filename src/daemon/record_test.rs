@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use crate::daemon::record::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_events_to_steps() {
+        let events = vec![
+            json!({"kind": "load", "url": "http://localhost:3000/login", "time": 1000}),
+            json!({"kind": "input", "selector": "#email", "value": "dev@example.com", "sensitive": false, "time": 2000}),
+            json!({"kind": "input", "selector": "#password", "value": null, "sensitive": true, "time": 3000}),
+            json!({"kind": "select", "selector": "select[name=\"plan\"]", "value": "pro", "time": 3500}),
+            json!({"kind": "click", "selector": "[data-testid=\"login\"]", "time": 4000}),
+            json!({"kind": "load", "url": "http://localhost:3000/dashboard", "time": 5000}),
+            json!({"kind": "click", "selector": "#settings", "time": 6000}),
+        ];
+
+        let steps = events_to_steps(&events);
+        let types: Vec<&str> = steps.iter().map(|s| s["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            types,
+            vec!["goto", "type", "type", "select", "click", "wait", "click"]
+        );
+        assert_eq!(steps[0]["url"], "http://localhost:3000/login");
+        assert_eq!(steps[1]["text"], "dev@example.com");
+        assert_eq!(steps[1]["clear"], true);
+        assert_eq!(steps[2]["text"], format!("${{env.{}}}", SECRET_VAR));
+        assert_eq!(steps[3]["value"], "pro");
+
+        // The load caused by the click isn't replayed; the next step waits instead
+        assert_eq!(steps[5]["selector"], "#settings");
+        assert_eq!(steps[5]["condition"], "visible");
+    }
+
+    #[test]
+    fn test_enter_and_duplicate_input() {
+        let events = vec![
+            json!({"kind": "load", "url": "http://localhost/", "time": 0}),
+            json!({"kind": "input", "selector": "#q", "value": "shoes", "time": 100}),
+            json!({"kind": "key", "selector": "#q", "key": "Enter", "time": 100}),
+            // change fires again after Enter with the same value
+            json!({"kind": "input", "selector": "#q", "value": "shoes", "time": 150}),
+            json!({"kind": "navigation", "spa": true, "url": "http://localhost/search", "time": 200}),
+            json!({"kind": "click", "selector": ".result:nth-of-type(1) > a", "time": 900}),
+        ];
+
+        let steps = events_to_steps(&events);
+        assert_eq!(steps.len(), 5);
+        assert_eq!(steps[2]["type"], "type");
+        assert_eq!(steps[2]["text"], "\u{E007}");
+        assert_eq!(steps[3]["type"], "wait");
+        assert_eq!(steps[4]["type"], "click");
+    }
+
+    #[test]
+    fn test_unprompted_loads_become_goto() {
+        let events = vec![
+            json!({"kind": "load", "url": "http://localhost/a", "time": 0}),
+            json!({"kind": "click", "selector": "#menu", "time": 1000}),
+            // Long after the click: typed into the address bar
+            json!({"kind": "load", "url": "http://localhost/b", "time": 60_000}),
+            json!({"kind": "navigation", "spa": true, "back": true, "url": "http://localhost/a", "time": 61_000}),
+        ];
+
+        let steps = events_to_steps(&events);
+        let urls: Vec<&str> = steps.iter().filter_map(|s| s["url"].as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "http://localhost/a",
+                "http://localhost/b",
+                "http://localhost/a"
+            ]
+        );
+
+        // Every generated step is a valid batch step
+        for step in &steps {
+            serde_json::from_value::<crate::daemon::batch::BatchStep>(step.clone()).unwrap();
+        }
+    }
+}
@@ -33,6 +33,8 @@ mod tests {
             all: false,
             index: None,
             expect_one: false,
+            states: vec![],
+            wait_stable_frames: None,
             profile: None,
         };
         let json = serde_json::to_string(&inspect).unwrap();
@@ -120,6 +122,7 @@ mod tests {
             tab_name: "validate_tab".to_string(),
             url: "https://example.com".to_string(),
             check_type: "accessibility".to_string(),
+            config: crate::validation::ValidationConfig::default(),
             profile: Some("test_profile".to_string()),
         };
         let json = serde_json::to_string(&validate).unwrap();
@@ -133,6 +136,7 @@ mod tests {
             url2: "https://example2.com".to_string(),
             mode: "visual".to_string(),
             selector: Some(".main".to_string()),
+            wait_stable_frames: None,
             profile: None,
         };
         let json = serde_json::to_string(&compare).unwrap();
@@ -209,13 +213,24 @@ mod tests {
         let batch = DaemonRequest::Batch {
             tab_name: "batch_tab".to_string(),
             commands: commands_json.to_string(),
+            stop_on_error: true,
+            viewport: Some("375x667".to_string()),
+            unsafe_eval: false,
             profile: None,
         };
 
         let json = serde_json::to_string(&batch).unwrap();
         let deserialized: DaemonRequest = serde_json::from_str(&json).unwrap();
 
-        if let DaemonRequest::Batch { commands, .. } = deserialized {
+        if let DaemonRequest::Batch {
+            commands,
+            stop_on_error,
+            viewport,
+            ..
+        } = deserialized
+        {
+            assert!(stop_on_error);
+            assert_eq!(viewport.as_deref(), Some("375x667"));
             let parsed_commands: Vec<serde_json::Value> = serde_json::from_str(&commands).unwrap();
             assert_eq!(parsed_commands.len(), 2);
             assert_eq!(parsed_commands[0]["action"], "click");
@@ -225,6 +240,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_profile_config_disable_animations() {
+        // Configs and registries written before the option existed still load
+        let config: ProfileConfig = serde_json::from_value(json!({
+            "browser_type": "Chrome",
+            "viewport": null,
+            "headless": true,
+            "persist_cookies": true,
+            "persist_storage": true
+        }))
+        .unwrap();
+        assert!(!config.disable_animations);
+
+        let config = ProfileConfig {
+            disable_animations: true,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["disable_animations"], true);
+    }
+
     #[tokio::test]
     #[ignore] // socket_path is not a public method
     async fn test_daemon_client_socket_path() {
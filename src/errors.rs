@@ -63,6 +63,12 @@ impl std::error::Error for WebprobeError {
 
 impl From<anyhow::Error> for WebprobeError {
     fn from(err: anyhow::Error) -> Self {
+        // Errors raised as a WebprobeError keep their kind
+        let err = match err.downcast::<WebprobeError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+
         // Try to detect specific error types from the error message
         let msg = err.to_string();
 
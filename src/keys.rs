@@ -0,0 +1,146 @@
+//! Key names for `press`, `type --submit` and batch `press` steps
+//!
+//! A chord is modifiers and one key joined by `+`, e.g. `Control+K` or
+//! `Shift+ArrowDown`. Key names follow `KeyboardEvent.key` ("Enter",
+//! "ArrowUp", "PageDown", "F5") plus a few common aliases; any other single
+//! character is typed as itself. Named keys map to WebDriver key codes.
+
+use anyhow::Result;
+use fantoccini::key::Key;
+
+use crate::types::Modifier;
+
+/// Named keys, matched case-insensitively
+const KEYS: &[(&str, Key)] = &[
+    ("enter", Key::Enter),
+    ("return", Key::Return),
+    ("tab", Key::Tab),
+    ("escape", Key::Escape),
+    ("esc", Key::Escape),
+    ("backspace", Key::Backspace),
+    ("delete", Key::Delete),
+    ("del", Key::Delete),
+    ("insert", Key::Insert),
+    ("space", Key::Space),
+    ("arrowup", Key::Up),
+    ("up", Key::Up),
+    ("arrowdown", Key::Down),
+    ("down", Key::Down),
+    ("arrowleft", Key::Left),
+    ("left", Key::Left),
+    ("arrowright", Key::Right),
+    ("right", Key::Right),
+    ("home", Key::Home),
+    ("end", Key::End),
+    ("pageup", Key::PageUp),
+    ("pagedown", Key::PageDown),
+    ("f1", Key::F1),
+    ("f2", Key::F2),
+    ("f3", Key::F3),
+    ("f4", Key::F4),
+    ("f5", Key::F5),
+    ("f6", Key::F6),
+    ("f7", Key::F7),
+    ("f8", Key::F8),
+    ("f9", Key::F9),
+    ("f10", Key::F10),
+    ("f11", Key::F11),
+    ("f12", Key::F12),
+];
+
+/// Keys that can't be written as themselves inside a chord
+const ALIASES: &[(&str, char)] = &[("plus", '+')];
+
+/// One key press, with modifiers held down around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChord {
+    pub modifiers: Vec<Modifier>,
+    /// A WebDriver key code or a plain character
+    pub key: char,
+}
+
+impl KeyChord {
+    /// Parse a chord in "Control+Shift+K" format
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            anyhow::bail!("Empty key");
+        }
+
+        // A "+" after a separator is the key itself ("Control++")
+        let (names, key) = match s.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None if s == "+" => ("", "+"),
+            None => match s.rsplit_once('+') {
+                Some((names, key)) if !key.is_empty() => (names, key),
+                Some(_) => anyhow::bail!("Missing key after '+' in '{}'", s),
+                None => ("", s),
+            },
+        };
+
+        let mut modifiers = Vec::new();
+        for name in names.split('+').filter(|n| !n.is_empty()) {
+            let modifier: Modifier = name.parse()?;
+            if !modifiers.contains(&modifier) {
+                modifiers.push(modifier);
+            }
+        }
+
+        // A modifier on its own is pressed and released like any other key
+        let key = match key_code(key) {
+            Some(key) => key,
+            None => match key.parse::<Modifier>() {
+                Ok(modifier) => modifier_key(modifier),
+                Err(_) => anyhow::bail!(
+                    "Unknown key '{}'. Use a single character or a name like Enter, Escape, Tab, ArrowDown, PageUp or F5",
+                    key
+                ),
+            },
+        };
+        // "Control+K" is what a keyboard sends as Control and the k key;
+        // only Shift makes the letter uppercase
+        let key = match key {
+            c if c.is_ascii_alphabetic() && !modifiers.is_empty() => {
+                if modifiers.contains(&Modifier::Shift) {
+                    c.to_ascii_uppercase()
+                } else {
+                    c.to_ascii_lowercase()
+                }
+            }
+            c => c,
+        };
+        Ok(KeyChord { modifiers, key })
+    }
+
+    /// Parse keys pressed one after another, e.g. "ArrowDown ArrowDown Enter"
+    pub fn parse_sequence(s: &str) -> Result<Vec<Self>> {
+        s.split_whitespace().map(KeyChord::parse).collect()
+    }
+}
+
+/// WebDriver code of a named key, or the character itself
+pub fn key_code(name: &str) -> Option<char> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c);
+    }
+    let name = name.to_lowercase();
+    KEYS.iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, key)| char::from(*key))
+        .or_else(|| ALIASES.iter().find(|(n, _)| *n == name).map(|(_, c)| *c))
+}
+
+/// WebDriver code of a modifier key
+pub fn modifier_key(modifier: Modifier) -> char {
+    char::from(match modifier {
+        Modifier::Shift => Key::Shift,
+        Modifier::Control => Key::Control,
+        Modifier::Alt => Key::Alt,
+        Modifier::Meta => Key::Meta,
+    })
+}
+
+#[cfg(test)]
+#[path = "keys_test.rs"]
+mod keys_test;
@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use crate::keys::*;
+    use crate::types::Modifier;
+    use fantoccini::key::Key;
+
+    #[test]
+    fn test_parse_chord() {
+        let chord = KeyChord::parse("Control+K").unwrap();
+        assert_eq!(chord.modifiers, vec![Modifier::Control]);
+        assert_eq!(chord.key, 'k');
+        assert_eq!(KeyChord::parse("Shift+k").unwrap().key, 'K');
+        assert_eq!(KeyChord::parse("K").unwrap().key, 'K');
+
+        let chord = KeyChord::parse("ctrl+shift+arrowdown").unwrap();
+        assert_eq!(chord.modifiers, vec![Modifier::Control, Modifier::Shift]);
+        assert_eq!(chord.key, char::from(Key::Down));
+
+        assert_eq!(
+            KeyChord::parse("Escape").unwrap().key,
+            char::from(Key::Escape)
+        );
+        assert_eq!(KeyChord::parse("Meta++").unwrap().key, '+');
+        assert_eq!(KeyChord::parse("Control+Plus").unwrap().key, '+');
+        assert_eq!(KeyChord::parse("+").unwrap().key, '+');
+        assert_eq!(
+            KeyChord::parse("Shift").unwrap().key,
+            char::from(Key::Shift)
+        );
+
+        assert!(KeyChord::parse("Control+").is_err());
+        assert!(KeyChord::parse("Hyper+K").is_err());
+        assert!(KeyChord::parse("Enterr").is_err());
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let keys = KeyChord::parse_sequence("ArrowDown ArrowDown Enter").unwrap();
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[2].key, char::from(Key::Enter));
+        assert!(keys.iter().all(|k| k.modifiers.is_empty()));
+    }
+}
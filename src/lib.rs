@@ -198,22 +198,33 @@
 /// Browser manager for tab management and isolation
 pub mod browser_manager;
 
+/// CSS cascade explanation (`why`)
+pub mod cascade;
+
+/// Key names and chords for keyboard input
+pub mod keys;
+
 /// Profile management for browser sessions
 pub mod profile;
 
+/// CSS source map decoding (`why` source locations)
+pub mod sourcemap;
+
 /// Type definitions for element information
 pub mod types;
 
+/// Validation rules, config and report formats
+pub mod validation;
+
 /// WebDriver browser control and automation
 pub mod webdriver;
 
 /// Automatic WebDriver process management
 pub mod webdriver_manager;
 
-
 pub use profile::ProfileManager;
 pub use types::{
     BoundingBox, BoxModel, BoxSides, ContentBox, ElementInfo, InspectionDepth, LayoutInfo,
-    OutputFormat, Position, Size, ViewportSize,
+    Occluder, Occlusion, OutputFormat, Position, Size, ViewportSize,
 };
 pub use webdriver::{Browser, BrowserType, ConsoleMessage};
@@ -3,19 +3,24 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use serde_json::json;
+use std::path::PathBuf;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 pub mod browser_manager;
 pub mod browser_pool;
+pub mod cascade;
 mod commands;
 mod daemon;
 mod errors;
+pub mod keys;
 mod profile;
+mod route;
+pub mod sourcemap;
 pub mod types;
+pub mod validation;
 pub mod webdriver;
 mod webdriver_manager;
 
-
 // Exit codes
 const EXIT_SUCCESS: i32 = 0;
 const _EXIT_COMMAND_ERROR: i32 = 1;
@@ -26,9 +31,11 @@ const _EXIT_TIMEOUT: i32 = 5;
 
 use crate::commands::daemon::DaemonCommands;
 use crate::commands::profile::ProfileCommands;
+use crate::commands::route::RouteCommands;
 use crate::commands::session::SessionCommands;
 use crate::commands::tab::TabCommands;
 use types::{InspectionDepth, OutputFormat};
+use validation::ReportFormat;
 
 #[derive(Parser)]
 #[command(name = "webprobe")]
@@ -40,6 +47,10 @@ struct Cli {
     /// Global session name
     #[arg(long, global = true)]
     session: Option<String>,
+
+    /// Replay a named route first unless the tab is already on that screen
+    #[arg(long, global = true)]
+    ensure_route: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -76,6 +87,14 @@ enum Commands {
         #[arg(long)]
         expect_one: bool,
 
+        /// Force pseudo-classes while inspecting, comma-separated (hover, active, focus, focus-visible, focus-within)
+        #[arg(long)]
+        state: Option<String>,
+
+        /// Wait until the element's rect is unchanged for this many frames before measuring
+        #[arg(long, num_args = 0..=1, default_missing_value = "3", value_name = "FRAMES")]
+        wait_stable_frames: Option<u32>,
+
         /// Set viewport size (WIDTHxHEIGHT, e.g., 1920x1080)
         #[arg(long)]
         viewport: Option<String>,
@@ -104,6 +123,14 @@ enum Commands {
         #[arg(long, default_value = "false")]
         clear: bool,
 
+        /// Milliseconds between characters (type one key at a time)
+        #[arg(long, default_value = "0")]
+        key_delay: u64,
+
+        /// Press Enter after typing
+        #[arg(long)]
+        submit: bool,
+
         /// Browser to use
         #[arg(short, long, default_value = "firefox")]
         browser: String,
@@ -142,6 +169,22 @@ enum Commands {
         #[arg(long)]
         to: Option<String>,
 
+        /// Keep scrolling to the bottom until this many elements match ("selector:count")
+        #[arg(long, conflicts_with_all = ["to", "by_x", "by_y"])]
+        until_selector_count: Option<String>,
+
+        /// Keep scrolling to the bottom until the page stops growing for this long (ms)
+        #[arg(long, conflicts_with_all = ["to", "by_x", "by_y"])]
+        until_stable: Option<u64>,
+
+        /// Most scrolls to make with --until-selector-count or --until-stable
+        #[arg(long, default_value = "50")]
+        max_steps: usize,
+
+        /// Output format for --until-* step stats
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+
         /// Browser to use
         #[arg(short, long, default_value = "firefox")]
         browser: String,
@@ -159,6 +202,40 @@ enum Commands {
         tab: Option<String>,
     },
 
+    /// Scroll through page offsets and record an element's viewport rect and position at each
+    ScrollProbe {
+        /// CSS selector for the element to follow (a sticky or fixed header, say)
+        selector: String,
+
+        /// Scroll offsets to stop at, in pixels, or top/bottom
+        #[arg(long, default_value = "0,500,1500,bottom")]
+        positions: String,
+
+        /// Wait until the element's rect is unchanged for this many frames at each stop
+        #[arg(long, num_args = 0..=1, default_missing_value = "3", value_name = "FRAMES")]
+        wait_stable_frames: Option<u32>,
+
+        /// URL to navigate to first (empty for the tab's current page)
+        #[arg(long, default_value = "")]
+        url: String,
+
+        /// Use a persistent tab (creates if doesn't exist)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+    },
+
     /// Analyze page with focus on specific context
     Analyze {
         /// URL to analyze
@@ -179,6 +256,14 @@ enum Commands {
         #[arg(long)]
         index: Option<usize>,
 
+        /// Force pseudo-classes while analyzing, comma-separated (hover, active, focus, focus-visible, focus-within)
+        #[arg(long)]
+        state: Option<String>,
+
+        /// Wait until the element's rect is unchanged for this many frames before measuring
+        #[arg(long, num_args = 0..=1, default_missing_value = "3", value_name = "FRAMES")]
+        wait_stable_frames: Option<u32>,
+
         /// Browser to use
         #[arg(short, long, default_value = "firefox")]
         browser: String,
@@ -283,7 +368,7 @@ enum Commands {
         #[arg(long, default_value = "100")]
         max_elements: usize,
 
-        /// Wait for layout to stabilize (in ms)
+        /// Wait up to this long (in ms) for the element to stop moving (0 to skip)
         #[arg(long, default_value = "500")]
         wait_stable: u64,
 
@@ -350,6 +435,22 @@ enum Commands {
         #[arg(long)]
         index: Option<usize>,
 
+        /// Mouse button: left, middle or right
+        #[arg(long, default_value = "left")]
+        button: String,
+
+        /// Double-click
+        #[arg(long)]
+        double: bool,
+
+        /// Click at X,Y from the element's top-left corner instead of its center
+        #[arg(long)]
+        offset: Option<String>,
+
+        /// Keys to hold during the click, comma-separated (shift, control, alt, meta)
+        #[arg(long)]
+        modifiers: Option<String>,
+
         /// Browser to use
         #[arg(short, long, default_value = "firefox")]
         browser: String,
@@ -371,6 +472,288 @@ enum Commands {
         tab: Option<String>,
     },
 
+    /// Drag an element onto another element or a point, e.g. kanban cards and sortable lists
+    Drag {
+        /// CSS selector for the element to drag
+        source: String,
+
+        /// CSS selector of the drop target, or X,Y in the viewport
+        target: String,
+
+        /// URL to navigate to first (empty for the tab's current page)
+        #[arg(long, default_value = "")]
+        url: String,
+
+        /// Number of intermediate pointer moves
+        #[arg(long, default_value = "10")]
+        steps: u32,
+
+        /// auto (HTML5 events for draggable elements), pointer or html5
+        #[arg(long, default_value = "auto")]
+        mode: String,
+
+        /// Tab to drag in (requires daemon)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+    },
+
+    /// Choose options in a <select> by value or label, firing input and change
+    Select {
+        /// CSS selector for the <select>
+        selector: String,
+
+        /// Option value to choose (repeat for <select multiple>)
+        #[arg(long)]
+        value: Vec<String>,
+
+        /// Visible option label to choose (repeat for <select multiple>)
+        #[arg(long)]
+        label: Vec<String>,
+
+        /// Use the <select> at this index (0-based) when several match
+        #[arg(long)]
+        index: Option<usize>,
+
+        /// URL to navigate to first (empty for the tab's current page)
+        #[arg(long, default_value = "")]
+        url: String,
+
+        /// Use a persistent tab (creates if doesn't exist)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+    },
+
+    /// Check a checkbox, radio button or aria-checked widget by clicking it
+    Check {
+        /// CSS selector for the control
+        selector: String,
+
+        /// URL to navigate to first (empty for the tab's current page)
+        #[arg(long, default_value = "")]
+        url: String,
+
+        /// Use a persistent tab (creates if doesn't exist)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+    },
+
+    /// Uncheck a checkbox or aria-checked widget by clicking it
+    Uncheck {
+        /// CSS selector for the control
+        selector: String,
+
+        /// URL to navigate to first (empty for the tab's current page)
+        #[arg(long, default_value = "")]
+        url: String,
+
+        /// Use a persistent tab (creates if doesn't exist)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+    },
+
+    /// Move a range slider to a value (clamped and snapped like a user's drag)
+    SetRange {
+        /// CSS selector for the <input type="range">
+        selector: String,
+
+        /// Value to set
+        #[arg(allow_negative_numbers = true)]
+        value: f64,
+
+        /// URL to navigate to first (empty for the tab's current page)
+        #[arg(long, default_value = "")]
+        url: String,
+
+        /// Use a persistent tab (creates if doesn't exist)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+    },
+
+    /// Choose files in an <input type="file">
+    Upload {
+        /// CSS selector for the file input
+        selector: String,
+
+        /// Files to choose (several only if the input has `multiple`)
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// URL to navigate to first (empty for the tab's current page)
+        #[arg(long, default_value = "")]
+        url: String,
+
+        /// Use a persistent tab (creates if doesn't exist)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+    },
+
+    /// Fill a form from a JSON map of field names, ids, labels or placeholders to values
+    FillForm {
+        /// URL to load first (or empty for the tab's current page)
+        #[arg(default_value = "")]
+        url: String,
+
+        /// CSS selector for the form (defaults to the form matching the most keys)
+        #[arg(long)]
+        form: Option<String>,
+
+        /// Values as a JSON object, or @file to read them from a file
+        #[arg(long)]
+        data: String,
+
+        /// Submit the form afterwards and report the validation messages shown
+        #[arg(long)]
+        submit: bool,
+
+        /// Use a persistent tab (creates if doesn't exist)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+    },
+
+    /// Press keys and shortcuts (Enter, Escape, Control+K) in the focused element
+    Press {
+        /// Keys to press in order, e.g. Control+K or ArrowDown ArrowDown Enter
+        #[arg(required = true)]
+        keys: Vec<String>,
+
+        /// Focus this element first (otherwise keys go to whatever has focus)
+        #[arg(long)]
+        selector: Option<String>,
+
+        /// URL to navigate to first (empty for the tab's current page)
+        #[arg(long, default_value = "")]
+        url: String,
+
+        /// Milliseconds between key presses
+        #[arg(long, default_value = "0")]
+        delay: u64,
+
+        /// Tab to press keys in (requires daemon)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+    },
+
+    /// Move the mouse over an element and leave it there (menus, tooltips)
+    Hover {
+        /// URL to navigate to (empty for the tab's current page)
+        url: String,
+
+        /// CSS selector for the element to hover
+        selector: String,
+
+        /// Hover element at specific index (0-based)
+        #[arg(long)]
+        index: Option<usize>,
+
+        /// Point at X,Y from the element's top-left corner instead of its center
+        #[arg(long)]
+        offset: Option<String>,
+
+        /// Tab to hover in (requires daemon)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+    },
+
     /// Execute multiple commands in batch
     Batch {
         /// Commands as string or file path (use @ prefix for file, e.g., @commands.txt)
@@ -388,10 +771,14 @@ enum Commands {
         #[arg(long)]
         stop_on_error: bool,
 
-        /// Run without showing browser window
-        #[arg(long, default_value = "true")]
+        /// Deprecated no-op, kept so existing scripts still parse (batches run headless by default)
+        #[arg(long)]
         headless: bool,
 
+        /// Run browser in visible mode (disables headless)
+        #[arg(long = "no-headless")]
+        no_headless: bool,
+
         /// Browser profile to use
         #[arg(short, long)]
         profile: Option<String>,
@@ -399,6 +786,41 @@ enum Commands {
         /// Viewport size (WIDTHxHEIGHT)
         #[arg(long)]
         viewport: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "simple")]
+        format: OutputFormat,
+
+        /// Allow eval steps (acknowledges security risks)
+        #[arg(long)]
+        unsafe_eval: bool,
+    },
+
+    /// Record interactions in a browser tab and save them as a batch flow
+    Record {
+        /// URL to open before recording (defaults to the tab's current page)
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Tab to record in
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser profile to use (a 'record' profile is created if not specified)
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Browser to use when the profile has to be created
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Run browser in visible mode (disables headless)
+        #[arg(long = "no-headless")]
+        no_headless: bool,
+
+        /// File to write the flow to (.json, or .yaml/.yml for YAML)
+        #[arg(short, long)]
+        output: String,
     },
 
     /// Detect smart elements (forms, tables, navigation, etc)
@@ -423,6 +845,178 @@ enum Commands {
         profile: Option<String>,
     },
 
+    /// Get the computed accessibility tree (roles, names, states)
+    A11yTree {
+        /// URL to inspect (or empty for current tab)
+        #[arg(default_value = "")]
+        url: String,
+
+        /// Root element of the tree (defaults to body)
+        selector: Option<String>,
+
+        /// Tab to use (requires daemon)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format (simple prints an indented outline)
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+
+        /// Stop after this many nodes
+        #[arg(long, default_value = "2000")]
+        max_nodes: usize,
+
+        /// Keep only interactive controls, headings and landmarks
+        #[arg(long)]
+        interactive_only: bool,
+    },
+
+    /// Measure Core Web Vitals (LCP, CLS, INP/FID, TTFB, FCP), navigation timing and long tasks
+    Perf {
+        /// URL to load and measure (or empty for current tab)
+        #[arg(default_value = "")]
+        url: String,
+
+        /// Tab to use (requires daemon)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+
+        /// Click this element after load to measure INP/FID
+        #[arg(long)]
+        interact: Option<String>,
+
+        /// Milliseconds to wait after load for late LCP candidates, shifts and long tasks
+        #[arg(long, default_value = "1000")]
+        settle: u64,
+    },
+
+    /// Explain which CSS rules set a property on an element, in cascade order
+    Why {
+        /// CSS selector for the element
+        selector: String,
+
+        /// Property to explain (a shorthand like `margin` lists each longhand)
+        #[arg(long)]
+        property: String,
+
+        /// URL to navigate to (optional if using tab)
+        #[arg(long, default_value = "")]
+        url: String,
+
+        /// Explain the element at this index (0-based) when several match
+        #[arg(long)]
+        index: Option<usize>,
+
+        /// Tab to use (requires daemon)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+    },
+
+    /// Audit images: natural vs rendered size, srcset/sizes, lazy loading, distortion, broken
+    Images {
+        /// URL to audit (or empty for current tab)
+        #[arg(default_value = "")]
+        url: String,
+
+        /// Tab to use (requires daemon)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+
+        /// Flag images with more than this many times the pixels the screen needs
+        #[arg(long, default_value = "1.5")]
+        tolerance: f64,
+    },
+
+    /// List layout shifts recorded in a tab: which elements moved, when and by how much
+    Shifts {
+        /// Tab whose shifts to list (requires daemon)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+
+        /// Forget the listed shifts so the next call only shows new ones
+        #[arg(long)]
+        clear: bool,
+    },
+
+    /// Tab through a page to audit focus order, focus indicators and traps
+    KeyboardAudit {
+        /// URL to audit (or empty for current tab)
+        #[arg(default_value = "")]
+        url: String,
+
+        /// Tab to use (requires daemon)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+
+        /// Give up after this many Tab presses
+        #[arg(long, default_value = "200")]
+        max_tabs: usize,
+    },
+
     /// Find elements by text content
     FindText {
         /// Text to search for
@@ -465,6 +1059,37 @@ enum Commands {
         format: OutputFormat,
     },
 
+    /// Wait for an element to be visible, hidden, detached or clickable, to contain text or to reach a count
+    Wait {
+        /// URL to load (optional) and the selector to wait for; without a URL, waits on the tab's current page
+        #[arg(required = true, num_args = 1..=2, value_name = "[URL] SELECTOR")]
+        target: Vec<String>,
+
+        /// Condition: present, visible, hidden, detached, clickable, text=<text> or count>=N (also =, >, <=, <)
+        #[arg(long = "for", default_value = "present")]
+        condition: String,
+
+        /// Maximum time to wait in seconds
+        #[arg(long, default_value = "10")]
+        timeout: u64,
+
+        /// Use a persistent tab (creates if doesn't exist)
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Browser to use
+        #[arg(short, long, default_value = "chrome")]
+        browser: String,
+
+        /// Browser profile to use
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "json")]
+        format: OutputFormat,
+    },
+
     /// Wait for navigation to occur (URL change)
     WaitNavigation {
         /// Current URL or empty to use current tab URL
@@ -556,13 +1181,18 @@ enum Commands {
         command: ProfileCommands,
     },
 
+    /// Manage named navigation recipes for SPA screens
+    Route {
+        #[command(subcommand)]
+        command: RouteCommands,
+    },
+
     /// Manage the background daemon
     Daemon {
         #[command(subcommand)]
         command: DaemonCommands,
     },
 
-
     /// Show version information
     Version,
 
@@ -609,10 +1239,12 @@ enum Commands {
 
     /// Validate page for accessibility and SEO
     Validate {
-        /// URL to validate
+        /// URL to validate (or empty for current tab)
+        #[arg(default_value = "")]
         url: String,
 
-        /// Type of validation (accessibility, seo, performance, all)
+        /// Rules to run: all, accessibility, contrast, seo, structured-data,
+        /// social, performance, or comma-separated rule IDs (see --list-rules)
         #[arg(long, default_value = "all")]
         check: String,
 
@@ -630,11 +1262,19 @@ enum Commands {
 
         /// Output format
         #[arg(short, long, default_value = "json")]
-        format: OutputFormat,
+        format: ReportFormat,
 
         /// Use a persistent tab
         #[arg(long)]
         tab: Option<String>,
+
+        /// Rule config file (default: .webprobe-validate.yaml/.yml/.json if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// List the available rules and exit
+        #[arg(long)]
+        list_rules: bool,
     },
 
     /// Compare two pages or states
@@ -653,6 +1293,10 @@ enum Commands {
         #[arg(short, long)]
         selector: Option<String>,
 
+        /// Wait until the selector's (or body's) rect is unchanged for this many frames before capturing each page
+        #[arg(long, num_args = 0..=1, default_missing_value = "3", value_name = "FRAMES")]
+        wait_stable_frames: Option<u32>,
+
         /// Browser to use
         #[arg(short, long, default_value = "firefox")]
         browser: String,
@@ -679,6 +1323,87 @@ enum Commands {
     },
 }
 
+impl Commands {
+    /// The `--profile`/`--tab` pair of commands that operate on a tab
+    fn tab_target(&self) -> Option<(&Option<String>, &Option<String>)> {
+        match self {
+            Commands::Inspect { profile, tab, .. }
+            | Commands::Type { profile, tab, .. }
+            | Commands::Scroll { profile, tab, .. }
+            | Commands::ScrollProbe { profile, tab, .. }
+            | Commands::Analyze { profile, tab, .. }
+            | Commands::Screenshot { profile, tab, .. }
+            | Commands::Iframe { profile, tab, .. }
+            | Commands::Layout { profile, tab, .. }
+            | Commands::Eval { profile, tab, .. }
+            | Commands::Click { profile, tab, .. }
+            | Commands::Hover { profile, tab, .. }
+            | Commands::Press { profile, tab, .. }
+            | Commands::Select { profile, tab, .. }
+            | Commands::Check { profile, tab, .. }
+            | Commands::Uncheck { profile, tab, .. }
+            | Commands::SetRange { profile, tab, .. }
+            | Commands::Upload { profile, tab, .. }
+            | Commands::FillForm { profile, tab, .. }
+            | Commands::Drag { profile, tab, .. }
+            | Commands::Batch { profile, tab, .. }
+            | Commands::Detect { profile, tab, .. }
+            | Commands::A11yTree { profile, tab, .. }
+            | Commands::KeyboardAudit { profile, tab, .. }
+            | Commands::Perf { profile, tab, .. }
+            | Commands::Shifts { profile, tab, .. }
+            | Commands::Images { profile, tab, .. }
+            | Commands::Why { profile, tab, .. }
+            | Commands::FindText { profile, tab, .. }
+            | Commands::Wait { profile, tab, .. }
+            | Commands::WaitIdle { profile, tab, .. }
+            | Commands::Diagnose { profile, tab, .. }
+            | Commands::Validate { profile, tab, .. }
+            | Commands::Compare { profile, tab, .. } => Some((profile, tab)),
+            _ => None,
+        }
+    }
+
+    /// Whether the command navigates to a URL of its own before acting
+    fn navigates(&self) -> bool {
+        match self {
+            Commands::Inspect { url, .. }
+            | Commands::Type { url, .. }
+            | Commands::Scroll { url, .. }
+            | Commands::ScrollProbe { url, .. }
+            | Commands::Analyze { url, .. }
+            | Commands::Screenshot { url, .. }
+            | Commands::Iframe { url, .. }
+            | Commands::Layout { url, .. }
+            | Commands::Click { url, .. }
+            | Commands::Hover { url, .. }
+            | Commands::Press { url, .. }
+            | Commands::Select { url, .. }
+            | Commands::Check { url, .. }
+            | Commands::Uncheck { url, .. }
+            | Commands::SetRange { url, .. }
+            | Commands::Upload { url, .. }
+            | Commands::FillForm { url, .. }
+            | Commands::Drag { url, .. }
+            | Commands::Detect { url, .. }
+            | Commands::A11yTree { url, .. }
+            | Commands::KeyboardAudit { url, .. }
+            | Commands::Perf { url, .. }
+            | Commands::Images { url, .. }
+            | Commands::Why { url, .. }
+            | Commands::FindText { url, .. }
+            | Commands::WaitIdle { url, .. }
+            | Commands::Diagnose { url, .. }
+            | Commands::Validate { url, .. } => !url.is_empty(),
+            Commands::Eval { url, .. } => url.as_deref().is_some_and(|url| !url.is_empty()),
+            // "[url] <selector>"
+            Commands::Wait { target, .. } => target.len() > 1,
+            Commands::Compare { .. } => true,
+            _ => false,
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let result = run().await;
@@ -727,6 +1452,17 @@ async fn run() -> Result<()> {
 
     let cli = Cli::parse();
 
+    if let Some(route) = &cli.ensure_route {
+        let Some((profile, tab)) = cli.command.tab_target() else {
+            anyhow::bail!("--ensure-route only applies to commands that take --profile/--tab");
+        };
+        // Loading the command's own URL would throw away the screen the route restores
+        if cli.command.navigates() {
+            anyhow::bail!("--ensure-route can't be combined with a URL argument");
+        }
+        commands::route::ensure_route(route, profile, tab.clone()).await?;
+    }
+
     match cli.command {
         Commands::Inspect {
             url,
@@ -737,12 +1473,25 @@ async fn run() -> Result<()> {
             all,
             index,
             expect_one,
+            state,
+            wait_stable_frames,
             viewport,
             tab,
             console,
         } => {
             commands::inspect::handle_inspect(
-                url, selector, profile, format, depth, all, index, expect_one, viewport, tab,
+                url,
+                selector,
+                profile,
+                format,
+                depth,
+                all,
+                index,
+                expect_one,
+                state,
+                wait_stable_frames,
+                viewport,
+                tab,
                 console,
             )
             .await?
@@ -753,6 +1502,8 @@ async fn run() -> Result<()> {
             selector,
             text,
             clear,
+            key_delay,
+            submit,
             browser,
             profile,
             no_headless,
@@ -763,6 +1514,8 @@ async fn run() -> Result<()> {
                 selector,
                 text,
                 clear,
+                key_delay,
+                submit,
                 browser,
                 profile,
                 no_headless,
@@ -777,6 +1530,10 @@ async fn run() -> Result<()> {
             by_x,
             by_y,
             to,
+            until_selector_count,
+            until_stable,
+            max_steps,
+            format,
             browser,
             profile,
             no_headless,
@@ -788,6 +1545,10 @@ async fn run() -> Result<()> {
                 by_x,
                 by_y,
                 to,
+                until_selector_count,
+                until_stable,
+                max_steps,
+                format,
                 browser,
                 profile,
                 no_headless,
@@ -796,6 +1557,29 @@ async fn run() -> Result<()> {
             .await?
         }
 
+        Commands::ScrollProbe {
+            selector,
+            positions,
+            wait_stable_frames,
+            url,
+            tab,
+            browser,
+            profile,
+            format,
+        } => {
+            commands::scroll_probe::handle_scroll_probe(
+                selector,
+                positions,
+                wait_stable_frames,
+                url,
+                tab,
+                browser,
+                profile,
+                format,
+            )
+            .await?
+        }
+
         Commands::Screenshot {
             url,
             selector,
@@ -894,6 +1678,10 @@ async fn run() -> Result<()> {
             url,
             selector,
             index,
+            button,
+            double,
+            offset,
+            modifiers,
             browser: browser_name,
             profile,
             viewport,
@@ -904,6 +1692,10 @@ async fn run() -> Result<()> {
                 url,
                 selector,
                 index,
+                button,
+                double,
+                offset,
+                modifiers,
                 browser_name,
                 profile,
                 viewport,
@@ -913,14 +1705,139 @@ async fn run() -> Result<()> {
             .await?
         }
 
+        Commands::Drag {
+            source,
+            target,
+            url,
+            steps,
+            mode,
+            tab,
+            browser,
+            profile,
+            format,
+        } => {
+            commands::drag::handle_drag(
+                source, target, url, steps, mode, tab, browser, profile, format,
+            )
+            .await?
+        }
+
+        Commands::Hover {
+            url,
+            selector,
+            index,
+            offset,
+            tab,
+            browser,
+            profile,
+        } => {
+            commands::hover::handle_hover(url, selector, index, offset, tab, browser, profile)
+                .await?
+        }
+
+        Commands::Select {
+            selector,
+            value,
+            label,
+            index,
+            url,
+            tab,
+            browser,
+            profile,
+            format,
+        } => {
+            commands::form::handle_select(
+                selector, value, label, index, url, tab, browser, profile, format,
+            )
+            .await?
+        }
+
+        Commands::Check {
+            selector,
+            url,
+            tab,
+            browser,
+            profile,
+            format,
+        } => {
+            commands::form::handle_check(selector, true, url, tab, browser, profile, format).await?
+        }
+
+        Commands::Uncheck {
+            selector,
+            url,
+            tab,
+            browser,
+            profile,
+            format,
+        } => {
+            commands::form::handle_check(selector, false, url, tab, browser, profile, format)
+                .await?
+        }
+
+        Commands::SetRange {
+            selector,
+            value,
+            url,
+            tab,
+            browser,
+            profile,
+            format,
+        } => {
+            commands::form::handle_set_range(selector, value, url, tab, browser, profile, format)
+                .await?
+        }
+
+        Commands::Upload {
+            selector,
+            files,
+            url,
+            tab,
+            browser,
+            profile,
+            format,
+        } => {
+            commands::form::handle_upload(selector, files, url, tab, browser, profile, format)
+                .await?
+        }
+
+        Commands::FillForm {
+            url,
+            form,
+            data,
+            submit,
+            tab,
+            browser,
+            profile,
+            format,
+        } => {
+            commands::form::handle_fill_form(url, form, data, submit, tab, browser, profile, format)
+                .await?
+        }
+
+        Commands::Press {
+            keys,
+            selector,
+            url,
+            delay,
+            tab,
+            browser,
+            profile,
+        } => {
+            commands::press::handle_press(keys, selector, url, delay, tab, browser, profile).await?
+        }
+
         Commands::Batch {
             commands,
             tab: tab_name,
             browser,
             stop_on_error,
             headless,
+            no_headless,
             profile,
             viewport,
+            format,
+            unsafe_eval,
         } => {
             commands::batch::handle_batch(
                 commands,
@@ -928,12 +1845,26 @@ async fn run() -> Result<()> {
                 browser,
                 stop_on_error,
                 headless,
+                no_headless,
                 profile,
                 viewport,
+                format,
+                unsafe_eval,
             )
             .await?
         }
 
+        Commands::Record {
+            url,
+            tab,
+            profile,
+            browser,
+            no_headless,
+            output,
+        } => {
+            commands::record::handle_record(url, tab, profile, browser, no_headless, output).await?
+        }
+
         Commands::Detect {
             url,
             context,
@@ -942,6 +1873,88 @@ async fn run() -> Result<()> {
             profile,
         } => commands::detect::handle_detect(url, context, tab, browser, profile).await?,
 
+        Commands::A11yTree {
+            url,
+            selector,
+            tab,
+            browser,
+            profile,
+            format,
+            max_nodes,
+            interactive_only,
+        } => {
+            commands::a11y_tree::handle_a11y_tree(
+                url,
+                selector,
+                tab,
+                browser,
+                profile,
+                format,
+                max_nodes,
+                interactive_only,
+            )
+            .await?
+        }
+
+        Commands::Perf {
+            url,
+            tab,
+            browser,
+            profile,
+            format,
+            interact,
+            settle,
+        } => {
+            commands::perf::handle_perf(url, tab, browser, profile, format, interact, settle)
+                .await?
+        }
+
+        Commands::Why {
+            selector,
+            property,
+            url,
+            index,
+            tab,
+            browser,
+            profile,
+            format,
+        } => {
+            commands::why::handle_why(
+                url, selector, property, index, tab, browser, profile, format,
+            )
+            .await?
+        }
+
+        Commands::Images {
+            url,
+            tab,
+            browser,
+            profile,
+            format,
+            tolerance,
+        } => commands::images::handle_images(url, tab, browser, profile, format, tolerance).await?,
+
+        Commands::Shifts {
+            tab,
+            profile,
+            format,
+            clear,
+        } => commands::shifts::handle_shifts(tab, profile, format, clear).await?,
+
+        Commands::KeyboardAudit {
+            url,
+            tab,
+            browser,
+            profile,
+            format,
+            max_tabs,
+        } => {
+            commands::keyboard_audit::handle_keyboard_audit(
+                url, tab, browser, profile, format, max_tabs,
+            )
+            .await?
+        }
+
         Commands::FindText {
             text,
             url,
@@ -969,6 +1982,19 @@ async fn run() -> Result<()> {
             .await?
         }
 
+        Commands::Wait {
+            target,
+            condition,
+            timeout,
+            tab,
+            browser,
+            profile,
+            format,
+        } => {
+            commands::wait::handle_wait(target, condition, timeout, tab, browser, profile, format)
+                .await?
+        }
+
         Commands::WaitNavigation {
             url,
             to,
@@ -1015,12 +2041,16 @@ async fn run() -> Result<()> {
 
         Commands::Profile { command } => commands::profile::handle_profile(command).await?,
 
+        Commands::Route { command } => commands::route::handle_route(command).await?,
+
         Commands::Analyze {
             url,
             selector,
             focus,
             proximity,
             index,
+            state,
+            wait_stable_frames,
             browser,
             profile,
             no_headless,
@@ -1033,6 +2063,8 @@ async fn run() -> Result<()> {
                 focus,
                 proximity,
                 index,
+                state,
+                wait_stable_frames,
                 browser,
                 profile,
                 no_headless,
@@ -1044,7 +2076,6 @@ async fn run() -> Result<()> {
 
         Commands::Daemon { command } => commands::daemon::handle_daemon(command).await?,
 
-
         Commands::Version => commands::version::handle_version().await?,
 
         Commands::Diagnose {
@@ -1078,17 +2109,24 @@ async fn run() -> Result<()> {
             no_headless,
             format,
             tab,
+            config,
+            list_rules,
         } => {
-            commands::validate::handle_validate(
-                url,
-                check,
-                browser,
-                profile,
-                no_headless,
-                format,
-                tab,
-            )
-            .await?
+            if list_rules {
+                commands::validate::list_rules(format)?
+            } else {
+                commands::validate::handle_validate(
+                    url,
+                    check,
+                    browser,
+                    profile,
+                    no_headless,
+                    format,
+                    tab,
+                    config,
+                )
+                .await?
+            }
         }
 
         Commands::Compare {
@@ -1096,6 +2134,7 @@ async fn run() -> Result<()> {
             url2,
             mode,
             selector,
+            wait_stable_frames,
             browser,
             profile,
             viewport,
@@ -1108,6 +2147,7 @@ async fn run() -> Result<()> {
                 url2,
                 mode,
                 selector,
+                wait_stable_frames,
                 browser,
                 profile,
                 viewport,
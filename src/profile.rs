@@ -38,6 +38,7 @@ impl ProfileManager {
         Ok(ProfileManager { profiles_dir })
     }
 
+    #[allow(dead_code)]
     pub fn create_profile(&self, name: &str, browser: &str) -> Result<PathBuf> {
         let profile_path = self.profiles_dir.join(name);
 
@@ -63,6 +64,7 @@ impl ProfileManager {
         Ok(profile_path)
     }
 
+    #[allow(dead_code)]
     pub fn delete_profile(&self, name: &str) -> Result<()> {
         let profile_path = self.profiles_dir.join(name);
 
@@ -75,6 +77,7 @@ impl ProfileManager {
         Ok(())
     }
 
+    #[allow(dead_code)]
     pub fn list_profiles(&self) -> Result<Vec<ProfileMetadata>> {
         let mut profiles = Vec::new();
 
@@ -92,7 +95,7 @@ impl ProfileManager {
             }
         }
 
-        profiles.sort_by(|a, b| b.last_used.cmp(&a.last_used));
+        profiles.sort_by_key(|p| std::cmp::Reverse(p.last_used));
         Ok(profiles)
     }
 
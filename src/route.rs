@@ -0,0 +1,149 @@
+//! Named navigation recipes ("routes") for re-establishing SPA state
+//!
+//! A route pairs a page predicate (the flow `if:` syntax, e.g.
+//! `exists('.product-details')`) with the batch steps that reach that screen.
+//! Routes are stored as JSON files under `~/.webprobe/routes/`.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::daemon::flow::{Condition, Flow};
+
+/// A saved navigation recipe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Route {
+    /// Route name
+    pub name: String,
+    /// Predicate that is true when the tab is already on this screen
+    pub check: String,
+    /// Batch steps (or a flow object) that navigate to the screen
+    pub steps: Value,
+    /// When the route was defined
+    pub created_at: DateTime<Utc>,
+}
+
+impl Route {
+    /// Build and validate a route
+    pub fn new(name: &str, check: &str, steps: Value) -> Result<Self> {
+        validate_name(name)?;
+        Condition::parse(check).context("Invalid --check predicate")?;
+        let flow = Flow::from_value(steps.clone())?;
+        if flow.steps.is_empty() {
+            anyhow::bail!("Route '{}' needs at least one step", name);
+        }
+        // Routes replay silently from --ensure-route, so no arbitrary JavaScript
+        if flow.contains_step_type("eval") {
+            anyhow::bail!("Route '{}' can't contain eval steps", name);
+        }
+
+        Ok(Route {
+            name: name.to_string(),
+            check: check.to_string(),
+            steps,
+            created_at: Utc::now(),
+        })
+    }
+}
+
+/// Route names become file names, so keep them simple
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!(
+            "Invalid route name '{}'. Use letters, digits, '-' and '_'",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Stores routes on disk
+pub struct RouteStore {
+    routes_dir: PathBuf,
+}
+
+impl RouteStore {
+    pub fn new() -> Result<Self> {
+        let home_dir = dirs::home_dir().context("Unable to determine home directory")?;
+        Self::with_dir(home_dir.join(".webprobe").join("routes"))
+    }
+
+    /// Use a custom directory (created if missing)
+    pub fn with_dir(routes_dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&routes_dir)?;
+        Ok(RouteStore { routes_dir })
+    }
+
+    fn route_path(&self, name: &str) -> PathBuf {
+        self.routes_dir.join(format!("{}.json", name))
+    }
+
+    /// Save a route, refusing to overwrite unless `replace` is set
+    pub fn save(&self, route: &Route, replace: bool) -> Result<()> {
+        let path = self.route_path(&route.name);
+        if path.exists() && !replace {
+            anyhow::bail!(
+                "Route '{}' already exists. Use --force to replace it",
+                route.name
+            );
+        }
+
+        fs::write(&path, serde_json::to_string_pretty(route)?)?;
+        info!("Saved route '{}'", route.name);
+        Ok(())
+    }
+
+    pub fn load(&self, name: &str) -> Result<Route> {
+        validate_name(name)?;
+        let path = self.route_path(name);
+        if !path.exists() {
+            anyhow::bail!(
+                "Route '{}' does not exist. Define it with: webprobe route define {} --steps <json|@file> --check <predicate>",
+                name,
+                name
+            );
+        }
+
+        let content = fs::read_to_string(&path)?;
+        serde_json::from_str(&content).context(format!("Failed to read route '{}'", name))
+    }
+
+    pub fn list(&self) -> Result<Vec<Route>> {
+        let mut routes = Vec::new();
+        for entry in fs::read_dir(&self.routes_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Ok(content) = fs::read_to_string(&path)
+                && let Ok(route) = serde_json::from_str::<Route>(&content)
+            {
+                routes.push(route);
+            }
+        }
+        routes.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(routes)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        validate_name(name)?;
+        let path = self.route_path(name);
+        if !path.exists() {
+            anyhow::bail!("Route '{}' does not exist", name);
+        }
+
+        fs::remove_file(&path)?;
+        info!("Deleted route '{}'", name);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "route_test.rs"]
+mod route_test;
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use crate::route::*;
+    use serde_json::json;
+
+    fn steps() -> serde_json::Value {
+        json!([
+            {"type": "goto", "url": "http://localhost:3000"},
+            {"type": "click", "selector": ".nav-products"}
+        ])
+    }
+
+    #[test]
+    fn test_route_validation() {
+        assert!(Route::new("product-details", "exists('.product-details')", steps()).is_ok());
+        assert!(Route::new("../escape", "exists('.x')", steps()).is_err());
+        assert!(Route::new("", "exists('.x')", steps()).is_err());
+        assert!(Route::new("products", "hovered('.x')", steps()).is_err());
+        assert!(Route::new("products", "exists('.x')", json!([])).is_err());
+        assert!(Route::new("products", "exists('.x')", json!("goto")).is_err());
+        let with_eval = json!([
+            {"type": "goto", "url": "http://localhost:3000"},
+            {"for_each": ".row", "steps": [{"type": "eval", "script": "1"}]}
+        ]);
+        assert!(Route::new("products", "exists('.x')", with_eval).is_err());
+    }
+
+    #[test]
+    fn test_route_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RouteStore::with_dir(dir.path().join("routes")).unwrap();
+
+        let route = Route::new("products", "url_contains('/products')", steps()).unwrap();
+        store.save(&route, false).unwrap();
+
+        // Saving again needs --force
+        assert!(store.save(&route, false).is_err());
+        store.save(&route, true).unwrap();
+
+        let loaded = store.load("products").unwrap();
+        assert_eq!(loaded.check, "url_contains('/products')");
+        assert_eq!(loaded.steps, steps());
+
+        let other = Route::new("checkout", "exists('#pay')", steps()).unwrap();
+        store.save(&other, false).unwrap();
+        let names: Vec<String> = store.list().unwrap().into_iter().map(|r| r.name).collect();
+        assert_eq!(names, vec!["checkout", "products"]);
+
+        store.delete("products").unwrap();
+        assert!(store.load("products").is_err());
+        assert!(store.delete("products").is_err());
+    }
+}
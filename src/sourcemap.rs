@@ -0,0 +1,382 @@
+//! CSS source maps for `why`
+//!
+//! A declaration found by the cascade walk is located in its stylesheet's
+//! text, and that position is translated through the stylesheet's source map
+//! (`sourceMappingURL`, including inline `data:` maps) to the file that was
+//! actually written, e.g. `src/components/Card.module.scss:42`.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// A position in an authored source file (1-based)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    column: u32,
+    /// (source index, line, column), all 0-based
+    original: Option<(usize, u32, u32)>,
+}
+
+/// A decoded version 3 source map
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    sources: Vec<String>,
+    /// Segments per generated line, sorted by column
+    lines: Vec<Vec<Segment>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMap {
+    #[serde(default)]
+    source_root: Option<String>,
+    #[serde(default)]
+    sources: Vec<Option<String>>,
+    #[serde(default)]
+    mappings: String,
+    #[serde(default)]
+    sections: Vec<RawSection>,
+}
+
+#[derive(Deserialize)]
+struct RawSection {
+    offset: RawOffset,
+    map: RawMap,
+}
+
+#[derive(Deserialize)]
+struct RawOffset {
+    line: u32,
+    column: u32,
+}
+
+impl SourceMap {
+    /// Parse a map; relative `sources` are resolved against `map_url`
+    pub fn parse(json: &str, map_url: Option<&url::Url>) -> Result<Self> {
+        let raw: RawMap = serde_json::from_str(json).context("Invalid source map")?;
+        let mut map = SourceMap::default();
+        map.add(&raw, map_url, 0, 0)?;
+        Ok(map)
+    }
+
+    /// Add a map (or an index map's sections) at a generated offset
+    fn add(
+        &mut self,
+        raw: &RawMap,
+        map_url: Option<&url::Url>,
+        line_offset: u32,
+        column_offset: u32,
+    ) -> Result<()> {
+        if !raw.sections.is_empty() {
+            for section in &raw.sections {
+                self.add(
+                    &section.map,
+                    map_url,
+                    section.offset.line,
+                    section.offset.column,
+                )?;
+            }
+            return Ok(());
+        }
+
+        let base = self.sources.len();
+        self.sources.extend(raw.sources.iter().map(|source| {
+            source_path(
+                source.as_deref().unwrap_or(""),
+                raw.source_root.as_deref().unwrap_or(""),
+                map_url,
+            )
+        }));
+
+        // Source index, line and column are relative to the previous segment
+        // across the whole map; the generated column resets every line
+        let (mut source, mut line, mut column) = (0i64, 0i64, 0i64);
+        for (i, text) in raw.mappings.split(';').enumerate() {
+            let generated_line = line_offset as usize + i;
+            let mut generated_column = 0i64;
+            let mut segments = Vec::new();
+
+            for segment in text.split(',').filter(|s| !s.is_empty()) {
+                let fields = decode_vlq(segment)?;
+                generated_column += fields[0];
+                let original = if fields.len() >= 4 {
+                    source += fields[1];
+                    line += fields[2];
+                    column += fields[3];
+                    Some((base + source as usize, line as u32, column as u32))
+                } else {
+                    None
+                };
+                let offset = if i == 0 { column_offset } else { 0 };
+                segments.push(Segment {
+                    column: generated_column as u32 + offset,
+                    original,
+                });
+            }
+
+            if self.lines.len() <= generated_line {
+                self.lines.resize(generated_line + 1, Vec::new());
+            }
+            self.lines[generated_line].extend(segments);
+            self.lines[generated_line].sort_by_key(|s| s.column);
+        }
+        Ok(())
+    }
+
+    /// Authored location of a 1-based generated line and column
+    pub fn lookup(&self, line: u32, column: u32) -> Option<SourceLocation> {
+        let segments = self.lines.get(line.checked_sub(1)? as usize)?;
+        let column = column.saturating_sub(1);
+        // The closest mapped segment at or before the column, or the line's
+        // first one when the declaration starts before any mapping
+        let segment = segments
+            .iter()
+            .rev()
+            .find(|s| s.column <= column && s.original.is_some())
+            .or_else(|| segments.iter().find(|s| s.original.is_some()))?;
+        let (source, line, column) = segment.original?;
+        Some(SourceLocation {
+            file: self.sources.get(source)?.clone(),
+            line: line + 1,
+            column: column + 1,
+        })
+    }
+}
+
+fn decode_vlq(segment: &str) -> Result<Vec<i64>> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut values = Vec::new();
+    let (mut value, mut shift) = (0i64, 0);
+    for byte in segment.bytes() {
+        let digit = ALPHABET
+            .iter()
+            .position(|&b| b == byte)
+            .with_context(|| format!("Invalid source map mapping: {}", segment))?
+            as i64;
+        value += (digit & 31) << shift;
+        if digit & 32 != 0 {
+            shift += 5;
+            // Values are 32-bit, so a longer run of continuations is garbage
+            if shift > 30 {
+                anyhow::bail!("Invalid source map mapping: {}", segment);
+            }
+            continue;
+        }
+        values.push(if value & 1 == 1 {
+            -(value >> 1)
+        } else {
+            value >> 1
+        });
+        value = 0;
+        shift = 0;
+    }
+    if values.is_empty() || shift != 0 {
+        anyhow::bail!("Invalid source map mapping: {}", segment);
+    }
+    Ok(values)
+}
+
+/// Path of a source relative to the server root (or on disk for file:
+/// URLs) when it's relative to the map, otherwise cleaned up as is
+fn source_path(source: &str, source_root: &str, map_url: Option<&url::Url>) -> String {
+    let joined = if source_root.is_empty() {
+        source.to_string()
+    } else {
+        format!("{}/{}", source_root.trim_end_matches('/'), source)
+    };
+    if let Some(map_url) = map_url
+        && !joined.contains("://")
+        && !joined.starts_with('/')
+        && let Ok(resolved) = map_url.join(&joined)
+        && resolved.scheme() == map_url.scheme()
+    {
+        return match resolved.scheme() {
+            "file" => resolved.path().to_string(),
+            _ => resolved.path().trim_start_matches('/').to_string(),
+        };
+    }
+    clean_source_path(source, source_root)
+}
+
+/// Readable path for a source: bundler prefixes dropped, `sourceRoot` applied
+pub fn clean_source_path(source: &str, source_root: &str) -> String {
+    let joined = if source_root.is_empty() || source.contains("://") || source.starts_with('/') {
+        source.to_string()
+    } else {
+        format!("{}/{}", source_root.trim_end_matches('/'), source)
+    };
+
+    let path = match joined.split_once("://") {
+        // webpack://app-name/./src/x.scss, webpack:///./src/x.scss
+        Some(("webpack", rest)) => rest.split_once('/').map_or(rest, |(_, p)| p),
+        Some(("file", rest)) => rest,
+        _ => joined.as_str(),
+    };
+    let path = path.strip_prefix("/@fs").unwrap_or(path);
+    let path = path.split('?').next().unwrap_or(path);
+    path.strip_prefix("./").unwrap_or(path).to_string()
+}
+
+/// The `sourceMappingURL` comment of a stylesheet (the last one wins)
+pub fn source_mapping_url(css: &str) -> Option<&str> {
+    let start = css.rfind("sourceMappingURL=")? + "sourceMappingURL=".len();
+    let rest = &css[start..];
+    let end = rest
+        .find(|c: char| c.is_whitespace() || c == '*')
+        .unwrap_or(rest.len());
+    Some(rest[..end].trim()).filter(|url| !url.is_empty())
+}
+
+/// Contents of a `data:` URL (base64 or percent-encoded)
+pub fn decode_data_url(url: &str) -> Result<String> {
+    let (header, data) = url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(','))
+        .context("Not a data: URL")?;
+
+    let bytes = if header.ends_with(";base64") {
+        base64::engine::general_purpose::STANDARD
+            .decode(data.trim())
+            .context("Invalid base64 in source map URL")?
+    } else {
+        let mut bytes = Vec::with_capacity(data.len());
+        let mut rest = data.as_bytes();
+        while let Some((&byte, tail)) = rest.split_first() {
+            let hex = tail.get(..2).and_then(|h| std::str::from_utf8(h).ok());
+            match (byte, hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+                (b'%', Some(decoded)) => {
+                    bytes.push(decoded);
+                    rest = &tail[2..];
+                }
+                _ => {
+                    bytes.push(byte);
+                    rest = tail;
+                }
+            }
+        }
+        bytes
+    };
+    String::from_utf8(bytes).context("Source map is not UTF-8")
+}
+
+/// Selector text compared the way CSSOM serializes it: whitespace collapsed,
+/// no spaces around combinators, no quotes
+fn normalize_selector(selector: &str) -> String {
+    let mut out = String::new();
+    for word in selector.split_whitespace() {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    for punct in [">", "+", "~", ",", "(", ")"] {
+        out = out
+            .replace(&format!(" {}", punct), punct)
+            .replace(&format!("{} ", punct), punct);
+    }
+    out.replace(['"', '\''], "")
+}
+
+/// 1-based line and column of `property` in the rule for `selector`, or of
+/// the rule itself when the property isn't written there literally
+pub fn locate_declaration(css: &str, selector: &str, property: &str) -> Option<(u32, u32)> {
+    let target = normalize_selector(selector);
+    let bytes = css.as_bytes();
+    let mut fallback = None;
+    let mut prelude_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = css[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 2);
+                continue;
+            }
+            b'"' | b'\'' => {
+                let quote = bytes[i];
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'{' => {
+                let prelude = css[prelude_start..i].trim();
+                if normalize_selector(prelude) == target {
+                    let rule_start =
+                        prelude_start + css[prelude_start..i].find(prelude).unwrap_or(0);
+                    if let Some(at) = find_property(css, i + 1, property) {
+                        return Some(position(css, at));
+                    }
+                    fallback.get_or_insert(rule_start);
+                }
+                prelude_start = i + 1;
+            }
+            b'}' | b';' => prelude_start = i + 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    fallback.map(|at| position(css, at))
+}
+
+/// Byte offset of `property:` among the block's own declarations
+fn find_property(css: &str, block_start: usize, property: &str) -> Option<usize> {
+    let bytes = css.as_bytes();
+    let mut depth = 0;
+    let mut declaration_start = block_start;
+    let mut i = block_start;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = css[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 2);
+                declaration_start = i;
+                continue;
+            }
+            b'{' => depth += 1,
+            b'}' if depth == 0 => return None,
+            b'}' => {
+                depth -= 1;
+                declaration_start = i + 1;
+            }
+            b';' if depth == 0 => declaration_start = i + 1,
+            b':' if depth == 0 => {
+                let name = css[declaration_start..i].trim();
+                if name.eq_ignore_ascii_case(property) {
+                    return Some(declaration_start + css[declaration_start..i].find(name)?);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn position(css: &str, offset: usize) -> (u32, u32) {
+    let before = &css[..offset];
+    let line = before.matches('\n').count() as u32 + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |newline| &before[newline + 1..])
+        .chars()
+        .count() as u32
+        + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+#[path = "sourcemap_test.rs"]
+mod sourcemap_test;
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use crate::sourcemap::*;
+
+    // .card{margin:24px}   <- src/components/Card.module.scss, margin on line 42
+    // .title{color:red}    <- src/Title.scss, color on line 6
+    const MAP: &str = r#"{
+        "version": 3,
+        "sourceRoot": "",
+        "sources": ["webpack://app/./src/components/Card.module.scss", "src/Title.scss"],
+        "names": [],
+        "mappings": "AAAA,MAyCE;ACrCF,QACE"
+    }"#;
+
+    #[test]
+    fn test_vlq_decoding() {
+        assert_eq!(decode_vlq("AAAA").unwrap(), vec![0, 0, 0, 0]);
+        assert_eq!(decode_vlq("jB").unwrap(), vec![-17]);
+        assert_eq!(decode_vlq("w+B").unwrap(), vec![1000]);
+        assert!(decode_vlq("w").is_err());
+        assert!(decode_vlq("!").is_err());
+        // Continuation digits past 32 bits used to overflow the shift
+        assert!(decode_vlq(&"g".repeat(20)).is_err());
+    }
+
+    #[test]
+    fn test_lookup() {
+        let map = SourceMap::parse(MAP, None).unwrap();
+
+        let margin = map.lookup(1, 7).unwrap();
+        assert_eq!(margin.file, "src/components/Card.module.scss");
+        assert_eq!((margin.line, margin.column), (42, 3));
+
+        // Between segments the closest one before wins
+        assert_eq!(map.lookup(1, 3).unwrap().line, 1);
+
+        let color = map.lookup(2, 9).unwrap();
+        assert_eq!(color.file, "src/Title.scss");
+        assert_eq!((color.line, color.column), (6, 3));
+
+        assert!(map.lookup(3, 1).is_none());
+        assert!(map.lookup(0, 1).is_none());
+    }
+
+    #[test]
+    fn test_index_map_sections() {
+        let index = format!(
+            r#"{{"version": 3, "sections": [{{"offset": {{"line": 10, "column": 0}}, "map": {}}}]}}"#,
+            MAP
+        );
+        let map = SourceMap::parse(&index, None).unwrap();
+        assert!(map.lookup(1, 1).is_none());
+        assert_eq!(map.lookup(11, 7).unwrap().line, 42);
+    }
+
+    #[test]
+    fn test_sources_relative_to_map() {
+        let json = r#"{"version": 3, "sources": ["../../src/Card.scss", "webpack:///./src/a.scss"], "mappings": "AAAA,CCAA"}"#;
+        let url = url::Url::parse("http://localhost:3000/assets/css/app.css.map").unwrap();
+        let map = SourceMap::parse(json, Some(&url)).unwrap();
+
+        assert_eq!(map.lookup(1, 1).unwrap().file, "src/Card.scss");
+        assert_eq!(map.lookup(1, 2).unwrap().file, "src/a.scss");
+    }
+
+    #[test]
+    fn test_clean_source_path() {
+        assert_eq!(
+            clean_source_path("webpack:///./src/a.scss", ""),
+            "src/a.scss"
+        );
+        assert_eq!(
+            clean_source_path("file:///home/dev/app/src/a.css", ""),
+            "/home/dev/app/src/a.css"
+        );
+        assert_eq!(clean_source_path("a.scss", "styles/"), "styles/a.scss");
+        assert_eq!(
+            clean_source_path("/@fs/home/dev/app/a.css?direct", ""),
+            "/home/dev/app/a.css"
+        );
+    }
+
+    #[test]
+    fn test_source_mapping_url() {
+        let css = ".a{color:red}\n/*# sourceMappingURL=app.css.map */\n";
+        assert_eq!(source_mapping_url(css), Some("app.css.map"));
+        assert_eq!(source_mapping_url(".a{}"), None);
+
+        let inline = "data:application/json;base64,eyJ2ZXJzaW9uIjozfQ==";
+        assert_eq!(decode_data_url(inline).unwrap(), r#"{"version":3}"#);
+        assert_eq!(
+            decode_data_url("data:application/json,%7B%22a%22%3A1%7D").unwrap(),
+            r#"{"a":1}"#
+        );
+    }
+
+    #[test]
+    fn test_locate_declaration() {
+        let css = "/* .card { margin: 0 } */\n\
+                   @media (min-width: 600px) {\n\
+                   \x20 .card  >  p,\n\
+                   \x20 .title { color: red; }\n\
+                   }\n\
+                   .card > p {\n\
+                   \x20 padding: 0;\n\
+                   \x20 margin: 24px 0;\n\
+                   }\n";
+
+        assert_eq!(locate_declaration(css, ".card > p", "margin"), Some((8, 3)));
+        // CSSOM serialization of the selector list still matches the source
+        assert_eq!(
+            locate_declaration(css, ".card > p, .title", "color"),
+            Some((4, 12))
+        );
+        // Not written in that rule: point at the rule itself
+        assert_eq!(
+            locate_declaration(css, ".card > p", "border-top"),
+            Some((6, 1))
+        );
+        assert_eq!(locate_declaration(css, ".missing", "margin"), None);
+    }
+}
@@ -85,6 +85,40 @@ pub struct ElementInfo {
     /// Metadata about element selection
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<ElementMetadata>,
+    /// How much of the element can actually be seen
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occlusion: Option<Occlusion>,
+    /// Pseudo-classes forced while the element was inspected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forced_states: Vec<PseudoState>,
+}
+
+/// Visible share of an element's box, sampled with `elementsFromPoint`
+///
+/// Percentages are of the whole box. Overlays with `pointer-events: none`
+/// aren't hit-tested, so they don't count as covering.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Occlusion {
+    /// Part of the box inside the viewport
+    pub in_viewport_percent: f64,
+    /// Part inside the viewport that isn't covered or clipped
+    pub visible_percent: f64,
+    /// Part hidden by an ancestor's overflow clipping
+    pub clipped_percent: f64,
+    /// Elements painted on top of it, most coverage first
+    pub covered_by: Vec<Occluder>,
+}
+
+/// An element covering part of another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Occluder {
+    /// Selector of the covering layer (its nearest positioned ancestor)
+    pub selector: String,
+    pub tag: String,
+    pub position: String,
+    pub z_index: String,
+    /// Part of the covered element's box it hides
+    pub percent: f64,
 }
 
 /// Metadata about element selection when multiple matches exist
@@ -168,6 +202,471 @@ impl ViewportSize {
     }
 }
 
+/// Mouse button used for a click
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MouseButton {
+    #[default]
+    Left,
+    Middle,
+    Right,
+}
+
+impl std::str::FromStr for MouseButton {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "left" => Ok(MouseButton::Left),
+            "middle" => Ok(MouseButton::Middle),
+            "right" => Ok(MouseButton::Right),
+            _ => anyhow::bail!("Invalid mouse button '{}'. Use left, middle or right", s),
+        }
+    }
+}
+
+/// Modifier key held down during a pointer action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Modifier {
+    Shift,
+    Control,
+    Alt,
+    Meta,
+}
+
+impl std::str::FromStr for Modifier {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "shift" => Ok(Modifier::Shift),
+            "control" | "ctrl" => Ok(Modifier::Control),
+            "alt" | "option" => Ok(Modifier::Alt),
+            "meta" | "cmd" | "command" => Ok(Modifier::Meta),
+            _ => anyhow::bail!(
+                "Invalid modifier '{}'. Use shift, control, alt or meta",
+                s.trim()
+            ),
+        }
+    }
+}
+
+/// How to click an element; the default is a plain left click
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClickOptions {
+    #[serde(default)]
+    pub button: MouseButton,
+    /// Click twice (fires `dblclick`)
+    #[serde(default)]
+    pub double: bool,
+    /// Point to click, relative to the element's top-left corner (its center if not set)
+    #[serde(default)]
+    pub offset: Option<(i64, i64)>,
+    /// Keys held down during the click
+    #[serde(default)]
+    pub modifiers: Vec<Modifier>,
+}
+
+impl ClickOptions {
+    /// A plain left click, done with WebDriver's element click
+    pub fn is_plain(&self) -> bool {
+        *self == ClickOptions::default()
+    }
+
+    /// Parse an offset from "X,Y" format (e.g., "10,5")
+    pub fn parse_offset(s: &str) -> Result<(i64, i64)> {
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| anyhow::anyhow!("Invalid offset format. Use X,Y (e.g., 10,5)"))?;
+        let x = x
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("Invalid x in offset"))?;
+        let y = y
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("Invalid y in offset"))?;
+        Ok((x, y))
+    }
+
+    /// Parse a comma-separated modifier list (e.g., "shift,meta")
+    pub fn parse_modifiers(s: &str) -> Result<Vec<Modifier>> {
+        s.split(',')
+            .filter(|m| !m.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+/// Where a dragged element is dropped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DragTarget {
+    /// The center of the first element matching a selector
+    Element(String),
+    /// A point in the viewport
+    Point(i64, i64),
+}
+
+impl DragTarget {
+    /// "X,Y" is a point, anything else a selector
+    pub fn parse(s: &str) -> Self {
+        match ClickOptions::parse_offset(s) {
+            Ok((x, y)) => DragTarget::Point(x, y),
+            Err(_) => DragTarget::Element(s.to_string()),
+        }
+    }
+}
+
+/// How a drag is performed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DragMode {
+    /// HTML5 events for `draggable` elements, pointer actions otherwise
+    #[default]
+    Auto,
+    /// Mouse down, intermediate moves, mouse up
+    Pointer,
+    /// Synthetic dragstart/dragover/drop events with a DataTransfer
+    Html5,
+}
+
+impl std::str::FromStr for DragMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(DragMode::Auto),
+            "pointer" => Ok(DragMode::Pointer),
+            "html5" => Ok(DragMode::Html5),
+            _ => anyhow::bail!("Invalid drag mode '{}'. Use auto, pointer or html5", s),
+        }
+    }
+}
+
+/// Which text of an `<option>` `select` matches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptionMatch {
+    /// The `value` attribute
+    Value,
+    /// The visible label
+    Label,
+    /// The value, falling back to the label
+    #[default]
+    Any,
+}
+
+/// Change to a native form control, made the way a user would make it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum FormAction {
+    /// Choose options of a `<select>` (several for `<select multiple>`)
+    Select {
+        values: Vec<String>,
+        by: OptionMatch,
+        /// Which matching `<select>` (0-based) when several match
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        index: Option<usize>,
+    },
+    /// Check or uncheck a checkbox, radio button or `aria-checked` widget
+    Check { checked: bool },
+    /// Move an `<input type="range">` to a value
+    SetRange { value: f64 },
+    /// Choose files (absolute paths) in an `<input type="file">`
+    Upload { files: Vec<String> },
+}
+
+impl FormAction {
+    /// Command name, as used in messages and layout shift phases
+    pub fn name(&self) -> &'static str {
+        match self {
+            FormAction::Select { .. } => "select",
+            FormAction::Check { checked: true } => "check",
+            FormAction::Check { checked: false } => "uncheck",
+            FormAction::SetRange { .. } => "set-range",
+            FormAction::Upload { .. } => "upload",
+        }
+    }
+}
+
+/// "selector:count" - enough elements matching a selector, e.g. ".feed-item:10"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectorCount {
+    pub selector: String,
+    pub count: usize,
+}
+
+impl SelectorCount {
+    /// The count follows the last ':', so pseudo-classes in the selector are fine
+    pub fn parse(s: &str) -> Result<Self> {
+        let (selector, count) = s
+            .rsplit_once(':')
+            .filter(|(selector, _)| !selector.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Expected 'selector:count', got '{}'", s))?;
+        let count = count
+            .trim()
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid count '{}' in '{}'", count, s))?;
+        Ok(SelectorCount {
+            selector: selector.trim().to_string(),
+            count,
+        })
+    }
+}
+
+/// When repeated scrolling stops: once enough items are loaded, once the page
+/// stops growing, or after `max_steps` scrolls, whichever comes first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrollUntil {
+    #[serde(default)]
+    pub selector_count: Option<SelectorCount>,
+    /// How long (ms) the scroll height must stay the same to count as the end
+    pub stable_ms: u64,
+    pub max_steps: usize,
+}
+
+impl Default for ScrollUntil {
+    fn default() -> Self {
+        ScrollUntil {
+            selector_count: None,
+            stable_ms: 1000,
+            max_steps: 50,
+        }
+    }
+}
+
+/// A page scroll offset `scroll-probe` stops at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollPosition {
+    /// Pixels from the top of the page
+    Offset(u32),
+    /// As far down as the page goes
+    Bottom,
+}
+
+impl ScrollPosition {
+    /// Parse a list like "0,500,1500,bottom" ("top" is 0)
+    pub fn parse_list(s: &str) -> Result<Vec<Self>> {
+        let positions = s
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(|p| match p.to_lowercase().as_str() {
+                "top" => Ok(ScrollPosition::Offset(0)),
+                "bottom" => Ok(ScrollPosition::Bottom),
+                _ => p.parse().map(ScrollPosition::Offset).map_err(|_| {
+                    anyhow::anyhow!(
+                        "Invalid scroll position '{}'. Use pixel offsets, top or bottom",
+                        p
+                    )
+                }),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if positions.is_empty() {
+            anyhow::bail!("No scroll positions given");
+        }
+        Ok(positions)
+    }
+}
+
+impl std::fmt::Display for ScrollPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScrollPosition::Offset(y) => write!(f, "{}", y),
+            ScrollPosition::Bottom => write!(f, "bottom"),
+        }
+    }
+}
+
+/// What `wait` polls for, given as "visible", "text=Saved", "count>=3" and so
+/// on. Element states look at the first match; `hidden` also holds when
+/// nothing matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WaitCondition {
+    Present,
+    Visible,
+    Hidden,
+    Detached,
+    Clickable,
+    /// Some matching element's text contains this
+    Text(String),
+    /// The number of matching elements compares with `count` by `op`
+    Count {
+        op: CountOp,
+        count: usize,
+    },
+}
+
+/// Comparison in a `count` wait condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountOp {
+    Eq,
+    Ge,
+    Gt,
+    Le,
+    Lt,
+}
+
+impl CountOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CountOp::Eq => "=",
+            CountOp::Ge => ">=",
+            CountOp::Gt => ">",
+            CountOp::Le => "<=",
+            CountOp::Lt => "<",
+        }
+    }
+
+    pub fn matches(&self, actual: usize, expected: usize) -> bool {
+        match self {
+            CountOp::Eq => actual == expected,
+            CountOp::Ge => actual >= expected,
+            CountOp::Gt => actual > expected,
+            CountOp::Le => actual <= expected,
+            CountOp::Lt => actual < expected,
+        }
+    }
+}
+
+impl WaitCondition {
+    pub fn parse(s: &str) -> Result<Self> {
+        if let Some(text) = s.strip_prefix("text=") {
+            if text.is_empty() {
+                anyhow::bail!("Empty text in wait condition '{}'", s);
+            }
+            return Ok(WaitCondition::Text(text.to_string()));
+        }
+        if let Some(rest) = s.trim().strip_prefix("count") {
+            // Longest operators first so ">=" isn't read as ">"
+            let (op, count) = [
+                ("==", CountOp::Eq),
+                (">=", CountOp::Ge),
+                ("<=", CountOp::Le),
+                ("=", CountOp::Eq),
+                (">", CountOp::Gt),
+                ("<", CountOp::Lt),
+            ]
+            .into_iter()
+            .find_map(|(prefix, op)| rest.trim_start().strip_prefix(prefix).map(|n| (op, n)))
+            .ok_or_else(|| anyhow::anyhow!("Expected 'count>=N' (or =, >, <=, <), got '{}'", s))?;
+            let count = count
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid count '{}' in '{}'", count.trim(), s))?;
+            return Ok(WaitCondition::Count { op, count });
+        }
+        match s.trim().to_lowercase().as_str() {
+            "present" => Ok(WaitCondition::Present),
+            "visible" => Ok(WaitCondition::Visible),
+            "hidden" => Ok(WaitCondition::Hidden),
+            "detached" => Ok(WaitCondition::Detached),
+            "clickable" => Ok(WaitCondition::Clickable),
+            _ => Err(anyhow::anyhow!(
+                "Unknown wait condition '{}'. Use present, visible, hidden, detached, clickable, text=... or count>=N",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for WaitCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WaitCondition::Present => write!(f, "present"),
+            WaitCondition::Visible => write!(f, "visible"),
+            WaitCondition::Hidden => write!(f, "hidden"),
+            WaitCondition::Detached => write!(f, "detached"),
+            WaitCondition::Clickable => write!(f, "clickable"),
+            WaitCondition::Text(text) => write!(f, "text={}", text),
+            WaitCondition::Count { op, count } => write!(f, "count{}{}", op.as_str(), count),
+        }
+    }
+}
+
+/// User-action pseudo-class forced onto an element while it's inspected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PseudoState {
+    Hover,
+    Active,
+    Focus,
+    FocusVisible,
+    FocusWithin,
+    Visited,
+    Target,
+}
+
+impl PseudoState {
+    /// Name without the colon, as in `:focus-visible`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PseudoState::Hover => "hover",
+            PseudoState::Active => "active",
+            PseudoState::Focus => "focus",
+            PseudoState::FocusVisible => "focus-visible",
+            PseudoState::FocusWithin => "focus-within",
+            PseudoState::Visited => "visited",
+            PseudoState::Target => "target",
+        }
+    }
+
+    /// Parse a list in "hover,focus-visible" format
+    pub fn parse_list(s: &str) -> Result<Vec<PseudoState>> {
+        let mut states = Vec::new();
+        for state in s.split(',').filter(|s| !s.trim().is_empty()) {
+            let state: PseudoState = state.parse()?;
+            if !states.contains(&state) {
+                states.push(state);
+            }
+        }
+        Ok(states)
+    }
+
+    /// States the element's ancestors are in as a result: a hovered or
+    /// pressed element's ancestors match `:hover`/`:active` too, a focused
+    /// one's match `:focus-within`
+    pub fn ancestor_states(states: &[PseudoState]) -> Vec<PseudoState> {
+        let mut inherited = Vec::new();
+        for state in states {
+            let state = match state {
+                PseudoState::Hover | PseudoState::Active => *state,
+                PseudoState::Focus | PseudoState::FocusVisible | PseudoState::FocusWithin => {
+                    PseudoState::FocusWithin
+                }
+                PseudoState::Visited | PseudoState::Target => continue,
+            };
+            if !inherited.contains(&state) {
+                inherited.push(state);
+            }
+        }
+        inherited
+    }
+}
+
+impl std::str::FromStr for PseudoState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().trim_start_matches(':').to_lowercase().as_str() {
+            "hover" => Ok(PseudoState::Hover),
+            "active" => Ok(PseudoState::Active),
+            "focus" => Ok(PseudoState::Focus),
+            "focus-visible" => Ok(PseudoState::FocusVisible),
+            "focus-within" => Ok(PseudoState::FocusWithin),
+            "visited" => Ok(PseudoState::Visited),
+            "target" => Ok(PseudoState::Target),
+            _ => anyhow::bail!(
+                "Invalid state '{}'. Use hover, active, focus, focus-visible, focus-within, visited or target",
+                s.trim()
+            ),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BoxModel {
     pub margin: BoxSides,
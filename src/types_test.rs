@@ -21,6 +21,171 @@ fn test_viewport_size_parse() {
     assert!(ViewportSize::parse("1920X1080").is_err()); // uppercase X
 }
 
+#[test]
+fn test_click_options_parse() {
+    assert_eq!(ClickOptions::parse_offset("10,5").unwrap(), (10, 5));
+    assert_eq!(ClickOptions::parse_offset(" -3 , 0").unwrap(), (-3, 0));
+    assert!(ClickOptions::parse_offset("10").is_err());
+    assert!(ClickOptions::parse_offset("10,y").is_err());
+
+    assert_eq!(
+        ClickOptions::parse_modifiers("shift,Cmd, ctrl").unwrap(),
+        vec![Modifier::Shift, Modifier::Meta, Modifier::Control]
+    );
+    assert!(ClickOptions::parse_modifiers("shift,hyper").is_err());
+
+    assert_eq!("RIGHT".parse::<MouseButton>().unwrap(), MouseButton::Right);
+    assert!("back".parse::<MouseButton>().is_err());
+
+    assert!(ClickOptions::default().is_plain());
+    let double = ClickOptions {
+        double: true,
+        ..Default::default()
+    };
+    assert!(!double.is_plain());
+}
+
+#[test]
+fn test_form_action_serde() {
+    let action = FormAction::Select {
+        values: vec!["lg".to_string()],
+        by: OptionMatch::Label,
+        index: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&action).unwrap(),
+        serde_json::json!({"action": "select", "values": ["lg"], "by": "label"})
+    );
+
+    let action: FormAction =
+        serde_json::from_value(serde_json::json!({"action": "set_range", "value": 40.0})).unwrap();
+    assert_eq!(action, FormAction::SetRange { value: 40.0 });
+    assert_eq!(action.name(), "set-range");
+    assert_eq!(FormAction::Check { checked: false }.name(), "uncheck");
+}
+
+#[test]
+fn test_pseudo_state_parse() {
+    assert_eq!(
+        PseudoState::parse_list("hover, :focus-visible,hover").unwrap(),
+        vec![PseudoState::Hover, PseudoState::FocusVisible]
+    );
+    assert!(PseudoState::parse_list("hovered").is_err());
+    assert_eq!(
+        serde_json::to_value(PseudoState::FocusWithin).unwrap(),
+        "focus-within"
+    );
+
+    // Ancestors of a hovered, focused element are hovered and contain focus
+    assert_eq!(
+        PseudoState::ancestor_states(&[
+            PseudoState::Hover,
+            PseudoState::FocusVisible,
+            PseudoState::Focus,
+            PseudoState::Visited,
+        ]),
+        vec![PseudoState::Hover, PseudoState::FocusWithin]
+    );
+}
+
+#[test]
+fn test_scroll_positions_parse() {
+    assert_eq!(
+        ScrollPosition::parse_list("0, 500,1500,bottom").unwrap(),
+        vec![
+            ScrollPosition::Offset(0),
+            ScrollPosition::Offset(500),
+            ScrollPosition::Offset(1500),
+            ScrollPosition::Bottom
+        ]
+    );
+    assert_eq!(
+        ScrollPosition::parse_list("TOP").unwrap(),
+        vec![ScrollPosition::Offset(0)]
+    );
+    assert!(ScrollPosition::parse_list("-100").is_err());
+    assert!(ScrollPosition::parse_list("middle").is_err());
+    assert!(ScrollPosition::parse_list(",").is_err());
+    assert_eq!(ScrollPosition::Bottom.to_string(), "bottom");
+}
+
+#[test]
+fn test_wait_condition_parse() {
+    assert_eq!(
+        WaitCondition::parse("visible").unwrap(),
+        WaitCondition::Visible
+    );
+    assert_eq!(
+        WaitCondition::parse("Detached").unwrap(),
+        WaitCondition::Detached
+    );
+    // Text is taken as-is, spaces and '=' included
+    assert_eq!(
+        WaitCondition::parse("text= a=b ").unwrap(),
+        WaitCondition::Text(" a=b ".to_string())
+    );
+    assert_eq!(
+        WaitCondition::parse("count>=3").unwrap(),
+        WaitCondition::Count {
+            op: CountOp::Ge,
+            count: 3
+        }
+    );
+    assert_eq!(
+        WaitCondition::parse("count == 0").unwrap(),
+        WaitCondition::Count {
+            op: CountOp::Eq,
+            count: 0
+        }
+    );
+    assert!(CountOp::Lt.matches(2, 3));
+    assert!(!CountOp::Gt.matches(3, 3));
+    assert_eq!(
+        WaitCondition::parse("count<10").unwrap().to_string(),
+        "count<10"
+    );
+    assert!(WaitCondition::parse("count~3").is_err());
+    assert!(WaitCondition::parse("count>=many").is_err());
+    assert!(WaitCondition::parse("text=").is_err());
+    assert!(WaitCondition::parse("gone").is_err());
+}
+
+#[test]
+fn test_selector_count_parse() {
+    assert_eq!(
+        SelectorCount::parse(".feed-item:10").unwrap(),
+        SelectorCount {
+            selector: ".feed-item".to_string(),
+            count: 10
+        }
+    );
+    // Only the last ':' separates the count
+    assert_eq!(
+        SelectorCount::parse("li:not(.ad):25").unwrap().selector,
+        "li:not(.ad)"
+    );
+    assert!(SelectorCount::parse(".feed-item").is_err());
+    assert!(SelectorCount::parse("li:first-child").is_err());
+    assert!(SelectorCount::parse(":10").is_err());
+}
+
+#[test]
+fn test_drag_target_parse() {
+    assert_eq!(DragTarget::parse("300,120"), DragTarget::Point(300, 120));
+    assert_eq!(
+        DragTarget::parse("#column-done"),
+        DragTarget::Element("#column-done".to_string())
+    );
+    // A selector list is not a point
+    assert_eq!(
+        DragTarget::parse(".a,.b"),
+        DragTarget::Element(".a,.b".to_string())
+    );
+
+    assert_eq!("HTML5".parse::<DragMode>().unwrap(), DragMode::Html5);
+    assert!("touch".parse::<DragMode>().is_err());
+}
+
 #[test]
 fn test_inspection_depth_values() {
     // Test that enum values are as expected
@@ -0,0 +1,814 @@
+//! Page validation rules
+//!
+//! Every check `validate` runs is a [`Rule`] with a stable ID, a default
+//! severity and (where one applies) a WCAG success criterion. The browser
+//! script returns raw findings per rule ID; this module turns them into the
+//! report, the score, and SARIF / JUnit output.
+//!
+//! Rules can be switched off or re-tuned per project with a config file
+//! (`.webprobe-validate.yaml` or `.webprobe-validate.json` in the current
+//! directory, or `validate --config <file>`):
+//!
+//! ```yaml
+//! rules:
+//!   canonical-link: off
+//!   title-length:
+//!     threshold: 70
+//!   dom-size:
+//!     severity: error
+//!     threshold: 2500
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Config files picked up from the current directory
+pub const CONFIG_FILES: &[&str] = &[
+    ".webprobe-validate.yaml",
+    ".webprobe-validate.yml",
+    ".webprobe-validate.json",
+];
+
+/// Score deducted per finding, and the most a single rule can deduct
+const ERROR_WEIGHT: i64 = 5;
+const WARNING_WEIGHT: i64 = 2;
+const RULE_CAP: i64 = 20;
+
+/// Output formats for `validate`
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Full JSON report
+    Json,
+    /// Human-readable summary
+    Simple,
+    /// SARIF 2.1.0 for code-scanning UIs
+    Sarif,
+    /// JUnit XML for CI test reports
+    Junit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    /// SARIF result level
+    fn sarif_level(&self) -> &'static str {
+        match self {
+            Severity::Info => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    fn weight(&self) -> i64 {
+        match self {
+            Severity::Info => 0,
+            Severity::Warning => WARNING_WEIGHT,
+            Severity::Error => ERROR_WEIGHT,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Accessibility,
+    Seo,
+    Performance,
+}
+
+impl Category {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::Accessibility => "accessibility",
+            Category::Seo => "seo",
+            Category::Performance => "performance",
+        }
+    }
+}
+
+/// A validation rule
+#[derive(Debug)]
+pub struct Rule {
+    /// Stable ID used in config files, reports and `--check`
+    pub id: &'static str,
+    pub category: Category,
+    pub severity: Severity,
+    /// WCAG success criterion, e.g. "1.1.1"
+    pub wcag: Option<&'static str>,
+    pub description: &'static str,
+    pub help_url: Option<&'static str>,
+    /// Default threshold for rules that have one (meaning is per rule)
+    pub threshold: Option<f64>,
+    /// Extra `--check` groups that select this rule besides its category
+    pub tags: &'static [&'static str],
+}
+
+/// All rules, in report order
+pub static RULES: &[Rule] = &[
+    Rule {
+        id: "image-alt",
+        category: Category::Accessibility,
+        severity: Severity::Error,
+        wcag: Some("1.1.1"),
+        description: "Images must have an alt attribute (empty for decorative images)",
+        help_url: Some("https://www.w3.org/WAI/WCAG21/Understanding/non-text-content"),
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "form-label",
+        category: Category::Accessibility,
+        severity: Severity::Error,
+        wcag: Some("4.1.2"),
+        description: "Form controls must have a label",
+        help_url: Some("https://www.w3.org/WAI/WCAG21/Understanding/name-role-value"),
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "button-name",
+        category: Category::Accessibility,
+        severity: Severity::Error,
+        wcag: Some("4.1.2"),
+        description: "Buttons must have an accessible name",
+        help_url: Some("https://www.w3.org/WAI/WCAG21/Understanding/name-role-value"),
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "heading-order",
+        category: Category::Accessibility,
+        severity: Severity::Warning,
+        wcag: Some("1.3.1"),
+        description: "Heading levels should only increase by one",
+        help_url: Some("https://www.w3.org/WAI/WCAG21/Understanding/info-and-relationships"),
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "landmark-main",
+        category: Category::Accessibility,
+        severity: Severity::Warning,
+        wcag: Some("1.3.1"),
+        description: "Page should have a main landmark",
+        help_url: Some("https://www.w3.org/WAI/WCAG21/Understanding/info-and-relationships"),
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "color-contrast",
+        category: Category::Accessibility,
+        severity: Severity::Error,
+        wcag: Some("1.4.3"),
+        description: "Text must meet the AA contrast ratio (4.5:1, 3:1 for large text)",
+        help_url: Some("https://www.w3.org/WAI/WCAG21/Understanding/contrast-minimum"),
+        threshold: None,
+        tags: &["contrast"],
+    },
+    Rule {
+        id: "color-contrast-enhanced",
+        category: Category::Accessibility,
+        severity: Severity::Info,
+        wcag: Some("1.4.6"),
+        description: "Text should meet the AAA contrast ratio (7:1, 4.5:1 for large text)",
+        help_url: Some("https://www.w3.org/WAI/WCAG21/Understanding/contrast-enhanced"),
+        threshold: None,
+        tags: &["contrast"],
+    },
+    Rule {
+        id: "document-title",
+        category: Category::Seo,
+        severity: Severity::Error,
+        wcag: Some("2.4.2"),
+        description: "Page must have a non-empty <title>",
+        help_url: Some("https://www.w3.org/WAI/WCAG21/Understanding/page-titled"),
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "title-length",
+        category: Category::Seo,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "Title should not exceed the threshold (characters)",
+        help_url: None,
+        threshold: Some(60.0),
+        tags: &[],
+    },
+    Rule {
+        id: "meta-description",
+        category: Category::Seo,
+        severity: Severity::Error,
+        wcag: None,
+        description: "Page should have a meta description",
+        help_url: None,
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "meta-description-length",
+        category: Category::Seo,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "Meta description should not exceed the threshold (characters)",
+        help_url: None,
+        threshold: Some(160.0),
+        tags: &[],
+    },
+    Rule {
+        id: "page-has-h1",
+        category: Category::Seo,
+        severity: Severity::Error,
+        wcag: None,
+        description: "Page should have an h1",
+        help_url: None,
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "single-h1",
+        category: Category::Seo,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "Page should have only one h1",
+        help_url: None,
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "canonical-link",
+        category: Category::Seo,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "Page should declare a canonical URL",
+        help_url: None,
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "structured-data-valid",
+        category: Category::Seo,
+        severity: Severity::Error,
+        wcag: None,
+        description: "JSON-LD must parse and use schema.org; microdata needs a schema.org itemtype",
+        help_url: Some(
+            "https://developers.google.com/search/docs/appearance/structured-data/intro-structured-data",
+        ),
+        threshold: None,
+        tags: &["structured-data"],
+    },
+    Rule {
+        id: "structured-data-required",
+        category: Category::Seo,
+        severity: Severity::Error,
+        wcag: None,
+        description: "schema.org items must have the properties rich results require",
+        help_url: Some(
+            "https://developers.google.com/search/docs/appearance/structured-data/search-gallery",
+        ),
+        threshold: None,
+        tags: &["structured-data"],
+    },
+    Rule {
+        id: "structured-data-recommended",
+        category: Category::Seo,
+        severity: Severity::Info,
+        wcag: None,
+        description: "schema.org items should have the recommended rich result properties",
+        help_url: Some(
+            "https://developers.google.com/search/docs/appearance/structured-data/search-gallery",
+        ),
+        threshold: None,
+        tags: &["structured-data"],
+    },
+    Rule {
+        id: "open-graph",
+        category: Category::Seo,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "Open Graph needs og:title, og:type, og:image and og:url (absolute URLs)",
+        help_url: Some("https://ogp.me/"),
+        threshold: None,
+        tags: &["social"],
+    },
+    Rule {
+        id: "twitter-card",
+        category: Category::Seo,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "Twitter card type must be valid, with title, description and image (or og: fallbacks)",
+        help_url: Some("https://developer.x.com/en/docs/x-for-websites/cards/overview/markup"),
+        threshold: None,
+        tags: &["social"],
+    },
+    Rule {
+        id: "social-image-size",
+        category: Category::Seo,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "og:image / twitter:image must load and meet the minimum size for the card",
+        help_url: None,
+        threshold: None,
+        tags: &["social"],
+    },
+    Rule {
+        id: "canonical-hreflang",
+        category: Category::Seo,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "Canonical and hreflang links must be absolute, consistent and self-referencing",
+        help_url: Some(
+            "https://developers.google.com/search/docs/specialty/international/localized-versions",
+        ),
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "robots-meta",
+        category: Category::Seo,
+        severity: Severity::Error,
+        wcag: None,
+        description: "Robots meta directives must not conflict with each other or the canonical",
+        help_url: Some(
+            "https://developers.google.com/search/docs/crawling-indexing/robots-meta-tag",
+        ),
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "heading-structure",
+        category: Category::Seo,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "Headings should not be empty and the h1 should come first",
+        help_url: None,
+        threshold: None,
+        tags: &[],
+    },
+    Rule {
+        id: "dom-size",
+        category: Category::Performance,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "DOM should not have more elements than the threshold",
+        help_url: None,
+        threshold: Some(1500.0),
+        tags: &[],
+    },
+    Rule {
+        id: "image-size",
+        category: Category::Performance,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "Images should not be larger than the threshold (pixels per side)",
+        help_url: None,
+        threshold: Some(2000.0),
+        tags: &[],
+    },
+    Rule {
+        id: "inline-styles",
+        category: Category::Performance,
+        severity: Severity::Warning,
+        wcag: None,
+        description: "Page should not have more inline style attributes than the threshold",
+        help_url: None,
+        threshold: Some(20.0),
+        tags: &[],
+    },
+];
+
+/// Look up a rule by ID
+pub fn rule(id: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|r| r.id == id)
+}
+
+/// A rule's entry in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RuleSetting {
+    /// `true` / `false`
+    Enabled(bool),
+    /// `off` or a severity
+    Level(String),
+    Options {
+        #[serde(default)]
+        enabled: Option<bool>,
+        #[serde(default)]
+        severity: Option<Severity>,
+        #[serde(default)]
+        threshold: Option<f64>,
+    },
+}
+
+/// Per-project rule configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidationConfig {
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleSetting>,
+}
+
+/// A rule as it will run: config applied
+#[derive(Debug, Clone)]
+pub struct ResolvedRule {
+    pub rule: &'static Rule,
+    pub severity: Severity,
+    pub threshold: Option<f64>,
+}
+
+impl ValidationConfig {
+    /// Parse a config file (YAML or JSON, by extension)
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).context(format!(
+            "Failed to read validation config: {}",
+            path.display()
+        ))?;
+        let parsed: Result<ValidationConfig> = if path.extension().is_some_and(|e| e == "json") {
+            serde_json::from_str(&text).map_err(Into::into)
+        } else {
+            serde_yaml::from_str(&text).map_err(Into::into)
+        };
+        parsed
+            .and_then(|config| config.check().map(|_| config))
+            .map_err(|e| anyhow::anyhow!("Invalid validation config {}: {}", path.display(), e))
+    }
+
+    /// Load the config given on the command line, else the project's, else defaults
+    pub fn discover(explicit: Option<&Path>) -> Result<Self> {
+        if let Some(path) = explicit {
+            return Self::load(path);
+        }
+        let found: Option<PathBuf> = CONFIG_FILES.iter().map(PathBuf::from).find(|p| p.is_file());
+        match found {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Reject unknown rule IDs and bad levels
+    pub fn check(&self) -> Result<()> {
+        for (id, setting) in &self.rules {
+            if rule(id).is_none() {
+                anyhow::bail!("Unknown rule '{}'. Known rules: {}", id, rule_ids());
+            }
+            if let RuleSetting::Level(level) = setting {
+                parse_level(level)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rules selected by `--check` with this config applied
+    ///
+    /// `check` is `all`, a category (`accessibility`, `seo`, `performance`),
+    /// a tag (`contrast`, `structured-data`, `social`) or a comma-separated
+    /// list of rule IDs.
+    pub fn resolve(&self, check: &str) -> Result<Vec<ResolvedRule>> {
+        let selected: Vec<&'static Rule> = match check {
+            "all" => RULES.iter().collect(),
+            group if RULES.iter().any(|r| r.category.as_str() == group || r.tags.contains(&group)) => {
+                RULES
+                    .iter()
+                    .filter(|r| r.category.as_str() == group || r.tags.contains(&group))
+                    .collect()
+            }
+            ids => ids
+                .split(',')
+                .map(|id| {
+                    rule(id.trim()).with_context(|| {
+                        format!(
+                            "Unknown check '{}'. Use all, accessibility, contrast, seo, structured-data, social, performance or a rule ID: {}",
+                            id.trim(),
+                            rule_ids()
+                        )
+                    })
+                })
+                .collect::<Result<_>>()?,
+        };
+
+        let mut resolved = Vec::new();
+        for rule in selected {
+            let mut run = ResolvedRule {
+                rule,
+                severity: rule.severity,
+                threshold: rule.threshold,
+            };
+            match self.rules.get(rule.id) {
+                Some(RuleSetting::Enabled(false)) => continue,
+                Some(RuleSetting::Level(level)) => match parse_level(level)? {
+                    Some(severity) => run.severity = severity,
+                    None => continue,
+                },
+                Some(RuleSetting::Options {
+                    enabled,
+                    severity,
+                    threshold,
+                }) => {
+                    if *enabled == Some(false) {
+                        continue;
+                    }
+                    run.severity = severity.unwrap_or(run.severity);
+                    run.threshold = threshold.or(run.threshold);
+                }
+                Some(RuleSetting::Enabled(true)) | None => {}
+            }
+            resolved.push(run);
+        }
+        Ok(resolved)
+    }
+}
+
+/// `off` → None, otherwise a severity
+fn parse_level(level: &str) -> Result<Option<Severity>> {
+    match level {
+        "off" => Ok(None),
+        "info" => Ok(Some(Severity::Info)),
+        "warning" => Ok(Some(Severity::Warning)),
+        "error" => Ok(Some(Severity::Error)),
+        other => anyhow::bail!(
+            "Invalid rule level '{}' (expected off, info, warning or error)",
+            other
+        ),
+    }
+}
+
+fn rule_ids() -> String {
+    RULES.iter().map(|r| r.id).collect::<Vec<_>>().join(", ")
+}
+
+/// The rules handed to the browser script
+pub fn script_rules(rules: &[ResolvedRule]) -> Value {
+    Value::Array(
+        rules
+            .iter()
+            .map(|r| json!({"id": r.rule.id, "threshold": r.threshold}))
+            .collect(),
+    )
+}
+
+/// Build the validation report from the script's raw findings
+///
+/// `raw` is `{url, findings: {rule_id: [finding]}, contrast}`; every finding
+/// has a `message` plus rule-specific data (selector, counts, colors...).
+pub fn build_report(check: &str, rules: &[ResolvedRule], raw: &Value) -> Result<Value> {
+    let mut categories: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for category in ["accessibility", "seo", "performance"] {
+        categories.insert(category, Vec::new());
+    }
+    let mut rule_results = Vec::new();
+    let mut score: i64 = 100;
+    let mut counts = [0usize; 3];
+
+    for run in rules {
+        let findings = match &raw["findings"][run.rule.id] {
+            Value::Array(findings) => findings,
+            Value::Null => anyhow::bail!(
+                "Rule '{}' is not implemented by the page script",
+                run.rule.id
+            ),
+            other => anyhow::bail!("Unexpected findings for rule '{}': {}", run.rule.id, other),
+        };
+
+        for finding in findings {
+            let mut issue = json!({
+                "rule": run.rule.id,
+                "severity": run.severity,
+                "wcag": run.rule.wcag,
+            });
+            if let (Some(issue), Some(data)) = (issue.as_object_mut(), finding.as_object()) {
+                for (key, value) in data {
+                    issue.insert(key.clone(), value.clone());
+                }
+            }
+            categories
+                .get_mut(run.rule.category.as_str())
+                .expect("category list covers every rule")
+                .push(issue);
+            counts[run.severity as usize] += 1;
+        }
+
+        score -= (findings.len() as i64 * run.severity.weight()).min(RULE_CAP);
+        rule_results.push(json!({
+            "id": run.rule.id,
+            "category": run.rule.category,
+            "severity": run.severity,
+            "threshold": run.threshold,
+            "findings": findings.len(),
+        }));
+    }
+    let score = score.max(0);
+
+    let contrast = &raw["contrast"];
+    let mut report = json!({
+        "url": raw["url"],
+        "score": score,
+        "accessibility": categories["accessibility"],
+        "seo": categories["seo"],
+        "performance": categories["performance"],
+        "rules": rule_results,
+        "summary": {
+            "check_type": check,
+            "rules_run": rules.len(),
+            "accessibility_issues": categories["accessibility"].len(),
+            "seo_issues": categories["seo"].len(),
+            "performance_issues": categories["performance"].len(),
+            "errors": counts[Severity::Error as usize],
+            "warnings": counts[Severity::Warning as usize],
+            "infos": counts[Severity::Info as usize],
+            "contrast_aa_failures": contrast["aa"]["failed"].as_u64().unwrap_or(0),
+            "contrast_aaa_failures": contrast["aaa"]["failed"].as_u64().unwrap_or(0),
+            "score": score,
+        },
+    });
+    if !contrast.is_null() {
+        report["contrast"] = contrast.clone();
+    }
+    Ok(report)
+}
+
+/// All issues in a report, in rule order
+fn issues(report: &Value) -> impl Iterator<Item = &Value> {
+    ["accessibility", "seo", "performance"]
+        .into_iter()
+        .flat_map(move |c| report[c].as_array().into_iter().flatten())
+}
+
+/// Render a report as SARIF 2.1.0 for code-scanning tools
+pub fn to_sarif(report: &Value) -> Value {
+    let ran: Vec<&str> = report["rules"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|r| r["id"].as_str())
+        .collect();
+
+    let rules: Vec<Value> = ran
+        .iter()
+        .filter_map(|id| rule(id))
+        .map(|r| {
+            let mut tags = vec![r.category.as_str().to_string()];
+            if let Some(wcag) = r.wcag {
+                tags.push(format!("wcag{}", wcag.replace('.', "")));
+            }
+            let mut descriptor = json!({
+                "id": r.id,
+                "shortDescription": {"text": r.description},
+                "defaultConfiguration": {"level": r.severity.sarif_level()},
+                "properties": {"tags": tags},
+            });
+            if let Some(url) = r.help_url {
+                descriptor["helpUri"] = json!(url);
+            }
+            descriptor
+        })
+        .collect();
+
+    let url = report["url"].as_str().unwrap_or("");
+    let results: Vec<Value> = issues(report)
+        .map(|issue| {
+            let id = issue["rule"].as_str().unwrap_or("");
+            let severity: Severity =
+                serde_json::from_value(issue["severity"].clone()).unwrap_or(Severity::Warning);
+            let mut location = json!({
+                "physicalLocation": {"artifactLocation": {"uri": url}},
+            });
+            if let Some(snippet) = issue["snippet"].as_str() {
+                location["physicalLocation"]["region"] = json!({"snippet": {"text": snippet}});
+            }
+            if let Some(selector) = issue["selector"].as_str() {
+                location["logicalLocations"] =
+                    json!([{"fullyQualifiedName": selector, "kind": "element"}]);
+            }
+            json!({
+                "ruleId": id,
+                "ruleIndex": ran.iter().position(|r| *r == id),
+                "level": severity.sarif_level(),
+                "message": {"text": issue["message"].as_str().unwrap_or(id)},
+                "locations": [location],
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "webprobe",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+/// Render a report as JUnit XML: one test case per rule, failing on
+/// warning or error findings
+pub fn to_junit(report: &Value) -> String {
+    let url = report["url"].as_str().unwrap_or("");
+    let mut suites = String::new();
+    let (mut total_tests, mut total_failures) = (0, 0);
+
+    for category in ["accessibility", "seo", "performance"] {
+        let rules: Vec<&Value> = report["rules"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|r| r["category"] == category)
+            .collect();
+        if rules.is_empty() {
+            continue;
+        }
+
+        let mut cases = String::new();
+        let mut failures = 0;
+        for run in &rules {
+            let id = run["id"].as_str().unwrap_or("");
+            let findings: Vec<&Value> = report[category]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|i| i["rule"] == id)
+                .collect();
+            let lines: Vec<String> = findings
+                .iter()
+                .map(|i| match i["selector"].as_str() {
+                    Some(selector) => {
+                        format!("{} ({})", i["message"].as_str().unwrap_or(""), selector)
+                    }
+                    None => i["message"].as_str().unwrap_or("").to_string(),
+                })
+                .collect();
+
+            cases.push_str(&format!(
+                "    <testcase classname=\"webprobe.{}\" name=\"{}\">\n",
+                category,
+                xml_escape(id)
+            ));
+            if !findings.is_empty() && run["severity"] != "info" {
+                failures += 1;
+                cases.push_str(&format!(
+                    "      <failure type=\"{}\" message=\"{} finding(s) on {}\">{}</failure>\n",
+                    run["severity"].as_str().unwrap_or(""),
+                    findings.len(),
+                    xml_escape(url),
+                    xml_escape(&lines.join("\n"))
+                ));
+            } else if !findings.is_empty() {
+                cases.push_str(&format!(
+                    "      <system-out>{}</system-out>\n",
+                    xml_escape(&lines.join("\n"))
+                ));
+            }
+            cases.push_str("    </testcase>\n");
+        }
+
+        total_tests += rules.len();
+        total_failures += failures;
+        suites.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n{}  </testsuite>\n",
+            category,
+            rules.len(),
+            failures,
+            cases
+        ));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"webprobe validate\" tests=\"{}\" failures=\"{}\">\n{}</testsuites>\n",
+        total_tests, total_failures, suites
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+#[path = "validation_test.rs"]
+mod validation_test;
@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod tests {
+    use crate::validation::*;
+    use serde_json::json;
+
+    fn ids(rules: &[ResolvedRule]) -> Vec<&'static str> {
+        rules.iter().map(|r| r.rule.id).collect()
+    }
+
+    #[test]
+    fn test_rule_ids_are_unique() {
+        let mut seen = std::collections::HashSet::new();
+        for rule in RULES {
+            assert!(seen.insert(rule.id), "duplicate rule id {}", rule.id);
+        }
+    }
+
+    #[test]
+    fn test_resolve_check_selection() {
+        let config = ValidationConfig::default();
+
+        assert_eq!(config.resolve("all").unwrap().len(), RULES.len());
+        assert_eq!(
+            ids(&config.resolve("contrast").unwrap()),
+            vec!["color-contrast", "color-contrast-enhanced"]
+        );
+        assert!(
+            config
+                .resolve("seo")
+                .unwrap()
+                .iter()
+                .all(|r| r.rule.category == Category::Seo)
+        );
+        assert_eq!(
+            ids(&config.resolve("image-alt, dom-size").unwrap()),
+            vec!["image-alt", "dom-size"]
+        );
+        assert_eq!(
+            ids(&config.resolve("social").unwrap()),
+            vec!["open-graph", "twitter-card", "social-image-size"]
+        );
+        assert_eq!(config.resolve("structured-data").unwrap().len(), 3);
+        assert!(config.resolve("no-such-rule").is_err());
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let config: ValidationConfig = serde_yaml::from_str(
+            r#"
+rules:
+  canonical-link: off
+  single-h1: false
+  landmark-main: error
+  dom-size:
+    severity: error
+    threshold: 2500
+"#,
+        )
+        .unwrap();
+        config.check().unwrap();
+
+        let rules = config.resolve("all").unwrap();
+        assert!(!ids(&rules).contains(&"canonical-link"));
+        assert!(!ids(&rules).contains(&"single-h1"));
+
+        let landmark = rules.iter().find(|r| r.rule.id == "landmark-main").unwrap();
+        assert_eq!(landmark.severity, Severity::Error);
+
+        let dom = rules.iter().find(|r| r.rule.id == "dom-size").unwrap();
+        assert_eq!(dom.severity, Severity::Error);
+        assert_eq!(dom.threshold, Some(2500.0));
+
+        let unknown: ValidationConfig =
+            serde_yaml::from_str("rules:\n  no-such-rule: off\n").unwrap();
+        assert!(unknown.check().is_err());
+        let bad_level: ValidationConfig =
+            serde_yaml::from_str("rules:\n  image-alt: fatal\n").unwrap();
+        assert!(bad_level.check().is_err());
+    }
+
+    fn sample_report() -> serde_json::Value {
+        let rules = ValidationConfig::default()
+            .resolve("image-alt,color-contrast-enhanced,document-title")
+            .unwrap();
+        let raw = json!({
+            "url": "http://localhost:3000/",
+            "findings": {
+                "image-alt": [
+                    {"message": "Image has no alt text", "selector": "img:nth-of-type(2)"},
+                    {"message": "Image has no alt text", "selector": "#logo"}
+                ],
+                "color-contrast-enhanced": [
+                    {"message": "Contrast 5:1 is below 7:1 (AAA)", "selector": "p"}
+                ],
+                "document-title": []
+            },
+            "contrast": null
+        });
+        build_report("all", &rules, &raw).unwrap()
+    }
+
+    #[test]
+    fn test_build_report() {
+        let report = sample_report();
+
+        // Two errors (5 each); info findings don't cost points
+        assert_eq!(report["score"], 90);
+        assert_eq!(report["summary"]["errors"], 2);
+        assert_eq!(report["summary"]["infos"], 1);
+        assert_eq!(report["accessibility"][0]["rule"], "image-alt");
+        assert_eq!(report["accessibility"][0]["wcag"], "1.1.1");
+        assert_eq!(report["accessibility"][0]["severity"], "error");
+        assert_eq!(report["accessibility"][1]["selector"], "#logo");
+        assert!(report.get("contrast").is_none());
+
+        // A rule the page script doesn't know is an error, not a silent pass
+        let rules = ValidationConfig::default().resolve("image-alt").unwrap();
+        assert!(build_report("all", &rules, &json!({"findings": {}})).is_err());
+    }
+
+    #[test]
+    fn test_sarif_output() {
+        let sarif = to_sarif(&sample_report());
+        let run = &sarif["runs"][0];
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 3);
+        assert_eq!(run["results"].as_array().unwrap().len(), 3);
+        assert_eq!(run["results"][0]["ruleId"], "image-alt");
+        assert_eq!(run["results"][0]["level"], "error");
+        assert_eq!(
+            run["results"][0]["locations"][0]["logicalLocations"][0]["fullyQualifiedName"],
+            "img:nth-of-type(2)"
+        );
+        assert_eq!(run["results"][2]["level"], "note");
+        assert_eq!(run["results"][2]["ruleIndex"], 1);
+    }
+
+    #[test]
+    fn test_sarif_snippet() {
+        let rules = ValidationConfig::default().resolve("robots-meta").unwrap();
+        let raw = json!({
+            "url": "http://localhost:3000/",
+            "findings": {
+                "robots-meta": [{
+                    "message": "Robots directives conflict: both index and noindex",
+                    "snippet": "<meta name=\"robots\" content=\"index, noindex\">"
+                }]
+            }
+        });
+        let sarif = to_sarif(&build_report("seo", &rules, &raw).unwrap());
+        let location = &sarif["runs"][0]["results"][0]["locations"][0];
+
+        assert_eq!(
+            location["physicalLocation"]["region"]["snippet"]["text"],
+            r#"<meta name="robots" content="index, noindex">"#
+        );
+        assert!(location.get("logicalLocations").is_none());
+    }
+
+    #[test]
+    fn test_junit_output() {
+        let xml = to_junit(&sample_report());
+
+        assert!(xml.contains(r#"<testsuites name="webprobe validate" tests="3" failures="1">"#));
+        assert!(xml.contains(r#"<testsuite name="accessibility" tests="2" failures="1">"#));
+        assert!(xml.contains(
+            r#"<failure type="error" message="2 finding(s) on http://localhost:3000/">"#
+        ));
+        assert!(xml.contains("Image has no alt text (#logo)"));
+        // Info-level findings are reported but don't fail the case
+        assert!(xml.contains("<system-out>Contrast 5:1 is below 7:1 (AAA) (p)</system-out>"));
+    }
+}
@@ -1,13 +1,16 @@
 use anyhow::{Context, Result};
 use fantoccini::{Client, ClientBuilder, Locator};
 use serde_json::json;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
+use crate::keys::KeyChord;
 use crate::profile::ProfileManager;
 use crate::types::{
-    DiagnosticResult, ElementInfo, ElementMetadata, InspectionDepth, LayoutInfo, Position, Size,
-    ViewportSize,
+    ClickOptions, DiagnosticResult, DragMode, DragTarget, ElementInfo, ElementMetadata,
+    InspectionDepth, LayoutInfo, MouseButton, Occlusion, OptionMatch, Position, PseudoState,
+    ScrollPosition, ScrollUntil, Size, ViewportSize, WaitCondition,
 };
+use crate::validation::{self, ValidationConfig};
 use crate::webdriver_manager::GLOBAL_WEBDRIVER_MANAGER;
 
 use std::sync::Arc;
@@ -76,6 +79,373 @@ impl BrowserType {
     }
 }
 
+/// Chrome DevTools Protocol command, sent through chromedriver's
+/// `goog/cdp/execute` extension endpoint
+#[derive(Debug)]
+struct CdpCommand {
+    cmd: &'static str,
+    params: serde_json::Value,
+}
+
+impl fantoccini::wd::WebDriverCompatibleCommand for CdpCommand {
+    fn endpoint(
+        &self,
+        base_url: &url::Url,
+        session_id: Option<&str>,
+    ) -> Result<url::Url, url::ParseError> {
+        base_url.join(&format!(
+            "session/{}/goog/cdp/execute",
+            session_id.unwrap_or_default()
+        ))
+    }
+
+    fn method_and_body(&self, _request_url: &url::Url) -> (http::Method, Option<String>) {
+        let body = json!({"cmd": self.cmd, "params": self.params});
+        (http::Method::POST, Some(body.to_string()))
+    }
+}
+
+/// Pseudo-classes forced by [`Browser::force_states`], undone by
+/// [`Browser::clear_states`]
+#[derive(Debug, Default)]
+pub struct ForcedStates {
+    /// DevTools node ids given forced states
+    nodes: Vec<i64>,
+    /// Emulated through attribute-rewritten copies of the page's rules
+    emulated: bool,
+}
+
+/// Emulates user-action pseudo-classes where DevTools isn't available: the
+/// targets (and their ancestors) get `data-webprobe-<state>` attributes and
+/// every readable rule using one of the states gets a copy right after it
+/// with `:state` replaced by the attribute, which has the same specificity.
+const FORCE_STATES_JS: &str = r#"
+    const [selector, all, index, states, ancestorStates] = arguments;
+    const matches = Array.from(document.querySelectorAll(selector));
+    const targets = all ? matches : [matches[index]].filter(Boolean);
+    if (targets.length === 0) return null;
+
+    const mark = (el, list) => list.forEach(s => el.setAttribute('data-webprobe-' + s, ''));
+    for (const el of targets) {
+        mark(el, states);
+        for (let p = el.parentElement; p; p = p.parentElement) mark(p, ancestorStates);
+    }
+
+    const wanted = [...new Set([...states, ...ancestorStates])];
+    const pattern = new RegExp(':(' + wanted.join('|') + ')(?![\\w-])', 'g');
+    const added = [];
+    let inaccessible = 0;
+    const rewrite = (list) => {
+        // Backwards, so inserted copies don't shift the rules still to visit
+        for (let i = list.cssRules.length - 1; i >= 0; i--) {
+            const rule = list.cssRules[i];
+            if (rule instanceof CSSStyleRule) {
+                pattern.lastIndex = 0;
+                if (!pattern.test(rule.selectorText)) continue;
+                const selectorText = rule.selectorText.replace(pattern, (_, s) => '[data-webprobe-' + s + ']');
+                try {
+                    list.insertRule(selectorText + ' { ' + rule.style.cssText + ' }', i + 1);
+                    added.push([list, list.cssRules[i + 1]]);
+                } catch (e) {}
+            } else if (rule.cssRules && rule.insertRule) {
+                rewrite(rule);
+            }
+        }
+    };
+    for (const sheet of document.styleSheets) {
+        try {
+            rewrite(sheet);
+        } catch (e) {
+            inaccessible++;
+        }
+    }
+    window.__webprobeForcedRules = added;
+    return { elements: targets.length, rules: added.length, inaccessible };
+"#;
+
+/// `selectorOf(el)`: a CSS selector reports can point back at. Unique ids
+/// that don't look generated are used as is (or as an anchor for the path
+/// below them); otherwise it's a tag/nth-of-type path from `<html>`. Null for
+/// detached nodes.
+const SELECTOR_OF_JS: &str = r#"
+    function selectorOf(el) {
+        if (!el || el.nodeType !== 1 || !el.isConnected) return null;
+        const uniqueId = (node) => node.id && !/\d{3,}|^\d|:/.test(node.id)
+            && document.querySelectorAll('#' + CSS.escape(node.id)).length === 1;
+        const parts = [];
+        let node = el;
+        while (node && node.nodeType === 1 && node !== document.documentElement) {
+            if (uniqueId(node)) {
+                parts.unshift('#' + CSS.escape(node.id));
+                break;
+            }
+            let part = node.tagName.toLowerCase();
+            const parent = node.parentElement;
+            if (parent) {
+                const same = Array.from(parent.children).filter(c => c.tagName === node.tagName);
+                if (same.length > 1) part += ':nth-of-type(' + (same.indexOf(node) + 1) + ')';
+            }
+            parts.unshift(part);
+            node = parent;
+        }
+        return parts.join(' > ') || 'html';
+    }
+"#;
+
+/// `occlusionOf(el)`: how much of an element's box is visible, sampled on a
+/// grid over its on-screen part with `elementsFromPoint`. Whatever is hit
+/// instead of the element covers it; hits on an ancestor mean it's clipped.
+/// Needs `SELECTOR_OF_JS` before it.
+const OCCLUSION_JS: &str = r#"
+    function occlusionOf(el) {
+        const GRID = 5;
+        const round = (n) => Math.round(n * 10) / 10;
+        // Report the layer a hit belongs to, so a link inside a sticky
+        // header is reported as the header
+        const layerOf = (hit) => {
+            for (let node = hit; node && !node.contains(el); node = node.parentElement) {
+                const style = getComputedStyle(node);
+                if (!['static', 'relative'].includes(style.position) || style.zIndex !== 'auto') {
+                    return node;
+                }
+            }
+            return hit;
+        };
+
+        const result = { in_viewport_percent: 0, visible_percent: 0, clipped_percent: 0, covered_by: [] };
+        const rect = el.getBoundingClientRect();
+        const area = rect.width * rect.height;
+        if (area === 0) return result;
+
+        const view = document.documentElement;
+        const left = Math.max(rect.left, 0), right = Math.min(rect.right, view.clientWidth);
+        const top = Math.max(rect.top, 0), bottom = Math.min(rect.bottom, view.clientHeight);
+        if (right <= left || bottom <= top) return result;
+        const onScreen = (right - left) * (bottom - top) / area * 100;
+        result.in_viewport_percent = round(onScreen);
+
+        const root = el.getRootNode().elementsFromPoint ? el.getRootNode() : document;
+        const covering = new Map();
+        let visible = 0, clipped = 0, samples = 0;
+        for (let i = 0; i < GRID; i++) {
+            for (let j = 0; j < GRID; j++) {
+                const x = left + (right - left) * (i + 0.5) / GRID;
+                const y = top + (bottom - top) * (j + 0.5) / GRID;
+                const stack = root.elementsFromPoint(x, y);
+                samples++;
+                if (stack.length && (stack[0] === el || el.contains(stack[0]))) {
+                    visible++;
+                } else if (!stack.length || stack[0].contains(el)) {
+                    clipped++;
+                } else {
+                    const layer = layerOf(stack[0]);
+                    covering.set(layer, (covering.get(layer) || 0) + 1);
+                }
+            }
+        }
+
+        result.visible_percent = round(onScreen * visible / samples);
+        result.clipped_percent = round(onScreen * clipped / samples);
+        result.covered_by = Array.from(covering.entries())
+            .sort((a, b) => b[1] - a[1])
+            .map(([node, hits]) => {
+                const style = getComputedStyle(node);
+                return {
+                    selector: selectorOf(node),
+                    tag: node.tagName.toLowerCase(),
+                    position: style.position,
+                    z_index: style.zIndex,
+                    percent: round(onScreen * hits / samples)
+                };
+            });
+        return result;
+    }
+"#;
+
+/// Frames an element's rect must hold still to count as settled
+pub const STABLE_FRAMES: u32 = 3;
+
+/// Longest `--wait-stable-frames` waits for an element to settle
+pub const STABLE_TIMEOUT_MS: u64 = 5000;
+
+/// Turns CSS transitions and animations off (`arguments[0]` true) or back on.
+/// Durations become zero rather than `none`, so animated elements still land
+/// on their end state; script animations that end are finished.
+const ANIMATIONS_JS: &str = r#"
+    const disabled = arguments[0];
+    const ID = '__webprobe-no-animations';
+    const existing = document.getElementById(ID);
+    if (!disabled) {
+        if (existing) existing.remove();
+        return false;
+    }
+    if (!existing) {
+        const style = document.createElement('style');
+        style.id = ID;
+        style.textContent = `*, *::before, *::after {
+            transition-duration: 0s !important;
+            transition-delay: 0s !important;
+            animation-duration: 0s !important;
+            animation-delay: 0s !important;
+            animation-iteration-count: 1 !important;
+            scroll-behavior: auto !important;
+        }`;
+        const attach = () => (document.head || document.documentElement).appendChild(style);
+        if (document.documentElement) {
+            attach();
+        } else {
+            // A new document, before its first element
+            new MutationObserver((_, observer) => {
+                if (!document.documentElement) return;
+                observer.disconnect();
+                attach();
+            }).observe(document, { childList: true });
+        }
+    }
+    if (document.getAnimations) {
+        for (const animation of document.getAnimations()) {
+            try {
+                if (animation.effect && animation.effect.getComputedTiming().endTime !== Infinity) {
+                    animation.finish();
+                }
+            } catch (e) {}
+        }
+    }
+    return true;
+"#;
+
+/// Resolves once the rects of the elements matching `arguments[0]` have
+/// stayed the same for `arguments[1]` frames in a row, or after
+/// `arguments[2]` ms
+const STABLE_RECT_JS: &str = r#"
+    const [selector, frames, timeout, done] = arguments;
+    const start = performance.now();
+    const snapshot = () => Array.from(document.querySelectorAll(selector))
+        .slice(0, 50)
+        .map(el => {
+            const r = el.getBoundingClientRect();
+            return [r.x, r.y, r.width, r.height].map(n => Math.round(n * 10) / 10).join(',');
+        })
+        .join('|');
+    // Background tabs get no animation frames, so fall back to a timer
+    const nextFrame = (callback) => {
+        let called = false;
+        const once = () => {
+            if (called) return;
+            called = true;
+            callback();
+        };
+        requestAnimationFrame(once);
+        setTimeout(once, 50);
+    };
+    let last = snapshot();
+    let same = 0;
+    let seen = 0;
+    const tick = () => {
+        const now = snapshot();
+        seen++;
+        same = now === last ? same + 1 : 0;
+        last = now;
+        const elapsed = Math.round(performance.now() - start);
+        if (same >= frames) return done({ stable: true, frames: seen, elapsed_ms: elapsed });
+        if (elapsed >= timeout) return done({ stable: false, frames: seen, elapsed_ms: elapsed });
+        nextFrame(tick);
+    };
+    nextFrame(tick);
+"#;
+
+/// `formFields(root)`: the fillable controls of a form (or any container),
+/// with the names a caller might know them by: name, id, label text and
+/// placeholder. `invalidFields(root)` lists the controls failing validation
+/// with the message the browser shows for them.
+/// Needs `SELECTOR_OF_JS` before it.
+const FORM_FIELDS_JS: &str = r#"
+    const squash = (text) => (text || '').replace(/\s+/g, ' ').trim();
+    // "Email address *" and "email address:" both match the key "Email address"
+    const normalize = (text) => squash(text).replace(/[\s:*]+$/, '').toLowerCase();
+
+    function labelOf(el) {
+        const labelledBy = el.getAttribute('aria-labelledby');
+        if (labelledBy) {
+            const text = squash(labelledBy.split(/\s+/)
+                .map(id => document.getElementById(id))
+                .filter(Boolean)
+                .map(node => node.textContent)
+                .join(' '));
+            if (text) return text;
+        }
+        if (el.getAttribute('aria-label')) return squash(el.getAttribute('aria-label'));
+        if (el.labels && el.labels.length) {
+            // Without the control itself: a wrapping label would include a select's options
+            const label = el.labels[0].cloneNode(true);
+            label.querySelectorAll('input, select, textarea, button').forEach(node => node.remove());
+            return squash(label.textContent);
+        }
+        return '';
+    }
+
+    function groupLabelOf(el) {
+        const fieldset = el.closest('fieldset');
+        const legend = fieldset && fieldset.querySelector('legend');
+        if (legend) return squash(legend.textContent);
+        const group = el.closest('[role="radiogroup"], [role="group"]');
+        return group ? labelOf(group) : '';
+    }
+
+    function formFields(root) {
+        const controls = root.tagName === 'FORM'
+            ? Array.from(root.elements)
+            : Array.from(root.querySelectorAll('input, select, textarea'));
+        return controls
+            .filter(el => ['INPUT', 'SELECT', 'TEXTAREA'].includes(el.tagName))
+            .filter(el => !['hidden', 'submit', 'reset', 'button', 'image'].includes(el.type))
+            .map(el => ({
+                el,
+                type: el.tagName === 'INPUT' ? el.type : el.tagName.toLowerCase(),
+                name: el.name || '',
+                id: el.id || '',
+                label: labelOf(el),
+                group_label: ['radio', 'checkbox'].includes(el.type) ? groupLabelOf(el) : '',
+                placeholder: el.placeholder || '',
+                selector: selectorOf(el),
+            }));
+    }
+
+    function messageOf(el) {
+        if (!el.validity.valid) return el.validationMessage;
+        // Script-validated forms mark fields aria-invalid and point at their error text
+        if (el.getAttribute('aria-invalid') === 'true') {
+            const ids = (el.getAttribute('aria-errormessage') || el.getAttribute('aria-describedby') || '').split(/\s+/);
+            const text = squash(ids
+                .map(id => id && document.getElementById(id))
+                .filter(Boolean)
+                .map(node => node.textContent)
+                .join(' '));
+            return text || 'Invalid';
+        }
+        return null;
+    }
+
+    function invalidFields(root) {
+        const seen = new Set();
+        return formFields(root)
+            .map(field => ({ field, message: messageOf(field.el) }))
+            .filter(({ field, message }) => {
+                // A radio group reports once
+                if (!message || (field.type === 'radio' && field.name && seen.has(field.name))) return false;
+                seen.add(field.name);
+                return true;
+            })
+            .map(({ field, message }) => ({
+                selector: field.selector,
+                name: field.name || null,
+                label: field.label || field.group_label || null,
+                missing: field.el.validity.valueMissing,
+                message,
+            }));
+    }
+"#;
+
 impl Browser {
     /// Create a new browser instance
     ///
@@ -943,6 +1313,14 @@ impl Browser {
                 None
             };
 
+            let occlusion = match self.occlusion(element).await {
+                Ok(occlusion) => Some(occlusion),
+                Err(e) => {
+                    debug!("Could not sample visibility of {}: {}", selector, e);
+                    None
+                }
+            };
+
             results.push(ElementInfo {
                 selector: selector.to_string(),
                 browser: format!("{:?}", self.browser_type),
@@ -960,6 +1338,8 @@ impl Browser {
                 text_content,
                 children_count,
                 metadata,
+                occlusion,
+                forced_states: Vec::new(),
             });
         }
 
@@ -967,6 +1347,245 @@ impl Browser {
         Ok(results)
     }
 
+    /// How much of an element is visible and what covers the rest
+    pub async fn occlusion(&self, element: &fantoccini::elements::Element) -> Result<Occlusion> {
+        let script = format!(
+            "{}\n{}\nreturn occlusionOf(arguments[0]);",
+            SELECTOR_OF_JS, OCCLUSION_JS
+        );
+        let result = self
+            .client
+            .execute(&script, vec![serde_json::to_value(element)?])
+            .await?;
+        serde_json::from_value(result).context("Unexpected occlusion data from page")
+    }
+
+    /// Force user-action pseudo-classes (`:hover`, `:focus-visible`, ...) on
+    /// the `index`th match of `selector` (or `all` of them), so computed
+    /// styles and geometry read as they would in that state
+    ///
+    /// Ancestors get the states real interaction would give them (see
+    /// [`PseudoState::ancestor_states`]). Chrome forces them through DevTools;
+    /// other browsers emulate them by rewriting the page's readable rules.
+    /// Transitions into the state are finished so nothing is caught midway.
+    pub async fn force_states(
+        &self,
+        selector: &str,
+        all: bool,
+        index: Option<usize>,
+        states: &[PseudoState],
+    ) -> Result<ForcedStates> {
+        if states.is_empty() {
+            return Ok(ForcedStates::default());
+        }
+
+        let forced = match self.browser_type {
+            BrowserType::Chrome => {
+                match self
+                    .devtools_force_states(selector, all, index, states)
+                    .await
+                {
+                    Ok(forced) => forced,
+                    Err(e) => {
+                        debug!("DevTools pseudo-states unavailable, emulating: {}", e);
+                        self.emulate_states(selector, all, index, states).await?
+                    }
+                }
+            }
+            BrowserType::Firefox => self.emulate_states(selector, all, index, states).await?,
+        };
+
+        self.finish_transitions().await;
+        Ok(forced)
+    }
+
+    /// Undo [`Browser::force_states`]; failures are only logged since the
+    /// result they'd spoil has already been read
+    pub async fn clear_states(&self, forced: ForcedStates) {
+        for node_id in &forced.nodes {
+            if let Err(e) = self
+                .cdp(
+                    "CSS.forcePseudoState",
+                    json!({ "nodeId": node_id, "forcedPseudoClasses": [] }),
+                )
+                .await
+            {
+                debug!("Failed to clear forced states: {}", e);
+            }
+        }
+
+        if forced.emulated {
+            let script = r#"
+                for (const [list, rule] of window.__webprobeForcedRules || []) {
+                    const i = Array.prototype.indexOf.call(list.cssRules, rule);
+                    if (i >= 0) list.deleteRule(i);
+                }
+                delete window.__webprobeForcedRules;
+                for (const el of document.querySelectorAll('*')) {
+                    for (const name of el.getAttributeNames()) {
+                        if (name.startsWith('data-webprobe-')) el.removeAttribute(name);
+                    }
+                }
+            "#;
+            if let Err(e) = self.client.execute(script, vec![]).await {
+                debug!("Failed to remove emulated states: {}", e);
+            }
+        }
+
+        if !forced.nodes.is_empty() || forced.emulated {
+            self.finish_transitions().await;
+        }
+    }
+
+    async fn devtools_force_states(
+        &self,
+        selector: &str,
+        all: bool,
+        index: Option<usize>,
+        states: &[PseudoState],
+    ) -> Result<ForcedStates> {
+        self.cdp("DOM.enable", json!({})).await?;
+        self.cdp("CSS.enable", json!({})).await?;
+        // Node ids can only be requested once the document has been
+        self.cdp("DOM.getDocument", json!({ "depth": 0 })).await?;
+
+        // Each target followed by its ancestors, and how many of those there are
+        let chains = format!(
+            r#"(() => {{
+                const matches = Array.from(document.querySelectorAll({}));
+                const targets = {} ? matches : [matches[{}]].filter(Boolean);
+                return targets.map(el => {{
+                    const chain = [];
+                    for (let e = el; e; e = e.parentElement) chain.push(e);
+                    return chain;
+                }});
+            }})()"#,
+            serde_json::to_string(selector)?,
+            all,
+            index.unwrap_or(0)
+        );
+        let lengths = self
+            .cdp(
+                "Runtime.evaluate",
+                json!({
+                    "expression": format!("{}.map(chain => chain.length)", chains),
+                    "returnByValue": true,
+                }),
+            )
+            .await?;
+        let lengths: Vec<usize> = serde_json::from_value(lengths["result"]["value"].clone())
+            .context("Unexpected element data from DevTools")?;
+        if lengths.is_empty() {
+            anyhow::bail!("No elements found matching selector: {}", selector);
+        }
+
+        let flat = self
+            .cdp(
+                "Runtime.evaluate",
+                json!({ "expression": format!("{}.flat()", chains) }),
+            )
+            .await?;
+        let array_id = flat["result"]["objectId"]
+            .as_str()
+            .context("Element not found through DevTools")?;
+        let properties = self
+            .cdp(
+                "Runtime.getProperties",
+                json!({ "objectId": array_id, "ownProperties": true }),
+            )
+            .await?;
+        let mut nodes = Vec::new();
+        for property in properties["result"].as_array().into_iter().flatten() {
+            if let Ok(position) = property["name"].as_str().unwrap_or("").parse::<usize>()
+                && let Some(object_id) = property["value"]["objectId"].as_str()
+            {
+                let node = self
+                    .cdp("DOM.requestNode", json!({ "objectId": object_id }))
+                    .await?;
+                nodes.push((position, node["nodeId"].as_i64().unwrap_or(0)));
+            }
+        }
+        nodes.sort_unstable();
+
+        // A node can be one target's ancestor and another target itself
+        let own: Vec<&str> = states.iter().map(|s| s.as_str()).collect();
+        let inherited: Vec<&str> = PseudoState::ancestor_states(states)
+            .iter()
+            .map(|s| s.as_str())
+            .collect();
+        let mut forced: std::collections::BTreeMap<i64, Vec<&str>> = Default::default();
+        let mut start = 0;
+        for length in lengths {
+            for (offset, (_, node_id)) in nodes.iter().skip(start).take(length).enumerate() {
+                let entry = forced.entry(*node_id).or_default();
+                for state in if offset == 0 { &own } else { &inherited } {
+                    if !entry.contains(state) {
+                        entry.push(state);
+                    }
+                }
+            }
+            start += length;
+        }
+
+        for (node_id, classes) in &forced {
+            self.cdp(
+                "CSS.forcePseudoState",
+                json!({ "nodeId": node_id, "forcedPseudoClasses": classes }),
+            )
+            .await?;
+        }
+        Ok(ForcedStates {
+            nodes: forced.into_keys().collect(),
+            emulated: false,
+        })
+    }
+
+    async fn emulate_states(
+        &self,
+        selector: &str,
+        all: bool,
+        index: Option<usize>,
+        states: &[PseudoState],
+    ) -> Result<ForcedStates> {
+        let names = |states: &[PseudoState]| -> Vec<&'static str> {
+            states.iter().map(|s| s.as_str()).collect()
+        };
+        let result = self
+            .client
+            .execute(
+                FORCE_STATES_JS,
+                vec![
+                    json!(selector),
+                    json!(all),
+                    json!(index.unwrap_or(0)),
+                    json!(names(states)),
+                    json!(names(&PseudoState::ancestor_states(states))),
+                ],
+            )
+            .await
+            .context("Failed to emulate pseudo-states")?;
+        if result.is_null() {
+            anyhow::bail!("No elements found matching selector: {}", selector);
+        }
+        debug!("Emulated pseudo-states: {}", result);
+        Ok(ForcedStates {
+            nodes: Vec::new(),
+            emulated: true,
+        })
+    }
+
+    /// Jump CSS transitions to their end state
+    async fn finish_transitions(&self) {
+        let script = r#"
+            for (const animation of document.getAnimations()) {
+                if (animation instanceof CSSTransition) animation.finish();
+            }
+        "#;
+        if let Err(e) = self.client.execute(script, vec![]).await {
+            debug!("Failed to finish transitions: {}", e);
+        }
+    }
+
     pub async fn wait_for_navigation(
         &self,
         initial_url: Option<String>,
@@ -1030,14 +1649,28 @@ impl Browser {
         timeout_secs: u64,
         condition: &str,
     ) -> Result<bool> {
-        use std::time::Duration;
-        use tokio::time::sleep;
-
         // Navigate if URL is provided
         if !url.is_empty() {
             self.goto(url).await?;
         }
 
+        let condition = WaitCondition::parse(condition)?;
+        let outcome = self.wait_for(selector, &condition, timeout_secs).await?;
+        Ok(outcome["met"] == true)
+    }
+
+    /// Poll the elements matching `selector` until `condition` holds or
+    /// `timeout_secs` pass. Returns whether it was met, how long that took,
+    /// the number of polls and how many elements matched last.
+    pub async fn wait_for(
+        &self,
+        selector: &str,
+        condition: &WaitCondition,
+        timeout_secs: u64,
+    ) -> Result<serde_json::Value> {
+        use std::time::Duration;
+        use tokio::time::sleep;
+
         debug!(
             "Waiting for element: {} with condition: {}",
             selector, condition
@@ -1046,50 +1679,88 @@ impl Browser {
         let start = std::time::Instant::now();
         let timeout_duration = Duration::from_secs(timeout_secs);
 
-        // Poll every 500ms
-        let poll_interval = Duration::from_millis(500);
-
-        while start.elapsed() < timeout_duration {
-            // Try to find the element
-            let elements = self.client.find_all(Locator::Css(selector)).await;
+        // Poll every 100ms
+        let poll_interval = Duration::from_millis(100);
+        let mut polls = 0;
 
-            if let Ok(elements) = elements
-                && !elements.is_empty()
-            {
-                let element = &elements[0];
-
-                // Check condition
-                let meets_condition = match condition {
-                    "present" => true, // Element exists
-                    "visible" => element.is_displayed().await.unwrap_or(false),
-                    "clickable" => {
-                        element.is_displayed().await.unwrap_or(false)
-                            && element.is_enabled().await.unwrap_or(false)
-                    }
-                    _ => {
-                        tracing::warn!(
-                            "Unknown wait condition: {}, defaulting to 'present'",
-                            condition
-                        );
-                        true
-                    }
-                };
+        loop {
+            polls += 1;
+            // Errors mid-navigation count as no match, but a bad selector
+            // would never match
+            let elements = match self.client.find_all(Locator::Css(selector)).await {
+                Ok(elements) => elements,
+                Err(fantoccini::error::CmdError::Standard(e))
+                    if e.error() == "invalid selector" =>
+                {
+                    anyhow::bail!("Invalid selector '{}': {}", selector, e);
+                }
+                Err(_) => Vec::new(),
+            };
+            let met = Self::condition_met(&elements, condition).await;
+            let elapsed = start.elapsed();
 
-                if meets_condition {
+            if met || elapsed >= timeout_duration {
+                if met {
                     info!(
-                        "Element found and meets condition '{}' after {:.2}s",
+                        "'{}' is {} after {:.2}s",
+                        selector,
                         condition,
-                        start.elapsed().as_secs_f64()
+                        elapsed.as_secs_f64()
                     );
-                    return Ok(true);
+                } else {
+                    info!("Timeout waiting for element after {} seconds", timeout_secs);
                 }
+                return Ok(json!({
+                    "selector": selector,
+                    "condition": condition.to_string(),
+                    "met": met,
+                    "elapsed_ms": elapsed.as_millis() as u64,
+                    "polls": polls,
+                    "count": elements.len(),
+                }));
             }
 
             sleep(poll_interval).await;
         }
+    }
 
-        info!("Timeout waiting for element after {} seconds", timeout_secs);
-        Ok(false)
+    async fn condition_met(
+        elements: &[fantoccini::elements::Element],
+        condition: &WaitCondition,
+    ) -> bool {
+        let first = elements.first();
+        match condition {
+            WaitCondition::Present => first.is_some(),
+            WaitCondition::Detached => first.is_none(),
+            WaitCondition::Visible => match first {
+                Some(element) => element.is_displayed().await.unwrap_or(false),
+                None => false,
+            },
+            WaitCondition::Hidden => match first {
+                Some(element) => !element.is_displayed().await.unwrap_or(true),
+                None => true,
+            },
+            WaitCondition::Clickable => match first {
+                Some(element) => {
+                    element.is_displayed().await.unwrap_or(false)
+                        && element.is_enabled().await.unwrap_or(false)
+                }
+                None => false,
+            },
+            WaitCondition::Text(text) => {
+                for element in elements {
+                    if element
+                        .text()
+                        .await
+                        .is_ok_and(|content| content.contains(text.as_str()))
+                    {
+                        return true;
+                    }
+                }
+                false
+            }
+            WaitCondition::Count { op, count } => op.matches(elements.len(), *count),
+        }
     }
 
     pub async fn get_page_html(&self, url: &str, selector: Option<&str>) -> Result<String> {
@@ -1128,2272 +1799,5683 @@ impl Browser {
         selector: &str,
         index: Option<usize>,
     ) -> Result<()> {
-        use std::time::Duration;
-        use tokio::time::sleep;
-
         // Navigate if URL is provided
         if !url.is_empty() {
             self.goto(url).await?;
         }
 
         debug!("Finding element with selector: {}", selector);
+        let element = self.find_for_action(selector, index).await?;
 
-        // Retry logic for finding elements
-        let mut retry_count = 0;
-        const MAX_RETRIES: u32 = 3;
-        const INITIAL_DELAY_MS: u64 = 500;
-
-        if let Some(idx) = index {
-            // Click specific index with retry
-            let elements = loop {
-                match self.client.find_all(Locator::Css(selector)).await {
-                    Ok(elems) if !elems.is_empty() => break elems,
-                    Ok(_) | Err(_) if retry_count < MAX_RETRIES => {
-                        retry_count += 1;
-                        let delay =
-                            Duration::from_millis(INITIAL_DELAY_MS * (2_u64.pow(retry_count - 1)));
-                        debug!(
-                            "No elements found for click, retrying in {:?} (attempt {}/{})",
-                            delay, retry_count, MAX_RETRIES
-                        );
-                        sleep(delay).await;
-                    }
-                    _ => {
-                        return Err(anyhow::anyhow!(
-                            "No elements found matching selector '{}' after {} retries",
-                            selector,
-                            MAX_RETRIES
-                        ));
-                    }
-                }
-            };
-
-            if idx >= elements.len() {
-                anyhow::bail!(
-                    "Index {} out of bounds. Found {} elements matching '{}'",
-                    idx,
-                    elements.len(),
-                    selector
-                );
-            }
-
-            info!("Clicking element at index {}", idx);
-            elements[idx].click().await?;
-        } else {
-            // Click first element with retry
-            let element = loop {
-                match self.client.find(Locator::Css(selector)).await {
-                    Ok(elem) => break elem,
-                    Err(_) if retry_count < MAX_RETRIES => {
-                        retry_count += 1;
-                        let delay =
-                            Duration::from_millis(INITIAL_DELAY_MS * (2_u64.pow(retry_count - 1)));
-                        debug!(
-                            "Element not found for click, retrying in {:?} (attempt {}/{})",
-                            delay, retry_count, MAX_RETRIES
-                        );
-                        sleep(delay).await;
-                    }
-                    Err(_) => {
-                        return Err(anyhow::anyhow!(
-                            "Element not found for selector '{}' after {} retries",
-                            selector,
-                            MAX_RETRIES
-                        ));
-                    }
-                }
-            };
-
-            info!("Clicking element");
-            element.click().await?;
-        }
-
+        info!("Clicking element at index {}", index.unwrap_or(0));
+        element.click().await?;
         Ok(())
     }
 
-    pub async fn type_text(
+    /// Click through the Actions API: any button, double click, a point
+    /// relative to the element's top-left corner and held modifier keys
+    pub async fn pointer_click(
         &self,
-        url: &str,
         selector: &str,
-        text: &str,
-        clear: bool,
+        index: Option<usize>,
+        options: &ClickOptions,
     ) -> Result<()> {
-        // Navigate if URL is provided
-        if !url.is_empty() {
-            self.goto(url).await?;
-        }
+        use fantoccini::actions::{
+            Actions, InputSource, KeyAction, KeyActions, MOUSE_BUTTON_LEFT, MOUSE_BUTTON_MIDDLE,
+            MOUSE_BUTTON_RIGHT, MouseActions, PointerAction,
+        };
 
-        debug!("Finding element with selector: {}", selector);
-        let element = self
-            .client
-            .find(Locator::Css(selector))
-            .await
-            .context(format!("Element not found: {}", selector))?;
+        let element = self.find_for_action(selector, index).await?;
+        let target = self.pointer_target(&element, options.offset).await?;
 
-        // Check if this is a password field
-        let tag_name = element.tag_name().await?;
-        if tag_name.to_lowercase() == "input"
-            && let Some(input_type) = element.attr("type").await.ok().flatten()
-            && input_type.to_lowercase() == "password"
-        {
-            info!("WARNING: Typing into password field. Value will be redacted in inspect output.");
+        let button = match options.button {
+            MouseButton::Left => MOUSE_BUTTON_LEFT,
+            MouseButton::Middle => MOUSE_BUTTON_MIDDLE,
+            MouseButton::Right => MOUSE_BUTTON_RIGHT,
+        };
+        let keys: Vec<char> = options
+            .modifiers
+            .iter()
+            .map(|&modifier| crate::keys::modifier_key(modifier))
+            .collect();
+
+        // Both sources advance one action per tick, so each pauses while the
+        // other acts: press modifiers, move, click (twice), release modifiers
+        let idle = std::time::Duration::ZERO;
+        let mut keyboard = KeyActions::new("keyboard".to_string());
+        let mut mouse = MouseActions::new("mouse".to_string());
+        for &key in &keys {
+            keyboard = keyboard.then(KeyAction::Down { value: key });
+            mouse = mouse.then(PointerAction::Pause { duration: idle });
         }
-
-        if clear {
-            info!("Clearing field");
-            element.clear().await?;
+        mouse = mouse.then(target);
+        keyboard = keyboard.then(KeyAction::Pause { duration: idle });
+        for _ in 0..if options.double { 2 } else { 1 } {
+            mouse = mouse
+                .then(PointerAction::Down { button })
+                .then(PointerAction::Up { button });
+            keyboard = keyboard
+                .then(KeyAction::Pause { duration: idle })
+                .then(KeyAction::Pause { duration: idle });
+        }
+        for &key in keys.iter().rev() {
+            keyboard = keyboard.then(KeyAction::Up { value: key });
+            mouse = mouse.then(PointerAction::Pause { duration: idle });
         }
 
-        info!("Typing text into element");
-        element.send_keys(text).await?;
-
+        info!("Clicking element with {:?}", options);
+        self.client
+            .perform_actions(Actions::from(keyboard).and(mouse))
+            .await
+            .context("Failed to perform click actions")?;
         Ok(())
     }
 
-    pub async fn scroll(
+    /// Move the mouse over an element (a point relative to its top-left
+    /// corner, or its center) and leave it there
+    pub async fn hover(
         &self,
-        url: &str,
-        selector: Option<&str>,
-        by_x: i32,
-        by_y: i32,
-        to: Option<&str>,
+        selector: &str,
+        index: Option<usize>,
+        offset: Option<(i64, i64)>,
     ) -> Result<()> {
-        // Navigate if URL is provided
-        if !url.is_empty() {
-            self.goto(url).await?;
+        use fantoccini::actions::{InputSource, MouseActions};
+
+        let element = self.find_for_action(selector, index).await?;
+        let target = self.pointer_target(&element, offset).await?;
+
+        info!("Hovering over element");
+        self.client
+            .perform_actions(MouseActions::new("mouse".to_string()).then(target))
+            .await
+            .context("Failed to move the mouse")?;
+        Ok(())
+    }
+
+    /// Drag an element onto another element or a viewport point and report
+    /// where the source ended up
+    ///
+    /// Pointer drags press, move in `steps` increments and release, which is
+    /// what pointer-event libraries (sortable lists, kanban boards) need.
+    /// Native HTML5 drag and drop doesn't react to WebDriver input, so
+    /// `draggable` elements get synthetic drag events instead.
+    pub async fn drag(
+        &self,
+        source: &str,
+        target: &DragTarget,
+        steps: u32,
+        mode: DragMode,
+    ) -> Result<serde_json::Value> {
+        use fantoccini::actions::{InputSource, MOUSE_BUTTON_LEFT, MouseActions, PointerAction};
+
+        let element = self.find_for_action(source, None).await?;
+        let drop_element = match target {
+            DragTarget::Element(selector) => Some(self.find_for_action(selector, None).await?),
+            DragTarget::Point(..) => None,
+        };
+        let before = element.rectangle().await?;
+
+        let script = r#"
+            const [source, target] = [arguments[0], arguments[1]];
+            source.scrollIntoView({ block: 'center', inline: 'center' });
+            const center = (el) => {
+                const r = el.getBoundingClientRect();
+                return [Math.round(r.left + r.width / 2), Math.round(r.top + r.height / 2)];
+            };
+            return {
+                source: center(source),
+                target: target ? center(target) : null,
+                draggable: !!source.closest('[draggable="true"]'),
+                viewport: [window.innerWidth, window.innerHeight]
+            };
+        "#;
+        let drop_arg = match &drop_element {
+            Some(el) => serde_json::to_value(el)?,
+            None => json!(null),
+        };
+        let layout = self
+            .client
+            .execute(
+                script,
+                vec![serde_json::to_value(&element)?, drop_arg.clone()],
+            )
+            .await
+            .context("Failed to locate drag source")?;
+
+        let point =
+            |v: &serde_json::Value| (v[0].as_i64().unwrap_or(0), v[1].as_i64().unwrap_or(0));
+        let from = point(&layout["source"]);
+        let to = match target {
+            DragTarget::Point(x, y) => (*x, *y),
+            DragTarget::Element(_) => point(&layout["target"]),
+        };
+        let viewport = point(&layout["viewport"]);
+        if to.0 < 0 || to.1 < 0 || to.0 >= viewport.0 || to.1 >= viewport.1 {
+            anyhow::bail!(
+                "Drop point ({}, {}) is outside the {}x{} viewport once the source is scrolled into view",
+                to.0,
+                to.1,
+                viewport.0,
+                viewport.1
+            );
         }
 
-        let script = if let Some(to_pos) = to {
-            // Scroll to specific position
-            match to_pos {
-                "top" => {
-                    if let Some(sel) = selector {
-                        format!("document.querySelector('{}').scrollTo(0, 0);", sel)
-                    } else {
-                        "window.scrollTo(0, 0);".to_string()
-                    }
-                }
-                "bottom" => {
-                    if let Some(sel) = selector {
-                        format!(
-                            "var el = document.querySelector('{}'); el.scrollTo(0, el.scrollHeight);",
-                            sel
-                        )
-                    } else {
-                        "window.scrollTo(0, document.body.scrollHeight);".to_string()
-                    }
-                }
-                pos if pos.contains(',') => {
-                    let parts: Vec<&str> = pos.split(',').collect();
-                    if parts.len() == 2 {
-                        let x = parts[0].trim();
-                        let y = parts[1].trim();
-                        if let Some(sel) = selector {
-                            format!("document.querySelector('{}').scrollTo({}, {});", sel, x, y)
-                        } else {
-                            format!("window.scrollTo({}, {});", x, y)
-                        }
-                    } else {
-                        anyhow::bail!("Invalid position format. Use 'x,y' or 'top'/'bottom'");
-                    }
-                }
-                _ => anyhow::bail!("Invalid scroll position: {}", to_pos),
-            }
-        } else {
-            // Scroll by relative amount
-            if let Some(sel) = selector {
-                format!(
-                    "document.querySelector('{}').scrollBy({}, {});",
-                    sel, by_x, by_y
+        let html5 = match mode {
+            DragMode::Html5 => true,
+            DragMode::Pointer => false,
+            DragMode::Auto => layout["draggable"] == true,
+        };
+
+        let mut dropped = json!(null);
+        if html5 {
+            let script = r#"
+                const [source, target, x, y] = arguments;
+                const dataTransfer = new DataTransfer();
+                const drop = target || document.elementFromPoint(x, y);
+                const r = source.getBoundingClientRect();
+                // dispatchEvent returns false when a handler called preventDefault()
+                const fire = (el, type, cx, cy) => el.dispatchEvent(new DragEvent(type, {
+                    bubbles: true, cancelable: true, composed: true,
+                    clientX: cx, clientY: cy, dataTransfer
+                }));
+                fire(source, 'dragstart', r.left + r.width / 2, r.top + r.height / 2);
+                fire(source, 'drag', x, y);
+                fire(drop, 'dragenter', x, y);
+                const accepted = !fire(drop, 'dragover', x, y);
+                if (accepted) fire(drop, 'drop', x, y);
+                fire(source, 'dragend', x, y);
+                return accepted;
+            "#;
+            dropped = self
+                .client
+                .execute(
+                    script,
+                    vec![
+                        serde_json::to_value(&element)?,
+                        drop_arg,
+                        json!(to.0),
+                        json!(to.1),
+                    ],
                 )
-            } else {
-                format!("window.scrollBy({}, {});", by_x, by_y)
+                .await
+                .context("Failed to dispatch drag events")?;
+        } else {
+            // A small first move gets past the drag threshold most libraries use
+            let step_duration = Some(std::time::Duration::from_millis(20));
+            let mut mouse = MouseActions::new("mouse".to_string())
+                .then(PointerAction::MoveTo {
+                    duration: None,
+                    x: from.0,
+                    y: from.1,
+                })
+                .then(PointerAction::Down {
+                    button: MOUSE_BUTTON_LEFT,
+                })
+                .then(PointerAction::MoveTo {
+                    duration: step_duration,
+                    x: from.0 + if to.0 >= from.0 { 5 } else { -5 },
+                    y: from.1,
+                });
+            let steps = steps.max(1);
+            for i in 1..=steps {
+                let t = i as f64 / steps as f64;
+                mouse = mouse.then(PointerAction::MoveTo {
+                    duration: step_duration,
+                    x: (from.0 as f64 + (to.0 - from.0) as f64 * t).round() as i64,
+                    y: (from.1 as f64 + (to.1 - from.1) as f64 * t).round() as i64,
+                });
             }
+            mouse = mouse.then(PointerAction::Up {
+                button: MOUSE_BUTTON_LEFT,
+            });
+
+            info!("Dragging {} in {} steps", source, steps);
+            self.client
+                .perform_actions(mouse)
+                .await
+                .context("Failed to perform drag actions")?;
+        }
+
+        // Let drop handlers and their re-render run before measuring
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+        // Frameworks often re-create the dropped element, so fall back to the selector
+        let after = match element.rectangle().await {
+            Ok(rect) => Some(rect),
+            Err(_) => match self.client.find(Locator::Css(source)).await {
+                Ok(el) => el.rectangle().await.ok(),
+                Err(_) => None,
+            },
         };
 
-        debug!("Executing scroll: {}", script);
-        self.client.execute(&script, vec![]).await?;
+        let rect = |(x, y, width, height): (f64, f64, f64, f64)| json!({ "x": x, "y": y, "width": width, "height": height });
+        Ok(json!({
+            "source": source,
+            "target": target,
+            "method": if html5 { "html5" } else { "pointer" },
+            "from": [from.0, from.1],
+            "to": [to.0, to.1],
+            "before": rect(before),
+            "after": after.map(rect),
+            "moved": after.is_some_and(|a| (a.0 - before.0).abs() >= 1.0 || (a.1 - before.1).abs() >= 1.0),
+            "dropped": dropped,
+        }))
+    }
 
-        info!("Scroll completed");
-        Ok(())
+    /// Find the element for a pointer action, retrying while the page renders
+    async fn find_for_action(
+        &self,
+        selector: &str,
+        index: Option<usize>,
+    ) -> Result<fantoccini::elements::Element> {
+        const MAX_RETRIES: u32 = 3;
+        const INITIAL_DELAY_MS: u64 = 500;
+
+        let mut retry_count = 0;
+        let mut elements = loop {
+            match self.client.find_all(Locator::Css(selector)).await {
+                Ok(elems) if !elems.is_empty() => break elems,
+                Ok(_) | Err(_) if retry_count < MAX_RETRIES => {
+                    retry_count += 1;
+                    let delay = std::time::Duration::from_millis(
+                        INITIAL_DELAY_MS * (2_u64.pow(retry_count - 1)),
+                    );
+                    debug!(
+                        "No elements found for action, retrying in {:?} (attempt {}/{})",
+                        delay, retry_count, MAX_RETRIES
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                _ => anyhow::bail!("No elements found matching selector: {}", selector),
+            }
+        };
+
+        let idx = index.unwrap_or(0);
+        if idx >= elements.len() {
+            anyhow::bail!(
+                "Index {} out of bounds. Found {} elements matching '{}'",
+                idx,
+                elements.len(),
+                selector
+            );
+        }
+        Ok(elements.swap_remove(idx))
     }
 
-    #[allow(dead_code)]
-    pub async fn execute(
+    /// Pointer move to an element, scrolled into view first since the
+    /// Actions API (unlike element click) doesn't scroll
+    async fn pointer_target(
         &self,
-        script: &str,
-        args: Vec<serde_json::Value>,
-    ) -> Result<serde_json::Value> {
+        element: &fantoccini::elements::Element,
+        offset: Option<(i64, i64)>,
+    ) -> Result<fantoccini::actions::PointerAction> {
         self.client
-            .execute(script, args)
+            .execute(
+                "arguments[0].scrollIntoView({ block: 'center', inline: 'center' });",
+                vec![serde_json::to_value(element)?],
+            )
+            .await?;
+
+        // Offsets in the Actions API are from the element's center
+        let (x, y) = match offset {
+            Some((x, y)) => {
+                let (_, _, width, height) = element.rectangle().await?;
+                (
+                    (x as f64 - width / 2.0).round() as i64,
+                    (y as f64 - height / 2.0).round() as i64,
+                )
+            }
+            None => (0, 0),
+        };
+        Ok(fantoccini::actions::PointerAction::MoveToElement {
+            element: element.clone(),
+            duration: None,
+            x,
+            y,
+        })
+    }
+
+    pub async fn type_text(
+        &self,
+        url: &str,
+        selector: &str,
+        text: &str,
+        clear: bool,
+    ) -> Result<()> {
+        self.type_text_with_delay(url, selector, text, clear, 0, false)
             .await
-            .context("Failed to execute script")
     }
 
-    pub async fn execute_javascript(
+    /// Type into an element; `key_delay` spaces the characters out (in ms)
+    /// and `submit` presses Enter afterwards
+    pub async fn type_text_with_delay(
         &self,
-        url: Option<&str>,
-        code: &str,
-    ) -> Result<serde_json::Value> {
-        // Navigate to URL if provided
-        if let Some(url) = url {
-            info!("Navigating to {}", url);
-            self.client.goto(url).await?;
+        url: &str,
+        selector: &str,
+        text: &str,
+        clear: bool,
+        key_delay: u64,
+        submit: bool,
+    ) -> Result<()> {
+        // Navigate if URL is provided
+        if !url.is_empty() {
+            self.goto(url).await?;
         }
 
-        debug!("Executing JavaScript: {}", code);
-
-        // Wrap the code to handle both sync and async/Promise results
-        let wrapped_code = format!(
-            r#"
-            return (async function() {{
-                try {{
-                    const result = {};
-                    // Check if result is a Promise
-                    if (result && typeof result.then === 'function') {{
-                        return await result;
-                    }}
-                    return result;
-                }} catch (error) {{
-                    return {{
-                        error: true,
-                        message: error.message || String(error),
-                        stack: error.stack
-                    }};
-                }}
-            }})();
-            "#,
-            code
-        );
-
-        // Execute the wrapped JavaScript
-        let result = self
+        debug!("Finding element with selector: {}", selector);
+        let element = self
             .client
-            .execute(&wrapped_code, vec![])
+            .find(Locator::Css(selector))
             .await
-            .context("Failed to execute JavaScript")?;
+            .context(format!("Element not found: {}", selector))?;
 
-        // Check if the result is an error object
-        if let Some(obj) = result.as_object()
-            && obj.get("error").and_then(|v| v.as_bool()).unwrap_or(false)
+        // Check if this is a password field
+        let tag_name = element.tag_name().await?;
+        if tag_name.to_lowercase() == "input"
+            && let Some(input_type) = element.attr("type").await.ok().flatten()
+            && input_type.to_lowercase() == "password"
         {
-            let msg = obj
-                .get("message")
-                .and_then(|v| v.as_str())
-                .unwrap_or("Unknown JavaScript error");
-            let stack = obj.get("stack").and_then(|v| v.as_str()).unwrap_or("");
+            info!("WARNING: Typing into password field. Value will be redacted in inspect output.");
+        }
 
-            return Err(anyhow::anyhow!("JavaScript error: {}\n{}", msg, stack));
+        if clear {
+            info!("Clearing field");
+            element.clear().await?;
         }
 
-        info!("JavaScript execution completed");
-        Ok(result)
+        info!("Typing text into element");
+        if key_delay == 0 {
+            element.send_keys(text).await?;
+        } else {
+            // One key at a time, like a person typing, so debounced handlers
+            // and autocomplete see each keystroke
+            for (i, c) in text.chars().enumerate() {
+                if i > 0 {
+                    tokio::time::sleep(std::time::Duration::from_millis(key_delay)).await;
+                }
+                element.send_keys(&c.to_string()).await?;
+            }
+        }
+
+        if submit {
+            element
+                .send_keys(&char::from(fantoccini::key::Key::Enter).to_string())
+                .await?;
+        }
+
+        Ok(())
     }
 
-    pub async fn analyze_layout(
+    /// Press keys one after another in the focused element (focusing the
+    /// first match of `selector` when given), each chord's modifiers held
+    /// down around its key, `delay` ms apart
+    pub async fn press(&self, selector: Option<&str>, keys: &[KeyChord], delay: u64) -> Result<()> {
+        use fantoccini::actions::{InputSource, KeyAction, KeyActions};
+
+        if let Some(selector) = selector {
+            let element = self.find_for_action(selector, None).await?;
+            self.client
+                .execute(
+                    "arguments[0].focus();",
+                    vec![serde_json::to_value(&element)?],
+                )
+                .await
+                .context("Failed to focus element")?;
+        }
+
+        let mut keyboard = KeyActions::new("keyboard".to_string());
+        for (i, chord) in keys.iter().enumerate() {
+            if i > 0 && delay > 0 {
+                keyboard = keyboard.then(KeyAction::Pause {
+                    duration: std::time::Duration::from_millis(delay),
+                });
+            }
+            for &modifier in &chord.modifiers {
+                keyboard = keyboard.then(KeyAction::Down {
+                    value: crate::keys::modifier_key(modifier),
+                });
+            }
+            keyboard = keyboard
+                .then(KeyAction::Down { value: chord.key })
+                .then(KeyAction::Up { value: chord.key });
+            for &modifier in chord.modifiers.iter().rev() {
+                keyboard = keyboard.then(KeyAction::Up {
+                    value: crate::keys::modifier_key(modifier),
+                });
+            }
+        }
+
+        info!("Pressing {} key(s)", keys.len());
+        self.client
+            .perform_actions(keyboard)
+            .await
+            .context("Failed to perform key actions")?;
+        Ok(())
+    }
+
+    /// Choose options of a `<select>`; with several values it must be a
+    /// `<select multiple>`, and every other option is deselected
+    ///
+    /// `input` and `change` fire only when the selection actually changes,
+    /// as they do for a user.
+    pub async fn select_options(
         &self,
-        url: &str,
         selector: &str,
-        depth: u8,
-        max_elements: usize,
-        wait_stable: u64,
-        detect_shadow: bool,
-    ) -> Result<LayoutInfo> {
-        // Navigate if URL is provided
-        if !url.is_empty() {
-            info!("Navigating to {} for layout analysis", url);
-            self.client.goto(url).await?;
+        index: Option<usize>,
+        values: &[String],
+        by: OptionMatch,
+    ) -> Result<serde_json::Value> {
+        if values.is_empty() {
+            anyhow::bail!("No option to select");
         }
 
-        // Wait for layout to stabilize
-        if wait_stable > 0 {
-            info!("Waiting {}ms for layout to stabilize", wait_stable);
-            tokio::time::sleep(tokio::time::Duration::from_millis(wait_stable)).await;
-        }
+        let script = r#"
+            const [selector, index, wanted, by] = arguments;
+            const matches = document.querySelectorAll(selector);
+            const el = matches[index];
+            if (!el) return { error: 'not_found', count: matches.length };
+            if (el.tagName !== 'SELECT') return { error: 'not_select', tag: el.tagName.toLowerCase() };
+            if (el.matches(':disabled')) return { error: 'disabled' };
+            if (wanted.length > 1 && !el.multiple) return { error: 'not_multiple' };
+
+            const options = Array.from(el.options);
+            const label = o => o.label.trim();
+            const byValue = w => options.find(o => o.value === w);
+            const byLabel = w => options.find(o => label(o) === w);
+            const chosen = [];
+            for (const w of wanted) {
+                const option = by === 'value' ? byValue(w) : by === 'label' ? byLabel(w) : byValue(w) || byLabel(w);
+                if (!option) {
+                    return { error: 'no_option', wanted: w, options: options.map(o => by === 'label' ? label(o) : o.value) };
+                }
+                if (option.matches(':disabled')) return { error: 'option_disabled', wanted: w };
+                chosen.push(option);
+            }
 
-        // JavaScript to analyze layout with performance limits
-        let analysis_script = r#"
-        function analyzeLayout(selector, maxDepth, maxElements, detectShadow) {
-            let elementCount = 0;
-            const warnings = [];
-            
-            function getBoxModel(element) {
-                const styles = window.getComputedStyle(element);
-                const rect = element.getBoundingClientRect();
-                
-                return {
-                    bounds: {
-                        x: rect.x,
-                        y: rect.y,
-                        width: rect.width,
-                        height: rect.height
-                    },
-                    box_model: {
-                        margin: {
-                            top: parseFloat(styles.marginTop) || 0,
-                            right: parseFloat(styles.marginRight) || 0,
-                            bottom: parseFloat(styles.marginBottom) || 0,
-                            left: parseFloat(styles.marginLeft) || 0
-                        },
-                        border: {
-                            top: parseFloat(styles.borderTopWidth) || 0,
-                            right: parseFloat(styles.borderRightWidth) || 0,
-                            bottom: parseFloat(styles.borderBottomWidth) || 0,
-                            left: parseFloat(styles.borderLeftWidth) || 0
-                        },
-                        padding: {
-                            top: parseFloat(styles.paddingTop) || 0,
-                            right: parseFloat(styles.paddingRight) || 0,
-                            bottom: parseFloat(styles.paddingBottom) || 0,
-                            left: parseFloat(styles.paddingLeft) || 0
-                        },
-                        content: {
-                            width: rect.width - 
-                                   parseFloat(styles.paddingLeft) - 
-                                   parseFloat(styles.paddingRight) -
-                                   parseFloat(styles.borderLeftWidth) -
-                                   parseFloat(styles.borderRightWidth),
-                            height: rect.height - 
-                                    parseFloat(styles.paddingTop) - 
-                                    parseFloat(styles.paddingBottom) -
-                                    parseFloat(styles.borderTopWidth) -
-                                    parseFloat(styles.borderBottomWidth)
-                        }
-                    },
-                    computed_styles: {
-                        display: styles.display,
-                        position: styles.position,
-                        zIndex: styles.zIndex,
-                        overflow: styles.overflow,
-                        float: styles.float,
-                        clear: styles.clear,
-                        boxSizing: styles.boxSizing,
-                        flexDirection: styles.flexDirection,
-                        justifyContent: styles.justifyContent,
-                        alignItems: styles.alignItems,
-                        gap: styles.gap
-                    },
-                    is_visible: rect.width > 0 && rect.height > 0 && 
-                               styles.display !== 'none' && 
-                               styles.visibility !== 'hidden'
-                };
-            }
-            
-            function analyzeElement(element, currentDepth) {
-                if (!element || elementCount >= maxElements || currentDepth > maxDepth) {
-                    return null;
-                }
-                
-                elementCount++;
-                
-                const result = {
-                    selector: selector,
-                    tag: element.tagName.toLowerCase(),
-                    classes: Array.from(element.classList),
-                    id: element.id || null,
-                    ...getBoxModel(element),
-                    children: [],
-                    warnings: [],
-                    element_count: 1,
-                    truncated: false
-                };
-                
-                // Detect shadow DOM
-                if (detectShadow && element.shadowRoot) {
-                    result.warnings.push('Element has shadow DOM (not analyzed)');
-                }
-                
-                // Detect iframes
-                if (element.tagName === 'IFRAME') {
-                    result.warnings.push('Element is an iframe (content not analyzed)');
-                }
-                
-                // Analyze children if within depth
-                if (currentDepth < maxDepth) {
-                    const children = Array.from(element.children);
-                    for (let child of children) {
-                        if (elementCount >= maxElements) {
-                            result.truncated = true;
-                            result.warnings.push(`Analysis truncated at ${maxElements} elements`);
-                            break;
-                        }
-                        const childAnalysis = analyzeElement(child, currentDepth + 1);
-                        if (childAnalysis) {
-                            result.children.push(childAnalysis);
-                            result.element_count += childAnalysis.element_count;
-                        }
-                    }
-                }
-                
-                return result;
-            }
-            
-            const element = document.querySelector(selector);
-            if (!element) {
-                throw new Error('Element not found: ' + selector);
+            const selected = () => options.filter(o => o.selected).map(o => o.value).join('\n');
+            const before = selected();
+            options.forEach(o => { o.selected = chosen.includes(o); });
+            const changed = selected() !== before;
+            if (changed) {
+                el.dispatchEvent(new Event('input', { bubbles: true }));
+                el.dispatchEvent(new Event('change', { bubbles: true }));
             }
-            
-            const analysis = analyzeElement(element, 0);
-            analysis.warnings = warnings;
-            return analysis;
-        }
-        
-        return analyzeLayout(arguments[0], arguments[1], arguments[2], arguments[3]);
+            return {
+                selector,
+                value: chosen[0].value,
+                text: label(chosen[0]),
+                values: chosen.map(o => o.value),
+                labels: chosen.map(label),
+                changed,
+            };
         "#;
 
-        debug!(
-            "Running layout analysis with depth={}, max_elements={}",
-            depth, max_elements
-        );
-
         let result = self
             .client
             .execute(
-                analysis_script,
+                script,
                 vec![
                     json!(selector),
-                    json!(depth),
-                    json!(max_elements),
-                    json!(detect_shadow),
+                    json!(index.unwrap_or(0)),
+                    json!(values),
+                    json!(by),
                 ],
             )
             .await
-            .context("Failed to analyze layout")?;
+            .context("Failed to select option")?;
 
-        // Convert the result to LayoutInfo
-        let layout: LayoutInfo =
-            serde_json::from_value(result).context("Failed to parse layout analysis")?;
+        match result["error"].as_str() {
+            Some("not_found") if result["count"].as_u64().unwrap_or(0) > 0 => anyhow::bail!(
+                "Index {} out of bounds. Found {} elements matching '{}'",
+                index.unwrap_or(0),
+                result["count"],
+                selector
+            ),
+            Some("not_found") => anyhow::bail!("Element not found: {}", selector),
+            Some("not_select") => anyhow::bail!(
+                "Element '{}' is a <{}>, not a <select>",
+                selector,
+                result["tag"].as_str().unwrap_or("unknown")
+            ),
+            Some("disabled") => anyhow::bail!("Select '{}' is disabled", selector),
+            Some("not_multiple") => anyhow::bail!(
+                "Select '{}' doesn't allow multiple options (no `multiple` attribute)",
+                selector
+            ),
+            Some("no_option") => anyhow::bail!(
+                "No option '{}' in '{}'. Available {}: {}",
+                result["wanted"].as_str().unwrap_or(""),
+                selector,
+                if by == OptionMatch::Label {
+                    "labels"
+                } else {
+                    "values"
+                },
+                result["options"]
+            ),
+            Some("option_disabled") => anyhow::bail!(
+                "Option '{}' in '{}' is disabled",
+                result["wanted"].as_str().unwrap_or(""),
+                selector
+            ),
+            _ => Ok(result),
+        }
+    }
 
-        info!(
-            "Layout analysis complete: {} elements analyzed",
-            layout.element_count
-        );
+    /// Check or uncheck a checkbox, radio button or `aria-checked` widget by
+    /// clicking it, so the page's own click and change handlers decide
+    pub async fn set_checked(&self, selector: &str, checked: bool) -> Result<serde_json::Value> {
+        let element = self.find_for_action(selector, None).await?;
+        let state_script = r#"
+            const el = arguments[0];
+            const native = el.tagName === 'INPUT' && (el.type === 'checkbox' || el.type === 'radio');
+            const aria = el.getAttribute('aria-checked');
+            if (!native && aria === null) {
+                return { error: 'not_checkable', tag: el.tagName.toLowerCase(), type: el.getAttribute('type') };
+            }
+            return {
+                kind: native ? el.type : (el.getAttribute('role') || 'aria-checked'),
+                checked: native ? el.checked : aria === 'true',
+                disabled: native ? el.matches(':disabled') : el.getAttribute('aria-disabled') === 'true',
+            };
+        "#;
+        let state = |value: serde_json::Value| -> Result<serde_json::Value> {
+            if value["error"] == "not_checkable" {
+                anyhow::bail!(
+                    "Element '{}' is a <{}{}>, not a checkbox, radio button or aria-checked widget",
+                    selector,
+                    value["tag"].as_str().unwrap_or("unknown"),
+                    value["type"]
+                        .as_str()
+                        .map(|t| format!(" type=\"{}\"", t))
+                        .unwrap_or_default()
+                );
+            }
+            Ok(value)
+        };
+        let element_arg = vec![serde_json::to_value(&element)?];
+
+        let before = state(
+            self.client
+                .execute(state_script, element_arg.clone())
+                .await?,
+        )?;
+        let kind = before["kind"].as_str().unwrap_or("checkbox").to_string();
+        if before["checked"] == checked {
+            return Ok(
+                json!({ "selector": selector, "kind": kind, "checked": checked, "changed": false }),
+            );
+        }
+        if before["disabled"] == true {
+            anyhow::bail!("{} '{}' is disabled", kind, selector);
+        }
+        if !checked && kind == "radio" {
+            anyhow::bail!(
+                "Radio button '{}' can't be unchecked; check another option in its group",
+                selector
+            );
+        }
 
-        Ok(layout)
+        // Styled controls often hide the real input, which WebDriver won't click
+        if let Err(e) = element.click().await {
+            debug!("Element click failed ({}), clicking from script", e);
+            self.client
+                .execute("arguments[0].click();", element_arg.clone())
+                .await
+                .context("Failed to click element")?;
+        }
+
+        let after = state(self.client.execute(state_script, element_arg).await?)?;
+        if after["checked"] != checked {
+            anyhow::bail!(
+                "Clicking {} '{}' didn't {} it (a click handler may prevent it)",
+                kind,
+                selector,
+                if checked { "check" } else { "uncheck" }
+            );
+        }
+        Ok(json!({ "selector": selector, "kind": kind, "checked": checked, "changed": true }))
     }
 
-    pub async fn analyze_context(
+    /// Move an `<input type="range">` to `value`; the browser clamps it to
+    /// min/max and snaps it to the step, and the result reports where it landed
+    pub async fn set_range(&self, selector: &str, value: f64) -> Result<serde_json::Value> {
+        let element = self.find_for_action(selector, None).await?;
+        // The native setter, so frameworks tracking the property see the change
+        let script = r#"
+            const [el, value] = arguments;
+            if (el.tagName !== 'INPUT' || el.type !== 'range') {
+                return { error: 'not_range', tag: el.tagName.toLowerCase(), type: el.getAttribute('type') };
+            }
+            if (el.matches(':disabled')) return { error: 'disabled' };
+            const before = el.value;
+            Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, 'value').set.call(el, String(value));
+            const changed = el.value !== before;
+            if (changed) {
+                el.dispatchEvent(new Event('input', { bubbles: true }));
+                el.dispatchEvent(new Event('change', { bubbles: true }));
+            }
+            return {
+                value: el.valueAsNumber,
+                min: el.min === '' ? 0 : Number(el.min),
+                max: el.max === '' ? 100 : Number(el.max),
+                step: el.step || '1',
+                changed,
+            };
+        "#;
+        let mut result = self
+            .client
+            .execute(script, vec![serde_json::to_value(&element)?, json!(value)])
+            .await
+            .context("Failed to set range")?;
+
+        match result["error"].as_str() {
+            Some("not_range") => anyhow::bail!(
+                "Element '{}' is a <{}{}>, not an <input type=\"range\">",
+                selector,
+                result["tag"].as_str().unwrap_or("unknown"),
+                result["type"]
+                    .as_str()
+                    .map(|t| format!(" type=\"{}\"", t))
+                    .unwrap_or_default()
+            ),
+            Some("disabled") => anyhow::bail!("Range '{}' is disabled", selector),
+            _ => {
+                result["selector"] = json!(selector);
+                result["requested"] = json!(value);
+                Ok(result)
+            }
+        }
+    }
+
+    /// Choose files in an `<input type="file">` (several only if it has
+    /// `multiple`), replacing any chosen before
+    pub async fn upload_files(
         &self,
-        url: &str,
         selector: &str,
-        focus: &str,
-        proximity: u32,
-        index: Option<usize>,
+        files: &[String],
     ) -> Result<serde_json::Value> {
-        // Navigate if URL is provided
-        if !url.is_empty() {
-            info!("Navigating to {} for context analysis", url);
-            self.client.goto(url).await?;
+        if files.is_empty() {
+            anyhow::bail!("No files to upload");
+        }
+        for file in files {
+            let path = std::path::Path::new(file);
+            if !path.is_absolute() {
+                anyhow::bail!("File path must be absolute: {}", file);
+            }
+            if !path.is_file() {
+                anyhow::bail!("File not found: {}", file);
+            }
         }
 
-        // Wait a bit for page to stabilize
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-
-        let script = match focus {
-            "spacing" => self.build_spacing_script(selector, proximity, index),
-            "wrapping" => self.build_wrapping_script(selector, index),
-            "anomalies" => self.build_anomalies_script(proximity),
-            "all" => self.build_comprehensive_script(selector, proximity, index),
-            _ => self.build_comprehensive_script(selector, proximity, index),
-        };
-
-        debug!("Running {} analysis", focus);
-
-        let result = self
+        let element = self.find_for_action(selector, None).await?;
+        let element_arg = vec![serde_json::to_value(&element)?];
+        let input = self
             .client
-            .execute(&script, vec![])
+            .execute(
+                r#"
+                const el = arguments[0];
+                return {
+                    file: el.tagName === 'INPUT' && el.type === 'file',
+                    tag: el.tagName.toLowerCase(),
+                    multiple: el.multiple,
+                    disabled: el.matches(':disabled'),
+                };
+                "#,
+                element_arg.clone(),
+            )
+            .await?;
+        if input["file"] != true {
+            anyhow::bail!(
+                "Element '{}' is a <{}>, not an <input type=\"file\">",
+                selector,
+                input["tag"].as_str().unwrap_or("unknown")
+            );
+        }
+        if input["disabled"] == true {
+            anyhow::bail!("File input '{}' is disabled", selector);
+        }
+        if files.len() > 1 && input["multiple"] != true {
+            anyhow::bail!(
+                "File input '{}' takes one file (no `multiple` attribute), got {}",
+                selector,
+                files.len()
+            );
+        }
+
+        // WebDriver sets the files and fires input and change itself
+        element
+            .send_keys(&files.join("\n"))
             .await
-            .context(format!("Failed to analyze {} context", focus))?;
+            .context("Failed to set files")?;
 
-        info!("Context analysis complete");
-        Ok(result)
+        let chosen = self
+            .client
+            .execute(
+                "return Array.from(arguments[0].files).map(f => ({ name: f.name, size: f.size, type: f.type }));",
+                element_arg,
+            )
+            .await?;
+        Ok(json!({ "selector": selector, "files": chosen }))
     }
 
-    fn build_spacing_script(&self, selector: &str, proximity: u32, index: Option<usize>) -> String {
-        let target_selection = if let Some(idx) = index {
-            format!(
-                "const elements = document.querySelectorAll('{}');
-            if (elements.length <= {}) return {{ error: 'Element at index {} not found' }};
-            const target = elements[{}];",
-                selector, idx, idx, idx
-            )
-        } else {
-            format!(
-                "const target = document.querySelector('{}');
-            if (!target) return {{ error: 'Element not found: {}' }};",
-                selector, selector
+    /// Fill a form from `data`, matching each key to a control by name, id,
+    /// label text or placeholder and setting it the way its type expects.
+    /// Without `form`, the form matching the most keys is used. Relative file
+    /// paths resolve against `files_base`. With `submit`, the form's submit
+    /// button is clicked and the validation messages the browser then shows
+    /// are reported.
+    pub async fn fill_form(
+        &self,
+        form: Option<&str>,
+        data: &serde_json::Map<String, serde_json::Value>,
+        submit: bool,
+        files_base: Option<&std::path::Path>,
+    ) -> Result<serde_json::Value> {
+        let plan_script = format!(
+            "{}\n{}\n{}",
+            SELECTOR_OF_JS,
+            FORM_FIELDS_JS,
+            r#"
+            const [formSelector, data] = arguments;
+            const keys = Object.keys(data);
+            const matchField = (fields, key) => {
+                const wanted = normalize(key);
+                const strategies = [
+                    ['name', f => f.name === key || (f.name !== '' && normalize(f.name) === wanted)],
+                    ['id', f => f.id === key],
+                    ['label', f => normalize(f.label) === wanted || normalize(f.group_label) === wanted],
+                    ['placeholder', f => normalize(f.placeholder) === wanted],
+                ];
+                for (const [by, test] of strategies) {
+                    const found = fields.filter(test);
+                    if (found.length) return { by, fields: found };
+                }
+                return null;
+            };
+
+            let root;
+            if (formSelector) {
+                root = document.querySelector(formSelector);
+                if (!root) return { error: 'not_found' };
+            } else if (document.forms.length) {
+                root = Array.from(document.forms)
+                    .map(form => ({ form, score: keys.filter(key => matchField(formFields(form), key)).length }))
+                    .reduce((best, next) => next.score > best.score ? next : best)
+                    .form;
+            } else {
+                root = document.body;
+            }
+            const form = root.tagName === 'FORM' ? root : (root.closest('form') || root.querySelector('form'));
+            const buttons = form ? Array.from(form.elements) : Array.from(root.querySelectorAll('button, input'));
+            const submitter = buttons.find(el => el.type === 'submit' && !el.disabled);
+
+            const fields = formFields(root);
+            const text = (value) => value === null || value === undefined ? '' : String(value);
+            const plan = [];
+            const unmatched = [];
+            for (const key of keys) {
+                const match = matchField(fields, key);
+                if (!match) {
+                    unmatched.push(key);
+                    continue;
+                }
+                const value = data[key];
+                const first = match.fields[0];
+                const step = (field, action, extra) =>
+                    plan.push({ key, matched_by: match.by, type: field.type, selector: field.selector, action, ...extra });
+                const groupOf = (type) => first.name
+                    ? fields.filter(f => f.type === type && f.name === first.name)
+                    : match.fields.filter(f => f.type === type);
+                const choose = (group, wanted) => group.find(f => f.el.value === wanted)
+                    || group.find(f => normalize(f.label) === normalize(wanted));
+                const choices = (group) => group.map(f => f.label || f.el.value).join(', ');
+
+                if (first.type === 'radio') {
+                    if (value === true && match.fields.length === 1) {
+                        step(first, 'check', { checked: true });
+                        continue;
+                    }
+                    const group = groupOf('radio');
+                    const radio = choose(group, text(value));
+                    if (radio) step(radio, 'check', { checked: true });
+                    else step(first, 'error', { message: `No option '${text(value)}' (choices: ${choices(group)})` });
+                } else if (first.type === 'checkbox') {
+                    if (typeof value === 'boolean') {
+                        step(first, 'check', { checked: value });
+                        continue;
+                    }
+                    // A list (or one value) checks those boxes of the group and unchecks the rest
+                    const group = groupOf('checkbox');
+                    const wanted = (Array.isArray(value) ? value : [value]).map(text);
+                    const chosen = wanted.map(w => choose(group, w));
+                    const unknown = wanted.filter((w, i) => !chosen[i]);
+                    if (unknown.length) {
+                        step(first, 'error', { message: `No option '${unknown.join("', '")}' (choices: ${choices(group)})` });
+                        continue;
+                    }
+                    group.forEach(f => step(f, 'check', { checked: chosen.includes(f) }));
+                } else if (first.type === 'select') {
+                    step(first, 'select', { values: (Array.isArray(value) ? value : [value]).map(text) });
+                } else if (first.type === 'range') {
+                    const number = Number(value);
+                    if (value === null || value === '' || Number.isNaN(number)) {
+                        step(first, 'error', { message: `'${text(value)}' is not a number` });
+                    } else {
+                        step(first, 'range', { value: number });
+                    }
+                } else if (first.type === 'file') {
+                    step(first, 'upload', { files: (Array.isArray(value) ? value : [value]).map(text) });
+                } else if (['date', 'time', 'datetime-local', 'month', 'week', 'color'].includes(first.type)) {
+                    // Typing into these depends on the browser's locale; their value format doesn't
+                    step(first, 'value', { value: text(value) });
+                } else {
+                    step(first, 'type', { value: text(value) });
+                }
+            }
+            return {
+                form: selectorOf(root),
+                has_form: !!form,
+                submitter: submitter ? selectorOf(submitter) : null,
+                plan,
+                unmatched,
+            };
+            "#
+        );
+
+        let planned = self
+            .client
+            .execute(
+                &plan_script,
+                vec![json!(form), serde_json::Value::Object(data.clone())],
             )
-        };
+            .await
+            .context("Failed to match form fields")?;
+        if planned["error"] == "not_found" {
+            anyhow::bail!("No form found matching selector: {}", form.unwrap_or(""));
+        }
+        let form_selector = planned["form"].as_str().unwrap_or("body").to_string();
+        let submitter = planned["submitter"].as_str().map(str::to_string);
+        if submit && submitter.is_none() && planned["has_form"] != true {
+            anyhow::bail!(
+                "Nothing to submit: '{}' has no <form> and no submit button",
+                form_selector
+            );
+        }
 
-        format!(
-            r#"
-        return (function() {{
-            {}
-            
-            const targetRect = target.getBoundingClientRect();
-            const targetStyles = window.getComputedStyle(target);
-            
-            // Get target's box model
-            const targetBox = {{
-                margin: {{
-                    top: parseFloat(targetStyles.marginTop) || 0,
-                    right: parseFloat(targetStyles.marginRight) || 0,
-                    bottom: parseFloat(targetStyles.marginBottom) || 0,
-                    left: parseFloat(targetStyles.marginLeft) || 0
-                }},
-                padding: {{
-                    top: parseFloat(targetStyles.paddingTop) || 0,
-                    right: parseFloat(targetStyles.paddingRight) || 0,
-                    bottom: parseFloat(targetStyles.paddingBottom) || 0,
-                    left: parseFloat(targetStyles.paddingLeft) || 0
-                }},
-                border: {{
-                    top: parseFloat(targetStyles.borderTopWidth) || 0,
-                    right: parseFloat(targetStyles.borderRightWidth) || 0,
-                    bottom: parseFloat(targetStyles.borderBottomWidth) || 0,
-                    left: parseFloat(targetStyles.borderLeftWidth) || 0
-                }}
-            }};
-            
-            // Find adjacent elements
-            const allElements = Array.from(document.querySelectorAll('*'));
-            const adjacentElements = [];
-            
-            for (let el of allElements) {{
-                if (el === target || target.contains(el) || el.contains(target)) continue;
-                
-                const rect = el.getBoundingClientRect();
-                const styles = window.getComputedStyle(el);
-                
-                // Check if element is above
-                if (rect.bottom <= targetRect.top && 
-                    Math.abs(rect.bottom - targetRect.top) < {}) {{
-                    adjacentElements.push({{
-                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + (el.className && typeof el.className === 'string' ? '.' + el.className.split(' ').join('.') : ''),
-                        position: 'above',
-                        bounds: {{
-                            x: rect.x,
-                            y: rect.y,
-                            width: rect.width,
-                            height: rect.height,
-                            bottom: rect.bottom
-                        }},
-                        margin_bottom: parseFloat(styles.marginBottom) || 0,
-                        actual_gap: targetRect.top - rect.bottom,
-                        margin_collapsed: (targetRect.top - rect.bottom) < (parseFloat(styles.marginBottom) + targetBox.margin.top)
-                    }});
-                }}
-                
-                // Check if element is below
-                if (rect.top >= targetRect.bottom && 
-                    Math.abs(rect.top - targetRect.bottom) < {}) {{
-                    adjacentElements.push({{
-                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + (el.className && typeof el.className === 'string' ? '.' + el.className.split(' ').join('.') : ''),
-                        position: 'below',
-                        bounds: {{
-                            x: rect.x,
-                            y: rect.y,
-                            width: rect.width,
-                            height: rect.height,
-                            top: rect.top
-                        }},
-                        margin_top: parseFloat(styles.marginTop) || 0,
-                        actual_gap: rect.top - targetRect.bottom,
-                        margin_collapsed: (rect.top - targetRect.bottom) < (targetBox.margin.bottom + parseFloat(styles.marginTop))
-                    }});
-                }}
-                
-                // Check if element is to the left
-                if (rect.right <= targetRect.left && 
-                    Math.abs(rect.right - targetRect.left) < {}) {{
-                    adjacentElements.push({{
-                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + (el.className && typeof el.className === 'string' ? '.' + el.className.split(' ').join('.') : ''),
-                        position: 'left',
-                        bounds: {{
-                            x: rect.x,
-                            y: rect.y,
-                            width: rect.width,
-                            height: rect.height,
-                            right: rect.right
-                        }},
-                        margin_right: parseFloat(styles.marginRight) || 0,
-                        actual_gap: targetRect.left - rect.right
-                    }});
-                }}
-                
-                // Check if element is to the right
-                if (rect.left >= targetRect.right && 
-                    Math.abs(rect.left - targetRect.right) < {}) {{
-                    adjacentElements.push({{
-                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + (el.className && typeof el.className === 'string' ? '.' + el.className.split(' ').join('.') : ''),
-                        position: 'right',
-                        bounds: {{
-                            x: rect.x,
-                            y: rect.y,
-                            width: rect.width,
-                            height: rect.height,
-                            left: rect.left
-                        }},
-                        margin_left: parseFloat(styles.marginLeft) || 0,
-                        actual_gap: rect.left - targetRect.right
-                    }});
-                }}
-            }}
-            
-            // Get parent padding
-            const parent = target.parentElement;
-            const parentStyles = parent ? window.getComputedStyle(parent) : null;
-            const parentPadding = parent ? {{
-                top: parseFloat(parentStyles.paddingTop) || 0,
-                right: parseFloat(parentStyles.paddingRight) || 0,
-                bottom: parseFloat(parentStyles.paddingBottom) || 0,
-                left: parseFloat(parentStyles.paddingLeft) || 0
-            }} : null;
-            
-            // Check for pseudo elements
-            const beforeStyles = window.getComputedStyle(target, ':before');
-            const afterStyles = window.getComputedStyle(target, ':after');
-            
-            const pseudoElements = {{
-                before: beforeStyles.content !== 'none' ? {{
-                    content: beforeStyles.content,
-                    height: parseFloat(beforeStyles.height) || 'auto',
-                    width: parseFloat(beforeStyles.width) || 'auto',
-                    display: beforeStyles.display
-                }} : null,
-                after: afterStyles.content !== 'none' ? {{
-                    content: afterStyles.content,
-                    height: parseFloat(afterStyles.height) || 'auto',
-                    width: parseFloat(afterStyles.width) || 'auto',
-                    display: afterStyles.display
-                }} : null
-            }};
-            
-            return {{
-                target: {{
-                    selector: '{}',
-                    bounds: {{
-                        x: targetRect.x,
-                        y: targetRect.y,
-                        width: targetRect.width,
-                        height: targetRect.height
-                    }},
-                    box: targetBox,
-                    display: targetStyles.display,
-                    position: targetStyles.position
-                }},
-                spacing_context: {{
-                    adjacent_elements: adjacentElements,
-                    parent: parent ? {{
-                        selector: parent.tagName.toLowerCase() + (parent.id ? '#' + parent.id : ''),
-                        padding: parentPadding
-                    }} : null,
-                    pseudo_elements: pseudoElements
-                }},
-                element_count: adjacentElements.length + 1
-            }};
-        }})();
-        "#,
-            target_selection, proximity, proximity, proximity, proximity, selector
-        )
-    }
+        let mut filled = Vec::new();
+        let mut failed = Vec::new();
+        for step in planned["plan"].as_array().into_iter().flatten() {
+            let selector = step["selector"].as_str().unwrap_or("");
+            let field_type = step["type"].as_str().unwrap_or("");
+            let value = match step["action"].as_str().unwrap_or("") {
+                "type" => {
+                    let text = step["value"].as_str().unwrap_or("");
+                    self.type_text_with_delay("", selector, text, true, 0, false)
+                        .await
+                        .map(|_| match field_type {
+                            "password" => json!("[hidden]"),
+                            _ => json!(text),
+                        })
+                }
+                "value" => self
+                    .set_input_value(selector, step["value"].as_str().unwrap_or(""))
+                    .await
+                    .map(|v| json!(v)),
+                "select" => {
+                    let values: Vec<String> = step["values"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|v| v.as_str().map(str::to_string))
+                        .collect();
+                    self.select_options(selector, None, &values, OptionMatch::Any)
+                        .await
+                        .map(|result| result["values"].clone())
+                }
+                "check" => {
+                    let checked = step["checked"] == true;
+                    self.set_checked(selector, checked)
+                        .await
+                        .map(|_| json!(checked))
+                }
+                "range" => self
+                    .set_range(selector, step["value"].as_f64().unwrap_or_default())
+                    .await
+                    .map(|result| result["value"].clone()),
+                "upload" => {
+                    let files: Vec<String> = step["files"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|f| f.as_str())
+                        .map(|f| match files_base {
+                            Some(base) if !std::path::Path::new(f).is_absolute() => {
+                                base.join(f).to_string_lossy().into_owned()
+                            }
+                            _ => f.to_string(),
+                        })
+                        .collect();
+                    self.upload_files(selector, &files).await.map(|result| {
+                        json!(
+                            result["files"]
+                                .as_array()
+                                .into_iter()
+                                .flatten()
+                                .map(|f| f["name"].clone())
+                                .collect::<Vec<_>>()
+                        )
+                    })
+                }
+                _ => Err(anyhow::anyhow!(
+                    "{}",
+                    step["message"].as_str().unwrap_or("Can't fill this field")
+                )),
+            };
+            match value {
+                Ok(value) => filled.push(json!({
+                    "key": step["key"],
+                    "selector": selector,
+                    "type": field_type,
+                    "matched_by": step["matched_by"],
+                    "value": value,
+                })),
+                Err(e) => failed.push(json!({
+                    "key": step["key"],
+                    "selector": selector,
+                    "error": e.to_string(),
+                })),
+            }
+        }
 
-    fn build_wrapping_script(&self, selector: &str, index: Option<usize>) -> String {
-        let target_selection = if let Some(idx) = index {
-            format!(
-                "const elements = document.querySelectorAll('{}');
-            if (elements.length <= {}) return {{ error: 'Container at index {} not found' }};
-            const container = elements[{}];",
-                selector, idx, idx, idx
+        let missing = self
+            .client
+            .execute(
+                &format!(
+                    "{}\n{}\n{}",
+                    SELECTOR_OF_JS,
+                    FORM_FIELDS_JS,
+                    "return invalidFields(document.querySelector(arguments[0])).filter(f => f.missing);"
+                ),
+                vec![json!(form_selector)],
             )
-        } else {
-            format!(
-                "const container = document.querySelector('{}');
-            if (!container) return {{ error: 'Container not found: {}' }};",
-                selector, selector
+            .await
+            .context("Failed to check required fields")?;
+        let missing_required: Vec<serde_json::Value> = missing
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|f| json!({ "selector": f["selector"], "name": f["name"], "label": f["label"] }))
+            .collect();
+
+        let mut result = json!({
+            "form": form_selector,
+            "filled": filled,
+            "failed": failed,
+            "unmatched": planned["unmatched"],
+            "missing_required": missing_required,
+        });
+        if submit {
+            let submission = self
+                .submit_form(&form_selector, submitter.as_deref())
+                .await?;
+            for (key, value) in submission {
+                result[key] = value;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Submit the form under `root` like a user would (clicking `submitter`
+    /// when there is one) and report whether it went through, or the
+    /// validation messages shown if it didn't
+    async fn submit_form(
+        &self,
+        root: &str,
+        submitter: Option<&str>,
+    ) -> Result<serde_json::Map<String, serde_json::Value>> {
+        // The marker disappears if the submit navigates away
+        let has_form = self
+            .client
+            .execute(
+                r#"
+                const root = document.querySelector(arguments[0]);
+                const form = root && (root.tagName === 'FORM' ? root : (root.closest('form') || root.querySelector('form')));
+                const state = { submitted: false };
+                window.__webprobeFillForm = state;
+                if (form) form.addEventListener('submit', () => { state.submitted = true; }, { once: true });
+                return !!form;
+                "#,
+                vec![json!(root)],
             )
-        };
+            .await
+            .context("Failed to watch form submission")?;
+
+        match submitter {
+            Some(selector) => {
+                let element = self.find_for_action(selector, None).await?;
+                if let Err(e) = element.click().await {
+                    debug!("Submit click failed ({}), clicking from script", e);
+                    self.client
+                        .execute(
+                            "arguments[0].click();",
+                            vec![serde_json::to_value(&element)?],
+                        )
+                        .await
+                        .context("Failed to click submit button")?;
+                }
+            }
+            None => {
+                self.client
+                    .execute(
+                        r#"
+                        const root = document.querySelector(arguments[0]);
+                        (root.tagName === 'FORM' ? root : (root.closest('form') || root.querySelector('form'))).requestSubmit();
+                        "#,
+                        vec![json!(root)],
+                    )
+                    .await
+                    .context("Failed to submit form")?;
+            }
+        }
+
+        // Give client-side validation a moment to mark fields
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+        let state = self
+            .client
+            .execute(
+                &format!(
+                    "{}\n{}\n{}",
+                    SELECTOR_OF_JS,
+                    FORM_FIELDS_JS,
+                    r#"
+                    const state = window.__webprobeFillForm;
+                    if (!state) return { navigated: true };
+                    const root = document.querySelector(arguments[0]);
+                    return { navigated: false, submitted: state.submitted, validation: root ? invalidFields(root) : [] };
+                    "#
+                ),
+                vec![json!(root)],
+            )
+            .await
+            .unwrap_or_else(|e| {
+                debug!("Form check failed after submit ({}), assuming navigation", e);
+                json!({ "navigated": true })
+            });
+
+        let navigated = state["navigated"] == true;
+        let mut submission = serde_json::Map::new();
+        submission.insert(
+            "submitted".to_string(),
+            json!(
+                navigated
+                    || state["submitted"] == true
+                    || (has_form != true && submitter.is_some())
+            ),
+        );
+        submission.insert("navigated".to_string(), json!(navigated));
+        submission.insert(
+            "url".to_string(),
+            json!(self.client.current_url().await?.to_string()),
+        );
+        submission.insert(
+            "validation".to_string(),
+            state
+                .get("validation")
+                .cloned()
+                .unwrap_or_else(|| json!([])),
+        );
+        Ok(submission)
+    }
+
+    /// Set an input's value from script (dates, times, colors), firing input
+    /// and change, and return the value the input kept
+    async fn set_input_value(&self, selector: &str, value: &str) -> Result<String> {
+        let element = self.find_for_action(selector, None).await?;
+        let result = self
+            .client
+            .execute(
+                r#"
+                const [el, value] = arguments;
+                if (el.matches(':disabled')) return { error: 'disabled' };
+                const before = el.value;
+                Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, 'value').set.call(el, value);
+                if (el.value !== before) {
+                    el.dispatchEvent(new Event('input', { bubbles: true }));
+                    el.dispatchEvent(new Event('change', { bubbles: true }));
+                }
+                return { value: el.value, type: el.type };
+                "#,
+                vec![serde_json::to_value(&element)?, json!(value)],
+            )
+            .await
+            .context("Failed to set value")?;
+        if result["error"] == "disabled" {
+            anyhow::bail!("Input '{}' is disabled", selector);
+        }
+        let kept = result["value"].as_str().unwrap_or("").to_string();
+        // The browser drops values that don't parse for the input's type
+        if kept.is_empty() && !value.is_empty() {
+            anyhow::bail!(
+                "'{}' is not a valid {} value for '{}'",
+                value,
+                result["type"].as_str().unwrap_or("input"),
+                selector
+            );
+        }
+        Ok(kept)
+    }
+
+    pub async fn scroll(
+        &self,
+        url: &str,
+        selector: Option<&str>,
+        by_x: i32,
+        by_y: i32,
+        to: Option<&str>,
+    ) -> Result<()> {
+        // Navigate if URL is provided
+        if !url.is_empty() {
+            self.goto(url).await?;
+        }
+
+        let script = if let Some(to_pos) = to {
+            // Scroll to specific position
+            match to_pos {
+                "top" => {
+                    if let Some(sel) = selector {
+                        format!("document.querySelector('{}').scrollTo(0, 0);", sel)
+                    } else {
+                        "window.scrollTo(0, 0);".to_string()
+                    }
+                }
+                "bottom" => {
+                    if let Some(sel) = selector {
+                        format!(
+                            "var el = document.querySelector('{}'); el.scrollTo(0, el.scrollHeight);",
+                            sel
+                        )
+                    } else {
+                        "window.scrollTo(0, document.body.scrollHeight);".to_string()
+                    }
+                }
+                pos if pos.contains(',') => {
+                    let parts: Vec<&str> = pos.split(',').collect();
+                    if parts.len() == 2 {
+                        let x = parts[0].trim();
+                        let y = parts[1].trim();
+                        if let Some(sel) = selector {
+                            format!("document.querySelector('{}').scrollTo({}, {});", sel, x, y)
+                        } else {
+                            format!("window.scrollTo({}, {});", x, y)
+                        }
+                    } else {
+                        anyhow::bail!("Invalid position format. Use 'x,y' or 'top'/'bottom'");
+                    }
+                }
+                _ => anyhow::bail!("Invalid scroll position: {}", to_pos),
+            }
+        } else {
+            // Scroll by relative amount
+            if let Some(sel) = selector {
+                format!(
+                    "document.querySelector('{}').scrollBy({}, {});",
+                    sel, by_x, by_y
+                )
+            } else {
+                format!("window.scrollBy({}, {});", by_x, by_y)
+            }
+        };
+
+        debug!("Executing scroll: {}", script);
+        self.client.execute(&script, vec![]).await?;
+
+        info!("Scroll completed");
+        Ok(())
+    }
+
+    /// Scroll the window (or the element matching `selector`) to the bottom
+    /// again and again, the way infinite-scroll lists load, until `until` is
+    /// met. Reports each step's scroll height, item count and the number of
+    /// requests it set off.
+    pub async fn scroll_until(
+        &self,
+        selector: Option<&str>,
+        until: &ScrollUntil,
+    ) -> Result<serde_json::Value> {
+        const POLL_MS: u64 = 100;
+
+        let started = std::time::Instant::now();
+        let stable = std::time::Duration::from_millis(until.stable_ms);
+        let count_selector = until.selector_count.as_ref().map(|c| c.selector.as_str());
+        let reached = |metrics: &serde_json::Value| match &until.selector_count {
+            Some(target) => metrics["count"].as_u64().unwrap_or(0) as usize >= target.count,
+            None => false,
+        };
+
+        let first = self.scroll_metrics(selector, count_selector, false).await?;
+        let mut last = first.clone();
+        let mut steps = Vec::new();
+        let mut reason = if reached(&first) {
+            "count"
+        } else {
+            "max_steps"
+        };
+        if reason != "count" {
+            for step in 1..=until.max_steps {
+                let before = last.clone();
+                let scrolled_at = std::time::Instant::now();
+                let mut current = self.scroll_metrics(selector, count_selector, true).await?;
+                // Give the list time to load more before calling it the end
+                while current["height"] == before["height"]
+                    && !reached(&current)
+                    && scrolled_at.elapsed() < stable
+                {
+                    tokio::time::sleep(std::time::Duration::from_millis(POLL_MS)).await;
+                    current = self.scroll_metrics(selector, count_selector, false).await?;
+                }
+
+                let grew = current["height"].as_f64() > before["height"].as_f64();
+                steps.push(json!({
+                    "step": step,
+                    "scroll_y": current["scroll_y"],
+                    "height": current["height"],
+                    "count": current["count"],
+                    "grew": grew,
+                    "requests": current["requests"].as_u64().unwrap_or(0)
+                        .saturating_sub(before["requests"].as_u64().unwrap_or(0)),
+                    "waited_ms": scrolled_at.elapsed().as_millis() as u64,
+                }));
+                last = current;
+                if reached(&last) {
+                    reason = "count";
+                    break;
+                }
+                if !grew {
+                    reason = "stable";
+                    break;
+                }
+            }
+        }
+
+        info!("Scrolled {} time(s), stopped on {}", steps.len(), reason);
+        Ok(json!({
+            "selector": selector,
+            "reason": reason,
+            "start": { "height": first["height"], "count": first["count"] },
+            "height": last["height"],
+            "count": last["count"],
+            "target": until.selector_count,
+            "requests": last["requests"].as_u64().unwrap_or(0)
+                .saturating_sub(first["requests"].as_u64().unwrap_or(0)),
+            "elapsed_ms": started.elapsed().as_millis() as u64,
+            "steps": steps,
+        }))
+    }
+
+    /// Scroll height, position, matching item count and resource requests so
+    /// far, optionally after scrolling to the bottom
+    async fn scroll_metrics(
+        &self,
+        selector: Option<&str>,
+        count_selector: Option<&str>,
+        scroll: bool,
+    ) -> Result<serde_json::Value> {
+        let script = r#"
+            const [selector, countSelector, scroll] = arguments;
+            const el = selector ? document.querySelector(selector) : document.scrollingElement;
+            if (!el) return { error: 'not_found' };
+            // Resource timing keeps 250 entries by default, too few to count a long feed
+            if (!window.__webprobeScrollBuffer) {
+                performance.setResourceTimingBufferSize(100000);
+                window.__webprobeScrollBuffer = true;
+            }
+            if (scroll) el.scrollTop = el.scrollHeight;
+            return {
+                height: el.scrollHeight,
+                scroll_y: Math.round(el.scrollTop),
+                count: countSelector ? document.querySelectorAll(countSelector).length : null,
+                requests: performance.getEntriesByType('resource').length,
+            };
+        "#;
+        let metrics = self
+            .client
+            .execute(
+                script,
+                vec![json!(selector), json!(count_selector), json!(scroll)],
+            )
+            .await
+            .context("Failed to scroll")?;
+        if metrics["error"] == "not_found" {
+            anyhow::bail!(
+                "No element found matching selector: {}",
+                selector.unwrap_or("")
+            );
+        }
+        Ok(metrics)
+    }
+
+    /// Scroll the page to each of `positions` and record where the element
+    /// matching `selector` sits in the viewport there, with its computed
+    /// `position`. Flags sticky elements that can't stick and fixed or sticky
+    /// headers that cover the targets of in-page links. With
+    /// `wait_stable_frames`, each measurement waits for the element to hold
+    /// still that many frames.
+    pub async fn scroll_probe(
+        &self,
+        selector: &str,
+        positions: &[ScrollPosition],
+        wait_stable_frames: Option<u32>,
+    ) -> Result<serde_json::Value> {
+        let element = self.find_for_action(selector, None).await?;
+        let element_arg = vec![serde_json::to_value(&element)?];
+
+        let checks = self
+            .client
+            .execute(
+                &format!(
+                    "{}\n{}\n{}",
+                    SELECTOR_OF_JS,
+                    OCCLUSION_JS,
+                    r#"
+                    const el = arguments[0];
+                    const style = getComputedStyle(el);
+                    const issues = [];
+                    if (style.position === 'sticky') {
+                        if (['top', 'bottom', 'left', 'right'].every(side => style[side] === 'auto')) {
+                            issues.push({
+                                kind: 'sticky_without_inset',
+                                message: 'position: sticky without top, bottom, left or right never sticks',
+                            });
+                        }
+                        // Sticky sticks inside its nearest scroll container; one that
+                        // never scrolls pins it in place
+                        for (let node = el.parentElement; node && node !== document.body; node = node.parentElement) {
+                            const s = getComputedStyle(node);
+                            const overflow = [s.overflowX, s.overflowY];
+                            if (!overflow.some(o => ['hidden', 'auto', 'scroll'].includes(o))) continue;
+                            const scrolls = !overflow.includes('hidden') && node.scrollHeight > node.clientHeight;
+                            if (!scrolls) {
+                                issues.push({
+                                    kind: 'sticky_overflow_ancestor',
+                                    ancestor: selectorOf(node),
+                                    overflow: s.overflowX === s.overflowY ? s.overflowY : `${s.overflowX} ${s.overflowY}`,
+                                    message: `${selectorOf(node)} has overflow ${s.overflowY === 'visible' ? s.overflowX : s.overflowY}, so the element sticks inside it instead of the viewport`,
+                                });
+                            }
+                            break;
+                        }
+                        const parent = el.parentElement;
+                        if (parent && parent.getBoundingClientRect().height - el.getBoundingClientRect().height < 1) {
+                            issues.push({
+                                kind: 'sticky_no_room',
+                                message: `Its parent ${selectorOf(parent)} is no taller than it, so it has no room to stick`,
+                            });
+                        }
+                    }
+                    return { position: style.position, top: style.top, bottom: style.bottom, start_y: scrollY, issues };
+                    "#
+                ),
+                element_arg.clone(),
+            )
+            .await
+            .context("Failed to read element position")?;
+        let position = checks["position"].as_str().unwrap_or("static").to_string();
+
+        let mut samples = Vec::new();
+        for at in positions {
+            let y = match at {
+                ScrollPosition::Offset(y) => json!(y),
+                ScrollPosition::Bottom => json!(null),
+            };
+            self.client
+                .execute(
+                    "const y = arguments[0]; window.scrollTo(0, y === null ? document.scrollingElement.scrollHeight : y);",
+                    vec![y],
+                )
+                .await
+                .context("Failed to scroll")?;
+            // Let scroll handlers (hide-on-scroll headers and the like) run
+            tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+            let settled = match wait_stable_frames {
+                Some(frames) => Some(
+                    self.wait_for_stable(selector, frames, STABLE_TIMEOUT_MS)
+                        .await?,
+                ),
+                None => None,
+            };
+
+            let mut sample = self
+                .client
+                .execute(
+                    r#"
+                    const el = arguments[0];
+                    const style = getComputedStyle(el);
+                    const r = el.getBoundingClientRect();
+                    const round = (n) => Math.round(n * 10) / 10;
+                    let stuck = null;
+                    if (style.position === 'sticky' && style.top !== 'auto') {
+                        stuck = Math.abs(r.top - parseFloat(style.top)) < 1;
+                    } else if (style.position === 'sticky' && style.bottom !== 'auto') {
+                        stuck = Math.abs(innerHeight - r.bottom - parseFloat(style.bottom)) < 1;
+                    }
+                    return {
+                        scroll_y: Math.round(scrollY),
+                        rect: { x: round(r.x), y: round(r.y), width: round(r.width), height: round(r.height) },
+                        position: style.position,
+                        in_viewport: r.bottom > 0 && r.top < innerHeight && r.width > 0 && r.height > 0,
+                        stuck,
+                    };
+                    "#,
+                    element_arg.clone(),
+                )
+                .await
+                .context("Failed to measure element")?;
+            sample["at"] = json!(at.to_string());
+            if let Some(settled) = settled {
+                sample["stable"] = settled["stable"].clone();
+            }
+            samples.push(sample);
+        }
+
+        let mut issues = checks["issues"].as_array().cloned().unwrap_or_default();
+        // At the very top a sticky element can sit at its inset without sticking
+        let scrolled: Vec<_> = samples
+            .iter()
+            .filter(|s| s["scroll_y"].as_f64() > Some(0.0))
+            .collect();
+        if position == "sticky"
+            && !scrolled.is_empty()
+            && !scrolled.iter().any(|s| s["stuck"] == true)
+        {
+            issues.push(json!({
+                "kind": "sticky_never_stuck",
+                "message": "It didn't stick at any of the probed positions below the top",
+            }));
+        }
+
+        // Headers pinned to the top can hide what an in-page link jumps to
+        let pinned_top =
+            position == "fixed" || (position == "sticky" && checks["top"].as_str() != Some("auto"));
+        let mut anchors_checked = 0;
+        if pinned_top {
+            let anchors = self
+                .client
+                .execute(
+                    &format!(
+                        "{}\n{}\n{}",
+                        SELECTOR_OF_JS,
+                        OCCLUSION_JS,
+                        r#"
+                        const header = arguments[0];
+                        const targets = Array.from(document.querySelectorAll("a[href^='#']"))
+                            .map(a => {
+                                try { return decodeURIComponent(a.getAttribute('href').slice(1)); } catch (e) { return ''; }
+                            })
+                            .filter(Boolean)
+                            .map(id => document.getElementById(id))
+                            .filter((target, i, all) => target && all.indexOf(target) === i && !header.contains(target) && !target.contains(header))
+                            .slice(0, 20);
+                        const overlaps = [];
+                        for (const target of targets) {
+                            // Where following the link puts it, scroll-margin and scroll-padding included
+                            target.scrollIntoView({ block: 'start', behavior: 'instant' });
+                            const h = header.getBoundingClientRect();
+                            const t = target.getBoundingClientRect();
+                            const covered = Math.round(h.bottom - t.top);
+                            if (covered > 0 && h.top <= t.top && h.right > t.left && h.left < t.right) {
+                                overlaps.push({
+                                    anchor: '#' + target.id,
+                                    target: selectorOf(target),
+                                    covered_px: covered,
+                                    scroll_margin_top: getComputedStyle(target).scrollMarginTop,
+                                    scroll_padding_top: getComputedStyle(document.documentElement).scrollPaddingTop,
+                                });
+                            }
+                        }
+                        return { checked: targets.length, overlaps };
+                        "#
+                    ),
+                    element_arg.clone(),
+                )
+                .await
+                .context("Failed to check anchor targets")?;
+            anchors_checked = anchors["checked"].as_u64().unwrap_or(0);
+            for overlap in anchors["overlaps"].as_array().into_iter().flatten() {
+                let mut issue = overlap.clone();
+                issue["kind"] = json!("anchor_overlap");
+                issue["message"] = json!(format!(
+                    "Covers the top {}px of {} after jumping to it; add scroll-margin-top to the target or scroll-padding-top to html",
+                    overlap["covered_px"],
+                    overlap["anchor"].as_str().unwrap_or("")
+                ));
+                issues.push(issue);
+            }
+        }
+
+        // Leave the page where it was
+        self.client
+            .execute(
+                "window.scrollTo(0, arguments[0]);",
+                vec![checks["start_y"].clone()],
+            )
+            .await?;
+
+        Ok(json!({
+            "selector": selector,
+            "position": position,
+            "samples": samples,
+            "anchors_checked": anchors_checked,
+            "issues": issues,
+        }))
+    }
+
+    /// Wait until the elements matching `selector` keep the same rect for
+    /// `frames` animation frames in a row, giving up after `timeout_ms`.
+    /// Returns whether they settled, the frames watched and the time taken.
+    pub async fn wait_for_stable(
+        &self,
+        selector: &str,
+        frames: u32,
+        timeout_ms: u64,
+    ) -> Result<serde_json::Value> {
+        let result = self
+            .client
+            .execute_async(
+                STABLE_RECT_JS,
+                vec![json!(selector), json!(frames), json!(timeout_ms)],
+            )
+            .await
+            .context("Failed to wait for a stable layout")?;
+        if result["stable"] == true {
+            debug!(
+                "'{}' stable after {}ms ({} frames)",
+                selector, result["elapsed_ms"], result["frames"]
+            );
+        } else {
+            warn!(
+                "'{}' still moving after {}ms; measuring anyway",
+                selector, result["elapsed_ms"]
+            );
+        }
+        Ok(result)
+    }
+
+    /// Turn CSS transitions and animations off (or back on) in the current
+    /// document
+    pub async fn set_animations(&self, disabled: bool) -> Result<()> {
+        self.client
+            .execute(ANIMATIONS_JS, vec![json!(disabled)])
+            .await
+            .context("Failed to toggle animations")?;
+        Ok(())
+    }
+
+    /// Keep animations off in documents the current tab loads from now on
+    /// (Chrome only); returns the id to pass to
+    /// `Page.removeScriptToEvaluateOnNewDocument`
+    pub async fn disable_animations_on_load(&self) -> Result<String> {
+        let registered = self
+            .cdp(
+                "Page.addScriptToEvaluateOnNewDocument",
+                json!({ "source": format!("(function() {{ {} }})(true);", ANIMATIONS_JS) }),
+            )
+            .await?;
+        registered["identifier"]
+            .as_str()
+            .map(str::to_string)
+            .context("No script identifier returned")
+    }
+
+    /// Run a Chrome DevTools Protocol command (Chrome only)
+    pub async fn cdp(
+        &self,
+        cmd: &'static str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        if !matches!(self.browser_type, BrowserType::Chrome) {
+            anyhow::bail!("{} needs Chrome (DevTools protocol)", cmd);
+        }
+        self.client
+            .issue_cmd(CdpCommand { cmd, params })
+            .await
+            .context(format!("DevTools command {} failed", cmd))
+    }
+
+    pub async fn execute(
+        &self,
+        script: &str,
+        args: Vec<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.client
+            .execute(script, args)
+            .await
+            .context("Failed to execute script")
+    }
+
+    pub async fn execute_javascript(
+        &self,
+        url: Option<&str>,
+        code: &str,
+    ) -> Result<serde_json::Value> {
+        // Navigate to URL if provided (an empty URL means stay on the current page)
+        if let Some(url) = url
+            && !url.is_empty()
+        {
+            info!("Navigating to {}", url);
+            self.client.goto(url).await?;
+        }
+
+        debug!("Executing JavaScript: {}", code);
+
+        // Wrap the code to handle both sync and async/Promise results
+        let wrapped_code = format!(
+            r#"
+            return (async function() {{
+                try {{
+                    const result = {};
+                    // Check if result is a Promise
+                    if (result && typeof result.then === 'function') {{
+                        return await result;
+                    }}
+                    return result;
+                }} catch (error) {{
+                    return {{
+                        error: true,
+                        message: error.message || String(error),
+                        stack: error.stack
+                    }};
+                }}
+            }})();
+            "#,
+            code
+        );
+
+        // Execute the wrapped JavaScript
+        let result = self
+            .client
+            .execute(&wrapped_code, vec![])
+            .await
+            .context("Failed to execute JavaScript")?;
+
+        // Check if the result is an error object
+        if let Some(obj) = result.as_object()
+            && obj.get("error").and_then(|v| v.as_bool()).unwrap_or(false)
+        {
+            let msg = obj
+                .get("message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown JavaScript error");
+            let stack = obj.get("stack").and_then(|v| v.as_str()).unwrap_or("");
+
+            return Err(anyhow::anyhow!("JavaScript error: {}\n{}", msg, stack));
+        }
+
+        info!("JavaScript execution completed");
+        Ok(result)
+    }
+
+    pub async fn analyze_layout(
+        &self,
+        url: &str,
+        selector: &str,
+        depth: u8,
+        max_elements: usize,
+        wait_stable: u64,
+        detect_shadow: bool,
+    ) -> Result<LayoutInfo> {
+        // Navigate if URL is provided
+        if !url.is_empty() {
+            info!("Navigating to {} for layout analysis", url);
+            self.client.goto(url).await?;
+        }
+
+        // Wait (up to wait_stable ms) for the root to stop moving
+        if wait_stable > 0 {
+            info!("Waiting up to {}ms for layout to stabilize", wait_stable);
+            self.wait_for_stable(selector, STABLE_FRAMES, wait_stable)
+                .await?;
+        }
+
+        // JavaScript to analyze layout with performance limits
+        let analysis_script = r#"
+        function analyzeLayout(selector, maxDepth, maxElements, detectShadow) {
+            let elementCount = 0;
+            const warnings = [];
+            
+            function getBoxModel(element) {
+                const styles = window.getComputedStyle(element);
+                const rect = element.getBoundingClientRect();
+                
+                return {
+                    bounds: {
+                        x: rect.x,
+                        y: rect.y,
+                        width: rect.width,
+                        height: rect.height
+                    },
+                    box_model: {
+                        margin: {
+                            top: parseFloat(styles.marginTop) || 0,
+                            right: parseFloat(styles.marginRight) || 0,
+                            bottom: parseFloat(styles.marginBottom) || 0,
+                            left: parseFloat(styles.marginLeft) || 0
+                        },
+                        border: {
+                            top: parseFloat(styles.borderTopWidth) || 0,
+                            right: parseFloat(styles.borderRightWidth) || 0,
+                            bottom: parseFloat(styles.borderBottomWidth) || 0,
+                            left: parseFloat(styles.borderLeftWidth) || 0
+                        },
+                        padding: {
+                            top: parseFloat(styles.paddingTop) || 0,
+                            right: parseFloat(styles.paddingRight) || 0,
+                            bottom: parseFloat(styles.paddingBottom) || 0,
+                            left: parseFloat(styles.paddingLeft) || 0
+                        },
+                        content: {
+                            width: rect.width - 
+                                   parseFloat(styles.paddingLeft) - 
+                                   parseFloat(styles.paddingRight) -
+                                   parseFloat(styles.borderLeftWidth) -
+                                   parseFloat(styles.borderRightWidth),
+                            height: rect.height - 
+                                    parseFloat(styles.paddingTop) - 
+                                    parseFloat(styles.paddingBottom) -
+                                    parseFloat(styles.borderTopWidth) -
+                                    parseFloat(styles.borderBottomWidth)
+                        }
+                    },
+                    computed_styles: {
+                        display: styles.display,
+                        position: styles.position,
+                        zIndex: styles.zIndex,
+                        overflow: styles.overflow,
+                        float: styles.float,
+                        clear: styles.clear,
+                        boxSizing: styles.boxSizing,
+                        flexDirection: styles.flexDirection,
+                        justifyContent: styles.justifyContent,
+                        alignItems: styles.alignItems,
+                        gap: styles.gap
+                    },
+                    is_visible: rect.width > 0 && rect.height > 0 && 
+                               styles.display !== 'none' && 
+                               styles.visibility !== 'hidden'
+                };
+            }
+            
+            function analyzeElement(element, currentDepth) {
+                if (!element || elementCount >= maxElements || currentDepth > maxDepth) {
+                    return null;
+                }
+                
+                elementCount++;
+                
+                const result = {
+                    selector: selector,
+                    tag: element.tagName.toLowerCase(),
+                    classes: Array.from(element.classList),
+                    id: element.id || null,
+                    ...getBoxModel(element),
+                    children: [],
+                    warnings: [],
+                    element_count: 1,
+                    truncated: false
+                };
+                
+                // Detect shadow DOM
+                if (detectShadow && element.shadowRoot) {
+                    result.warnings.push('Element has shadow DOM (not analyzed)');
+                }
+                
+                // Detect iframes
+                if (element.tagName === 'IFRAME') {
+                    result.warnings.push('Element is an iframe (content not analyzed)');
+                }
+                
+                // Analyze children if within depth
+                if (currentDepth < maxDepth) {
+                    const children = Array.from(element.children);
+                    for (let child of children) {
+                        if (elementCount >= maxElements) {
+                            result.truncated = true;
+                            result.warnings.push(`Analysis truncated at ${maxElements} elements`);
+                            break;
+                        }
+                        const childAnalysis = analyzeElement(child, currentDepth + 1);
+                        if (childAnalysis) {
+                            result.children.push(childAnalysis);
+                            result.element_count += childAnalysis.element_count;
+                        }
+                    }
+                }
+                
+                return result;
+            }
+            
+            const element = document.querySelector(selector);
+            if (!element) {
+                throw new Error('Element not found: ' + selector);
+            }
+            
+            const analysis = analyzeElement(element, 0);
+            analysis.warnings = warnings;
+            return analysis;
+        }
+        
+        return analyzeLayout(arguments[0], arguments[1], arguments[2], arguments[3]);
+        "#;
+
+        debug!(
+            "Running layout analysis with depth={}, max_elements={}",
+            depth, max_elements
+        );
+
+        let result = self
+            .client
+            .execute(
+                analysis_script,
+                vec![
+                    json!(selector),
+                    json!(depth),
+                    json!(max_elements),
+                    json!(detect_shadow),
+                ],
+            )
+            .await
+            .context("Failed to analyze layout")?;
+
+        // Convert the result to LayoutInfo
+        let layout: LayoutInfo =
+            serde_json::from_value(result).context("Failed to parse layout analysis")?;
+
+        info!(
+            "Layout analysis complete: {} elements analyzed",
+            layout.element_count
+        );
+
+        Ok(layout)
+    }
+
+    pub async fn analyze_context(
+        &self,
+        url: &str,
+        selector: &str,
+        focus: &str,
+        proximity: u32,
+        index: Option<usize>,
+    ) -> Result<serde_json::Value> {
+        // Navigate if URL is provided
+        if !url.is_empty() {
+            info!("Navigating to {} for context analysis", url);
+            self.client.goto(url).await?;
+        }
+
+        // Wait a bit for page to stabilize
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+
+        let script = match focus {
+            "spacing" => self.build_spacing_script(selector, proximity, index),
+            "wrapping" => self.build_wrapping_script(selector, index),
+            "anomalies" => self.build_anomalies_script(proximity),
+            "all" => self.build_comprehensive_script(selector, proximity, index),
+            _ => self.build_comprehensive_script(selector, proximity, index),
+        };
+
+        debug!("Running {} analysis", focus);
+
+        let result = self
+            .client
+            .execute(&script, vec![])
+            .await
+            .context(format!("Failed to analyze {} context", focus))?;
+
+        info!("Context analysis complete");
+        Ok(result)
+    }
+
+    fn build_spacing_script(&self, selector: &str, proximity: u32, index: Option<usize>) -> String {
+        let target_selection = if let Some(idx) = index {
+            format!(
+                "const elements = document.querySelectorAll('{}');
+            if (elements.length <= {}) return {{ error: 'Element at index {} not found' }};
+            const target = elements[{}];",
+                selector, idx, idx, idx
+            )
+        } else {
+            format!(
+                "const target = document.querySelector('{}');
+            if (!target) return {{ error: 'Element not found: {}' }};",
+                selector, selector
+            )
+        };
+
+        format!(
+            r#"
+        return (function() {{
+            {}
+            
+            const targetRect = target.getBoundingClientRect();
+            const targetStyles = window.getComputedStyle(target);
+            
+            // Get target's box model
+            const targetBox = {{
+                margin: {{
+                    top: parseFloat(targetStyles.marginTop) || 0,
+                    right: parseFloat(targetStyles.marginRight) || 0,
+                    bottom: parseFloat(targetStyles.marginBottom) || 0,
+                    left: parseFloat(targetStyles.marginLeft) || 0
+                }},
+                padding: {{
+                    top: parseFloat(targetStyles.paddingTop) || 0,
+                    right: parseFloat(targetStyles.paddingRight) || 0,
+                    bottom: parseFloat(targetStyles.paddingBottom) || 0,
+                    left: parseFloat(targetStyles.paddingLeft) || 0
+                }},
+                border: {{
+                    top: parseFloat(targetStyles.borderTopWidth) || 0,
+                    right: parseFloat(targetStyles.borderRightWidth) || 0,
+                    bottom: parseFloat(targetStyles.borderBottomWidth) || 0,
+                    left: parseFloat(targetStyles.borderLeftWidth) || 0
+                }}
+            }};
+            
+            // Find adjacent elements
+            const allElements = Array.from(document.querySelectorAll('*'));
+            const adjacentElements = [];
+            
+            for (let el of allElements) {{
+                if (el === target || target.contains(el) || el.contains(target)) continue;
+                
+                const rect = el.getBoundingClientRect();
+                const styles = window.getComputedStyle(el);
+                
+                // Check if element is above
+                if (rect.bottom <= targetRect.top && 
+                    Math.abs(rect.bottom - targetRect.top) < {}) {{
+                    adjacentElements.push({{
+                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + (el.className && typeof el.className === 'string' ? '.' + el.className.split(' ').join('.') : ''),
+                        position: 'above',
+                        bounds: {{
+                            x: rect.x,
+                            y: rect.y,
+                            width: rect.width,
+                            height: rect.height,
+                            bottom: rect.bottom
+                        }},
+                        margin_bottom: parseFloat(styles.marginBottom) || 0,
+                        actual_gap: targetRect.top - rect.bottom,
+                        margin_collapsed: (targetRect.top - rect.bottom) < (parseFloat(styles.marginBottom) + targetBox.margin.top)
+                    }});
+                }}
+                
+                // Check if element is below
+                if (rect.top >= targetRect.bottom && 
+                    Math.abs(rect.top - targetRect.bottom) < {}) {{
+                    adjacentElements.push({{
+                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + (el.className && typeof el.className === 'string' ? '.' + el.className.split(' ').join('.') : ''),
+                        position: 'below',
+                        bounds: {{
+                            x: rect.x,
+                            y: rect.y,
+                            width: rect.width,
+                            height: rect.height,
+                            top: rect.top
+                        }},
+                        margin_top: parseFloat(styles.marginTop) || 0,
+                        actual_gap: rect.top - targetRect.bottom,
+                        margin_collapsed: (rect.top - targetRect.bottom) < (targetBox.margin.bottom + parseFloat(styles.marginTop))
+                    }});
+                }}
+                
+                // Check if element is to the left
+                if (rect.right <= targetRect.left && 
+                    Math.abs(rect.right - targetRect.left) < {}) {{
+                    adjacentElements.push({{
+                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + (el.className && typeof el.className === 'string' ? '.' + el.className.split(' ').join('.') : ''),
+                        position: 'left',
+                        bounds: {{
+                            x: rect.x,
+                            y: rect.y,
+                            width: rect.width,
+                            height: rect.height,
+                            right: rect.right
+                        }},
+                        margin_right: parseFloat(styles.marginRight) || 0,
+                        actual_gap: targetRect.left - rect.right
+                    }});
+                }}
+                
+                // Check if element is to the right
+                if (rect.left >= targetRect.right && 
+                    Math.abs(rect.left - targetRect.right) < {}) {{
+                    adjacentElements.push({{
+                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + (el.className && typeof el.className === 'string' ? '.' + el.className.split(' ').join('.') : ''),
+                        position: 'right',
+                        bounds: {{
+                            x: rect.x,
+                            y: rect.y,
+                            width: rect.width,
+                            height: rect.height,
+                            left: rect.left
+                        }},
+                        margin_left: parseFloat(styles.marginLeft) || 0,
+                        actual_gap: rect.left - targetRect.right
+                    }});
+                }}
+            }}
+            
+            // Get parent padding
+            const parent = target.parentElement;
+            const parentStyles = parent ? window.getComputedStyle(parent) : null;
+            const parentPadding = parent ? {{
+                top: parseFloat(parentStyles.paddingTop) || 0,
+                right: parseFloat(parentStyles.paddingRight) || 0,
+                bottom: parseFloat(parentStyles.paddingBottom) || 0,
+                left: parseFloat(parentStyles.paddingLeft) || 0
+            }} : null;
+            
+            // Check for pseudo elements
+            const beforeStyles = window.getComputedStyle(target, ':before');
+            const afterStyles = window.getComputedStyle(target, ':after');
+            
+            const pseudoElements = {{
+                before: beforeStyles.content !== 'none' ? {{
+                    content: beforeStyles.content,
+                    height: parseFloat(beforeStyles.height) || 'auto',
+                    width: parseFloat(beforeStyles.width) || 'auto',
+                    display: beforeStyles.display
+                }} : null,
+                after: afterStyles.content !== 'none' ? {{
+                    content: afterStyles.content,
+                    height: parseFloat(afterStyles.height) || 'auto',
+                    width: parseFloat(afterStyles.width) || 'auto',
+                    display: afterStyles.display
+                }} : null
+            }};
+            
+            return {{
+                target: {{
+                    selector: '{}',
+                    bounds: {{
+                        x: targetRect.x,
+                        y: targetRect.y,
+                        width: targetRect.width,
+                        height: targetRect.height
+                    }},
+                    box: targetBox,
+                    display: targetStyles.display,
+                    position: targetStyles.position
+                }},
+                spacing_context: {{
+                    adjacent_elements: adjacentElements,
+                    parent: parent ? {{
+                        selector: parent.tagName.toLowerCase() + (parent.id ? '#' + parent.id : ''),
+                        padding: parentPadding
+                    }} : null,
+                    pseudo_elements: pseudoElements
+                }},
+                element_count: adjacentElements.length + 1
+            }};
+        }})();
+        "#,
+            target_selection, proximity, proximity, proximity, proximity, selector
+        )
+    }
+
+    fn build_wrapping_script(&self, selector: &str, index: Option<usize>) -> String {
+        let target_selection = if let Some(idx) = index {
+            format!(
+                "const elements = document.querySelectorAll('{}');
+            if (elements.length <= {}) return {{ error: 'Container at index {} not found' }};
+            const container = elements[{}];",
+                selector, idx, idx, idx
+            )
+        } else {
+            format!(
+                "const container = document.querySelector('{}');
+            if (!container) return {{ error: 'Container not found: {}' }};",
+                selector, selector
+            )
+        };
+
+        format!(
+            r#"
+        return (function() {{
+            {}
+            
+            const containerRect = container.getBoundingClientRect();
+            const containerStyles = window.getComputedStyle(container);
+            
+            // Get container properties
+            const containerInfo = {{
+                selector: '{}',
+                display: containerStyles.display,
+                flexWrap: containerStyles.flexWrap,
+                flexDirection: containerStyles.flexDirection,
+                gridTemplateColumns: containerStyles.gridTemplateColumns,
+                gap: containerStyles.gap,
+                columnGap: containerStyles.columnGap,
+                rowGap: containerStyles.rowGap,
+                width: containerRect.width,
+                padding: {{
+                    left: parseFloat(containerStyles.paddingLeft) || 0,
+                    right: parseFloat(containerStyles.paddingRight) || 0,
+                    top: parseFloat(containerStyles.paddingTop) || 0,
+                    bottom: parseFloat(containerStyles.paddingBottom) || 0
+                }},
+                available_width: containerRect.width - 
+                    (parseFloat(containerStyles.paddingLeft) || 0) - 
+                    (parseFloat(containerStyles.paddingRight) || 0)
+            }};
+            
+            // Analyze children
+            const children = Array.from(container.children);
+            const childrenInfo = [];
+            let currentRowY = null;
+            let currentRowWidth = 0;
+            let rowCount = 1;
+            
+            children.forEach((child, index) => {{
+                const rect = child.getBoundingClientRect();
+                const styles = window.getComputedStyle(child);
+                
+                // Detect row wrapping
+                let wrapped = false;
+                if (currentRowY !== null && Math.abs(rect.y - currentRowY) > 5) {{
+                    wrapped = true;
+                    rowCount++;
+                    currentRowY = rect.y;
+                    currentRowWidth = 0;
+                }}
+                if (currentRowY === null) {{
+                    currentRowY = rect.y;
+                }}
+                
+                const childWidth = rect.width + 
+                    (parseFloat(styles.marginLeft) || 0) + 
+                    (parseFloat(styles.marginRight) || 0);
+                
+                currentRowWidth += childWidth;
+                
+                childrenInfo.push({{
+                    index: index,
+                    tag: child.tagName.toLowerCase(),
+                    width: rect.width,
+                    height: rect.height,
+                    margin: {{
+                        left: parseFloat(styles.marginLeft) || 0,
+                        right: parseFloat(styles.marginRight) || 0,
+                        top: parseFloat(styles.marginTop) || 0,
+                        bottom: parseFloat(styles.marginBottom) || 0
+                    }},
+                    total_width: childWidth,
+                    wrapped: wrapped,
+                    row: rowCount,
+                    position_in_row: wrapped ? 1 : childrenInfo.filter(c => c.row === rowCount).length + 1
+                }});
+            }});
+            
+            // Calculate total width needed
+            const totalChildrenWidth = childrenInfo
+                .filter(c => c.row === 1)
+                .reduce((sum, c) => sum + c.total_width, 0);
+            
+            // Detect gap/spacing
+            let estimatedGap = 0;
+            if (childrenInfo.length > 1) {{
+                const firstChild = container.children[0].getBoundingClientRect();
+                const secondChild = container.children[1].getBoundingClientRect();
+                if (Math.abs(firstChild.y - secondChild.y) < 5) {{
+                    estimatedGap = secondChild.left - firstChild.right;
+                }}
+            }}
+            
+            return {{
+                container: containerInfo,
+                children: {{
+                    total_count: children.length,
+                    rows: rowCount,
+                    per_row: childrenInfo.filter(c => c.row === 1).length,
+                    child_analysis: childrenInfo
+                }},
+                width_calculation: {{
+                    container_available: containerInfo.available_width,
+                    total_children_width: totalChildrenWidth,
+                    estimated_gap: estimatedGap,
+                    overflow: totalChildrenWidth - containerInfo.available_width,
+                    fits: totalChildrenWidth <= containerInfo.available_width
+                }},
+                element_count: children.length + 1
+            }};
+        }})();
+        "#,
+            target_selection, selector
+        )
+    }
+
+    fn build_anomalies_script(&self, _proximity: u32) -> String {
+        format!(
+            r#"
+        {}
+        {}
+        return (function() {{
+            const viewport = {{
+                width: window.innerWidth,
+                height: window.innerHeight,
+                scrollWidth: document.documentElement.scrollWidth,
+                scrollHeight: document.documentElement.scrollHeight,
+                hasHorizontalScroll: document.documentElement.scrollWidth > window.innerWidth,
+                hasVerticalScroll: document.documentElement.scrollHeight > window.innerHeight
+            }};
+            
+            const allElements = Array.from(document.querySelectorAll('*'));
+            const anomalies = {{
+                elements_with_unusual_properties: [],
+                elements_beyond_viewport: [],
+                invisible_elements: [],
+                interaction_conflicts: [],
+                covered_elements: [],
+                contrast_issues: []
+            }};
+            
+            const stats = {{
+                total_elements: allElements.length,
+                with_display_none: 0,
+                with_visibility_hidden: 0,
+                with_zero_dimensions: 0,
+                outside_viewport: 0,
+                with_negative_z_index: 0
+            }};
+            
+            allElements.forEach(el => {{
+                const rect = el.getBoundingClientRect();
+                const styles = window.getComputedStyle(el);
+                const zIndex = styles.zIndex !== 'auto' ? parseInt(styles.zIndex) : 0;
+                
+                // Track statistics
+                if (styles.display === 'none') stats.with_display_none++;
+                if (styles.visibility === 'hidden') stats.with_visibility_hidden++;
+                if (rect.width === 0 || rect.height === 0) stats.with_zero_dimensions++;
+                if (rect.right < 0 || rect.left > viewport.width || 
+                    rect.bottom < 0 || rect.top > viewport.height) stats.outside_viewport++;
+                if (zIndex < 0) stats.with_negative_z_index++;
+                
+                // Detect unusual properties
+                if (zIndex < 0 || 
+                    (styles.opacity === '0' && styles.position === 'fixed') ||
+                    (styles.position === 'absolute' && (rect.x < -100 || rect.y < -100))) {{
+                    anomalies.elements_with_unusual_properties.push({{
+                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
+                        properties: {{
+                            z_index: zIndex,
+                            position: styles.position,
+                            opacity: parseFloat(styles.opacity),
+                            display: styles.display,
+                            visibility: styles.visibility,
+                            bounds: {{ x: rect.x, y: rect.y, width: rect.width, height: rect.height }}
+                        }}
+                    }});
+                }}
+                
+                // Detect elements beyond viewport
+                if (rect.right > viewport.width && rect.width > 0) {{
+                    anomalies.elements_beyond_viewport.push({{
+                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
+                        bounds: {{ x: rect.x, y: rect.y, width: rect.width, height: rect.height }},
+                        viewport_width: viewport.width,
+                        overflow_amount: rect.right - viewport.width
+                    }});
+                }}
+                
+                // Detect invisible but space-taking elements
+                if (el.textContent && el.textContent.trim().length > 0 && rect.width > 0 && rect.height > 0) {{
+                    const isInvisible = styles.opacity === '0' || 
+                                       styles.visibility === 'hidden' || 
+                                       (styles.color === styles.backgroundColor && styles.backgroundColor !== 'transparent');
+                    
+                    if (isInvisible) {{
+                        anomalies.invisible_elements.push({{
+                            selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
+                            reason: styles.opacity === '0' ? 'opacity: 0' : 
+                                   styles.visibility === 'hidden' ? 'visibility: hidden' : 
+                                   'text color matches background',
+                            text_length: el.textContent.trim().length,
+                            bounds: {{ x: rect.x, y: rect.y, width: rect.width, height: rect.height }}
+                        }});
+                    }}
+                    
+                    // Check contrast
+                    if (styles.color && styles.backgroundColor && styles.backgroundColor !== 'transparent') {{
+                        anomalies.contrast_issues.push({{
+                            selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
+                            color: styles.color,
+                            background: styles.backgroundColor,
+                            text_sample: el.textContent.substring(0, 50)
+                        }});
+                    }}
+                }}
+            }});
+            
+            // Interactive elements and text on screen that something else is painted over
+            const interactive = ['BUTTON', 'A', 'INPUT', 'SELECT', 'TEXTAREA'];
+            const candidates = allElements.filter(el => {{
+                const rect = el.getBoundingClientRect();
+                if (rect.width === 0 || rect.height === 0 || rect.bottom < 0 || rect.top > viewport.height) return false;
+                if (interactive.includes(el.tagName) || el.getAttribute('role') === 'button') return true;
+                return Array.from(el.childNodes).some(n => n.nodeType === 3 && n.textContent.trim());
+            }}).slice(0, 300);
+            
+            candidates.forEach(el => {{
+                const occlusion = occlusionOf(el);
+                if (occlusion.covered_by.length === 0) return;
+                const blocking = occlusion.covered_by[0];
+                if (interactive.includes(el.tagName) || el.getAttribute('role') === 'button') {{
+                    anomalies.interaction_conflicts.push({{
+                        clickable_element: selectorOf(el),
+                        blocking_element: blocking.selector,
+                        blocking_z_index: blocking.z_index,
+                        clickable_z_index: window.getComputedStyle(el).zIndex,
+                        visible_percent: occlusion.visible_percent,
+                        covered_by: occlusion.covered_by
+                    }});
+                }} else {{
+                    anomalies.covered_elements.push({{
+                        selector: selectorOf(el),
+                        text_sample: el.textContent.trim().substring(0, 50),
+                        visible_percent: occlusion.visible_percent,
+                        covered_by: occlusion.covered_by
+                    }});
+                }}
+            }});
+            
+            return {{
+                viewport: viewport,
+                anomalies: anomalies,
+                statistics: stats,
+                element_count: allElements.length
+            }};
+        }})();
+        "#,
+            SELECTOR_OF_JS, OCCLUSION_JS
+        )
+    }
+
+    fn build_comprehensive_script(
+        &self,
+        selector: &str,
+        proximity: u32,
+        index: Option<usize>,
+    ) -> String {
+        let target_selection = if let Some(idx) = index {
+            format!(
+                "const elements = document.querySelectorAll('{}');
+            if (elements.length <= {}) return {{ error: 'Element at index {} not found' }};
+            const target = elements[{}];",
+                selector, idx, idx, idx
+            )
+        } else {
+            format!(
+                "const target = document.querySelector('{}');
+            if (!target) return {{ error: 'Element not found: {}' }};",
+                selector, selector
+            )
+        };
+
+        // Combine key aspects from all focused scripts
+        format!(
+            r#"
+        {}
+        {}
+        return (function() {{
+            {}
+            
+            const targetRect = target.getBoundingClientRect();
+            const targetStyles = window.getComputedStyle(target);
+            
+            // Basic element info
+            const elementInfo = {{
+                selector: '{}',
+                tag: target.tagName.toLowerCase(),
+                id: target.id || null,
+                classes: Array.from(target.classList),
+                bounds: {{
+                    x: targetRect.x,
+                    y: targetRect.y,
+                    width: targetRect.width,
+                    height: targetRect.height
+                }},
+                styles: {{
+                    display: targetStyles.display,
+                    position: targetStyles.position,
+                    zIndex: targetStyles.zIndex,
+                    opacity: targetStyles.opacity,
+                    overflow: targetStyles.overflow,
+                    visibility: targetStyles.visibility
+                }},
+                box_model: {{
+                    margin: {{
+                        top: parseFloat(targetStyles.marginTop) || 0,
+                        right: parseFloat(targetStyles.marginRight) || 0,
+                        bottom: parseFloat(targetStyles.marginBottom) || 0,
+                        left: parseFloat(targetStyles.marginLeft) || 0
+                    }},
+                    padding: {{
+                        top: parseFloat(targetStyles.paddingTop) || 0,
+                        right: parseFloat(targetStyles.paddingRight) || 0,
+                        bottom: parseFloat(targetStyles.paddingBottom) || 0,
+                        left: parseFloat(targetStyles.paddingLeft) || 0
+                    }},
+                    border: {{
+                        top: parseFloat(targetStyles.borderTopWidth) || 0,
+                        right: parseFloat(targetStyles.borderRightWidth) || 0,
+                        bottom: parseFloat(targetStyles.borderBottomWidth) || 0,
+                        left: parseFloat(targetStyles.borderLeftWidth) || 0
+                    }}
+                }},
+                occlusion: occlusionOf(target)
+            }};
+            
+            // Get nearby elements
+            const nearbyElements = [];
+            const allElements = Array.from(document.querySelectorAll('*'));
+            
+            allElements.forEach(el => {{
+                if (el === target || target.contains(el) || el.contains(target)) return;
+                
+                const rect = el.getBoundingClientRect();
+                const distance = Math.sqrt(
+                    Math.pow(rect.x - targetRect.x, 2) + 
+                    Math.pow(rect.y - targetRect.y, 2)
+                );
+                
+                if (distance < {}) {{
+                    const styles = window.getComputedStyle(el);
+                    nearbyElements.push({{
+                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
+                        distance: Math.round(distance),
+                        bounds: {{
+                            x: rect.x,
+                            y: rect.y,
+                            width: rect.width,
+                            height: rect.height
+                        }},
+                        display: styles.display,
+                        position: styles.position,
+                        zIndex: styles.zIndex
+                    }});
+                }}
+            }});
+            
+            // Check children if container
+            const children = Array.from(target.children);
+            const childrenInfo = children.slice(0, 10).map(child => {{
+                const rect = child.getBoundingClientRect();
+                return {{
+                    tag: child.tagName.toLowerCase(),
+                    bounds: {{
+                        x: rect.x,
+                        y: rect.y,
+                        width: rect.width,
+                        height: rect.height
+                    }}
+                }};
+            }});
+            
+            // Viewport info
+            const viewport = {{
+                width: window.innerWidth,
+                height: window.innerHeight,
+                scrollWidth: document.documentElement.scrollWidth,
+                scrollHeight: document.documentElement.scrollHeight
+            }};
+            
+            return {{
+                element: elementInfo,
+                nearby_elements: nearbyElements.slice(0, 20),
+                children: childrenInfo,
+                children_count: children.length,
+                viewport: viewport,
+                element_count: nearbyElements.length + children.length + 1
+            }};
+        }})();
+        "#,
+            SELECTOR_OF_JS, OCCLUSION_JS, target_selection, selector, proximity
+        )
+    }
+
+    pub async fn close(self) -> Result<()> {
+        self.client.close().await?;
+        Ok(())
+    }
+
+    /// Take a screenshot of the current page
+    pub async fn screenshot(&self, path: Option<&str>) -> Result<Vec<u8>> {
+        debug!("Taking screenshot");
+
+        // Get the screenshot as bytes
+        let screenshot_data = self
+            .client
+            .screenshot()
+            .await
+            .context("Failed to take screenshot")?;
+
+        // Save to file if path is provided
+        if let Some(file_path) = path {
+            use std::fs::File;
+            use std::io::Write;
+
+            let mut file = File::create(file_path)
+                .context(format!("Failed to create screenshot file: {}", file_path))?;
+            file.write_all(&screenshot_data)
+                .context(format!("Failed to write screenshot to: {}", file_path))?;
+
+            info!("Screenshot saved to: {}", file_path);
+        }
+
+        Ok(screenshot_data)
+    }
+
+    /// Take a screenshot of a specific element
+    pub async fn screenshot_element(&self, selector: &str, path: Option<&str>) -> Result<Vec<u8>> {
+        debug!("Taking screenshot of element: {}", selector);
+
+        // Find the element
+        let element = self
+            .client
+            .find(Locator::Css(selector))
+            .await
+            .context(format!("Element not found for screenshot: {}", selector))?;
+
+        // Get element screenshot
+        let screenshot_data = element.screenshot().await.context(format!(
+            "Failed to take screenshot of element: {}",
+            selector
+        ))?;
+
+        // Save to file if path is provided
+        if let Some(file_path) = path {
+            use std::fs::File;
+            use std::io::Write;
+
+            let mut file = File::create(file_path)
+                .context(format!("Failed to create screenshot file: {}", file_path))?;
+            file.write_all(&screenshot_data)
+                .context(format!("Failed to write screenshot to: {}", file_path))?;
+
+            info!("Element screenshot saved to: {}", file_path);
+        }
+
+        Ok(screenshot_data)
+    }
+
+    /// Inspect elements within iframes
+    pub async fn inspect_iframe(
+        &self,
+        iframe_selector: &str,
+        element_selector: &str,
+    ) -> Result<Vec<ElementInfo>> {
+        debug!("Switching to iframe: {}", iframe_selector);
+
+        // Use JavaScript to switch to iframe and find elements
+        let script = r#"
+            // Find the iframe
+            const iframe = document.querySelector(arguments[0]);
+            if (!iframe) {
+                throw new Error('Iframe not found: ' + arguments[0]);
+            }
+            
+            // Get the iframe's document
+            const iframeDoc = iframe.contentDocument || iframe.contentWindow.document;
+            if (!iframeDoc) {
+                throw new Error('Cannot access iframe content (may be cross-origin)');
+            }
+            
+            // Find elements within the iframe
+            const elements = iframeDoc.querySelectorAll(arguments[1]);
+            const results = [];
+            
+            elements.forEach(el => {
+                const rect = el.getBoundingClientRect();
+                const styles = window.getComputedStyle(el);
+                
+                results.push({
+                    tag: el.tagName.toLowerCase(),
+                    text: el.textContent || '',
+                    position: {
+                        x: rect.x + iframe.getBoundingClientRect().x,
+                        y: rect.y + iframe.getBoundingClientRect().y
+                    },
+                    size: {
+                        width: rect.width,
+                        height: rect.height
+                    },
+                    display: styles.display,
+                    visible: rect.width > 0 && rect.height > 0
+                });
+            });
+            
+            return results;
+        "#;
+
+        let elements_data = self
+            .client
+            .execute(
+                script,
+                vec![json!(iframe_selector), json!(element_selector)],
+            )
+            .await
+            .context("Failed to inspect iframe content")?;
+
+        // Convert JavaScript results to ElementInfo
+        let mut results = Vec::new();
+        if let serde_json::Value::Array(elements) = elements_data {
+            let total_matches = elements.len();
+            for (idx, el) in elements.into_iter().enumerate() {
+                if let serde_json::Value::Object(obj) = el {
+                    let position = Position {
+                        x: obj
+                            .get("position")
+                            .and_then(|p| p.get("x"))
+                            .and_then(|v| v.as_f64())
+                            .unwrap_or(0.0),
+                        y: obj
+                            .get("position")
+                            .and_then(|p| p.get("y"))
+                            .and_then(|v| v.as_f64())
+                            .unwrap_or(0.0),
+                        unit: "px".to_string(),
+                    };
+
+                    let size = Size {
+                        width: obj
+                            .get("size")
+                            .and_then(|s| s.get("width"))
+                            .and_then(|v| v.as_f64())
+                            .unwrap_or(0.0),
+                        height: obj
+                            .get("size")
+                            .and_then(|s| s.get("height"))
+                            .and_then(|v| v.as_f64())
+                            .unwrap_or(0.0),
+                        unit: "px".to_string(),
+                    };
+
+                    results.push(ElementInfo {
+                        selector: element_selector.to_string(),
+                        browser: format!("{:?}", self.browser_type),
+                        position,
+                        size,
+                        computed_styles: json!({
+                            "display": obj.get("display").and_then(|v| v.as_str()).unwrap_or(""),
+                            "tag": obj.get("tag").and_then(|v| v.as_str()).unwrap_or(""),
+                        }),
+                        text_content: obj
+                            .get("text")
+                            .and_then(|v| v.as_str())
+                            .map(|s| s.to_string()),
+                        children_count: 0,
+                        metadata: Some(ElementMetadata {
+                            total_matches,
+                            returned_index: idx,
+                            warning: Some(format!("Element found in iframe: {}", iframe_selector)),
+                        }),
+                        occlusion: None,
+                        forced_states: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        if results.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No elements found matching '{}' in iframe '{}'",
+                element_selector,
+                iframe_selector
+            ));
+        }
+
+        Ok(results)
+    }
+
+    /// Get all iframes on the page
+    #[allow(dead_code)]
+    pub async fn list_iframes(&self) -> Result<Vec<serde_json::Value>> {
+        let script = r#"
+            const iframes = Array.from(document.querySelectorAll('iframe'));
+            return iframes.map((iframe, index) => {
+                const rect = iframe.getBoundingClientRect();
+                return {
+                    index: index,
+                    id: iframe.id || null,
+                    name: iframe.name || null,
+                    src: iframe.src || null,
+                    selector: iframe.id ? `#${iframe.id}` : 
+                             iframe.name ? `iframe[name="${iframe.name}"]` :
+                             `iframe:nth-of-type(${index + 1})`,
+                    width: rect.width,
+                    height: rect.height,
+                    position: {
+                        x: rect.x,
+                        y: rect.y
+                    },
+                    visible: rect.width > 0 && rect.height > 0
+                };
+            });
+        "#;
+
+        let iframes = self
+            .client
+            .execute(script, vec![])
+            .await
+            .context("Failed to list iframes")?;
+
+        // Convert to Vec<serde_json::Value>
+        if let serde_json::Value::Array(frames) = iframes {
+            Ok(frames)
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// Diagnose layout issues on the page
+    pub async fn diagnose_layout(
+        &self,
+        selector: Option<&str>,
+        check_type: &str,
+    ) -> Result<serde_json::Value> {
+        let script = r#"
+            function diagnoseLayout(selector, checkType) {
+                const results = {
+                    issues: [],
+                    warnings: [],
+                    suggestions: [],
+                    metrics: {}
+                };
+                
+                const elements = selector ? 
+                    Array.from(document.querySelectorAll(selector)) : 
+                    Array.from(document.querySelectorAll('*'));
+                
+                // Check for overflow issues
+                if (checkType === 'overflow' || checkType === 'all') {
+                    // First check if document has horizontal scroll
+                    const docScrollWidth = Math.max(
+                        document.documentElement.scrollWidth || 0,
+                        document.body.scrollWidth || 0,
+                        document.documentElement.offsetWidth || 0,
+                        document.body.offsetWidth || 0
+                    );
+                    
+                    if (docScrollWidth > window.innerWidth) {
+                        results.issues.push({
+                            type: 'horizontal_overflow',
+                            selector: 'body',
+                            width: docScrollWidth,
+                            viewport_width: window.innerWidth,
+                            overflow: docScrollWidth - window.innerWidth
+                        });
+                    }
+                    
+                    elements.forEach(el => {
+                        const rect = el.getBoundingClientRect();
+                        const styles = window.getComputedStyle(el);
+                        
+                        // Check multiple width properties
+                        const elementWidth = Math.max(
+                            el.offsetWidth || 0,
+                            el.scrollWidth || 0,
+                            parseFloat(styles.width) || 0,
+                            rect.width || 0
+                        );
+                        
+                        // If any width measurement exceeds viewport, it's overflow
+                        if (elementWidth > window.innerWidth) {
+                            const selector = el.tagName.toLowerCase() + 
+                                (el.id ? '#' + el.id : '') + 
+                                (el.className && typeof el.className === 'string' ? 
+                                    '.' + el.className.split(' ').filter(c => c).join('.') : '');
+                            
+                            // Avoid duplicates
+                            const existing = results.issues.find(i => i.selector === selector);
+                            if (!existing) {
+                                results.issues.push({
+                                    type: 'horizontal_overflow',
+                                    selector: selector,
+                                    width: elementWidth,
+                                    viewport_width: window.innerWidth,
+                                    overflow: elementWidth - window.innerWidth
+                                });
+                            }
+                        }
+                        
+                        // Check if element is cut off
+                        if (rect.right > window.innerWidth || rect.left < 0) {
+                            results.warnings.push({
+                                type: 'element_cutoff',
+                                selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + (el.className ? '.' + el.className.split(' ').filter(c => c).join('.') : ''),
+                                position: { left: rect.left, right: rect.right }
+                            });
+                        }
+                        
+                        // Check for text overflow
+                        if (el.scrollWidth > el.clientWidth || el.scrollHeight > el.clientHeight) {
+                            results.warnings.push({
+                                type: 'content_overflow',
+                                selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
+                                scrollWidth: el.scrollWidth,
+                                clientWidth: el.clientWidth,
+                                overflow: styles.overflow
+                            });
+                        }
+                    });
+                }
+                
+                // Check for spacing issues
+                if (checkType === 'spacing' || checkType === 'all') {
+                    const spacingIssues = [];
+                    elements.forEach(el => {
+                        const styles = window.getComputedStyle(el);
+                        const margin = parseFloat(styles.marginTop) + parseFloat(styles.marginBottom);
+                        const padding = parseFloat(styles.paddingTop) + parseFloat(styles.paddingBottom);
+                        
+                        // Check for excessive spacing
+                        if (margin > 100) {
+                            spacingIssues.push({
+                                type: 'excessive_margin',
+                                selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
+                                margin: margin
+                            });
+                        }
+                        
+                        // Check for margin collapse
+                        const parent = el.parentElement;
+                        if (parent) {
+                            const parentStyles = window.getComputedStyle(parent);
+                            if (parseFloat(styles.marginTop) > 0 && parseFloat(parentStyles.marginBottom) > 0) {
+                                results.warnings.push({
+                                    type: 'potential_margin_collapse',
+                                    element: el.tagName.toLowerCase(),
+                                    parent: parent.tagName.toLowerCase()
+                                });
+                            }
+                        }
+                    });
+                    if (spacingIssues.length > 0) {
+                        results.issues = results.issues.concat(spacingIssues);
+                    }
+                }
+                
+                // Check for alignment issues
+                if (checkType === 'alignment' || checkType === 'all') {
+                    const alignmentMap = new Map();
+                    elements.forEach(el => {
+                        const rect = el.getBoundingClientRect();
+                        const key = Math.round(rect.left);
+                        if (!alignmentMap.has(key)) {
+                            alignmentMap.set(key, []);
+                        }
+                        alignmentMap.get(key).push(el);
+                    });
+                    
+                    // Find misaligned elements
+                    alignmentMap.forEach((els, xPos) => {
+                        if (els.length === 1 && Math.abs(xPos) > 5) {
+                            const el = els[0];
+                            const siblings = Array.from(el.parentElement?.children || []);
+                            if (siblings.length > 1) {
+                                results.warnings.push({
+                                    type: 'misalignment',
+                                    selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
+                                    position: xPos,
+                                    siblings: siblings.length
+                                });
+                            }
+                        }
+                    });
+                }
+                
+                // Check for responsiveness issues
+                if (checkType === 'responsiveness' || checkType === 'all') {
+                    results.metrics.viewport = {
+                        width: window.innerWidth,
+                        height: window.innerHeight
+                    };
+                    
+                    // Check for fixed widths that might break on mobile
+                    elements.forEach(el => {
+                        const styles = window.getComputedStyle(el);
+                        const width = styles.width;
+                        
+                        if (width.endsWith('px')) {
+                            const widthValue = parseFloat(width);
+                            if (widthValue > 400 && !styles.maxWidth) {
+                                results.warnings.push({
+                                    type: 'fixed_width_no_max',
+                                    selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
+                                    width: widthValue,
+                                    suggestion: 'Consider using max-width or responsive units'
+                                });
+                            }
+                        }
+                    });
+                    
+                    // Check for horizontal scrolling
+                    if (document.documentElement.scrollWidth > window.innerWidth) {
+                        results.issues.push({
+                            type: 'horizontal_scroll',
+                            page_width: document.documentElement.scrollWidth,
+                            viewport_width: window.innerWidth
+                        });
+                    }
+                }
+                
+                // Add suggestions based on issues found
+                if (results.issues.some(i => i.type === 'horizontal_overflow')) {
+                    results.suggestions.push('Use max-width: 100% or overflow-x: auto for wide elements');
+                }
+                if (results.warnings.some(w => w.type === 'content_overflow')) {
+                    results.suggestions.push('Consider using overflow: auto or text-overflow: ellipsis');
+                }
+                if (results.issues.some(i => i.type === 'excessive_margin')) {
+                    results.suggestions.push('Review margin values - consider using consistent spacing scale');
+                }
+                
+                results.summary = {
+                    total_issues: results.issues.length,
+                    total_warnings: results.warnings.length,
+                    check_type: checkType,
+                    elements_checked: elements.length
+                };
+                
+                return results;
+            }
+            
+            return diagnoseLayout(arguments[0], arguments[1]);
+        "#;
+
+        let selector_arg = selector.map(|s| json!(s)).unwrap_or(json!(null));
+        let diagnosis = self
+            .client
+            .execute(script, vec![selector_arg, json!(check_type)])
+            .await
+            .context("Failed to diagnose layout")?;
+
+        Ok(diagnosis)
+    }
+
+    /// Validate page for accessibility and SEO
+    pub async fn validate_page(
+        &self,
+        check_type: &str,
+        config: &ValidationConfig,
+    ) -> Result<serde_json::Value> {
+        let rules = config.resolve(check_type)?;
+
+        let script = format!(
+            "{}\n{}",
+            SELECTOR_OF_JS,
+            r#"
+            // Opening tag (or the whole element when short) for reports
+            function snippetOf(el) {
+                if (!el) return null;
+                const html = el.outerHTML;
+                if (html.length <= 200) return html;
+                const open = html.slice(0, html.indexOf('>') + 1);
+                return open.length <= 200 ? open + '…' : open.slice(0, 199) + '…';
+            }
+
+            function metaContent(attr, key) {
+                const el = document.querySelector('meta[' + attr + '="' + key + '"]');
+                return el && el.content ? el.content.trim() : null;
+            }
+
+            // Natural size of an image URL, or null if it doesn't load
+            function imageSize(url) {
+                return new Promise(resolve => {
+                    const img = new Image();
+                    const timer = setTimeout(() => resolve(null), 5000);
+                    img.onload = () => {
+                        clearTimeout(timer);
+                        resolve({ width: img.naturalWidth, height: img.naturalHeight });
+                    };
+                    img.onerror = () => {
+                        clearTimeout(timer);
+                        resolve(null);
+                    };
+                    img.src = url;
+                });
+            }
+
+            // schema.org properties Google needs (required) or uses (recommended)
+            // for rich results; 'a|b' means either one
+            const SCHEMA_TYPES = {
+                Article: { required: ['headline'], recommended: ['image', 'datePublished', 'author'] },
+                NewsArticle: { required: ['headline'], recommended: ['image', 'datePublished', 'author'] },
+                BlogPosting: { required: ['headline'], recommended: ['image', 'datePublished', 'author'] },
+                Product: { required: ['name', 'offers|review|aggregateRating'], recommended: ['image', 'description'] },
+                Offer: { required: ['price|priceSpecification', 'priceCurrency|priceSpecification'], recommended: ['availability'] },
+                AggregateRating: { required: ['ratingValue', 'ratingCount|reviewCount'], recommended: [] },
+                Review: { required: ['author', 'reviewRating'], recommended: [] },
+                BreadcrumbList: { required: ['itemListElement'], recommended: [] },
+                ListItem: { required: ['position'], recommended: ['name|item'] },
+                Organization: { required: ['name'], recommended: ['url', 'logo'] },
+                LocalBusiness: { required: ['name', 'address'], recommended: ['telephone', 'openingHours|openingHoursSpecification'] },
+                Person: { required: ['name'], recommended: [] },
+                Event: { required: ['name', 'startDate', 'location'], recommended: ['endDate', 'image', 'description'] },
+                FAQPage: { required: ['mainEntity'], recommended: [] },
+                Question: { required: ['name', 'acceptedAnswer|suggestedAnswer'], recommended: [] },
+                Answer: { required: ['text'], recommended: [] },
+                Recipe: { required: ['name', 'image'], recommended: ['author', 'recipeIngredient', 'recipeInstructions'] },
+                VideoObject: { required: ['name', 'thumbnailUrl', 'uploadDate'], recommended: ['description', 'duration'] },
+                JobPosting: { required: ['title', 'description', 'datePosted', 'hiringOrganization'], recommended: ['jobLocation|applicantLocationRequirements', 'validThrough'] },
+                HowTo: { required: ['name', 'step'], recommended: ['image'] },
+                WebSite: { required: ['name|url'], recommended: [] }
+            };
+            const DATE_PROPS = ['datePublished', 'dateModified', 'startDate', 'endDate', 'uploadDate', 'datePosted', 'validThrough'];
+
+            // JSON-LD and microdata items, checked against SCHEMA_TYPES
+            let structuredDataResult = null;
+            function structuredData() {
+                if (structuredDataResult) return structuredDataResult;
+                const problems = [];
+                const missing = [];
+
+                const typesOf = (item) => [].concat(item['@type'] || [])
+                    .map(t => String(t).replace(/^https?:\/\/schema\.org\//, ''));
+
+                const checkItem = (item, source, path, depth) => {
+                    if (!item || typeof item !== 'object' || depth > 6) return;
+                    if (Array.isArray(item)) {
+                        item.forEach((child, i) => checkItem(child, source, path + '[' + i + ']', depth + 1));
+                        return;
+                    }
+                    const types = typesOf(item);
+                    types.forEach(type => {
+                        const spec = SCHEMA_TYPES[type];
+                        if (!spec) return;
+                        ['required', 'recommended'].forEach(kind => {
+                            spec[kind].forEach(prop => {
+                                const options = prop.split('|');
+                                if (options.some(p => item[p] !== undefined && item[p] !== '' && item[p] !== null)) return;
+                                missing.push({
+                                    required: kind === 'required',
+                                    finding: {
+                                        message: type + ' (' + path + ') is missing ' + kind + ' property ' + options.join(' or '),
+                                        schema_type: type,
+                                        property: prop,
+                                        format: source.format,
+                                        selector: source.selector,
+                                        snippet: source.snippet
+                                    }
+                                });
+                            });
+                        });
+                    });
+                    DATE_PROPS.forEach(prop => {
+                        const value = item[prop];
+                        if (typeof value === 'string' && !/^\d{4}-\d{2}-\d{2}/.test(value)) {
+                            problems.push({
+                                message: (types[0] || 'Item') + ' ' + prop + ' "' + value + '" is not an ISO 8601 date',
+                                format: source.format,
+                                selector: source.selector,
+                                snippet: source.snippet
+                            });
+                        }
+                    });
+                    Object.keys(item).filter(k => !k.startsWith('@')).forEach(key => {
+                        const value = item[key];
+                        if (value && typeof value === 'object') {
+                            checkItem(value, source, path + '.' + key, depth + 1);
+                        }
+                    });
+                };
+
+                document.querySelectorAll('script[type="application/ld+json"]').forEach(script => {
+                    const source = {
+                        format: 'json-ld',
+                        selector: selectorOf(script),
+                        snippet: script.textContent.trim().slice(0, 300)
+                    };
+                    let data;
+                    try {
+                        data = JSON.parse(script.textContent);
+                    } catch (e) {
+                        problems.push({ message: 'JSON-LD is not valid JSON: ' + e.message, ...source });
+                        return;
+                    }
+                    [].concat(data).forEach(block => {
+                        if (!block || typeof block !== 'object') return;
+                        const context = JSON.stringify(block['@context'] || '');
+                        if (!/schema\.org/.test(context)) {
+                            problems.push({ message: 'JSON-LD @context is not schema.org', ...source });
+                        }
+                        const items = block['@graph'] ? [].concat(block['@graph']) : [block];
+                        items.forEach((item, i) => {
+                            if (!item['@type']) {
+                                problems.push({ message: 'JSON-LD item has no @type', ...source });
+                            }
+                            checkItem(item, source, block['@graph'] ? '@graph[' + i + ']' : 'root', 0);
+                        });
+                    });
+                });
+
+                // Microdata: top-level itemscopes become plain objects
+                const microdataItem = (scope) => {
+                    const item = {};
+                    const type = scope.getAttribute('itemtype');
+                    if (type) item['@type'] = type.trim().split(/\s+/).map(t => t.replace(/^https?:\/\/schema\.org\//, ''));
+                    scope.querySelectorAll('[itemprop]').forEach(el => {
+                        if (el.parentElement.closest('[itemscope]') !== scope) return;
+                        const value = el.hasAttribute('itemscope') ? microdataItem(el)
+                            : el.getAttribute('content') || el.getAttribute('datetime') ||
+                              el.getAttribute('href') || el.getAttribute('src') || el.textContent.trim();
+                        el.getAttribute('itemprop').split(/\s+/).forEach(prop => {
+                            item[prop] = item[prop] === undefined ? value : [].concat(item[prop], value);
+                        });
+                    });
+                    return item;
+                };
+                document.querySelectorAll('[itemscope]:not([itemprop])').forEach(scope => {
+                    const source = { format: 'microdata', selector: selectorOf(scope), snippet: snippetOf(scope) };
+                    const itemtype = scope.getAttribute('itemtype');
+                    if (!itemtype) {
+                        problems.push({ message: 'Microdata itemscope has no itemtype', ...source });
+                    } else if (!/schema\.org/.test(itemtype)) {
+                        problems.push({ message: 'Microdata itemtype is not schema.org: ' + itemtype, ...source });
+                    }
+                    checkItem(microdataItem(scope), source, 'root', 0);
+                });
+
+                structuredDataResult = { problems, missing };
+                return structuredDataResult;
+            }
+
+            // WCAG 2.x contrast of every visible text element against the
+            // background painted behind it (ancestor backgrounds, alpha blended)
+            function checkContrast() {
+                const MAX_ELEMENTS = 5000;
+                const MAX_LISTED = 50;
+                const canvas = document.createElement('canvas').getContext('2d');
+
+                const parseColor = (value) => {
+                    const match = /^rgba?\(([^)]+)\)$/.exec(value.trim());
+                    if (match) {
+                        const parts = match[1].split(/[\s,\/]+/).filter(Boolean).map(parseFloat);
+                        if (parts.length >= 3) {
+                            return { r: parts[0], g: parts[1], b: parts[2], a: parts.length > 3 ? parts[3] : 1 };
+                        }
+                    }
+                    // Other color spaces (oklch, color(...)): let canvas convert to sRGB
+                    if (!canvas) return null;
+                    canvas.clearRect(0, 0, 1, 1);
+                    canvas.fillStyle = '#000';
+                    canvas.fillStyle = value;
+                    canvas.fillRect(0, 0, 1, 1);
+                    const [r, g, b, a] = canvas.getImageData(0, 0, 1, 1).data;
+                    return { r, g, b, a: a / 255 };
+                };
+
+                const blend = (top, bottom) => {
+                    const a = top.a + bottom.a * (1 - top.a);
+                    if (a === 0) return { r: 0, g: 0, b: 0, a: 0 };
+                    const mix = (t, b) => (t * top.a + b * bottom.a * (1 - top.a)) / a;
+                    return { r: mix(top.r, bottom.r), g: mix(top.g, bottom.g), b: mix(top.b, bottom.b), a };
+                };
+
+                const luminance = (c) => {
+                    const channel = (v) => {
+                        v /= 255;
+                        return v <= 0.03928 ? v / 12.92 : Math.pow((v + 0.055) / 1.055, 2.4);
+                    };
+                    return 0.2126 * channel(c.r) + 0.7152 * channel(c.g) + 0.0722 * channel(c.b);
+                };
+
+                const hex = (c) => '#' + [c.r, c.g, c.b]
+                    .map(v => Math.round(v).toString(16).padStart(2, '0')).join('');
+
+                // The painted background, or the reason it can't be known
+                const backgroundOf = (el) => {
+                    const layers = [];
+                    for (let node = el; node && node.nodeType === 1; node = node.parentElement) {
+                        const style = getComputedStyle(node);
+                        if (style.backgroundImage && style.backgroundImage !== 'none') {
+                            return { unknown: 'background-image', element: selectorOf(node) };
+                        }
+                        const color = parseColor(style.backgroundColor);
+                        if (color && color.a > 0) {
+                            layers.push(color);
+                            if (color.a >= 1) break;
+                        }
+                    }
+                    // Anything still transparent shows the (white) canvas
+                    let painted = { r: 255, g: 255, b: 255, a: 1 };
+                    for (const layer of layers.reverse()) painted = blend(layer, painted);
+                    return { color: painted };
+                };
+
+                const report = {
+                    checked: 0,
+                    // AAA lists only text that passes AA, so each element is
+                    // reported once; both lists are capped on their own
+                    aa: { passed: 0, failed: 0, failures: [] },
+                    aaa: { passed: 0, failed: 0, failures: [] },
+                    unknown: 0,
+                    unknown_elements: [],
+                    truncated: false
+                };
+
+                const seen = new Set();
+                const walker = document.createTreeWalker(document.body, NodeFilter.SHOW_TEXT);
+                while (walker.nextNode()) {
+                    const el = walker.currentNode.parentElement;
+                    if (!el || seen.has(el) || !walker.currentNode.data.trim()) continue;
+                    seen.add(el);
+                    if (['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE', 'OPTION'].includes(el.tagName)) continue;
+                    // Text in disabled controls is exempt
+                    if (el.closest(':disabled')) continue;
+
+                    const style = getComputedStyle(el);
+                    const rect = el.getBoundingClientRect();
+                    if (style.visibility !== 'visible' || rect.width === 0 || rect.height === 0) continue;
+                    if (el.checkVisibility && !el.checkVisibility({ opacityProperty: true })) continue;
+
+                    if (report.checked >= MAX_ELEMENTS) { report.truncated = true; break; }
+                    report.checked++;
+
+                    const text = walker.currentNode.data.replace(/\s+/g, ' ').trim().slice(0, 80);
+                    const background = backgroundOf(el);
+                    if (background.unknown) {
+                        report.unknown++;
+                        if (report.unknown_elements.length < MAX_LISTED) {
+                            report.unknown_elements.push({
+                                selector: selectorOf(el),
+                                text,
+                                reason: background.unknown,
+                                source: background.element
+                            });
+                        }
+                        continue;
+                    }
+
+                    let foreground = parseColor(style.color) || { r: 0, g: 0, b: 0, a: 1 };
+                    let opacity = 1;
+                    for (let node = el; node && node.nodeType === 1; node = node.parentElement) {
+                        opacity *= parseFloat(getComputedStyle(node).opacity);
+                    }
+                    foreground = blend({ ...foreground, a: foreground.a * opacity }, background.color);
+
+                    const l1 = luminance(foreground);
+                    const l2 = luminance(background.color);
+                    const ratio = (Math.max(l1, l2) + 0.05) / (Math.min(l1, l2) + 0.05);
+
+                    const size = parseFloat(style.fontSize);
+                    const weight = parseInt(style.fontWeight, 10) || 400;
+                    const large = size >= 24 || (size >= 18.66 && weight >= 700);
+                    const requiredAa = large ? 3 : 4.5;
+                    const requiredAaa = large ? 4.5 : 7;
+                    const passesAa = ratio >= requiredAa;
+                    const passesAaa = ratio >= requiredAaa;
+
+                    report.aa[passesAa ? 'passed' : 'failed']++;
+                    report.aaa[passesAaa ? 'passed' : 'failed']++;
+                    const listed = passesAa ? report.aaa.failures : report.aa.failures;
+                    if (!passesAaa && listed.length < MAX_LISTED) {
+                        listed.push({
+                            selector: selectorOf(el),
+                            text,
+                            foreground: hex(foreground),
+                            background: hex(background.color),
+                            ratio: Math.round(ratio * 100) / 100,
+                            large_text: large,
+                            level: passesAa ? 'AAA' : 'AA',
+                            required_aa: requiredAa,
+                            required_aaa: requiredAaa
+                        });
+                    }
+                }
+
+                return report;
+            }
+
+            // Async so checks can load resources; WebDriver waits for the promise
+            async function validatePage(rules) {
+                // Lazily computed: shared by both contrast rules
+                let contrast = null;
+                const contrastReport = () => contrast || (contrast = checkContrast());
+
+                const RULES = {
+                    'image-alt': () => Array.from(document.querySelectorAll('img'))
+                        .filter(img => !img.hasAttribute('alt') && !img.getAttribute('aria-label'))
+                        .map(img => ({
+                            message: 'Image has no alt text',
+                            selector: selectorOf(img),
+                            src: img.src
+                        })),
+
+                    'form-label': () => Array.from(document.querySelectorAll('input, select, textarea'))
+                        .filter(input => !['hidden', 'submit', 'button', 'reset', 'image'].includes(input.type))
+                        .filter(input => !(input.labels && input.labels.length) &&
+                            !input.getAttribute('aria-label') &&
+                            !input.getAttribute('aria-labelledby') &&
+                            !input.title)
+                        .map(input => ({
+                            message: 'Form control has no label',
+                            selector: selectorOf(input),
+                            element: input.tagName.toLowerCase()
+                        })),
+
+                    'button-name': () => Array.from(document.querySelectorAll('button, [role="button"]'))
+                        .filter(button => !button.textContent.trim() &&
+                            !button.getAttribute('aria-label') &&
+                            !button.getAttribute('aria-labelledby') &&
+                            !button.title)
+                        .map(button => ({
+                            message: 'Button has no accessible name',
+                            selector: selectorOf(button)
+                        })),
+
+                    'heading-order': () => {
+                        const findings = [];
+                        let lastLevel = 0;
+                        document.querySelectorAll('h1, h2, h3, h4, h5, h6').forEach(h => {
+                            const level = parseInt(h.tagName.substring(1));
+                            if (level - lastLevel > 1) {
+                                findings.push({
+                                    message: 'Heading level skips from ' + (lastLevel ? 'h' + lastLevel : 'the start') +
+                                        ' to ' + h.tagName.toLowerCase(),
+                                    selector: selectorOf(h),
+                                    from: 'h' + lastLevel,
+                                    to: h.tagName.toLowerCase()
+                                });
+                            }
+                            lastLevel = level;
+                        });
+                        return findings;
+                    },
+
+                    'landmark-main': () => document.querySelector('main, [role="main"]')
+                        ? []
+                        : [{ message: 'Page has no main landmark' }],
+
+                    'color-contrast': () => contrastReport().aa.failures
+                        .map(f => ({
+                            message: 'Contrast ' + f.ratio + ':1 is below ' + f.required_aa + ':1',
+                            selector: f.selector,
+                            text: f.text,
+                            foreground: f.foreground,
+                            background: f.background,
+                            ratio: f.ratio,
+                            required: f.required_aa
+                        })),
+
+                    'color-contrast-enhanced': () => contrastReport().aaa.failures
+                        .map(f => ({
+                            message: 'Contrast ' + f.ratio + ':1 is below ' + f.required_aaa + ':1 (AAA)',
+                            selector: f.selector,
+                            text: f.text,
+                            foreground: f.foreground,
+                            background: f.background,
+                            ratio: f.ratio,
+                            required: f.required_aaa
+                        })),
+
+                    'document-title': () => document.title.trim()
+                        ? []
+                        : [{ message: 'Page has no title' }],
+
+                    'title-length': (max) => document.title.trim().length > max
+                        ? [{
+                            message: 'Title is ' + document.title.trim().length + ' characters (max ' + max + ')',
+                            length: document.title.trim().length,
+                            snippet: snippetOf(document.querySelector('title'))
+                        }]
+                        : [],
+
+                    'meta-description': () => {
+                        const meta = document.querySelector('meta[name="description"]');
+                        return meta && meta.content ? [] : [{ message: 'Page has no meta description' }];
+                    },
+
+                    'meta-description-length': (max) => {
+                        const meta = document.querySelector('meta[name="description"]');
+                        return meta && meta.content.length > max
+                            ? [{
+                                message: 'Meta description is ' + meta.content.length + ' characters (max ' + max + ')',
+                                length: meta.content.length,
+                                snippet: snippetOf(meta)
+                            }]
+                            : [];
+                    },
+
+                    'page-has-h1': () => document.querySelector('h1')
+                        ? []
+                        : [{ message: 'Page has no h1' }],
+
+                    'single-h1': () => {
+                        const h1s = document.querySelectorAll('h1');
+                        return h1s.length > 1
+                            ? [{
+                                message: 'Page has ' + h1s.length + ' h1 elements',
+                                count: h1s.length,
+                                snippet: Array.from(h1s).map(snippetOf).join('\n')
+                            }]
+                            : [];
+                    },
+
+                    'canonical-link': () => document.querySelector('link[rel="canonical"]')
+                        ? []
+                        : [{ message: 'Page has no canonical link' }],
+
+                    'dom-size': (max) => {
+                        const count = document.querySelectorAll('*').length;
+                        return count > max
+                            ? [{ message: 'DOM has ' + count + ' elements (max ' + max + ')', count }]
+                            : [];
+                    },
+
+                    'image-size': (max) => Array.from(document.querySelectorAll('img'))
+                        .filter(img => img.naturalWidth > max || img.naturalHeight > max)
+                        .map(img => ({
+                            message: 'Image is ' + img.naturalWidth + 'x' + img.naturalHeight + ' (max ' + max + 'px)',
+                            selector: selectorOf(img),
+                            src: img.src,
+                            dimensions: img.naturalWidth + 'x' + img.naturalHeight
+                        })),
+
+                    'inline-styles': (max) => {
+                        const count = document.querySelectorAll('[style]').length;
+                        return count > max
+                            ? [{ message: count + ' elements use inline styles (max ' + max + ')', count }]
+                            : [];
+                    },
+                    'structured-data-valid': () => structuredData().problems,
+
+                    'structured-data-required': () => structuredData().missing
+                        .filter(m => m.required)
+                        .map(m => m.finding),
+
+                    'structured-data-recommended': () => structuredData().missing
+                        .filter(m => !m.required)
+                        .map(m => m.finding),
+
+                    'open-graph': () => {
+                        const findings = [];
+                        const missing = ['og:title', 'og:type', 'og:image', 'og:url']
+                            .filter(p => !metaContent('property', p));
+                        if (missing.length) {
+                            findings.push({
+                                message: 'Open Graph is missing ' + missing.join(', '),
+                                missing,
+                                snippet: snippetOf(document.querySelector('meta[property^="og:"]'))
+                            });
+                        }
+                        ['og:image', 'og:url'].forEach(p => {
+                            const value = metaContent('property', p);
+                            if (value && !/^https?:\/\//.test(value)) {
+                                const el = document.querySelector('meta[property="' + p + '"]');
+                                findings.push({
+                                    message: p + ' must be an absolute URL',
+                                    selector: selectorOf(el),
+                                    snippet: snippetOf(el)
+                                });
+                            }
+                        });
+                        return findings;
+                    },
+
+                    'twitter-card': () => {
+                        const card = metaContent('name', 'twitter:card');
+                        if (!card) {
+                            return [{ message: 'Page has no twitter:card meta tag' }];
+                        }
+                        const el = document.querySelector('meta[name="twitter:card"]');
+                        if (!['summary', 'summary_large_image', 'app', 'player'].includes(card)) {
+                            return [{
+                                message: 'Unknown twitter:card type "' + card + '"',
+                                selector: selectorOf(el),
+                                snippet: snippetOf(el)
+                            }];
+                        }
+                        // Twitter falls back to the Open Graph equivalents
+                        const missing = ['title', 'description', 'image']
+                            .filter(p => !metaContent('name', 'twitter:' + p) && !metaContent('property', 'og:' + p))
+                            .map(p => 'twitter:' + p);
+                        return missing.length
+                            ? [{
+                                message: 'Twitter card is missing ' + missing.join(', ') + ' (no og: fallback either)',
+                                missing,
+                                selector: selectorOf(el),
+                                snippet: snippetOf(el)
+                            }]
+                            : [];
+                    },
+
+                    'social-image-size': async () => {
+                        const card = metaContent('name', 'twitter:card');
+                        const images = [
+                            { tag: 'og:image', url: metaContent('property', 'og:image'), min: [200, 200] },
+                            {
+                                tag: 'twitter:image',
+                                url: metaContent('name', 'twitter:image'),
+                                min: card === 'summary_large_image' ? [300, 157] : [144, 144]
+                            }
+                        ].filter(i => i.url);
+
+                        const findings = [];
+                        for (const image of images) {
+                            const el = document.querySelector(
+                                'meta[property="' + image.tag + '"], meta[name="' + image.tag + '"]');
+                            const size = await imageSize(new URL(image.url, location.href).href);
+                            if (!size) {
+                                findings.push({
+                                    message: image.tag + ' could not be loaded',
+                                    url: image.url,
+                                    selector: selectorOf(el),
+                                    snippet: snippetOf(el)
+                                });
+                            } else if (size.width < image.min[0] || size.height < image.min[1]) {
+                                findings.push({
+                                    message: image.tag + ' is ' + size.width + 'x' + size.height +
+                                        ' (minimum ' + image.min[0] + 'x' + image.min[1] + ')',
+                                    url: image.url,
+                                    width: size.width,
+                                    height: size.height,
+                                    selector: selectorOf(el),
+                                    snippet: snippetOf(el)
+                                });
+                            }
+                        }
+                        return findings;
+                    },
+
+                    'canonical-hreflang': () => {
+                        const findings = [];
+                        const canonicals = Array.from(document.querySelectorAll('link[rel="canonical"]'));
+                        if (canonicals.length > 1) {
+                            findings.push({
+                                message: 'Page has ' + canonicals.length + ' canonical links',
+                                snippet: canonicals.map(snippetOf).join('\n')
+                            });
+                        }
+                        const canonical = canonicals[0];
+                        if (canonical && !/^https?:\/\//.test(canonical.getAttribute('href') || '')) {
+                            findings.push({
+                                message: 'Canonical URL should be absolute',
+                                selector: selectorOf(canonical),
+                                snippet: snippetOf(canonical)
+                            });
+                        }
+
+                        const alternates = Array.from(document.querySelectorAll('link[rel="alternate"][hreflang]'));
+                        if (!alternates.length) return findings;
+
+                        const seen = {};
+                        alternates.forEach(link => {
+                            const lang = link.getAttribute('hreflang');
+                            if (lang !== 'x-default' && !/^[a-z]{2,3}(-[A-Za-z]{4})?(-([A-Za-z]{2}|[0-9]{3}))?$/i.test(lang)) {
+                                findings.push({
+                                    message: 'Invalid hreflang code "' + lang + '"',
+                                    selector: selectorOf(link),
+                                    snippet: snippetOf(link)
+                                });
+                            }
+                            if (!/^https?:\/\//.test(link.getAttribute('href') || '')) {
+                                findings.push({
+                                    message: 'hreflang URL for "' + lang + '" should be absolute',
+                                    selector: selectorOf(link),
+                                    snippet: snippetOf(link)
+                                });
+                            }
+                            if (seen[lang.toLowerCase()] && seen[lang.toLowerCase()] !== link.href) {
+                                findings.push({
+                                    message: 'hreflang "' + lang + '" points to more than one URL',
+                                    selector: selectorOf(link),
+                                    snippet: snippetOf(link)
+                                });
+                            }
+                            seen[lang.toLowerCase()] = link.href;
+                        });
+
+                        // The page's own URL must be among its alternates
+                        const self = canonical ? canonical.href : location.href.split('#')[0];
+                        if (!alternates.some(link => link.href === self)) {
+                            findings.push({
+                                message: 'hreflang set has no entry for this page (' + self + ')',
+                                snippet: alternates.map(snippetOf).join('\n')
+                            });
+                        }
+                        return findings;
+                    },
+
+                    'robots-meta': () => {
+                        const findings = [];
+                        const metas = Array.from(document.querySelectorAll('meta[name="robots" i], meta[name="googlebot" i]'));
+                        const directives = metas.flatMap(m => (m.content || '').toLowerCase()
+                            .split(',').map(d => d.trim()).filter(Boolean));
+                        const has = (d) => directives.includes(d) ||
+                            (directives.includes('none') && (d === 'noindex' || d === 'nofollow')) ||
+                            (directives.includes('all') && (d === 'index' || d === 'follow'));
+
+                        [['index', 'noindex'], ['follow', 'nofollow']].forEach(([yes, no]) => {
+                            if (has(yes) && has(no)) {
+                                findings.push({
+                                    message: 'Robots directives conflict: both ' + yes + ' and ' + no,
+                                    snippet: metas.map(snippetOf).join('\n')
+                                });
+                            }
+                        });
+
+                        // noindex with a canonical elsewhere sends opposite signals
+                        const canonical = document.querySelector('link[rel="canonical"]');
+                        if (has('noindex') && canonical && canonical.href !== location.href.split('#')[0]) {
+                            findings.push({
+                                message: 'Page is noindex but declares a canonical URL elsewhere (' + canonical.href + ')',
+                                snippet: metas.concat([canonical]).map(snippetOf).join('\n')
+                            });
+                        }
+                        return findings;
+                    },
+
+                    'heading-structure': () => {
+                        const findings = [];
+                        const headings = Array.from(document.querySelectorAll('h1, h2, h3, h4, h5, h6'));
+                        headings.filter(h => !h.textContent.trim() && !h.querySelector('img[alt]:not([alt=""])'))
+                            .forEach(h => findings.push({
+                                message: 'Empty ' + h.tagName.toLowerCase(),
+                                selector: selectorOf(h),
+                                snippet: snippetOf(h)
+                            }));
+                        if (headings.length && headings[0].tagName !== 'H1' && document.querySelector('h1')) {
+                            findings.push({
+                                message: 'First heading is ' + headings[0].tagName.toLowerCase() + ', not the h1',
+                                selector: selectorOf(headings[0]),
+                                snippet: snippetOf(headings[0])
+                            });
+                        }
+                        return findings;
+                    }
+
+                };
+
+                const findings = {};
+                for (const rule of rules) {
+                    const check = RULES[rule.id];
+                    findings[rule.id] = check ? await check(rule.threshold) : null;
+                }
+
+                return { url: location.href, findings, contrast };
+            }
+
+            return validatePage(arguments[0]);
+        "#
+        );
+
+        let raw = self
+            .client
+            .execute(&script, vec![validation::script_rules(&rules)])
+            .await
+            .context("Failed to validate page")?;
+
+        validation::build_report(check_type, &rules, &raw)
+    }
+
+    /// Let a freshly loaded page settle before `compare_pages` captures it:
+    /// a fixed pause, or until the compared elements stop moving
+    async fn settle_for_compare(
+        &self,
+        selector: Option<&str>,
+        wait_stable_frames: Option<u32>,
+    ) -> Result<()> {
+        match wait_stable_frames {
+            Some(frames) => {
+                self.wait_for_stable(selector.unwrap_or("body"), frames, STABLE_TIMEOUT_MS)
+                    .await?;
+            }
+            None => tokio::time::sleep(tokio::time::Duration::from_millis(500)).await,
+        }
+        Ok(())
+    }
+
+    /// Compare two pages or states
+    pub async fn compare_pages(
+        &self,
+        url1: &str,
+        url2: &str,
+        mode: &str,
+        selector: Option<&str>,
+        wait_stable_frames: Option<u32>,
+    ) -> Result<serde_json::Value> {
+        // Capture first page
+        self.goto(url1).await?;
+        self.settle_for_compare(selector, wait_stable_frames)
+            .await?;
 
-        format!(
-            r#"
-        return (function() {{
-            {}
-            
-            const containerRect = container.getBoundingClientRect();
-            const containerStyles = window.getComputedStyle(container);
-            
-            // Get container properties
-            const containerInfo = {{
-                selector: '{}',
-                display: containerStyles.display,
-                flexWrap: containerStyles.flexWrap,
-                flexDirection: containerStyles.flexDirection,
-                gridTemplateColumns: containerStyles.gridTemplateColumns,
-                gap: containerStyles.gap,
-                columnGap: containerStyles.columnGap,
-                rowGap: containerStyles.rowGap,
-                width: containerRect.width,
-                padding: {{
-                    left: parseFloat(containerStyles.paddingLeft) || 0,
-                    right: parseFloat(containerStyles.paddingRight) || 0,
-                    top: parseFloat(containerStyles.paddingTop) || 0,
-                    bottom: parseFloat(containerStyles.paddingBottom) || 0
-                }},
-                available_width: containerRect.width - 
-                    (parseFloat(containerStyles.paddingLeft) || 0) - 
-                    (parseFloat(containerStyles.paddingRight) || 0)
-            }};
-            
-            // Analyze children
-            const children = Array.from(container.children);
-            const childrenInfo = [];
-            let currentRowY = null;
-            let currentRowWidth = 0;
-            let rowCount = 1;
-            
-            children.forEach((child, index) => {{
-                const rect = child.getBoundingClientRect();
-                const styles = window.getComputedStyle(child);
-                
-                // Detect row wrapping
-                let wrapped = false;
-                if (currentRowY !== null && Math.abs(rect.y - currentRowY) > 5) {{
-                    wrapped = true;
-                    rowCount++;
-                    currentRowY = rect.y;
-                    currentRowWidth = 0;
-                }}
-                if (currentRowY === null) {{
-                    currentRowY = rect.y;
-                }}
+        let script = r#"
+            function capturePage(selector) {
+                const elements = selector ? 
+                    document.querySelectorAll(selector) : 
+                    document.querySelectorAll('body *');
                 
-                const childWidth = rect.width + 
-                    (parseFloat(styles.marginLeft) || 0) + 
-                    (parseFloat(styles.marginRight) || 0);
+                const data = {
+                    url: window.location.href,
+                    title: document.title,
+                    elements: [],
+                    text_content: [],
+                    structure: []
+                };
                 
-                currentRowWidth += childWidth;
+                Array.from(elements).forEach(el => {
+                    const rect = el.getBoundingClientRect();
+                    const styles = window.getComputedStyle(el);
+                    
+                    data.elements.push({
+                        tag: el.tagName.toLowerCase(),
+                        id: el.id,
+                        classes: Array.from(el.classList),
+                        position: { x: rect.x, y: rect.y },
+                        size: { width: rect.width, height: rect.height },
+                        color: styles.color,
+                        background: styles.backgroundColor,
+                        display: styles.display,
+                        visible: rect.width > 0 && rect.height > 0
+                    });
+                    
+                    if (el.textContent.trim()) {
+                        data.text_content.push(el.textContent.trim());
+                    }
+                    
+                    data.structure.push({
+                        tag: el.tagName.toLowerCase(),
+                        children: el.children.length
+                    });
+                });
                 
-                childrenInfo.push({{
-                    index: index,
-                    tag: child.tagName.toLowerCase(),
-                    width: rect.width,
-                    height: rect.height,
-                    margin: {{
-                        left: parseFloat(styles.marginLeft) || 0,
-                        right: parseFloat(styles.marginRight) || 0,
-                        top: parseFloat(styles.marginTop) || 0,
-                        bottom: parseFloat(styles.marginBottom) || 0
-                    }},
-                    total_width: childWidth,
-                    wrapped: wrapped,
-                    row: rowCount,
-                    position_in_row: wrapped ? 1 : childrenInfo.filter(c => c.row === rowCount).length + 1
-                }});
-            }});
-            
-            // Calculate total width needed
-            const totalChildrenWidth = childrenInfo
-                .filter(c => c.row === 1)
-                .reduce((sum, c) => sum + c.total_width, 0);
-            
-            // Detect gap/spacing
-            let estimatedGap = 0;
-            if (childrenInfo.length > 1) {{
-                const firstChild = container.children[0].getBoundingClientRect();
-                const secondChild = container.children[1].getBoundingClientRect();
-                if (Math.abs(firstChild.y - secondChild.y) < 5) {{
-                    estimatedGap = secondChild.left - firstChild.right;
-                }}
-            }}
+                return data;
+            }
             
-            return {{
-                container: containerInfo,
-                children: {{
-                    total_count: children.length,
-                    rows: rowCount,
-                    per_row: childrenInfo.filter(c => c.row === 1).length,
-                    child_analysis: childrenInfo
-                }},
-                width_calculation: {{
-                    container_available: containerInfo.available_width,
-                    total_children_width: totalChildrenWidth,
-                    estimated_gap: estimatedGap,
-                    overflow: totalChildrenWidth - containerInfo.available_width,
-                    fits: totalChildrenWidth <= containerInfo.available_width
-                }},
-                element_count: children.length + 1
-            }};
-        }})();
-        "#,
-            target_selection, selector
-        )
-    }
+            return capturePage(arguments[0]);
+        "#;
 
-    fn build_anomalies_script(&self, _proximity: u32) -> String {
-        r#"
-        return (function() {{
-            const viewport = {{
-                width: window.innerWidth,
-                height: window.innerHeight,
-                scrollWidth: document.documentElement.scrollWidth,
-                scrollHeight: document.documentElement.scrollHeight,
-                hasHorizontalScroll: document.documentElement.scrollWidth > window.innerWidth,
-                hasVerticalScroll: document.documentElement.scrollHeight > window.innerHeight
-            }};
-            
-            const allElements = Array.from(document.querySelectorAll('*'));
-            const anomalies = {{
-                elements_with_unusual_properties: [],
-                elements_beyond_viewport: [],
-                invisible_elements: [],
-                interaction_conflicts: [],
-                contrast_issues: []
-            }};
-            
-            const stats = {{
-                total_elements: allElements.length,
-                with_display_none: 0,
-                with_visibility_hidden: 0,
-                with_zero_dimensions: 0,
-                outside_viewport: 0,
-                with_negative_z_index: 0
-            }};
-            
-            // Track z-index layers for conflict detection
-            const zIndexLayers = new Map();
-            
-            allElements.forEach(el => {{
-                const rect = el.getBoundingClientRect();
-                const styles = window.getComputedStyle(el);
-                const zIndex = styles.zIndex !== 'auto' ? parseInt(styles.zIndex) : 0;
-                
-                // Track statistics
-                if (styles.display === 'none') stats.with_display_none++;
-                if (styles.visibility === 'hidden') stats.with_visibility_hidden++;
-                if (rect.width === 0 || rect.height === 0) stats.with_zero_dimensions++;
-                if (rect.right < 0 || rect.left > viewport.width || 
-                    rect.bottom < 0 || rect.top > viewport.height) stats.outside_viewport++;
-                if (zIndex < 0) stats.with_negative_z_index++;
-                
-                // Detect unusual properties
-                if (zIndex < 0 || 
-                    (styles.opacity === '0' && styles.position === 'fixed') ||
-                    (styles.position === 'absolute' && (rect.x < -100 || rect.y < -100))) {{
-                    anomalies.elements_with_unusual_properties.push({{
-                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
-                        properties: {{
-                            z_index: zIndex,
-                            position: styles.position,
-                            opacity: parseFloat(styles.opacity),
-                            display: styles.display,
-                            visibility: styles.visibility,
-                            bounds: {{ x: rect.x, y: rect.y, width: rect.width, height: rect.height }}
-                        }}
-                    }});
-                }}
+        let selector_arg = selector.map(|s| json!(s)).unwrap_or(json!(null));
+        let page1_data = self
+            .client
+            .execute(script, vec![selector_arg.clone()])
+            .await?;
+
+        // Capture second page
+        self.goto(url2).await?;
+        self.settle_for_compare(selector, wait_stable_frames)
+            .await?;
+
+        let page2_data = self.client.execute(script, vec![selector_arg]).await?;
+
+        // Compare the data
+        let comparison_script = r#"
+            function comparePagesData(data1, data2, mode) {
+                const results = {
+                    differences: [],
+                    similarities: [],
+                    metrics: {}
+                };
                 
-                // Detect elements beyond viewport
-                if (rect.right > viewport.width && rect.width > 0) {{
-                    anomalies.elements_beyond_viewport.push({{
-                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
-                        bounds: {{ x: rect.x, y: rect.y, width: rect.width, height: rect.height }},
-                        viewport_width: viewport.width,
-                        overflow_amount: rect.right - viewport.width
-                    }});
-                }}
+                if (mode === 'visual' || mode === 'all') {
+                    // Compare visual properties
+                    const maxLen = Math.max(data1.elements.length, data2.elements.length);
+                    for (let i = 0; i < maxLen; i++) {
+                        const el1 = data1.elements[i];
+                        const el2 = data2.elements[i];
+                        
+                        if (!el1 || !el2) {
+                            results.differences.push({
+                                type: 'element_count',
+                                page1: el1 ? 'exists' : 'missing',
+                                page2: el2 ? 'exists' : 'missing'
+                            });
+                        } else {
+                            if (el1.position.x !== el2.position.x || el1.position.y !== el2.position.y) {
+                                results.differences.push({
+                                    type: 'position',
+                                    element: el1.tag + (el1.id ? '#' + el1.id : ''),
+                                    page1: el1.position,
+                                    page2: el2.position
+                                });
+                            }
+                            if (el1.size.width !== el2.size.width || el1.size.height !== el2.size.height) {
+                                results.differences.push({
+                                    type: 'size',
+                                    element: el1.tag + (el1.id ? '#' + el1.id : ''),
+                                    page1: el1.size,
+                                    page2: el2.size
+                                });
+                            }
+                            if (el1.color !== el2.color || el1.background !== el2.background) {
+                                results.differences.push({
+                                    type: 'color',
+                                    element: el1.tag + (el1.id ? '#' + el1.id : ''),
+                                    page1: { color: el1.color, bg: el1.background },
+                                    page2: { color: el2.color, bg: el2.background }
+                                });
+                            }
+                        }
+                    }
+                }
                 
-                // Detect invisible but space-taking elements
-                if (el.textContent && el.textContent.trim().length > 0 && rect.width > 0 && rect.height > 0) {{
-                    const isInvisible = styles.opacity === '0' || 
-                                       styles.visibility === 'hidden' || 
-                                       (styles.color === styles.backgroundColor && styles.backgroundColor !== 'transparent');
+                if (mode === 'content' || mode === 'all') {
+                    // Compare text content
+                    const text1 = new Set(data1.text_content);
+                    const text2 = new Set(data2.text_content);
                     
-                    if (isInvisible) {{
-                        anomalies.invisible_elements.push({{
-                            selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
-                            reason: styles.opacity === '0' ? 'opacity: 0' : 
-                                   styles.visibility === 'hidden' ? 'visibility: hidden' : 
-                                   'text color matches background',
-                            text_length: el.textContent.trim().length,
-                            bounds: {{ x: rect.x, y: rect.y, width: rect.width, height: rect.height }}
-                        }});
-                    }}
+                    const onlyIn1 = [...text1].filter(t => !text2.has(t));
+                    const onlyIn2 = [...text2].filter(t => !text1.has(t));
+                    const inBoth = [...text1].filter(t => text2.has(t));
                     
-                    // Check contrast
-                    if (styles.color && styles.backgroundColor && styles.backgroundColor !== 'transparent') {{
-                        anomalies.contrast_issues.push({{
-                            selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
-                            color: styles.color,
-                            background: styles.backgroundColor,
-                            text_sample: el.textContent.substring(0, 50)
-                        }});
-                    }}
-                }}
+                    if (onlyIn1.length > 0) {
+                        results.differences.push({
+                            type: 'text_removed',
+                            content: onlyIn1
+                        });
+                    }
+                    if (onlyIn2.length > 0) {
+                        results.differences.push({
+                            type: 'text_added',
+                            content: onlyIn2
+                        });
+                    }
+                    
+                    results.similarities.push({
+                        type: 'shared_content',
+                        count: inBoth.length
+                    });
+                }
                 
-                // Track z-index layers for interaction conflict detection
-                if (styles.position !== 'static' && rect.width > 0 && rect.height > 0) {{
-                    if (!zIndexLayers.has(zIndex)) {{
-                        zIndexLayers.set(zIndex, []);
-                    }}
-                    zIndexLayers.get(zIndex).push({{
-                        element: el,
-                        rect: rect,
-                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '')
-                    }});
-                }}
-            }});
-            
-            // Detect interaction conflicts (overlapping clickable elements)
-            const sortedZIndexes = Array.from(zIndexLayers.keys()).sort((a, b) => b - a);
-            for (let i = 0; i < sortedZIndexes.length - 1; i++) {{
-                const higherElements = zIndexLayers.get(sortedZIndexes[i]);
-                for (let j = i + 1; j < sortedZIndexes.length; j++) {{
-                    const lowerElements = zIndexLayers.get(sortedZIndexes[j]);
+                if (mode === 'structure' || mode === 'all') {
+                    // Compare DOM structure
+                    const struct1 = JSON.stringify(data1.structure);
+                    const struct2 = JSON.stringify(data2.structure);
                     
-                    higherElements.forEach(higher => {{
-                        lowerElements.forEach(lower => {{
-                            // Check if they overlap
-                            if (higher.rect.left < lower.rect.right &&
-                                higher.rect.right > lower.rect.left &&
-                                higher.rect.top < lower.rect.bottom &&
-                                higher.rect.bottom > lower.rect.top) {{
-                                
-                                // Check if lower element is interactive
-                                const interactive = ['BUTTON', 'A', 'INPUT', 'SELECT', 'TEXTAREA'];
-                                if (interactive.includes(lower.element.tagName)) {{
-                                    anomalies.interaction_conflicts.push({{
-                                        clickable_element: lower.selector,
-                                        blocking_element: higher.selector,
-                                        blocking_z_index: sortedZIndexes[i],
-                                        clickable_z_index: sortedZIndexes[j]
-                                    }});
-                                }}
-                            }}
-                        }});
-                    }});
-                }}
-            }}
-            
-            return {{
-                viewport: viewport,
-                anomalies: anomalies,
-                statistics: stats,
-                element_count: allElements.length
-            }};
-        }})();
-        "#.to_string()
-    }
-
-    fn build_comprehensive_script(
-        &self,
-        selector: &str,
-        proximity: u32,
-        index: Option<usize>,
-    ) -> String {
-        let target_selection = if let Some(idx) = index {
-            format!(
-                "const elements = document.querySelectorAll('{}');
-            if (elements.length <= {}) return {{ error: 'Element at index {} not found' }};
-            const target = elements[{}];",
-                selector, idx, idx, idx
-            )
-        } else {
-            format!(
-                "const target = document.querySelector('{}');
-            if (!target) return {{ error: 'Element not found: {}' }};",
-                selector, selector
-            )
-        };
-
-        // Combine key aspects from all focused scripts
-        format!(
-            r#"
-        return (function() {{
-            {}
-            
-            const targetRect = target.getBoundingClientRect();
-            const targetStyles = window.getComputedStyle(target);
-            
-            // Basic element info
-            const elementInfo = {{
-                selector: '{}',
-                tag: target.tagName.toLowerCase(),
-                id: target.id || null,
-                classes: Array.from(target.classList),
-                bounds: {{
-                    x: targetRect.x,
-                    y: targetRect.y,
-                    width: targetRect.width,
-                    height: targetRect.height
-                }},
-                styles: {{
-                    display: targetStyles.display,
-                    position: targetStyles.position,
-                    zIndex: targetStyles.zIndex,
-                    opacity: targetStyles.opacity,
-                    overflow: targetStyles.overflow,
-                    visibility: targetStyles.visibility
-                }},
-                box_model: {{
-                    margin: {{
-                        top: parseFloat(targetStyles.marginTop) || 0,
-                        right: parseFloat(targetStyles.marginRight) || 0,
-                        bottom: parseFloat(targetStyles.marginBottom) || 0,
-                        left: parseFloat(targetStyles.marginLeft) || 0
-                    }},
-                    padding: {{
-                        top: parseFloat(targetStyles.paddingTop) || 0,
-                        right: parseFloat(targetStyles.paddingRight) || 0,
-                        bottom: parseFloat(targetStyles.paddingBottom) || 0,
-                        left: parseFloat(targetStyles.paddingLeft) || 0
-                    }},
-                    border: {{
-                        top: parseFloat(targetStyles.borderTopWidth) || 0,
-                        right: parseFloat(targetStyles.borderRightWidth) || 0,
-                        bottom: parseFloat(targetStyles.borderBottomWidth) || 0,
-                        left: parseFloat(targetStyles.borderLeftWidth) || 0
-                    }}
-                }}
-            }};
-            
-            // Get nearby elements
-            const nearbyElements = [];
-            const allElements = Array.from(document.querySelectorAll('*'));
-            
-            allElements.forEach(el => {{
-                if (el === target || target.contains(el) || el.contains(target)) return;
+                    if (struct1 !== struct2) {
+                        results.differences.push({
+                            type: 'structure_change',
+                            page1_elements: data1.structure.length,
+                            page2_elements: data2.structure.length
+                        });
+                    } else {
+                        results.similarities.push({
+                            type: 'identical_structure'
+                        });
+                    }
+                }
                 
-                const rect = el.getBoundingClientRect();
-                const distance = Math.sqrt(
-                    Math.pow(rect.x - targetRect.x, 2) + 
-                    Math.pow(rect.y - targetRect.y, 2)
-                );
+                // Calculate similarity score
+                const totalChecks = results.differences.length + results.similarities.length;
+                results.metrics.similarity_score = totalChecks > 0 ? 
+                    (results.similarities.length / totalChecks) * 100 : 100;
                 
-                if (distance < {}) {{
-                    const styles = window.getComputedStyle(el);
-                    nearbyElements.push({{
-                        selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
-                        distance: Math.round(distance),
-                        bounds: {{
-                            x: rect.x,
-                            y: rect.y,
-                            width: rect.width,
-                            height: rect.height
-                        }},
-                        display: styles.display,
-                        position: styles.position,
-                        zIndex: styles.zIndex
-                    }});
-                }}
-            }});
-            
-            // Check children if container
-            const children = Array.from(target.children);
-            const childrenInfo = children.slice(0, 10).map(child => {{
-                const rect = child.getBoundingClientRect();
-                return {{
-                    tag: child.tagName.toLowerCase(),
-                    bounds: {{
-                        x: rect.x,
-                        y: rect.y,
-                        width: rect.width,
-                        height: rect.height
-                    }}
-                }};
-            }});
-            
-            // Viewport info
-            const viewport = {{
-                width: window.innerWidth,
-                height: window.innerHeight,
-                scrollWidth: document.documentElement.scrollWidth,
-                scrollHeight: document.documentElement.scrollHeight
-            }};
-            
-            return {{
-                element: elementInfo,
-                nearby_elements: nearbyElements.slice(0, 20),
-                children: childrenInfo,
-                children_count: children.length,
-                viewport: viewport,
-                element_count: nearbyElements.length + children.length + 1
-            }};
-        }})();
-        "#,
-            target_selection, selector, proximity
-        )
-    }
+                results.metrics.total_differences = results.differences.length;
+                results.metrics.comparison_mode = mode;
+                
+                return results;
+            }
+            
+            return comparePagesData(arguments[0], arguments[1], arguments[2]);
+        "#;
 
-    pub async fn close(self) -> Result<()> {
-        self.client.close().await?;
-        Ok(())
+        let comparison = self
+            .client
+            .execute(comparison_script, vec![page1_data, page2_data, json!(mode)])
+            .await
+            .context("Failed to compare pages")?;
+
+        Ok(comparison)
     }
 
-    /// Take a screenshot of the current page
-    pub async fn screenshot(&self, path: Option<&str>) -> Result<Vec<u8>> {
-        debug!("Taking screenshot");
+    /// Get session status information including cookies and localStorage
+    pub async fn get_session_status(&self) -> Result<serde_json::Value> {
+        // Get current URL
+        let current_url = self.client.current_url().await?;
 
-        // Get the screenshot as bytes
-        let screenshot_data = self
+        // Get page title
+        let title = self
             .client
-            .screenshot()
+            .title()
             .await
-            .context("Failed to take screenshot")?;
+            .unwrap_or_else(|_| "N/A".to_string());
 
-        // Save to file if path is provided
-        if let Some(file_path) = path {
-            use std::fs::File;
-            use std::io::Write;
+        // Get cookies for current domain
+        let cookies = self
+            .client
+            .get_all_cookies()
+            .await
+            .map(|cookies| cookies.len())
+            .unwrap_or(0);
 
-            let mut file = File::create(file_path)
-                .context(format!("Failed to create screenshot file: {}", file_path))?;
-            file.write_all(&screenshot_data)
-                .context(format!("Failed to write screenshot to: {}", file_path))?;
+        // Get localStorage keys
+        let local_storage_keys = self
+            .client
+            .execute("return Object.keys(localStorage || {})", vec![])
+            .await
+            .map(|v| {
+                if let Some(arr) = v.as_array() {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect::<Vec<_>>()
+                } else {
+                    vec![]
+                }
+            })
+            .unwrap_or_else(|_| vec![]);
 
-            info!("Screenshot saved to: {}", file_path);
-        }
+        // Get sessionStorage keys
+        let session_storage_keys = self
+            .client
+            .execute("return Object.keys(sessionStorage || {})", vec![])
+            .await
+            .map(|v| {
+                if let Some(arr) = v.as_array() {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect::<Vec<_>>()
+                } else {
+                    vec![]
+                }
+            })
+            .unwrap_or_else(|_| vec![]);
 
-        Ok(screenshot_data)
+        // Check common auth indicators
+        let auth_indicators = json!({
+            "has_jwt_in_storage": local_storage_keys.iter().any(|k|
+                k.contains("token") || k.contains("jwt") || k.contains("auth")),
+            "has_user_in_storage": local_storage_keys.iter().any(|k|
+                k.contains("user") || k.contains("profile")),
+            "cookies_present": cookies > 0,
+        });
+
+        Ok(json!({
+            "current_url": current_url.as_str(),
+            "title": title,
+            "cookies_count": cookies,
+            "local_storage_keys": local_storage_keys,
+            "session_storage_keys": session_storage_keys,
+            "auth_indicators": auth_indicators,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }))
     }
 
-    /// Take a screenshot of a specific element
-    pub async fn screenshot_element(&self, selector: &str, path: Option<&str>) -> Result<Vec<u8>> {
-        debug!("Taking screenshot of element: {}", selector);
+    /// Get debug information about the current page state
+    pub async fn get_debug_info(&self) -> Result<serde_json::Value> {
+        // Get current URL
+        let current_url = self.client.current_url().await?;
 
-        // Find the element
-        let element = self
+        // Get page title
+        let title = self
             .client
-            .find(Locator::Css(selector))
+            .title()
             .await
-            .context(format!("Element not found for screenshot: {}", selector))?;
-
-        // Get element screenshot
-        let screenshot_data = element.screenshot().await.context(format!(
-            "Failed to take screenshot of element: {}",
-            selector
-        ))?;
-
-        // Save to file if path is provided
-        if let Some(file_path) = path {
-            use std::fs::File;
-            use std::io::Write;
+            .unwrap_or_else(|_| "N/A".to_string());
 
-            let mut file = File::create(file_path)
-                .context(format!("Failed to create screenshot file: {}", file_path))?;
-            file.write_all(&screenshot_data)
-                .context(format!("Failed to write screenshot to: {}", file_path))?;
+        // Count total elements on page
+        let all_elements = self.client.find_all(Locator::Css("*")).await;
+        let element_count = all_elements.map(|e| e.len()).unwrap_or(0);
 
-            info!("Element screenshot saved to: {}", file_path);
-        }
+        // Get console errors
+        let console_errors = self
+            .console_logs
+            .lock()
+            .await
+            .iter()
+            .filter(|log| log.level == "error")
+            .cloned()
+            .collect::<Vec<_>>();
 
-        Ok(screenshot_data)
-    }
+        // Check if page is still loading
+        let ready_state = self
+            .client
+            .execute("return document.readyState", vec![])
+            .await
+            .map(|v| v.as_str().unwrap_or("unknown").to_string())
+            .unwrap_or_else(|_| "error".to_string());
 
-    /// Inspect elements within iframes
-    pub async fn inspect_iframe(
-        &self,
-        iframe_selector: &str,
-        element_selector: &str,
-    ) -> Result<Vec<ElementInfo>> {
-        debug!("Switching to iframe: {}", iframe_selector);
+        // Get viewport size
+        let viewport = self
+            .client
+            .execute(
+                "return {width: window.innerWidth, height: window.innerHeight}",
+                vec![],
+            )
+            .await
+            .ok();
 
-        // Use JavaScript to switch to iframe and find elements
-        let script = r#"
-            // Find the iframe
-            const iframe = document.querySelector(arguments[0]);
-            if (!iframe) {
-                throw new Error('Iframe not found: ' + arguments[0]);
-            }
-            
-            // Get the iframe's document
-            const iframeDoc = iframe.contentDocument || iframe.contentWindow.document;
-            if (!iframeDoc) {
-                throw new Error('Cannot access iframe content (may be cross-origin)');
-            }
-            
-            // Find elements within the iframe
-            const elements = iframeDoc.querySelectorAll(arguments[1]);
-            const results = [];
-            
-            elements.forEach(el => {
-                const rect = el.getBoundingClientRect();
-                const styles = window.getComputedStyle(el);
-                
-                results.push({
-                    tag: el.tagName.toLowerCase(),
-                    text: el.textContent || '',
-                    position: {
-                        x: rect.x + iframe.getBoundingClientRect().x,
-                        y: rect.y + iframe.getBoundingClientRect().y
-                    },
-                    size: {
-                        width: rect.width,
-                        height: rect.height
-                    },
-                    display: styles.display,
-                    visible: rect.width > 0 && rect.height > 0
-                });
-            });
-            
-            return results;
-        "#;
+        // Check for common elements
+        let has_forms = self
+            .client
+            .find_all(Locator::Css("form"))
+            .await
+            .map(|e| !e.is_empty())
+            .unwrap_or(false);
+        let has_inputs = self
+            .client
+            .find_all(Locator::Css("input"))
+            .await
+            .map(|e| !e.is_empty())
+            .unwrap_or(false);
+        let has_buttons = self
+            .client
+            .find_all(Locator::Css("button"))
+            .await
+            .map(|e| !e.is_empty())
+            .unwrap_or(false);
 
-        let elements_data = self
+        // Get network activity (basic check)
+        let network_status = self
             .client
             .execute(
-                script,
-                vec![json!(iframe_selector), json!(element_selector)],
+                "return performance.getEntriesByType('resource').length",
+                vec![],
             )
             .await
-            .context("Failed to inspect iframe content")?;
-
-        // Convert JavaScript results to ElementInfo
-        let mut results = Vec::new();
-        if let serde_json::Value::Array(elements) = elements_data {
-            let total_matches = elements.len();
-            for (idx, el) in elements.into_iter().enumerate() {
-                if let serde_json::Value::Object(obj) = el {
-                    let position = Position {
-                        x: obj
-                            .get("position")
-                            .and_then(|p| p.get("x"))
-                            .and_then(|v| v.as_f64())
-                            .unwrap_or(0.0),
-                        y: obj
-                            .get("position")
-                            .and_then(|p| p.get("y"))
-                            .and_then(|v| v.as_f64())
-                            .unwrap_or(0.0),
-                        unit: "px".to_string(),
-                    };
+            .ok();
 
-                    let size = Size {
-                        width: obj
-                            .get("size")
-                            .and_then(|s| s.get("width"))
-                            .and_then(|v| v.as_f64())
-                            .unwrap_or(0.0),
-                        height: obj
-                            .get("size")
-                            .and_then(|s| s.get("height"))
-                            .and_then(|v| v.as_f64())
-                            .unwrap_or(0.0),
-                        unit: "px".to_string(),
-                    };
+        Ok(json!({
+            "current_url": current_url.as_str(),
+            "title": title,
+            "ready_state": ready_state,
+            "element_count": element_count,
+            "viewport": viewport,
+            "console_errors": console_errors,
+            "has_forms": has_forms,
+            "has_inputs": has_inputs,
+            "has_buttons": has_buttons,
+            "network_requests": network_status,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }))
+    }
 
-                    results.push(ElementInfo {
-                        selector: element_selector.to_string(),
-                        browser: format!("{:?}", self.browser_type),
-                        position,
-                        size,
-                        computed_styles: json!({
-                            "display": obj.get("display").and_then(|v| v.as_str()).unwrap_or(""),
-                            "tag": obj.get("tag").and_then(|v| v.as_str()).unwrap_or(""),
-                        }),
-                        text_content: obj
-                            .get("text")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string()),
-                        children_count: 0,
-                        metadata: Some(ElementMetadata {
-                            total_matches,
-                            returned_index: idx,
-                            warning: Some(format!("Element found in iframe: {}", iframe_selector)),
-                        }),
-                    });
-                }
-            }
+    /// Smart element detection - finds forms, tables, navigation, and interactive elements
+    pub async fn detect_smart_elements(
+        &self,
+        url: &str,
+        context_selector: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        // Navigate if URL provided
+        if !url.is_empty() {
+            self.goto(url).await?;
         }
 
-        if results.is_empty() {
-            return Err(anyhow::anyhow!(
-                "No elements found matching '{}' in iframe '{}'",
-                element_selector,
-                iframe_selector
-            ));
-        }
+        let context_clause = context_selector
+            .map(|s| format!("document.querySelector('{}') || document", s))
+            .unwrap_or_else(|| "document".to_string());
 
-        Ok(results)
+        let script = format!(
+            r#"
+            return (function() {{
+                const context = {};
+                if (!context) return {{ error: 'Context not found' }};
+                
+                // Detect forms and their fields
+                const forms = Array.from(context.querySelectorAll('form')).map(form => {{
+                    const inputs = Array.from(form.querySelectorAll('input, textarea, select'));
+                    return {{
+                        id: form.id || null,
+                        name: form.name || null,
+                        action: form.action || null,
+                        method: form.method || 'GET',
+                        fields: inputs.map(input => ({{
+                            type: input.type || input.tagName.toLowerCase(),
+                            name: input.name || null,
+                            id: input.id || null,
+                            required: input.required || false,
+                            placeholder: input.placeholder || null,
+                            value: input.type === 'password' ? '[hidden]' : (input.value || null),
+                            selector: input.id ? '#' + input.id : 
+                                     input.name ? `[name="${{input.name}}"]` :
+                                     `${{input.tagName.toLowerCase()}}:nth-of-type(${{Array.from(form.querySelectorAll(input.tagName)).indexOf(input) + 1}})`
+                        }})),
+                        submit_buttons: Array.from(form.querySelectorAll('button[type="submit"], input[type="submit"]')).map(btn => ({{
+                            text: btn.innerText || btn.value || null,
+                            selector: btn.id ? '#' + btn.id : 
+                                     `button:contains("${{btn.innerText || btn.value}}")`
+                        }}))
+                    }};
+                }});
+                
+                // Detect tables
+                const tables = Array.from(context.querySelectorAll('table')).map(table => {{
+                    const headers = Array.from(table.querySelectorAll('th')).map(th => th.innerText.trim());
+                    const rows = table.querySelectorAll('tbody tr, tr').length;
+                    return {{
+                        id: table.id || null,
+                        headers: headers,
+                        row_count: rows,
+                        has_thead: !!table.querySelector('thead'),
+                        has_tbody: !!table.querySelector('tbody'),
+                        selector: table.id ? '#' + table.id : 
+                                 `table:nth-of-type(${{Array.from(context.querySelectorAll('table')).indexOf(table) + 1}})`
+                    }};
+                }});
+                
+                // Detect navigation elements
+                const navs = Array.from(context.querySelectorAll('nav, [role="navigation"], .nav, .navbar, .menu')).map(nav => {{
+                    const links = Array.from(nav.querySelectorAll('a'));
+                    return {{
+                        tag: nav.tagName.toLowerCase(),
+                        class: nav.className || null,
+                        id: nav.id || null,
+                        link_count: links.length,
+                        links: links.slice(0, 10).map(a => ({{
+                            text: a.innerText.trim(),
+                            href: a.href,
+                            selector: a.id ? '#' + a.id : `a:contains("${{a.innerText.trim()}}")`
+                        }}))
+                    }};
+                }});
+                
+                // Detect interactive elements
+                const buttons = Array.from(context.querySelectorAll('button:not([type="submit"])')).map(btn => ({{
+                    text: btn.innerText.trim(),
+                    onclick: !!btn.onclick,
+                    disabled: btn.disabled,
+                    selector: btn.id ? '#' + btn.id : 
+                             `button:contains("${{btn.innerText.trim()}}")`
+                }}));
+                
+                // Detect clickable elements (with onclick or cursor:pointer)
+                const clickables = Array.from(context.querySelectorAll('*')).filter(el => {{
+                    const style = window.getComputedStyle(el);
+                    return (el.onclick || style.cursor === 'pointer') && 
+                           !['A', 'BUTTON', 'INPUT'].includes(el.tagName);
+                }}).slice(0, 20).map(el => ({{
+                    tag: el.tagName.toLowerCase(),
+                    text: el.innerText ? el.innerText.substring(0, 50).trim() : null,
+                    id: el.id || null,
+                    class: el.className || null,
+                    selector: el.id ? '#' + el.id : 
+                             el.className ? '.' + el.className.split(' ')[0] :
+                             el.tagName.toLowerCase()
+                }}));
+                
+                // Detect modal/dialog elements
+                const modals = Array.from(context.querySelectorAll('[role="dialog"], .modal, .dialog, .popup')).map(modal => ({{
+                    visible: window.getComputedStyle(modal).display !== 'none',
+                    id: modal.id || null,
+                    class: modal.className || null,
+                    selector: modal.id ? '#' + modal.id : '.' + (modal.className ? modal.className.split(' ')[0] : 'modal')
+                }}));
+                
+                // Detect login/auth forms specifically
+                const authForms = forms.filter(form => {{
+                    const hasPassword = form.fields.some(f => f.type === 'password');
+                    const hasEmail = form.fields.some(f => 
+                        f.type === 'email' || 
+                        (f.name && f.name.toLowerCase().includes('email')) ||
+                        (f.id && f.id.toLowerCase().includes('email'))
+                    );
+                    const hasUsername = form.fields.some(f => 
+                        (f.name && f.name.toLowerCase().includes('user')) ||
+                        (f.id && f.id.toLowerCase().includes('user'))
+                    );
+                    return hasPassword && (hasEmail || hasUsername);
+                }});
+                
+                return {{
+                    forms: forms,
+                    auth_forms: authForms,
+                    tables: tables,
+                    navigation: navs,
+                    buttons: buttons.slice(0, 20),
+                    clickable_elements: clickables,
+                    modals: modals,
+                    summary: {{
+                        form_count: forms.length,
+                        has_auth_form: authForms.length > 0,
+                        table_count: tables.length,
+                        navigation_count: navs.length,
+                        button_count: buttons.length,
+                        modal_count: modals.length,
+                        total_links: context.querySelectorAll('a').length,
+                        total_images: context.querySelectorAll('img').length
+                    }}
+                }};
+            }})();
+            "#,
+            context_clause
+        );
+
+        let result = self.client.execute(&script, vec![]).await?;
+        Ok(result)
     }
 
-    /// Get all iframes on the page
-    #[allow(dead_code)]
-    pub async fn list_iframes(&self) -> Result<Vec<serde_json::Value>> {
-        let script = r#"
-            const iframes = Array.from(document.querySelectorAll('iframe'));
-            return iframes.map((iframe, index) => {
-                const rect = iframe.getBoundingClientRect();
-                return {
-                    index: index,
-                    id: iframe.id || null,
-                    name: iframe.name || null,
-                    src: iframe.src || null,
-                    selector: iframe.id ? `#${iframe.id}` : 
-                             iframe.name ? `iframe[name="${iframe.name}"]` :
-                             `iframe:nth-of-type(${index + 1})`,
-                    width: rect.width,
-                    height: rect.height,
-                    position: {
-                        x: rect.x,
-                        y: rect.y
-                    },
-                    visible: rect.width > 0 && rect.height > 0
-                };
-            });
-        "#;
+    /// Build the accessibility tree (roles, names, states) under an element
+    ///
+    /// Roles and names are computed in the page from ARIA attributes and native
+    /// semantics; generic containers are pruned and their children hoisted.
+    pub async fn accessibility_tree(
+        &self,
+        url: &str,
+        selector: Option<&str>,
+        max_nodes: usize,
+        interactive_only: bool,
+    ) -> Result<serde_json::Value> {
+        // Navigate if URL provided
+        if !url.is_empty() {
+            self.goto(url).await?;
+        }
 
-        let iframes = self
-            .client
-            .execute(script, vec![])
-            .await
-            .context("Failed to list iframes")?;
+        let script = format!(
+            "{}\n{}",
+            SELECTOR_OF_JS,
+            r#"
+            const [rootSelector, maxNodes, interactiveOnly] = arguments;
+            const root = rootSelector ? document.querySelector(rootSelector) : document.body;
+            if (!root) return { error: 'not_found' };
+
+            const INTERACTIVE = new Set(['button', 'link', 'textbox', 'searchbox', 'combobox', 'listbox',
+                'option', 'checkbox', 'radio', 'switch', 'slider', 'spinbutton', 'tab', 'menuitem',
+                'menuitemcheckbox', 'menuitemradio', 'treeitem']);
+            const STRUCTURE = new Set(['heading', 'banner', 'navigation', 'main', 'contentinfo',
+                'complementary', 'region', 'form', 'search', 'dialog', 'alertdialog']);
+            const NAME_FROM_CONTENT = new Set(['button', 'link', 'heading', 'tab', 'menuitem',
+                'menuitemcheckbox', 'menuitemradio', 'option', 'checkbox', 'radio', 'switch',
+                'treeitem', 'tooltip', 'cell', 'columnheader', 'rowheader', 'row', 'legend', 'caption']);
+            const LEAF = new Set(['button', 'link', 'heading', 'tab', 'menuitem', 'menuitemcheckbox',
+                'menuitemradio', 'option', 'checkbox', 'radio', 'switch', 'textbox', 'searchbox',
+                'slider', 'spinbutton', 'img', 'progressbar', 'meter', 'separator']);
+
+            const clean = (text) => (text || '').replace(/\s+/g, ' ').trim().slice(0, 200);
+
+            const hidden = (el) => {
+                if (el.hidden || el.getAttribute('aria-hidden') === 'true' || el.inert) return true;
+                const style = getComputedStyle(el);
+                return style.display === 'none' || style.visibility === 'hidden' || style.visibility === 'collapse';
+            };
 
-        // Convert to Vec<serde_json::Value>
-        if let serde_json::Value::Array(frames) = iframes {
-            Ok(frames)
-        } else {
-            Ok(vec![])
-        }
-    }
+            const landmarkScoped = (el) => !!el.parentElement &&
+                !!el.parentElement.closest('article, aside, main, nav, section');
+
+            const implicitRole = (el) => {
+                const tag = el.tagName.toLowerCase();
+                const type = (el.getAttribute('type') || '').toLowerCase();
+                switch (tag) {
+                    case 'a': case 'area': return el.hasAttribute('href') ? 'link' : 'generic';
+                    case 'button': case 'summary': return 'button';
+                    case 'input':
+                        if (type === 'hidden') return 'none';
+                        if (['button', 'submit', 'reset', 'image'].includes(type)) return 'button';
+                        if (type === 'checkbox') return 'checkbox';
+                        if (type === 'radio') return 'radio';
+                        if (type === 'range') return 'slider';
+                        if (type === 'number') return 'spinbutton';
+                        if (type === 'search') return el.hasAttribute('list') ? 'combobox' : 'searchbox';
+                        return el.hasAttribute('list') ? 'combobox' : 'textbox';
+                    case 'textarea': return 'textbox';
+                    case 'select': return el.multiple || el.size > 1 ? 'listbox' : 'combobox';
+                    case 'option': return 'option';
+                    case 'optgroup': case 'fieldset': case 'details': return 'group';
+                    case 'h1': case 'h2': case 'h3': case 'h4': case 'h5': case 'h6': return 'heading';
+                    case 'ul': case 'ol': case 'menu': return 'list';
+                    case 'li': return 'listitem';
+                    case 'nav': return 'navigation';
+                    case 'main': return 'main';
+                    case 'aside': return 'complementary';
+                    case 'header': return landmarkScoped(el) ? 'generic' : 'banner';
+                    case 'footer': return landmarkScoped(el) ? 'generic' : 'contentinfo';
+                    case 'section': return el.hasAttribute('aria-label') || el.hasAttribute('aria-labelledby') ? 'region' : 'generic';
+                    case 'form': return 'form';
+                    case 'search': return 'search';
+                    case 'article': return 'article';
+                    case 'dialog': return 'dialog';
+                    case 'table': return 'table';
+                    case 'thead': case 'tbody': case 'tfoot': return 'rowgroup';
+                    case 'tr': return 'row';
+                    case 'td': return 'cell';
+                    case 'th': return el.getAttribute('scope') === 'row' ? 'rowheader' : 'columnheader';
+                    case 'caption': return 'caption';
+                    case 'legend': return 'legend';
+                    case 'img': return el.getAttribute('alt') === '' ? 'presentation' : 'img';
+                    case 'svg': return el.querySelector(':scope > title') || el.hasAttribute('aria-label') ? 'img' : 'none';
+                    case 'figure': return 'figure';
+                    case 'hr': return 'separator';
+                    case 'progress': return 'progressbar';
+                    case 'meter': return 'meter';
+                    case 'output': return 'status';
+                    case 'p': return 'paragraph';
+                    case 'blockquote': return 'blockquote';
+                    case 'iframe': return 'iframe';
+                    default: return 'generic';
+                }
+            };
 
-    /// Diagnose layout issues on the page
-    pub async fn diagnose_layout(
-        &self,
-        selector: Option<&str>,
-        check_type: &str,
-    ) -> Result<serde_json::Value> {
-        let script = r#"
-            function diagnoseLayout(selector, checkType) {
-                const results = {
-                    issues: [],
-                    warnings: [],
-                    suggestions: [],
-                    metrics: {}
-                };
-                
-                const elements = selector ? 
-                    Array.from(document.querySelectorAll(selector)) : 
-                    Array.from(document.querySelectorAll('*'));
-                
-                // Check for overflow issues
-                if (checkType === 'overflow' || checkType === 'all') {
-                    // First check if document has horizontal scroll
-                    const docScrollWidth = Math.max(
-                        document.documentElement.scrollWidth || 0,
-                        document.body.scrollWidth || 0,
-                        document.documentElement.offsetWidth || 0,
-                        document.body.offsetWidth || 0
-                    );
-                    
-                    if (docScrollWidth > window.innerWidth) {
-                        results.issues.push({
-                            type: 'horizontal_overflow',
-                            selector: 'body',
-                            width: docScrollWidth,
-                            viewport_width: window.innerWidth,
-                            overflow: docScrollWidth - window.innerWidth
-                        });
-                    }
-                    
-                    elements.forEach(el => {
-                        const rect = el.getBoundingClientRect();
-                        const styles = window.getComputedStyle(el);
-                        
-                        // Check multiple width properties
-                        const elementWidth = Math.max(
-                            el.offsetWidth || 0,
-                            el.scrollWidth || 0,
-                            parseFloat(styles.width) || 0,
-                            rect.width || 0
-                        );
-                        
-                        // If any width measurement exceeds viewport, it's overflow
-                        if (elementWidth > window.innerWidth) {
-                            const selector = el.tagName.toLowerCase() + 
-                                (el.id ? '#' + el.id : '') + 
-                                (el.className && typeof el.className === 'string' ? 
-                                    '.' + el.className.split(' ').filter(c => c).join('.') : '');
-                            
-                            // Avoid duplicates
-                            const existing = results.issues.find(i => i.selector === selector);
-                            if (!existing) {
-                                results.issues.push({
-                                    type: 'horizontal_overflow',
-                                    selector: selector,
-                                    width: elementWidth,
-                                    viewport_width: window.innerWidth,
-                                    overflow: elementWidth - window.innerWidth
-                                });
-                            }
-                        }
-                        
-                        // Check if element is cut off
-                        if (rect.right > window.innerWidth || rect.left < 0) {
-                            results.warnings.push({
-                                type: 'element_cutoff',
-                                selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + (el.className ? '.' + el.className.split(' ').filter(c => c).join('.') : ''),
-                                position: { left: rect.left, right: rect.right }
-                            });
-                        }
-                        
-                        // Check for text overflow
-                        if (el.scrollWidth > el.clientWidth || el.scrollHeight > el.clientHeight) {
-                            results.warnings.push({
-                                type: 'content_overflow',
-                                selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
-                                scrollWidth: el.scrollWidth,
-                                clientWidth: el.clientWidth,
-                                overflow: styles.overflow
-                            });
-                        }
-                    });
+            const roleOf = (el) => {
+                const explicit = (el.getAttribute('role') || '').trim().split(/\s+/)[0];
+                const focusable = el.tabIndex >= 0 && el.hasAttribute('tabindex');
+                // Presentational roles are ignored on focusable elements
+                if (explicit && !((explicit === 'none' || explicit === 'presentation') && focusable)) return explicit;
+                return implicitRole(el);
+            };
+
+            const textOf = (node, depth = 0) => {
+                if (depth > 20) return '';
+                if (node.nodeType === Node.TEXT_NODE) return node.data;
+                if (node.nodeType !== Node.ELEMENT_NODE || hidden(node)) return '';
+                if (node.hasAttribute('aria-label')) return ' ' + node.getAttribute('aria-label') + ' ';
+                const tag = node.tagName.toLowerCase();
+                if (tag === 'img') return ' ' + (node.getAttribute('alt') || '') + ' ';
+                if (tag === 'input' && ['button', 'submit', 'reset'].includes(node.type)) return ' ' + node.value + ' ';
+                if (tag === 'input' || tag === 'textarea') return ' ' + (node.value || '') + ' ';
+                if (tag === 'select') return ' ' + (node.selectedOptions[0] ? node.selectedOptions[0].text : '') + ' ';
+                if (tag === 'script' || tag === 'style' || tag === 'template') return '';
+                const children = node.shadowRoot ? node.shadowRoot.childNodes : node.childNodes;
+                const block = /^(block|flex|grid|list-item|table)/.test(getComputedStyle(node).display);
+                const inner = Array.from(children).map(child => textOf(child, depth + 1)).join('');
+                return block ? ' ' + inner + ' ' : inner;
+            };
+
+            const byIds = (el, attr) => clean((el.getAttribute(attr) || '').split(/\s+/)
+                .map(id => document.getElementById(id)).filter(Boolean)
+                .map(ref => ref.hasAttribute('aria-label') ? ref.getAttribute('aria-label') : textOf(ref))
+                .join(' '));
+
+            const nameOf = (el, role) => {
+                if (el.hasAttribute('aria-labelledby')) {
+                    const name = byIds(el, 'aria-labelledby');
+                    if (name) return { name, source: 'aria-labelledby' };
                 }
-                
-                // Check for spacing issues
-                if (checkType === 'spacing' || checkType === 'all') {
-                    const spacingIssues = [];
-                    elements.forEach(el => {
-                        const styles = window.getComputedStyle(el);
-                        const margin = parseFloat(styles.marginTop) + parseFloat(styles.marginBottom);
-                        const padding = parseFloat(styles.paddingTop) + parseFloat(styles.paddingBottom);
-                        
-                        // Check for excessive spacing
-                        if (margin > 100) {
-                            spacingIssues.push({
-                                type: 'excessive_margin',
-                                selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
-                                margin: margin
-                            });
-                        }
-                        
-                        // Check for margin collapse
-                        const parent = el.parentElement;
-                        if (parent) {
-                            const parentStyles = window.getComputedStyle(parent);
-                            if (parseFloat(styles.marginTop) > 0 && parseFloat(parentStyles.marginBottom) > 0) {
-                                results.warnings.push({
-                                    type: 'potential_margin_collapse',
-                                    element: el.tagName.toLowerCase(),
-                                    parent: parent.tagName.toLowerCase()
-                                });
-                            }
-                        }
-                    });
-                    if (spacingIssues.length > 0) {
-                        results.issues = results.issues.concat(spacingIssues);
-                    }
+                const label = clean(el.getAttribute('aria-label'));
+                if (label) return { name: label, source: 'aria-label' };
+
+                const tag = el.tagName.toLowerCase();
+                const type = (el.getAttribute('type') || '').toLowerCase();
+                if (tag === 'input' && ['button', 'submit', 'reset'].includes(type)) {
+                    const value = clean(el.value) || (type === 'submit' ? 'Submit' : type === 'reset' ? 'Reset' : '');
+                    if (value) return { name: value, source: 'value' };
                 }
-                
-                // Check for alignment issues
-                if (checkType === 'alignment' || checkType === 'all') {
-                    const alignmentMap = new Map();
-                    elements.forEach(el => {
-                        const rect = el.getBoundingClientRect();
-                        const key = Math.round(rect.left);
-                        if (!alignmentMap.has(key)) {
-                            alignmentMap.set(key, []);
-                        }
-                        alignmentMap.get(key).push(el);
-                    });
-                    
-                    // Find misaligned elements
-                    alignmentMap.forEach((els, xPos) => {
-                        if (els.length === 1 && Math.abs(xPos) > 5) {
-                            const el = els[0];
-                            const siblings = Array.from(el.parentElement?.children || []);
-                            if (siblings.length > 1) {
-                                results.warnings.push({
-                                    type: 'misalignment',
-                                    selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
-                                    position: xPos,
-                                    siblings: siblings.length
-                                });
-                            }
-                        }
-                    });
+                if (tag === 'input' && type === 'image' && clean(el.alt)) return { name: clean(el.alt), source: 'alt' };
+                if (el.labels && el.labels.length) {
+                    const name = clean(Array.from(el.labels).map(l => textOf(l)).join(' '));
+                    if (name) return { name, source: 'label' };
                 }
-                
-                // Check for responsiveness issues
-                if (checkType === 'responsiveness' || checkType === 'all') {
-                    results.metrics.viewport = {
-                        width: window.innerWidth,
-                        height: window.innerHeight
-                    };
-                    
-                    // Check for fixed widths that might break on mobile
-                    elements.forEach(el => {
-                        const styles = window.getComputedStyle(el);
-                        const width = styles.width;
-                        
-                        if (width.endsWith('px')) {
-                            const widthValue = parseFloat(width);
-                            if (widthValue > 400 && !styles.maxWidth) {
-                                results.warnings.push({
-                                    type: 'fixed_width_no_max',
-                                    selector: el.tagName.toLowerCase() + (el.id ? '#' + el.id : ''),
-                                    width: widthValue,
-                                    suggestion: 'Consider using max-width or responsive units'
-                                });
-                            }
-                        }
-                    });
-                    
-                    // Check for horizontal scrolling
-                    if (document.documentElement.scrollWidth > window.innerWidth) {
-                        results.issues.push({
-                            type: 'horizontal_scroll',
-                            page_width: document.documentElement.scrollWidth,
-                            viewport_width: window.innerWidth
-                        });
-                    }
+                if (tag === 'img' || tag === 'area') {
+                    const alt = clean(el.getAttribute('alt'));
+                    if (alt) return { name: alt, source: 'alt' };
                 }
-                
-                // Add suggestions based on issues found
-                if (results.issues.some(i => i.type === 'horizontal_overflow')) {
-                    results.suggestions.push('Use max-width: 100% or overflow-x: auto for wide elements');
+                if (tag === 'svg') {
+                    const title = el.querySelector(':scope > title');
+                    if (title && clean(title.textContent)) return { name: clean(title.textContent), source: 'title' };
                 }
-                if (results.warnings.some(w => w.type === 'content_overflow')) {
-                    results.suggestions.push('Consider using overflow: auto or text-overflow: ellipsis');
+                const captions = { fieldset: ':scope > legend', table: ':scope > caption', figure: ':scope > figcaption' };
+                if (captions[tag]) {
+                    const caption = el.querySelector(captions[tag]);
+                    if (caption && clean(textOf(caption))) return { name: clean(textOf(caption)), source: 'contents' };
                 }
-                if (results.issues.some(i => i.type === 'excessive_margin')) {
-                    results.suggestions.push('Review margin values - consider using consistent spacing scale');
+                if (NAME_FROM_CONTENT.has(role)) {
+                    const text = clean(textOf(el));
+                    if (text) return { name: text, source: 'contents' };
                 }
-                
-                results.summary = {
-                    total_issues: results.issues.length,
-                    total_warnings: results.warnings.length,
-                    check_type: checkType,
-                    elements_checked: elements.length
-                };
-                
-                return results;
-            }
-            
-            return diagnoseLayout(arguments[0], arguments[1]);
-        "#;
+                const title = clean(el.getAttribute('title'));
+                if (title) return { name: title, source: 'title' };
+                if (tag === 'input' || tag === 'textarea') {
+                    const placeholder = clean(el.getAttribute('placeholder'));
+                    if (placeholder) return { name: placeholder, source: 'placeholder' };
+                }
+                return { name: '', source: null };
+            };
 
-        let selector_arg = selector.map(|s| json!(s)).unwrap_or(json!(null));
-        let diagnosis = self
-            .client
-            .execute(script, vec![selector_arg, json!(check_type)])
-            .await
-            .context("Failed to diagnose layout")?;
+            const statesOf = (el, role) => {
+                const states = {};
+                const aria = (attr) => el.getAttribute('aria-' + attr);
+                const bool = (attr) => aria(attr) === 'true';
 
-        Ok(diagnosis)
-    }
+                if (aria('expanded') !== null) states.expanded = bool('expanded');
+                else if (el.tagName === 'SUMMARY' && el.parentElement && el.parentElement.tagName === 'DETAILS') {
+                    states.expanded = el.parentElement.open;
+                }
+                if (['checkbox', 'radio', 'switch', 'menuitemcheckbox', 'menuitemradio'].includes(role)) {
+                    if (aria('checked') !== null) states.checked = aria('checked') === 'mixed' ? 'mixed' : bool('checked');
+                    else if ('checked' in el) states.checked = el.indeterminate ? 'mixed' : el.checked;
+                }
+                if (aria('pressed') !== null) states.pressed = aria('pressed') === 'mixed' ? 'mixed' : bool('pressed');
+                if (role === 'option' || role === 'tab' || role === 'row' || role === 'treeitem') {
+                    if (aria('selected') !== null) states.selected = bool('selected');
+                    else if (el.tagName === 'OPTION') states.selected = el.selected;
+                }
+                if ((el.matches && el.matches(':disabled')) || bool('disabled')) states.disabled = true;
+                if (el.required || bool('required')) states.required = true;
+                if (el.readOnly || bool('readonly')) states.readonly = true;
+                if (aria('invalid') && aria('invalid') !== 'false') states.invalid = true;
+                if (bool('busy')) states.busy = true;
+                if (bool('modal') || (el.tagName === 'DIALOG' && el.matches(':modal'))) states.modal = true;
+                if (aria('haspopup') && aria('haspopup') !== 'false') states.haspopup = aria('haspopup');
+                if (aria('current') && aria('current') !== 'false') states.current = aria('current');
+                if (document.activeElement === el) states.focused = true;
+                return states;
+            };
 
-    /// Validate page for accessibility and SEO
-    pub async fn validate_page(&self, check_type: &str) -> Result<serde_json::Value> {
-        let script = r#"
-            function validatePage(checkType) {
-                const results = {
-                    accessibility: [],
-                    seo: [],
-                    performance: [],
-                    score: 100
-                };
-                
-                // Accessibility checks
-                if (checkType === 'accessibility' || checkType === 'all') {
-                    // Check for images without alt text
-                    document.querySelectorAll('img').forEach(img => {
-                        if (!img.alt && !img.getAttribute('aria-label')) {
-                            results.accessibility.push({
-                                type: 'missing_alt_text',
-                                element: 'img',
-                                src: img.src,
-                                severity: 'error'
-                            });
-                            results.score -= 5;
-                        }
-                    });
-                    
-                    // Check for form inputs without labels
-                    document.querySelectorAll('input, select, textarea').forEach(input => {
-                        const id = input.id;
-                        const label = id ? document.querySelector(`label[for="${id}"]`) : null;
-                        const ariaLabel = input.getAttribute('aria-label');
-                        
-                        if (!label && !ariaLabel && input.type !== 'hidden') {
-                            results.accessibility.push({
-                                type: 'missing_label',
-                                element: input.tagName.toLowerCase(),
-                                id: id || 'no-id',
-                                severity: 'error'
-                            });
-                            results.score -= 5;
-                        }
-                    });
-                    
-                    // Check for buttons without accessible text
-                    document.querySelectorAll('button').forEach(button => {
-                        if (!button.textContent.trim() && !button.getAttribute('aria-label')) {
-                            results.accessibility.push({
-                                type: 'button_no_text',
-                                severity: 'error'
-                            });
-                            results.score -= 3;
-                        }
-                    });
-                    
-                    // Check heading hierarchy
-                    const headings = Array.from(document.querySelectorAll('h1, h2, h3, h4, h5, h6'));
-                    let lastLevel = 0;
-                    headings.forEach(h => {
-                        const level = parseInt(h.tagName.substring(1));
-                        if (level - lastLevel > 1) {
-                            results.accessibility.push({
-                                type: 'heading_skip',
-                                from: 'h' + lastLevel,
-                                to: h.tagName.toLowerCase(),
-                                severity: 'warning'
-                            });
-                            results.score -= 2;
-                        }
-                        lastLevel = level;
-                    });
-                    
-                    // Check for ARIA landmarks
-                    const hasMain = document.querySelector('main, [role="main"]');
-                    const hasNav = document.querySelector('nav, [role="navigation"]');
-                    if (!hasMain) {
-                        results.accessibility.push({
-                            type: 'missing_main_landmark',
-                            severity: 'warning'
-                        });
-                        results.score -= 2;
-                    }
+            const valueOf = (el, role) => {
+                if (['slider', 'spinbutton', 'progressbar', 'meter', 'scrollbar'].includes(role)) {
+                    const now = el.getAttribute('aria-valuenow');
+                    if (now !== null) return el.getAttribute('aria-valuetext') || now;
+                    if ('value' in el && el.value !== '') return String(el.value);
+                }
+                if ((role === 'textbox' || role === 'searchbox' || role === 'combobox') && el.type !== 'password') {
+                    if (el.tagName === 'SELECT') return el.selectedOptions[0] ? clean(el.selectedOptions[0].text) : undefined;
+                    if ('value' in el && el.value) return clean(el.value);
+                }
+                return undefined;
+            };
+
+            let count = 0;
+            let truncated = false;
+
+            const childNodesOf = (el) => {
+                if (el.shadowRoot) return Array.from(el.shadowRoot.childNodes);
+                if (el.tagName === 'SLOT') {
+                    const assigned = el.assignedNodes({ flatten: true });
+                    if (assigned.length) return assigned;
                 }
-                
-                // SEO checks
-                if (checkType === 'seo' || checkType === 'all') {
-                    // Check for title tag
-                    const title = document.querySelector('title');
-                    if (!title || !title.textContent.trim()) {
-                        results.seo.push({
-                            type: 'missing_title',
-                            severity: 'error'
-                        });
-                        results.score -= 10;
-                    } else if (title.textContent.length > 60) {
-                        results.seo.push({
-                            type: 'title_too_long',
-                            length: title.textContent.length,
-                            severity: 'warning'
-                        });
-                        results.score -= 2;
-                    }
-                    
-                    // Check for meta description
-                    const metaDesc = document.querySelector('meta[name="description"]');
-                    if (!metaDesc || !metaDesc.content) {
-                        results.seo.push({
-                            type: 'missing_meta_description',
-                            severity: 'error'
-                        });
-                        results.score -= 8;
-                    } else if (metaDesc.content.length > 160) {
-                        results.seo.push({
-                            type: 'meta_description_too_long',
-                            length: metaDesc.content.length,
-                            severity: 'warning'
-                        });
-                        results.score -= 2;
-                    }
-                    
-                    // Check for h1
-                    const h1s = document.querySelectorAll('h1');
-                    if (h1s.length === 0) {
-                        results.seo.push({
-                            type: 'missing_h1',
-                            severity: 'error'
-                        });
-                        results.score -= 5;
-                    } else if (h1s.length > 1) {
-                        results.seo.push({
-                            type: 'multiple_h1',
-                            count: h1s.length,
-                            severity: 'warning'
-                        });
-                        results.score -= 3;
-                    }
-                    
-                    // Check for canonical URL
-                    const canonical = document.querySelector('link[rel="canonical"]');
-                    if (!canonical) {
-                        results.seo.push({
-                            type: 'missing_canonical',
-                            severity: 'warning'
-                        });
-                        results.score -= 2;
-                    }
+                return Array.from(el.childNodes);
+            };
+
+            // Returns a list: the node itself, or its children hoisted when pruned
+            const walk = (node, depth) => {
+                if (truncated || depth > 60) return [];
+                if (node.nodeType === Node.TEXT_NODE) {
+                    const text = clean(node.data);
+                    if (!text || interactiveOnly) return [];
+                    count++;
+                    if (count > maxNodes) { truncated = true; return []; }
+                    return [{ role: 'text', name: text }];
                 }
-                
-                // Performance checks
-                if (checkType === 'performance' || checkType === 'all') {
-                    // Count DOM nodes
-                    const nodeCount = document.querySelectorAll('*').length;
-                    if (nodeCount > 1500) {
-                        results.performance.push({
-                            type: 'excessive_dom_nodes',
-                            count: nodeCount,
-                            severity: nodeCount > 3000 ? 'error' : 'warning'
-                        });
-                        results.score -= nodeCount > 3000 ? 10 : 5;
-                    }
-                    
-                    // Check for large images
-                    document.querySelectorAll('img').forEach(img => {
-                        if (img.naturalWidth > 2000 || img.naturalHeight > 2000) {
-                            results.performance.push({
-                                type: 'large_image',
-                                src: img.src,
-                                dimensions: img.naturalWidth + 'x' + img.naturalHeight,
-                                severity: 'warning'
-                            });
-                            results.score -= 2;
+                if (node.nodeType !== Node.ELEMENT_NODE) return [];
+                const el = node;
+                const tag = el.tagName.toLowerCase();
+                if (['script', 'style', 'template', 'noscript', 'head', 'meta', 'link'].includes(tag)) return [];
+                if (hidden(el)) return [];
+
+                const role = roleOf(el);
+                const focusable = el.hasAttribute('tabindex') && el.tabIndex >= 0;
+                let keep = !['generic', 'none', 'presentation'].includes(role) || focusable;
+                if (interactiveOnly && !INTERACTIVE.has(role) && !STRUCTURE.has(role)) keep = false;
+
+                const children = () => {
+                    if (keep && LEAF.has(role)) return [];
+                    const out = [];
+                    for (const child of childNodesOf(el)) {
+                        for (const item of walk(child, depth + 1)) {
+                            // Text already used as the name of a kept node is redundant
+                            if (keep && item.role === 'text' && NAME_FROM_CONTENT.has(role)) continue;
+                            out.push(item);
                         }
-                    });
-                    
-                    // Check for inline styles
-                    const inlineStyles = document.querySelectorAll('[style]').length;
-                    if (inlineStyles > 20) {
-                        results.performance.push({
-                            type: 'excessive_inline_styles',
-                            count: inlineStyles,
-                            severity: 'warning'
-                        });
-                        results.score -= 3;
                     }
-                }
-                
-                results.score = Math.max(0, results.score);
-                results.summary = {
-                    check_type: checkType,
-                    accessibility_issues: results.accessibility.length,
-                    seo_issues: results.seo.length,
-                    performance_issues: results.performance.length,
-                    score: results.score
+                    return out;
                 };
-                
-                return results;
-            }
-            
-            return validatePage(arguments[0]);
-        "#;
 
-        let validation = self
+                if (!keep) return children();
+
+                count++;
+                if (count > maxNodes) { truncated = true; return []; }
+
+                const item = { role };
+                const { name, source } = nameOf(el, role);
+                if (name) item.name = name;
+                const description = el.hasAttribute('aria-describedby') ? byIds(el, 'aria-describedby')
+                    : (source !== 'title' ? clean(el.getAttribute('title')) : '');
+                if (description) item.description = description;
+                const level = role === 'heading'
+                    ? Number(el.getAttribute('aria-level')) || Number((tag.match(/^h([1-6])$/) || [])[1]) || 2
+                    : Number(el.getAttribute('aria-level')) || undefined;
+                if (level) item.level = level;
+                const value = valueOf(el, role);
+                if (value !== undefined && value !== '') item.value = value;
+                const states = statesOf(el, role);
+                if (focusable && (role === 'generic' || role === 'none' || role === 'presentation')) states.focusable = true;
+                if (Object.keys(states).length) item.states = states;
+                item.selector = selectorOf(el);
+
+                const kids = children();
+                if (kids.length) item.children = kids;
+                return [item];
+            };
+
+            const tree = walk(root, 0);
+            return {
+                url: location.href,
+                title: document.title,
+                root: rootSelector || 'body',
+                node_count: Math.min(count, maxNodes),
+                truncated,
+                tree,
+            };
+        "#
+        );
+
+        let result = self
             .client
-            .execute(script, vec![json!(check_type)])
+            .execute(
+                &script,
+                vec![json!(selector), json!(max_nodes), json!(interactive_only)],
+            )
             .await
-            .context("Failed to validate page")?;
+            .context("Failed to compute accessibility tree")?;
 
-        Ok(validation)
+        if result["error"] == "not_found" {
+            anyhow::bail!("Element not found: {}", selector.unwrap_or("body"));
+        }
+        Ok(result)
     }
 
-    /// Compare two pages or states
-    pub async fn compare_pages(
-        &self,
-        url1: &str,
-        url2: &str,
-        mode: &str,
-        selector: Option<&str>,
-    ) -> Result<serde_json::Value> {
-        // Capture first page
-        self.goto(url1).await?;
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    /// Walk the page with Tab key presses, recording focus order and indicators,
+    /// focus traps, and clickable elements the keyboard never reaches
+    pub async fn keyboard_audit(&self, url: &str, max_tabs: usize) -> Result<serde_json::Value> {
+        use fantoccini::actions::{InputSource, KeyAction, KeyActions};
+        use fantoccini::key::Key;
 
-        let script = r#"
-            function capturePage(selector) {
-                const elements = selector ? 
-                    document.querySelectorAll(selector) : 
-                    document.querySelectorAll('body *');
-                
-                const data = {
-                    url: window.location.href,
-                    title: document.title,
-                    elements: [],
-                    text_content: [],
-                    structure: []
-                };
-                
-                Array.from(elements).forEach(el => {
-                    const rect = el.getBoundingClientRect();
-                    const styles = window.getComputedStyle(el);
-                    
-                    data.elements.push({
-                        tag: el.tagName.toLowerCase(),
-                        id: el.id,
-                        classes: Array.from(el.classList),
-                        position: { x: rect.x, y: rect.y },
-                        size: { width: rect.width, height: rect.height },
-                        color: styles.color,
-                        background: styles.backgroundColor,
-                        display: styles.display,
-                        visible: rect.width > 0 && rect.height > 0
-                    });
-                    
-                    if (el.textContent.trim()) {
-                        data.text_content.push(el.textContent.trim());
-                    }
-                    
-                    data.structure.push({
-                        tag: el.tagName.toLowerCase(),
-                        children: el.children.length
-                    });
+        if !url.is_empty() {
+            self.goto(url).await?;
+        }
+
+        // Start from the top of the document with nothing focused, and record
+        // every focusable element's unfocused styles as the baseline
+        let setup_script = r#"
+            const PROPS = ['outlineStyle', 'outlineWidth', 'outlineColor', 'outlineOffset',
+                'boxShadow', 'borderTopColor', 'borderBottomColor', 'borderTopWidth',
+                'backgroundColor', 'color', 'textDecorationLine'];
+            const audit = {
+                props: PROPS,
+                baseline: new WeakMap(),
+                elements: [],
+            };
+            window.__webprobeKeyboardAudit = audit;
+
+            const collect = (root) => {
+                root.querySelectorAll('*').forEach(el => {
+                    if (el.shadowRoot) collect(el.shadowRoot);
+                    if (el.tabIndex < 0 && !el.isContentEditable) return;
+                    const style = getComputedStyle(el);
+                    audit.baseline.set(el, PROPS.map(p => style[p]));
                 });
-                
-                return data;
+            };
+
+            if (document.activeElement && document.activeElement !== document.body) {
+                document.activeElement.blur();
             }
-            
-            return capturePage(arguments[0]);
+            collect(document);
+            window.scrollTo(0, 0);
+            return true;
         "#;
 
-        let selector_arg = selector.map(|s| json!(s)).unwrap_or(json!(null));
-        let page1_data = self
+        // Describe whatever the last Tab press focused
+        let step_script = format!(
+            "{}\n{}",
+            SELECTOR_OF_JS,
+            r#"
+            const audit = window.__webprobeKeyboardAudit;
+            let el = document.activeElement;
+            while (el && el.shadowRoot && el.shadowRoot.activeElement) {
+                el = el.shadowRoot.activeElement;
+            }
+            if (!el || el === document.body || el === document.documentElement) {
+                return { id: null };
+            }
+
+            let id = audit.elements.indexOf(el);
+            if (id < 0) {
+                id = audit.elements.length;
+                audit.elements.push(el);
+            }
+
+            // Compare focused styles with the unfocused baseline
+            const style = getComputedStyle(el);
+            const baseline = audit.baseline.get(el);
+            const changed = [];
+            audit.props.forEach((prop, i) => {
+                if (baseline && baseline[i] !== style[prop]) changed.push(prop);
+            });
+            const outlineVisible = style.outlineStyle !== 'none' && parseFloat(style.outlineWidth) > 0;
+            const indicatorChanges = changed.filter(p => !p.startsWith('outline') || outlineVisible);
+            const indicator = baseline ? indicatorChanges.length > 0 : outlineVisible;
+
+            const rect = el.getBoundingClientRect();
+            const name = (el.getAttribute('aria-label') || el.innerText || el.value || el.title || '')
+                .replace(/\s+/g, ' ').trim().slice(0, 80);
+            const modal = el.closest('[aria-modal="true"], dialog[open]');
+
+            return {
+                id,
+                selector: selectorOf(el),
+                tag: el.tagName.toLowerCase(),
+                role: el.getAttribute('role'),
+                name,
+                tabindex: el.getAttribute('tabindex') !== null ? el.tabIndex : null,
+                rect: {
+                    x: Math.round(rect.left + window.scrollX),
+                    y: Math.round(rect.top + window.scrollY),
+                    width: Math.round(rect.width),
+                    height: Math.round(rect.height)
+                },
+                visible: rect.width > 0 && rect.height > 0,
+                focus_visible: el.matches(':focus-visible'),
+                indicator,
+                indicator_changes: indicatorChanges,
+                in_modal: !!modal,
+            };
+        "#
+        );
+
+        // Clickable-looking elements that no Tab press reached
+        let finish_script = format!(
+            "{}\n{}",
+            SELECTOR_OF_JS,
+            r#"
+            const audit = window.__webprobeKeyboardAudit;
+            const reached = audit.elements;
+            const CLICKABLE = 'a[href], button, input:not([type="hidden"]), select, textarea, summary, ' +
+                '[onclick], [role="button"], [role="link"], [role="checkbox"], [role="radio"], ' +
+                '[role="switch"], [role="tab"], [role="menuitem"], [role="option"], [contenteditable="true"]';
+
+            const candidates = new Set(document.querySelectorAll(CLICKABLE));
+            // cursor:pointer catches handlers attached with addEventListener
+            document.querySelectorAll('body *').forEach(el => {
+                if (getComputedStyle(el).cursor === 'pointer' &&
+                    !(el.parentElement && getComputedStyle(el.parentElement).cursor === 'pointer')) {
+                    candidates.add(el);
+                }
+            });
+
+            const unreachable = [];
+            candidates.forEach(el => {
+                if (el.disabled || el.closest('[inert], [aria-hidden="true"]')) return;
+                const style = getComputedStyle(el);
+                const rect = el.getBoundingClientRect();
+                if (style.visibility !== 'visible' || rect.width === 0 || rect.height === 0) return;
+                // A focusable ancestor or descendant covers it (e.g. an icon inside a button)
+                if (reached.some(r => r === el || r.contains(el) || el.contains(r))) return;
+
+                let reason;
+                if (el.getAttribute('tabindex') !== null && el.tabIndex < 0) {
+                    reason = 'tabindex="' + el.getAttribute('tabindex') + '" removes it from the tab order';
+                } else if (el.tabIndex < 0) {
+                    reason = '<' + el.tagName.toLowerCase() + '> is not focusable; add tabindex="0" or use a button/link';
+                } else {
+                    reason = 'focusable but never reached by Tab';
+                }
+
+                unreachable.push({
+                    selector: selectorOf(el),
+                    tag: el.tagName.toLowerCase(),
+                    role: el.getAttribute('role'),
+                    text: (el.innerText || el.getAttribute('aria-label') || '').replace(/\s+/g, ' ').trim().slice(0, 80),
+                    reason,
+                });
+            });
+
+            if (document.activeElement) document.activeElement.blur();
+            delete window.__webprobeKeyboardAudit;
+            return unreachable;
+        "#
+        );
+
+        self.client
+            .execute(setup_script, vec![])
+            .await
+            .context("Failed to prepare keyboard audit")?;
+
+        let tab = char::from(Key::Tab);
+        let mut stops: Vec<serde_json::Value> = Vec::new();
+        let mut traps = Vec::new();
+        let mut completed = false;
+
+        for _ in 0..max_tabs {
+            let press = KeyActions::new("keyboard".to_string())
+                .then(KeyAction::Down { value: tab })
+                .then(KeyAction::Up { value: tab });
+            self.client
+                .perform_actions(press)
+                .await
+                .context("Failed to press Tab")?;
+
+            let stop = self
+                .client
+                .execute(&step_script, vec![])
+                .await
+                .context("Failed to read focused element")?;
+
+            let Some(id) = stop["id"].as_u64() else {
+                // Focus left the document: the whole page has been walked
+                if !stops.is_empty() {
+                    completed = true;
+                    break;
+                }
+                continue;
+            };
+
+            // Revisiting an element means focus either wrapped around to the
+            // start (complete) or got caught in a cycle that excludes it (trap)
+            if let Some(first) = stops.iter().position(|s| s["id"] == id) {
+                // Tabbing inside a frame keeps the <iframe> itself focused
+                if first == stops.len() - 1 && stop["tag"] == "iframe" {
+                    continue;
+                }
+                if first == 0 {
+                    completed = true;
+                } else {
+                    let cycle: Vec<&serde_json::Value> = stops[first..].iter().collect();
+                    let intentional = cycle.iter().all(|s| s["in_modal"] == true);
+                    traps.push(json!({
+                        "elements": cycle.iter().map(|s| &s["selector"]).collect::<Vec<_>>(),
+                        "entered_at": first,
+                        "modal": intentional,
+                        "severity": if intentional { "info" } else { "error" },
+                    }));
+                }
+                break;
+            }
+
+            stops.push(stop);
+        }
+
+        let _ = self.client.release_actions().await;
+
+        let unreachable = self
             .client
-            .execute(script, vec![selector_arg.clone()])
-            .await?;
+            .execute(&finish_script, vec![])
+            .await
+            .context("Failed to find unreachable elements")?;
 
-        // Capture second page
-        self.goto(url2).await?;
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        let without_indicator = stops.iter().filter(|s| s["indicator"] == false).count();
+        let positive_tabindex = stops
+            .iter()
+            .filter(|s| s["tabindex"].as_i64().is_some_and(|t| t > 0))
+            .count();
+
+        let stops: Vec<serde_json::Value> = stops
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut stop)| {
+                if let Some(obj) = stop.as_object_mut() {
+                    obj.remove("id");
+                    obj.insert("order".to_string(), json!(i + 1));
+                }
+                stop
+            })
+            .collect();
 
-        let page2_data = self.client.execute(script, vec![selector_arg]).await?;
+        Ok(json!({
+            "url": self.get_current_url().await.unwrap_or_default(),
+            "completed": completed,
+            "summary": {
+                "tab_stops": stops.len(),
+                "without_focus_indicator": without_indicator,
+                "positive_tabindex": positive_tabindex,
+                "traps": traps.len(),
+                "unreachable": unreachable.as_array().map_or(0, |u| u.len()),
+            },
+            "focus_order": stops,
+            "traps": traps,
+            "unreachable": unreachable,
+        }))
+    }
 
-        // Compare the data
-        let comparison_script = r#"
-            function comparePagesData(data1, data2, mode) {
-                const results = {
-                    differences: [],
-                    similarities: [],
-                    metrics: {}
-                };
-                
-                if (mode === 'visual' || mode === 'all') {
-                    // Compare visual properties
-                    const maxLen = Math.max(data1.elements.length, data2.elements.length);
-                    for (let i = 0; i < maxLen; i++) {
-                        const el1 = data1.elements[i];
-                        const el2 = data2.elements[i];
-                        
-                        if (!el1 || !el2) {
-                            results.differences.push({
-                                type: 'element_count',
-                                page1: el1 ? 'exists' : 'missing',
-                                page2: el2 ? 'exists' : 'missing'
-                            });
-                        } else {
-                            if (el1.position.x !== el2.position.x || el1.position.y !== el2.position.y) {
-                                results.differences.push({
-                                    type: 'position',
-                                    element: el1.tag + (el1.id ? '#' + el1.id : ''),
-                                    page1: el1.position,
-                                    page2: el2.position
-                                });
-                            }
-                            if (el1.size.width !== el2.size.width || el1.size.height !== el2.size.height) {
-                                results.differences.push({
-                                    type: 'size',
-                                    element: el1.tag + (el1.id ? '#' + el1.id : ''),
-                                    page1: el1.size,
-                                    page2: el2.size
-                                });
-                            }
-                            if (el1.color !== el2.color || el1.background !== el2.background) {
-                                results.differences.push({
-                                    type: 'color',
-                                    element: el1.tag + (el1.id ? '#' + el1.id : ''),
-                                    page1: { color: el1.color, bg: el1.background },
-                                    page2: { color: el2.color, bg: el2.background }
-                                });
-                            }
+    /// Collect the declarations that could set `property` on an element (for `why`)
+    ///
+    /// Same-origin stylesheets are read from the page; in Chrome, DevTools
+    /// adds user-agent rules and rules from stylesheets the page can't read.
+    pub async fn matched_styles(
+        &self,
+        url: &str,
+        selector: &str,
+        index: Option<usize>,
+        property: &str,
+    ) -> Result<crate::cascade::MatchedStyles> {
+        if !url.is_empty() {
+            self.goto(url).await?;
+        }
+
+        let script = format!(
+            "{}\n{}",
+            SELECTOR_OF_JS,
+            r#"
+            const [selector, index, property] = arguments;
+            const matches = document.querySelectorAll(selector);
+            const el = matches[index];
+            if (!el) return { error: 'not_found', count: matches.length };
+
+            // Longhands a property expands to (a longhand or custom property maps to itself)
+            const expansions = {};
+            const longhandsOf = (name) => {
+                if (!expansions[name]) {
+                    const probe = document.createElement('div').style;
+                    probe.setProperty(name, 'inherit');
+                    expansions[name] = Array.from(probe);
+                }
+                return expansions[name];
+            };
+            const longhands = longhandsOf(property);
+            if (!longhands.length) return { error: 'unknown_property' };
+
+            // Every style rule whose conditions currently apply, in source order
+            const rules = [];
+            const layers = [];
+            const inaccessible = [];
+            const layerOrder = (name) => {
+                if (!layers.includes(name)) layers.push(name);
+                return layers.indexOf(name);
+            };
+            const styleElements = Array.from(document.querySelectorAll('style'));
+            const sheetName = (sheet) => sheet.href
+                || (sheet.ownerNode && sheet.ownerNode.tagName === 'STYLE'
+                    ? '<style> #' + (styleElements.indexOf(sheet.ownerNode) + 1)
+                    : 'constructed stylesheet');
+            const walk = (list, sheet, conditions, layer) => {
+                for (const rule of list) {
+                    if (rule instanceof CSSStyleRule) {
+                        rules.push({ rule, sheet: sheetName(sheet), conditions, layer });
+                    } else if (rule instanceof CSSMediaRule) {
+                        if (matchMedia(rule.media.mediaText).matches) {
+                            walk(rule.cssRules, sheet, [...conditions, '@media ' + rule.media.mediaText], layer);
+                        }
+                    } else if (window.CSSSupportsRule && rule instanceof CSSSupportsRule) {
+                        if (CSS.supports(rule.conditionText)) {
+                            walk(rule.cssRules, sheet, [...conditions, '@supports ' + rule.conditionText], layer);
+                        }
+                    } else if (window.CSSContainerRule && rule instanceof CSSContainerRule) {
+                        // Container conditions can't be evaluated from script
+                        walk(rule.cssRules, sheet, [...conditions, '@container ' + rule.conditionText + ' (not evaluated)'], layer);
+                    } else if (window.CSSLayerBlockRule && rule instanceof CSSLayerBlockRule) {
+                        const name = (layer ? layer + '.' : '') + (rule.name || '<anonymous ' + rules.length + '>');
+                        layerOrder(name);
+                        walk(rule.cssRules, sheet, conditions, name);
+                    } else if (window.CSSLayerStatementRule && rule instanceof CSSLayerStatementRule) {
+                        rule.nameList.forEach(name => layerOrder((layer ? layer + '.' : '') + name));
+                    } else if (rule instanceof CSSImportRule && rule.styleSheet) {
+                        const media = rule.media.mediaText;
+                        if (!media || matchMedia(media).matches) {
+                            const imported = rule.layerName != null
+                                ? (layer ? layer + '.' : '') + (rule.layerName || '<anonymous import>')
+                                : layer;
+                            if (imported !== layer) layerOrder(imported);
+                            readSheet(rule.styleSheet, media ? [...conditions, '@media ' + media] : conditions, imported);
                         }
                     }
                 }
-                
-                if (mode === 'content' || mode === 'all') {
-                    // Compare text content
-                    const text1 = new Set(data1.text_content);
-                    const text2 = new Set(data2.text_content);
-                    
-                    const onlyIn1 = [...text1].filter(t => !text2.has(t));
-                    const onlyIn2 = [...text2].filter(t => !text1.has(t));
-                    const inBoth = [...text1].filter(t => text2.has(t));
-                    
-                    if (onlyIn1.length > 0) {
-                        results.differences.push({
-                            type: 'text_removed',
-                            content: onlyIn1
-                        });
-                    }
-                    if (onlyIn2.length > 0) {
-                        results.differences.push({
-                            type: 'text_added',
-                            content: onlyIn2
-                        });
+            };
+            const readSheet = (sheet, conditions, layer) => {
+                if (sheet.disabled) return;
+                const media = sheet.media && sheet.media.mediaText;
+                if (media && !matchMedia(media).matches) return;
+                let list;
+                try {
+                    list = sheet.cssRules;
+                } catch (e) {
+                    inaccessible.push(sheet.href);
+                    return;
+                }
+                walk(list, sheet, conditions, layer);
+            };
+            Array.from(document.styleSheets).forEach(sheet => readSheet(sheet, [], null));
+            (document.adoptedStyleSheets || []).forEach(sheet => readSheet(sheet, [], null));
+
+            const splitSelectors = (text) => {
+                const parts = [];
+                let depth = 0, current = '';
+                for (const c of text) {
+                    if (c === '(' || c === '[') depth++;
+                    if (c === ')' || c === ']') depth--;
+                    if (c === ',' && depth === 0) {
+                        parts.push(current.trim());
+                        current = '';
+                    } else {
+                        current += c;
                     }
-                    
-                    results.similarities.push({
-                        type: 'shared_content',
-                        count: inBoth.length
-                    });
                 }
-                
-                if (mode === 'structure' || mode === 'all') {
-                    // Compare DOM structure
-                    const struct1 = JSON.stringify(data1.structure);
-                    const struct2 = JSON.stringify(data2.structure);
-                    
-                    if (struct1 !== struct2) {
-                        results.differences.push({
-                            type: 'structure_change',
-                            page1_elements: data1.structure.length,
-                            page2_elements: data2.structure.length
+                if (current.trim()) parts.push(current.trim());
+                return parts;
+            };
+            const matching = (node, text) => {
+                try {
+                    return node.matches(text);
+                } catch (e) {
+                    return false;
+                }
+            };
+
+            // The element and its ancestors, with the rules matching each
+            const chain = [];
+            for (let node = el; node && node.nodeType === 1; node = node.parentElement) {
+                chain.push({
+                    node,
+                    selector: selectorOf(node),
+                    rules: rules.filter(r => matching(node, r.rule.selectorText))
+                });
+            }
+
+            // A value set for `prop` in a declaration block, including a
+            // longhand set through a shorthand that uses var()
+            const declared = (style, prop) => {
+                const value = style.getPropertyValue(prop);
+                if (value) {
+                    return { value: value.trim(), important: style.getPropertyPriority(prop) === 'important' };
+                }
+                if (!Array.prototype.includes.call(style, prop)) return null;
+                for (const part of style.cssText.split(';')) {
+                    const colon = part.indexOf(':');
+                    if (colon < 0) continue;
+                    const name = part.slice(0, colon).trim();
+                    if (name === prop || !longhandsOf(name).includes(prop)) continue;
+                    let text = part.slice(colon + 1).trim();
+                    const important = /!\s*important$/i.test(text);
+                    text = text.replace(/!\s*important$/i, '').trim();
+                    return { value: text, important, shorthand: name };
+                }
+                return null;
+            };
+
+            // Collect the requested longhands, then every custom property their values use
+            const wanted = [...longhands];
+            const declarations = [];
+            const refer = (value) => {
+                for (const match of value.matchAll(/var\(\s*(--[\w-]+)/g)) {
+                    if (!wanted.includes(match[1]) && wanted.length < 100) wanted.push(match[1]);
+                }
+            };
+            for (let i = 0; i < wanted.length; i++) {
+                const prop = wanted[i];
+                chain.forEach((entry, distance) => {
+                    entry.rules.forEach(r => {
+                        const found = declared(r.rule.style, prop);
+                        if (!found) return;
+                        refer(found.value);
+                        declarations.push({
+                            property: prop,
+                            value: found.value,
+                            important: found.important,
+                            origin: 'author',
+                            selector: r.rule.selectorText,
+                            matched_selectors: splitSelectors(r.rule.selectorText)
+                                .filter(s => matching(entry.node, s)),
+                            stylesheet: r.sheet,
+                            conditions: r.conditions,
+                            layer: r.layer,
+                            layer_order: r.layer ? layers.indexOf(r.layer) : null,
+                            shorthand: found.shorthand || null,
+                            order: rules.indexOf(r),
+                            distance,
+                            element: entry.selector
                         });
-                    } else {
-                        results.similarities.push({
-                            type: 'identical_structure'
+                    });
+                    const inline = entry.node.style && declared(entry.node.style, prop);
+                    if (inline) {
+                        refer(inline.value);
+                        declarations.push({
+                            property: prop,
+                            value: inline.value,
+                            important: inline.important,
+                            origin: 'inline',
+                            selector: null,
+                            stylesheet: null,
+                            shorthand: inline.shorthand || null,
+                            order: 0,
+                            distance,
+                            element: entry.selector
                         });
                     }
-                }
-                
-                // Calculate similarity score
-                const totalChecks = results.differences.length + results.similarities.length;
-                results.metrics.similarity_score = totalChecks > 0 ? 
-                    (results.similarities.length / totalChecks) * 100 : 100;
-                
-                results.metrics.total_differences = results.differences.length;
-                results.metrics.comparison_mode = mode;
-                
-                return results;
+                });
             }
-            
-            return comparePagesData(arguments[0], arguments[1], arguments[2]);
-        "#;
 
-        let comparison = self
+            const style = getComputedStyle(el);
+            const computed = {};
+            wanted.forEach(prop => { computed[prop] = style.getPropertyValue(prop).trim(); });
+
+            return {
+                url: location.href,
+                element: selectorOf(el),
+                chain: chain.map(entry => entry.selector),
+                longhands,
+                computed,
+                declarations,
+                inaccessible_sheets: inaccessible.filter(Boolean)
+            };
+        "#
+        );
+
+        let result = self
             .client
-            .execute(comparison_script, vec![page1_data, page2_data, json!(mode)])
+            .execute(
+                &script,
+                vec![json!(selector), json!(index.unwrap_or(0)), json!(property)],
+            )
             .await
-            .context("Failed to compare pages")?;
+            .context("Failed to read stylesheets")?;
 
-        Ok(comparison)
+        match result["error"].as_str() {
+            Some("not_found") => anyhow::bail!("No elements found matching selector: {}", selector),
+            Some("unknown_property") => anyhow::bail!("Unknown CSS property: {}", property),
+            _ => {}
+        }
+        let mut styles: crate::cascade::MatchedStyles =
+            serde_json::from_value(result).context("Unexpected stylesheet data from page")?;
+
+        if matches!(self.browser_type, BrowserType::Chrome) {
+            match self
+                .devtools_matched_styles(selector, index.unwrap_or(0))
+                .await
+            {
+                Ok(matched) => {
+                    let fallback = match styles.inaccessible_sheets.as_slice() {
+                        [only] => Some(only.clone()),
+                        _ => None,
+                    };
+                    let declarations = crate::cascade::from_devtools(
+                        &matched,
+                        &styles.wanted(),
+                        &styles.chain,
+                        fallback.as_deref(),
+                    );
+                    styles.merge_devtools(declarations);
+                }
+                Err(e) => debug!("DevTools matched styles unavailable: {}", e),
+            }
+        }
+
+        Ok(styles)
     }
 
-    /// Get session status information including cookies and localStorage
-    pub async fn get_session_status(&self) -> Result<serde_json::Value> {
-        // Get current URL
-        let current_url = self.client.current_url().await?;
+    /// Fill in where each author declaration sits in its stylesheet and,
+    /// when the sheet has a source map, the file it was written in
+    ///
+    /// Best-effort: sheets that can't be fetched keep no location.
+    pub async fn map_to_sources(&self, styles: &mut crate::cascade::MatchedStyles) {
+        use crate::sourcemap::{self, SourceMap};
 
-        // Get page title
-        let title = self
-            .client
-            .title()
-            .await
-            .unwrap_or_else(|_| "N/A".to_string());
+        let page_url = styles.url.clone();
+        let mut sheets: std::collections::HashMap<String, Option<(String, Option<SourceMap>)>> =
+            std::collections::HashMap::new();
 
-        // Get cookies for current domain
-        let cookies = self
-            .client
-            .get_all_cookies()
-            .await
-            .map(|cookies| cookies.len())
-            .unwrap_or(0);
+        for declaration in styles.declarations.iter_mut() {
+            if declaration.origin != crate::cascade::Origin::Author {
+                continue;
+            }
+            let (Some(sheet), Some(selector)) = (&declaration.stylesheet, &declaration.selector)
+            else {
+                continue;
+            };
+            if !sheets.contains_key(sheet) {
+                let loaded = match self.load_stylesheet(sheet, &page_url).await {
+                    Ok(loaded) => Some(loaded),
+                    Err(e) => {
+                        debug!("No source for {}: {}", sheet, e);
+                        None
+                    }
+                };
+                sheets.insert(sheet.clone(), loaded);
+            }
+            let Some(Some((css, map))) = sheets.get(sheet) else {
+                continue;
+            };
 
-        // Get localStorage keys
-        let local_storage_keys = self
-            .client
-            .execute("return Object.keys(localStorage || {})", vec![])
-            .await
-            .map(|v| {
-                if let Some(arr) = v.as_array() {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(String::from))
-                        .collect::<Vec<_>>()
-                } else {
-                    vec![]
-                }
-            })
-            .unwrap_or_else(|_| vec![]);
+            let property = declaration
+                .shorthand
+                .as_deref()
+                .unwrap_or(&declaration.property);
+            if let Some((line, column)) = sourcemap::locate_declaration(css, selector, property) {
+                declaration.line = Some(line);
+                declaration.column = Some(column);
+                declaration.original = map.as_ref().and_then(|map| map.lookup(line, column));
+            }
+        }
+    }
 
-        // Get sessionStorage keys
-        let session_storage_keys = self
-            .client
-            .execute("return Object.keys(sessionStorage || {})", vec![])
-            .await
-            .map(|v| {
-                if let Some(arr) = v.as_array() {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(String::from))
-                        .collect::<Vec<_>>()
+    /// Text of a stylesheet named the way `matched_styles` names it, and its
+    /// source map if it has one
+    async fn load_stylesheet(
+        &self,
+        sheet: &str,
+        page_url: &str,
+    ) -> Result<(String, Option<crate::sourcemap::SourceMap>)> {
+        use crate::sourcemap::{self, SourceMap};
+
+        let (css, base) = if let Some(number) = sheet.strip_prefix("<style> #") {
+            let index = number
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .context("Invalid <style> reference")?;
+            let text = self
+                .client
+                .execute(
+                    "const style = document.querySelectorAll('style')[arguments[0]]; return style ? style.textContent : null;",
+                    vec![json!(index)],
+                )
+                .await?;
+            let text = text
+                .as_str()
+                .context("<style> element is gone")?
+                .to_string();
+            (text, url::Url::parse(page_url)?)
+        } else {
+            let url = url::Url::parse(sheet)?;
+            (fetch_text(&url, &url::Url::parse(page_url)?).await?, url)
+        };
+
+        let map = match sourcemap::source_mapping_url(&css) {
+            None => None,
+            Some(reference) => {
+                let parsed = if reference.starts_with("data:") {
+                    sourcemap::decode_data_url(reference)
+                        .and_then(|json| SourceMap::parse(&json, Some(&base)))
                 } else {
-                    vec![]
+                    match base.join(reference) {
+                        Ok(map_url) => match fetch_text(&map_url, &base).await {
+                            Ok(json) => SourceMap::parse(&json, Some(&map_url)),
+                            Err(e) => Err(e),
+                        },
+                        Err(e) => Err(e.into()),
+                    }
+                };
+                match parsed {
+                    Ok(map) => Some(map),
+                    Err(e) => {
+                        debug!("Ignoring source map of {}: {}", sheet, e);
+                        None
+                    }
                 }
-            })
-            .unwrap_or_else(|_| vec![]);
+            }
+        };
+        Ok((css, map))
+    }
 
-        // Check common auth indicators
-        let auth_indicators = json!({
-            "has_jwt_in_storage": local_storage_keys.iter().any(|k|
-                k.contains("token") || k.contains("jwt") || k.contains("auth")),
-            "has_user_in_storage": local_storage_keys.iter().any(|k|
-                k.contains("user") || k.contains("profile")),
-            "cookies_present": cookies > 0,
-        });
+    /// `CSS.getMatchedStylesForNode` for the `index`th match of `selector`
+    async fn devtools_matched_styles(
+        &self,
+        selector: &str,
+        index: usize,
+    ) -> Result<serde_json::Value> {
+        self.cdp("DOM.enable", json!({})).await?;
+        self.cdp("CSS.enable", json!({})).await?;
+
+        let document = self.cdp("DOM.getDocument", json!({ "depth": 0 })).await?;
+        let nodes = self
+            .cdp(
+                "DOM.querySelectorAll",
+                json!({ "nodeId": document["root"]["nodeId"], "selector": selector }),
+            )
+            .await?;
+        let node_id = nodes["nodeIds"]
+            .get(index)
+            .cloned()
+            .context("Element not found through DevTools")?;
 
-        Ok(json!({
-            "current_url": current_url.as_str(),
-            "title": title,
-            "cookies_count": cookies,
-            "local_storage_keys": local_storage_keys,
-            "session_storage_keys": session_storage_keys,
-            "auth_indicators": auth_indicators,
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        }))
+        self.cdp("CSS.getMatchedStylesForNode", json!({ "nodeId": node_id }))
+            .await
     }
 
-    /// Get debug information about the current page state
-    pub async fn get_debug_info(&self) -> Result<serde_json::Value> {
-        // Get current URL
-        let current_url = self.client.current_url().await?;
+    /// Audit every `<img>`: natural vs rendered size (times devicePixelRatio),
+    /// srcset/sizes, lazy loading above the fold, distortion and broken images
+    ///
+    /// `tolerance` is how many times more pixels than needed an image may
+    /// have before it counts as oversized.
+    pub async fn image_audit(&self, url: &str, tolerance: f64) -> Result<serde_json::Value> {
+        if !url.is_empty() {
+            self.goto(url).await?;
+        }
 
-        // Get page title
-        let title = self
-            .client
-            .title()
-            .await
-            .unwrap_or_else(|_| "N/A".to_string());
+        let script = format!(
+            "{}\n{}",
+            SELECTOR_OF_JS,
+            r#"
+            // WebDriver resolves the returned promise
+            async function auditImages(tolerance) {
+                const dpr = window.devicePixelRatio || 1;
+                const foldY = window.innerHeight;
+
+                // Give images that are still downloading a moment to finish
+                const images = Array.from(document.images);
+                const pending = images.filter(img => !img.complete && img.loading !== 'lazy');
+                await Promise.race([
+                    Promise.all(pending.map(img => new Promise(resolve => {
+                        img.addEventListener('load', resolve, { once: true });
+                        img.addEventListener('error', resolve, { once: true });
+                    }))),
+                    new Promise(resolve => setTimeout(resolve, 5000))
+                ]);
+
+                const bytesOf = {};
+                performance.getEntriesByType('resource').forEach(entry => {
+                    bytesOf[entry.name] = entry.encodedBodySize || entry.transferSize || null;
+                });
 
-        // Count total elements on page
-        let all_elements = self.client.find_all(Locator::Css("*")).await;
-        let element_count = all_elements.map(|e| e.len()).unwrap_or(0);
+                const summary = {
+                    images: images.length, broken: 0, oversized: 0, undersized: 0,
+                    missing_srcset: 0, missing_sizes: 0, lazy_above_fold: 0, distorted: 0,
+                    wasted_bytes: 0
+                };
+                const report = images.map(img => {
+                    const rect = img.getBoundingClientRect();
+                    const style = getComputedStyle(img);
+                    const px = (prop) => parseFloat(style[prop]) || 0;
+                    // object-fit applies to the content box
+                    const width = rect.width - px('paddingLeft') - px('paddingRight')
+                        - px('borderLeftWidth') - px('borderRightWidth');
+                    const height = rect.height - px('paddingTop') - px('paddingBottom')
+                        - px('borderTopWidth') - px('borderBottomWidth');
+                    const src = img.currentSrc || img.src || '';
+                    const picture = img.parentElement && img.parentElement.tagName === 'PICTURE'
+                        ? img.parentElement : null;
+                    const srcset = img.getAttribute('srcset')
+                        || (picture && Array.from(picture.querySelectorAll('source[srcset]'))
+                            .map(s => s.getAttribute('srcset')).join(', '))
+                        || null;
+                    const sizes = img.getAttribute('sizes')
+                        || (picture && picture.querySelector('source[sizes]')?.getAttribute('sizes'))
+                        || null;
+                    const isVector = /\.svg(\?|#|$)/i.test(src) || src.startsWith('data:image/svg');
+                    const visible = style.display !== 'none' && style.visibility !== 'hidden'
+                        && width > 0 && height > 0;
+                    const aboveFold = visible && rect.top < foldY && rect.bottom > 0;
+                    const bytes = bytesOf[src] || null;
+
+                    const entry = {
+                        selector: selectorOf(img),
+                        src: src.startsWith('data:') ? src.slice(0, 40) + '...' : src,
+                        alt: img.getAttribute('alt'),
+                        status: !src ? 'no-source'
+                            : !img.complete ? 'not-loaded'
+                            : img.naturalWidth === 0 ? 'broken' : 'loaded',
+                        natural: { width: img.naturalWidth, height: img.naturalHeight },
+                        rendered: { width: Math.round(width), height: Math.round(height) },
+                        needed: { width: Math.round(width * dpr), height: Math.round(height * dpr) },
+                        density: null,
+                        bytes,
+                        loading: img.getAttribute('loading'),
+                        srcset,
+                        sizes,
+                        above_fold: aboveFold,
+                        object_fit: style.objectFit,
+                        issues: []
+                    };
+                    const issue = (type, message) => {
+                        entry.issues.push({ type, message });
+                        summary[type.replace(/-/g, '_')] += 1;
+                    };
 
-        // Get console errors
-        let console_errors = self
-            .console_logs
-            .lock()
-            .await
-            .iter()
-            .filter(|log| log.level == "error")
-            .cloned()
-            .collect::<Vec<_>>();
+                    if (entry.status === 'broken') {
+                        issue('broken', 'Image failed to load (naturalWidth is 0)');
+                        return entry;
+                    }
+                    if (img.loading === 'lazy' && aboveFold) {
+                        issue('lazy-above-fold', 'loading="lazy" on an image visible without scrolling delays LCP');
+                    }
+                    if (entry.status !== 'loaded' || !visible) return entry;
+
+                    if (!isVector) {
+                        // Pixels delivered per pixel needed on this screen
+                        const density = img.naturalWidth / (width * dpr);
+                        entry.density = Math.round(density * 100) / 100;
+                        if (density * density > tolerance) {
+                            const wasted = bytes ? Math.round(bytes * (1 - 1 / (density * density))) : null;
+                            entry.wasted_bytes = wasted;
+                            if (wasted) summary.wasted_bytes += wasted;
+                            issue('oversized', `${img.naturalWidth}x${img.naturalHeight} delivered for `
+                                + `${entry.needed.width}x${entry.needed.height} needed at ${dpr}x`);
+                        } else if (density < 0.95) {
+                            issue('undersized', `${img.naturalWidth}x${img.naturalHeight} is upscaled to `
+                                + `${entry.needed.width}x${entry.needed.height} at ${dpr}x and will look blurry`);
+                        }
 
-        // Check if page is still loading
-        let ready_state = self
-            .client
-            .execute("return document.readyState", vec![])
-            .await
-            .map(|v| v.as_str().unwrap_or("unknown").to_string())
-            .unwrap_or_else(|_| "error".to_string());
+                        if (!srcset) {
+                            issue('missing-srcset', 'No srcset: every screen density gets the same file');
+                        } else if (/\d\s*w\b/.test(srcset) && !sizes) {
+                            issue('missing-sizes', 'srcset uses width descriptors without sizes, so 100vw is assumed');
+                        }
+                    }
 
-        // Get viewport size
-        let viewport = self
-            .client
-            .execute(
-                "return {width: window.innerWidth, height: window.innerHeight}",
-                vec![],
-            )
-            .await
-            .ok();
+                    const fit = style.objectFit;
+                    if ((fit === 'fill' || !fit) && img.naturalHeight > 0) {
+                        const natural = img.naturalWidth / img.naturalHeight;
+                        const rendered = width / height;
+                        if (Math.abs(rendered - natural) / natural > 0.05) {
+                            issue('distorted', `Aspect ratio ${natural.toFixed(2)} is stretched to `
+                                + `${rendered.toFixed(2)} (set object-fit or matching width/height)`);
+                        }
+                    }
+                    return entry;
+                });
 
-        // Check for common elements
-        let has_forms = self
-            .client
-            .find_all(Locator::Css("form"))
-            .await
-            .map(|e| !e.is_empty())
-            .unwrap_or(false);
-        let has_inputs = self
+                return {
+                    url: location.href,
+                    device_pixel_ratio: dpr,
+                    viewport: { width: window.innerWidth, height: window.innerHeight },
+                    summary,
+                    images: report
+                };
+            }
+            return auditImages(arguments[0]);
+        "#
+        );
+
+        let result = self
             .client
-            .find_all(Locator::Css("input"))
+            .execute(&script, vec![json!(tolerance)])
             .await
-            .map(|e| !e.is_empty())
-            .unwrap_or(false);
-        let has_buttons = self
-            .client
-            .find_all(Locator::Css("button"))
+            .context("Failed to audit images")?;
+        Ok(result)
+    }
+
+    /// Start (or continue) recording layout shifts in the current document,
+    /// attributing new shifts to `phase` (e.g. "click .buy")
+    ///
+    /// The observer is buffered, so shifts that happened before it was
+    /// installed are recorded too, under a "load <url>" phase.
+    pub async fn mark_layout_shifts(&self, phase: &str) -> Result<()> {
+        let script = format!(
+            "{}\n{}",
+            SELECTOR_OF_JS,
+            r#"
+            const phase = arguments[0];
+            if (window.__webprobeShifts) {
+                window.__webprobeShifts.phase = phase;
+                return;
+            }
+
+            const store = window.__webprobeShifts = {
+                phase,
+                entries: [],
+                installedAt: performance.now(),
+                loadPhase: 'load ' + location.href
+            };
+            const rect = (r) => ({
+                x: Math.round(r.x), y: Math.round(r.y),
+                width: Math.round(r.width), height: Math.round(r.height)
+            });
+
+            try {
+                new PerformanceObserver(list => list.getEntries().forEach(e => {
+                    store.entries.push({
+                        phase: e.startTime < store.installedAt ? store.loadPhase : store.phase,
+                        url: location.href,
+                        time: Math.round(e.startTime),
+                        timestamp: Math.round(performance.timeOrigin + e.startTime),
+                        value: Math.round(e.value * 10000) / 10000,
+                        had_recent_input: e.hadRecentInput,
+                        sources: (e.sources || []).map(s => {
+                            const before = rect(s.previousRect);
+                            const after = rect(s.currentRect);
+                            return {
+                                selector: selectorOf(s.node),
+                                tag: s.node && s.node.nodeType === 1 ? s.node.tagName.toLowerCase() : null,
+                                previous: before,
+                                current: after,
+                                delta: {
+                                    x: after.x - before.x,
+                                    y: after.y - before.y,
+                                    width: after.width - before.width,
+                                    height: after.height - before.height
+                                }
+                            };
+                        })
+                    });
+                    // Keep memory bounded on pages that shift continuously
+                    if (store.entries.length > 500) store.entries.shift();
+                })).observe({ type: 'layout-shift', buffered: true });
+            } catch (e) {
+                // layout-shift is not supported by this browser
+            }
+        "#
+        );
+
+        self.client
+            .execute(&script, vec![json!(phase)])
             .await
-            .map(|e| !e.is_empty())
-            .unwrap_or(false);
+            .context("Failed to install layout shift observer")?;
+        Ok(())
+    }
 
-        // Get network activity (basic check)
-        let network_status = self
+    /// Take the layout shifts recorded in the current document since the last drain
+    pub async fn drain_layout_shifts(&self) -> Result<Vec<serde_json::Value>> {
+        let script = r#"
+            const store = window.__webprobeShifts;
+            if (!store) return [];
+            const entries = store.entries;
+            store.entries = [];
+            return entries;
+        "#;
+
+        let entries = self
             .client
-            .execute(
-                "return performance.getEntriesByType('resource').length",
-                vec![],
-            )
+            .execute(script, vec![])
             .await
-            .ok();
-
-        Ok(json!({
-            "current_url": current_url.as_str(),
-            "title": title,
-            "ready_state": ready_state,
-            "element_count": element_count,
-            "viewport": viewport,
-            "console_errors": console_errors,
-            "has_forms": has_forms,
-            "has_inputs": has_inputs,
-            "has_buttons": has_buttons,
-            "network_requests": network_status,
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        }))
+            .context("Failed to read layout shifts")?;
+        Ok(serde_json::from_value(entries).unwrap_or_default())
     }
 
-    /// Smart element detection - finds forms, tables, navigation, and interactive elements
-    pub async fn detect_smart_elements(
+    /// Core Web Vitals and navigation timing
+    ///
+    /// With a URL, Chrome gets the PerformanceObservers registered before the
+    /// page's own scripts run (DevTools `addScriptToEvaluateOnNewDocument`);
+    /// otherwise they are registered afterwards with `buffered: true`, which
+    /// recovers everything except long tasks from before registration.
+    /// `interact` clicks an element to produce an INP/FID measurement.
+    pub async fn performance_metrics(
         &self,
         url: &str,
-        context_selector: Option<&str>,
+        interact: Option<&str>,
+        settle_ms: u64,
     ) -> Result<serde_json::Value> {
-        // Navigate if URL provided
-        if !url.is_empty() {
-            self.goto(url).await?;
-        }
+        let observer_script = r#"
+            (() => {
+                if (window.__webprobePerf) return;
+                const store = window.__webprobePerf = {
+                    lcp: null,
+                    fcp: null,
+                    cls: 0,
+                    shifts: [],
+                    fid: null,
+                    events: [],
+                    longTasks: []
+                };
+                const observe = (type, callback, options) => {
+                    try {
+                        new PerformanceObserver(list => list.getEntries().forEach(callback))
+                            .observe(Object.assign({ type, buffered: true }, options || {}));
+                    } catch (e) {
+                        // Entry type not supported by this browser
+                    }
+                };
 
-        let context_clause = context_selector
-            .map(|s| format!("document.querySelector('{}') || document", s))
-            .unwrap_or_else(|| "document".to_string());
+                observe('paint', e => {
+                    if (e.name === 'first-contentful-paint') store.fcp = e.startTime;
+                });
+                observe('largest-contentful-paint', e => {
+                    store.lcp = { time: e.startTime, size: e.size, element: e.element, url: e.url };
+                });
 
-        let script = format!(
+                // CLS is the largest session window (gaps < 1s, window < 5s)
+                let session = 0, first = 0, last = 0;
+                observe('layout-shift', e => {
+                    if (e.hadRecentInput) return;
+                    if (session && e.startTime - last < 1000 && e.startTime - first < 5000) {
+                        session += e.value;
+                    } else {
+                        session = e.value;
+                        first = e.startTime;
+                    }
+                    last = e.startTime;
+                    store.cls = Math.max(store.cls, session);
+                    store.shifts.push({
+                        time: e.startTime,
+                        value: e.value,
+                        nodes: (e.sources || []).map(s => s.node).filter(Boolean)
+                    });
+                });
+
+                observe('first-input', e => {
+                    store.fid = { delay: e.processingStart - e.startTime, name: e.name, time: e.startTime };
+                });
+                observe('event', e => {
+                    if (!e.interactionId) return;
+                    store.events.push({
+                        id: e.interactionId,
+                        name: e.name,
+                        duration: e.duration,
+                        time: e.startTime,
+                        target: e.target
+                    });
+                }, { durationThreshold: 16 });
+                observe('longtask', e => {
+                    const attribution = (e.attribution || [])[0] || {};
+                    store.longTasks.push({
+                        time: e.startTime,
+                        duration: e.duration,
+                        source: attribution.containerSrc || attribution.containerName || e.name
+                    });
+                });
+            })();
+        "#;
+
+        let collect_script = format!(
+            "{}\n{}",
+            SELECTOR_OF_JS,
             r#"
-            return (function() {{
-                const context = {};
-                if (!context) return {{ error: 'Context not found' }};
-                
-                // Detect forms and their fields
-                const forms = Array.from(context.querySelectorAll('form')).map(form => {{
-                    const inputs = Array.from(form.querySelectorAll('input, textarea, select'));
-                    return {{
-                        id: form.id || null,
-                        name: form.name || null,
-                        action: form.action || null,
-                        method: form.method || 'GET',
-                        fields: inputs.map(input => ({{
-                            type: input.type || input.tagName.toLowerCase(),
-                            name: input.name || null,
-                            id: input.id || null,
-                            required: input.required || false,
-                            placeholder: input.placeholder || null,
-                            value: input.type === 'password' ? '[hidden]' : (input.value || null),
-                            selector: input.id ? '#' + input.id : 
-                                     input.name ? `[name="${{input.name}}"]` :
-                                     `${{input.tagName.toLowerCase()}}:nth-of-type(${{Array.from(form.querySelectorAll(input.tagName)).indexOf(input) + 1}})`
-                        }})),
-                        submit_buttons: Array.from(form.querySelectorAll('button[type="submit"], input[type="submit"]')).map(btn => ({{
-                            text: btn.innerText || btn.value || null,
-                            selector: btn.id ? '#' + btn.id : 
-                                     `button:contains("${{btn.innerText || btn.value}}")`
-                        }}))
-                    }};
-                }});
-                
-                // Detect tables
-                const tables = Array.from(context.querySelectorAll('table')).map(table => {{
-                    const headers = Array.from(table.querySelectorAll('th')).map(th => th.innerText.trim());
-                    const rows = table.querySelectorAll('tbody tr, tr').length;
-                    return {{
-                        id: table.id || null,
-                        headers: headers,
-                        row_count: rows,
-                        has_thead: !!table.querySelector('thead'),
-                        has_tbody: !!table.querySelector('tbody'),
-                        selector: table.id ? '#' + table.id : 
-                                 `table:nth-of-type(${{Array.from(context.querySelectorAll('table')).indexOf(table) + 1}})`
-                    }};
-                }});
-                
-                // Detect navigation elements
-                const navs = Array.from(context.querySelectorAll('nav, [role="navigation"], .nav, .navbar, .menu')).map(nav => {{
-                    const links = Array.from(nav.querySelectorAll('a'));
-                    return {{
-                        tag: nav.tagName.toLowerCase(),
-                        class: nav.className || null,
-                        id: nav.id || null,
-                        link_count: links.length,
-                        links: links.slice(0, 10).map(a => ({{
-                            text: a.innerText.trim(),
-                            href: a.href,
-                            selector: a.id ? '#' + a.id : `a:contains("${{a.innerText.trim()}}")`
-                        }}))
-                    }};
-                }});
-                
-                // Detect interactive elements
-                const buttons = Array.from(context.querySelectorAll('button:not([type="submit"])')).map(btn => ({{
-                    text: btn.innerText.trim(),
-                    onclick: !!btn.onclick,
-                    disabled: btn.disabled,
-                    selector: btn.id ? '#' + btn.id : 
-                             `button:contains("${{btn.innerText.trim()}}")`
-                }}));
-                
-                // Detect clickable elements (with onclick or cursor:pointer)
-                const clickables = Array.from(context.querySelectorAll('*')).filter(el => {{
-                    const style = window.getComputedStyle(el);
-                    return (el.onclick || style.cursor === 'pointer') && 
-                           !['A', 'BUTTON', 'INPUT'].includes(el.tagName);
-                }}).slice(0, 20).map(el => ({{
-                    tag: el.tagName.toLowerCase(),
-                    text: el.innerText ? el.innerText.substring(0, 50).trim() : null,
-                    id: el.id || null,
-                    class: el.className || null,
-                    selector: el.id ? '#' + el.id : 
-                             el.className ? '.' + el.className.split(' ')[0] :
-                             el.tagName.toLowerCase()
-                }}));
-                
-                // Detect modal/dialog elements
-                const modals = Array.from(context.querySelectorAll('[role="dialog"], .modal, .dialog, .popup')).map(modal => ({{
-                    visible: window.getComputedStyle(modal).display !== 'none',
-                    id: modal.id || null,
-                    class: modal.className || null,
-                    selector: modal.id ? '#' + modal.id : '.' + (modal.className ? modal.className.split(' ')[0] : 'modal')
-                }}));
-                
-                // Detect login/auth forms specifically
-                const authForms = forms.filter(form => {{
-                    const hasPassword = form.fields.some(f => f.type === 'password');
-                    const hasEmail = form.fields.some(f => 
-                        f.type === 'email' || 
-                        (f.name && f.name.toLowerCase().includes('email')) ||
-                        (f.id && f.id.toLowerCase().includes('email'))
-                    );
-                    const hasUsername = form.fields.some(f => 
-                        (f.name && f.name.toLowerCase().includes('user')) ||
-                        (f.id && f.id.toLowerCase().includes('user'))
-                    );
-                    return hasPassword && (hasEmail || hasUsername);
-                }});
-                
-                return {{
-                    forms: forms,
-                    auth_forms: authForms,
-                    tables: tables,
-                    navigation: navs,
-                    buttons: buttons.slice(0, 20),
-                    clickable_elements: clickables,
-                    modals: modals,
-                    summary: {{
-                        form_count: forms.length,
-                        has_auth_form: authForms.length > 0,
-                        table_count: tables.length,
-                        navigation_count: navs.length,
-                        button_count: buttons.length,
-                        modal_count: modals.length,
-                        total_links: context.querySelectorAll('a').length,
-                        total_images: context.querySelectorAll('img').length
-                    }}
-                }};
-            }})();
-            "#,
-            context_clause
+            const store = window.__webprobePerf;
+            if (!store) return null;
+
+            const ms = (v) => v == null ? null : Math.round(v * 10) / 10;
+
+            // INP: worst interaction, or the 98th percentile with 50+ interactions
+            const interactions = {};
+            store.events.forEach(e => {
+                if (!interactions[e.id] || interactions[e.id].duration < e.duration) interactions[e.id] = e;
+            });
+            const worst = Object.values(interactions).sort((a, b) => b.duration - a.duration);
+            const inp = worst.length ? worst[Math.min(worst.length - 1, Math.floor(worst.length / 50))] : null;
+
+            const nav = performance.getEntriesByType('navigation')[0];
+            const navigation = nav ? {
+                type: nav.type,
+                redirect: ms(nav.redirectEnd - nav.redirectStart),
+                dns: ms(nav.domainLookupEnd - nav.domainLookupStart),
+                connect: ms(nav.connectEnd - nav.connectStart),
+                tls: nav.secureConnectionStart > 0 ? ms(nav.connectEnd - nav.secureConnectionStart) : 0,
+                request: ms(nav.responseStart - nav.requestStart),
+                response: ms(nav.responseEnd - nav.responseStart),
+                dom_interactive: ms(nav.domInteractive),
+                dom_content_loaded: ms(nav.domContentLoadedEventEnd),
+                load: ms(nav.loadEventEnd),
+                transfer_size: nav.transferSize,
+                encoded_body_size: nav.encodedBodySize
+            } : null;
+
+            // Blocking time: the part of each long task after FCP beyond 50ms
+            const fcp = store.fcp || 0;
+            const blocking = store.longTasks
+                .filter(t => t.time >= fcp)
+                .reduce((sum, t) => sum + Math.max(0, t.duration - 50), 0);
+
+            const shiftSources = {};
+            store.shifts.forEach(s => s.nodes.forEach(node => {
+                const selector = selectorOf(node);
+                if (selector) shiftSources[selector] = (shiftSources[selector] || 0) + s.value;
+            }));
+
+            return {
+                url: location.href,
+                ttfb: nav ? ms(nav.responseStart) : null,
+                fcp: ms(store.fcp),
+                lcp: store.lcp ? {
+                    value: ms(store.lcp.time),
+                    size: store.lcp.size,
+                    element: selectorOf(store.lcp.element),
+                    url: store.lcp.url || null
+                } : null,
+                cls: {
+                    value: Math.round(store.cls * 10000) / 10000,
+                    shifts: store.shifts.length,
+                    sources: Object.entries(shiftSources)
+                        .sort((a, b) => b[1] - a[1])
+                        .slice(0, 5)
+                        .map(([selector, value]) => ({ selector, value: Math.round(value * 10000) / 10000 }))
+                },
+                inp: inp ? {
+                    value: ms(inp.duration),
+                    event: inp.name,
+                    target: selectorOf(inp.target),
+                    interactions: worst.length
+                } : null,
+                fid: store.fid ? { value: ms(store.fid.delay), event: store.fid.name } : null,
+                navigation,
+                long_tasks: {
+                    count: store.longTasks.length,
+                    total_blocking_time: ms(blocking),
+                    longest: store.longTasks
+                        .slice()
+                        .sort((a, b) => b.duration - a.duration)
+                        .slice(0, 10)
+                        .map(t => ({ start: ms(t.time), duration: ms(t.duration), source: t.source }))
+                }
+            };
+        "#
         );
 
-        let result = self.client.execute(&script, vec![]).await?;
-        Ok(result)
+        let mut before_navigation = false;
+        if !url.is_empty() {
+            let registered = if matches!(self.browser_type, BrowserType::Chrome) {
+                self.cdp(
+                    "Page.addScriptToEvaluateOnNewDocument",
+                    json!({"source": observer_script}),
+                )
+                .await
+                .map_err(|e| debug!("Falling back to buffered observers: {}", e))
+                .ok()
+            } else {
+                None
+            };
+
+            let navigated = self.goto(url).await;
+            if let Some(identifier) = registered.as_ref().and_then(|r| r["identifier"].as_str()) {
+                before_navigation = true;
+                let _ = self
+                    .cdp(
+                        "Page.removeScriptToEvaluateOnNewDocument",
+                        json!({"identifier": identifier}),
+                    )
+                    .await;
+            }
+            navigated?;
+        }
+
+        // No-op when the observers are already installed
+        self.client
+            .execute(observer_script, vec![])
+            .await
+            .context("Failed to install performance observers")?;
+
+        // Let late LCP candidates, shifts and long tasks arrive
+        tokio::time::sleep(tokio::time::Duration::from_millis(settle_ms)).await;
+
+        if let Some(selector) = interact {
+            self.click_element("", selector, None).await?;
+            // Event timing entries are dispatched after the next paint
+            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        }
+
+        let mut metrics = self
+            .client
+            .execute(&collect_script, vec![])
+            .await
+            .context("Failed to collect performance metrics")?;
+
+        if let Some(obj) = metrics.as_object_mut() {
+            obj.insert(
+                "observers".to_string(),
+                json!(if before_navigation {
+                    "before-navigation"
+                } else {
+                    "buffered"
+                }),
+            );
+        }
+        Ok(metrics)
     }
 
     pub async fn wait_for_network_idle(&self, timeout_ms: u64, idle_time_ms: u64) -> Result<bool> {
@@ -3778,6 +7860,26 @@ impl Browser {
             critical_issues.push("unusual-properties");
         }
 
+        if let Some(conflicts) = data["anomalies"]["interaction_conflicts"].as_array()
+            && let Some(first) = conflicts.first()
+        {
+            evidence.push(format!(
+                "{} interactive element(s) are covered by other elements (e.g. {} under {})",
+                conflicts.len(),
+                first["clickable_element"].as_str().unwrap_or("?"),
+                first["blocking_element"].as_str().unwrap_or("?")
+            ));
+            critical_issues.push("covered-interactive");
+        }
+        if let Some(covered) = data["anomalies"]["covered_elements"].as_array()
+            && !covered.is_empty()
+        {
+            evidence.push(format!(
+                "{} text element(s) are partly hidden under other elements",
+                covered.len()
+            ));
+        }
+
         let (diagnosis, confidence, suggested_fix) = if critical_issues.contains(&"zero-dimensions")
         {
             (
@@ -3788,6 +7890,15 @@ impl Browser {
                         .to_string(),
                 ),
             )
+        } else if critical_issues.contains(&"covered-interactive") {
+            (
+                "Interactive elements are hidden under other elements".to_string(),
+                0.9,
+                Some(
+                    "Check sticky headers, overlays and modal backdrops painted over them (z-index, position, pointer-events)"
+                        .to_string(),
+                ),
+            )
         } else if critical_issues.contains(&"outside-viewport") {
             (
                 "Elements are positioned outside the visible viewport".to_string(),
@@ -3839,6 +7950,26 @@ impl Browser {
                     evidence.push(format!("Position: {}", position));
                 }
             }
+            if let Some(occlusion) = element.get("occlusion")
+                && let Some(visible) = occlusion["visible_percent"].as_f64()
+                && visible < 100.0
+            {
+                let covered: Vec<&str> = occlusion["covered_by"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|o| o["selector"].as_str())
+                    .collect();
+                evidence.push(if covered.is_empty() {
+                    format!("Visible: {:.0}% of its box", visible)
+                } else {
+                    format!(
+                        "Visible: {:.0}% of its box, covered by {}",
+                        visible,
+                        covered.join(", ")
+                    )
+                });
+            }
         }
 
         DiagnosticResult {
@@ -3850,3 +7981,37 @@ impl Browser {
         }
     }
 }
+
+/// Text of an http(s) or file URL (stylesheets and their source maps)
+///
+/// `referrer` is the page or sheet that named the URL. Fetches have to keep
+/// its scheme, so a remote page can't point the daemon at local files.
+async fn fetch_text(url: &url::Url, referrer: &url::Url) -> Result<String> {
+    if url.scheme() != referrer.scheme() {
+        anyhow::bail!(
+            "Refusing to fetch {} referenced from a {} URL",
+            url,
+            referrer.scheme()
+        );
+    }
+    match url.scheme() {
+        "file" => {
+            let path = url
+                .to_file_path()
+                .map_err(|_| anyhow::anyhow!("Invalid file URL: {}", url))?;
+            tokio::fs::read_to_string(&path)
+                .await
+                .with_context(|| format!("Failed to read {}", path.display()))
+        }
+        "http" | "https" => {
+            let response = reqwest::Client::new()
+                .get(url.as_str())
+                .timeout(std::time::Duration::from_secs(5))
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(response.text().await?)
+        }
+        scheme => anyhow::bail!("Can't fetch {} URLs", scheme),
+    }
+}
@@ -208,6 +208,1381 @@ fn test_validate_scoring_system() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_validate_contrast() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("contrast.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <body style="background: #fff">
+            <p id="good" style="color: #000">Readable</p>
+            <p id="faint" style="color: #aaa">Too faint</p>
+            <div style="background: rgba(0, 0, 0, 0.5)">
+                <p id="blended" style="color: #555">Dark on blended grey</p>
+            </div>
+            <div style="background-image: linear-gradient(#000, #fff)">
+                <p id="gradient" style="color: #777">Over a gradient</p>
+            </div>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let result = run_command(&[
+        "validate",
+        &format!("file://{}", test_page.display()),
+        "--check",
+        "contrast",
+    ])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["contrast"].is_object() {
+        let contrast = &result["contrast"];
+        let failures = contrast["aa"]["failures"].as_array().unwrap();
+        let failing: Vec<&str> = failures
+            .iter()
+            .filter_map(|f| f["selector"].as_str())
+            .collect();
+
+        assert!(failing.contains(&"#faint"), "Failures: {:?}", failures);
+        assert!(failing.contains(&"#blended"), "Failures: {:?}", failures);
+        assert!(!failing.contains(&"#good"));
+        assert_eq!(contrast["unknown_elements"][0]["selector"], "#gradient");
+
+        let faint = failures.iter().find(|f| f["selector"] == "#faint").unwrap();
+        assert_eq!(faint["foreground"], "#aaaaaa");
+        assert_eq!(faint["background"], "#ffffff");
+        assert!(faint["ratio"].as_f64().unwrap() < 4.5);
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_structured_data() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("seo.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <head>
+            <title>Widget</title>
+            <meta name="robots" content="index, noindex">
+            <meta property="og:title" content="Widget">
+            <script type="application/ld+json">
+                {"@context": "https://schema.org", "@type": "Product",
+                 "offers": {"@type": "Offer", "price": "9.99"}}
+            </script>
+            <script type="application/ld+json">{ not json </script>
+        </head>
+        <body>
+            <h1>Widget</h1>
+            <div itemscope itemtype="https://schema.org/Event">
+                <span itemprop="name">Launch</span>
+            </div>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let result = run_command(&[
+        "validate",
+        &format!("file://{}", test_page.display()),
+        "--check",
+        "seo",
+    ])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if let Some(issues) = result["seo"].as_array() {
+        let messages = |rule: &str| -> Vec<String> {
+            issues
+                .iter()
+                .filter(|i| i["rule"] == rule)
+                .filter_map(|i| i["message"].as_str().map(String::from))
+                .collect()
+        };
+
+        let required = messages("structured-data-required");
+        assert!(
+            required
+                .iter()
+                .any(|m| m.contains("Product") && m.contains("name"))
+        );
+        assert!(
+            required
+                .iter()
+                .any(|m| m.contains("Offer") && m.contains("priceCurrency"))
+        );
+        assert!(
+            required
+                .iter()
+                .any(|m| m.contains("Event") && m.contains("startDate"))
+        );
+        assert!(
+            messages("structured-data-valid")
+                .iter()
+                .any(|m| m.contains("not valid JSON"))
+        );
+        assert_eq!(messages("robots-meta").len(), 1);
+        assert!(messages("open-graph")[0].contains("og:image"));
+
+        let robots = issues.iter().find(|i| i["rule"] == "robots-meta").unwrap();
+        assert!(robots["snippet"].as_str().unwrap().contains("noindex"));
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_keyboard_audit() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("keyboard.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <head>
+            <style>
+                #quiet:focus { outline: none; }
+                .card { cursor: pointer; }
+            </style>
+        </head>
+        <body>
+            <a id="home" href="index.html">Home</a>
+            <button id="quiet">No focus ring</button>
+            <div class="card" id="card" onclick="void 0">Clickable card</div>
+            <button id="skipped" tabindex="-1">Skipped</button>
+            <input id="last" aria-label="Search">
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let result = run_command(&["keyboard-audit", &format!("file://{}", test_page.display())])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["focus_order"].is_array() {
+        let order: Vec<&str> = result["focus_order"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|s| s["selector"].as_str())
+            .collect();
+        assert_eq!(order, vec!["#home", "#quiet", "#last"]);
+
+        let quiet = &result["focus_order"][1];
+        assert_eq!(quiet["indicator"], false, "Stop: {:?}", quiet);
+
+        let unreachable: Vec<&str> = result["unreachable"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|u| u["selector"].as_str())
+            .collect();
+        assert!(
+            unreachable.contains(&"#card"),
+            "Unreachable: {:?}",
+            unreachable
+        );
+        assert!(
+            unreachable.contains(&"#skipped"),
+            "Unreachable: {:?}",
+            unreachable
+        );
+        assert_eq!(result["traps"].as_array().unwrap().len(), 0);
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_wait_conditions() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("async.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <body>
+            <div id="spinner">Loading...</div>
+            <p id="status"></p>
+            <ul id="results"></ul>
+            <script>
+                setTimeout(() => {
+                    document.getElementById('spinner').remove();
+                    document.getElementById('status').textContent = 'Saved 3 items';
+                }, 500);
+                let n = 0;
+                const timer = setInterval(() => {
+                    const li = document.createElement('li');
+                    li.textContent = 'Item ' + (++n);
+                    document.getElementById('results').appendChild(li);
+                    if (n === 5) clearInterval(timer);
+                }, 100);
+            </script>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let result = run_command(&["wait", &url, "#spinner", "--for", "detached"])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["met"].is_boolean() {
+        assert_eq!(result["met"], true);
+        assert_eq!(result["condition"], "detached");
+        assert_eq!(result["count"], 0);
+        assert!(result["elapsed_ms"].is_u64());
+        assert!(result["polls"].as_u64().unwrap() >= 1);
+
+        let result = run_command(&["wait", &url, "#status", "--for", "text=Saved"])?;
+        assert_eq!(result["met"], true);
+
+        let result = run_command(&["wait", &url, "#results li", "--for", "count>=5"])?;
+        assert_eq!(result["met"], true);
+        assert_eq!(result["count"], 5);
+
+        // Hidden holds for elements that don't exist
+        let result = run_command(&["wait", &url, "#missing", "--for", "hidden"])?;
+        assert_eq!(result["met"], true);
+
+        let result = run_command(&[
+            "wait",
+            &url,
+            "#missing",
+            "--for",
+            "visible",
+            "--timeout",
+            "1",
+        ])?;
+        assert_eq!(result["error"], true);
+        assert_eq!(result["exit_code"], 5);
+        assert!(result["message"].as_str().unwrap().contains("#missing"));
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_stable_waits() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let _ = run_command(&["profile", "destroy", "test-anim", "--force"]);
+    let _ = run_command(&["profile", "create", "test-anim", "--browser", "chrome"]);
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("slide.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <head>
+            <style>
+                @keyframes slide { from { left: 0; } to { left: 300px; } }
+                #panel { position: absolute; top: 0; left: 0; width: 100px; height: 50px;
+                         animation: slide 1s linear forwards; }
+            </style>
+        </head>
+        <body style="margin: 0">
+            <div id="panel">Panel</div>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let result = run_command(&["inspect", &url, "#panel", "--wait-stable-frames", "3"])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["position"].is_object() {
+        // Measured once the slide has finished, not mid-flight
+        assert_eq!(result["position"]["x"], 300.0);
+
+        // With animations off in the tab the panel lands at once
+        let _ = Command::new(env!("CARGO_BIN_EXE_webprobe"))
+            .args(["tab", "animations", "main", "off", "--profile", "test-anim"])
+            .output()?;
+        let result = run_command(&["inspect", &url, "#panel", "--profile", "test-anim"])?;
+        assert_eq!(result["position"]["x"], 300.0);
+    }
+
+    let _ = run_command(&["profile", "destroy", "test-anim", "--force"]);
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_scroll_probe() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("sticky.html");
+    fs::write(
+        &test_page,
+        r##"
+        <!DOCTYPE html>
+        <html>
+        <body style="margin: 0">
+            <header id="top" style="position: fixed; top: 0; left: 0; right: 0; height: 60px; background: #fff">
+                <a href="#pricing">Pricing</a>
+            </header>
+            <div style="overflow: hidden">
+                <nav id="toc" style="position: sticky; top: 60px; height: 40px">Contents</nav>
+                <div style="height: 2000px"></div>
+            </div>
+            <section id="pricing" style="height: 1000px">Pricing</section>
+        </body>
+        </html>
+    "##,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let result = run_command(&[
+        "scroll-probe",
+        "#top",
+        "--positions",
+        "0,500,bottom",
+        "--url",
+        &url,
+    ])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["samples"].is_array() {
+        assert_eq!(result["position"], "fixed");
+        let samples = result["samples"].as_array().unwrap();
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[1]["scroll_y"], 500);
+        assert_eq!(samples[2]["at"], "bottom");
+        assert!(samples.iter().all(|s| s["rect"]["y"] == 0.0));
+
+        // The fixed header hides the top of #pricing after following the link
+        let issues = result["issues"].as_array().unwrap();
+        let overlap = issues
+            .iter()
+            .find(|i| i["kind"] == "anchor_overlap")
+            .unwrap();
+        assert_eq!(overlap["anchor"], "#pricing");
+        assert_eq!(overlap["covered_px"], 60);
+
+        let result = run_command(&[
+            "scroll-probe",
+            "#toc",
+            "--positions",
+            "0,500",
+            "--url",
+            &url,
+        ])?;
+        assert_eq!(result["position"], "sticky");
+        let kinds: Vec<&str> = result["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|i| i["kind"].as_str())
+            .collect();
+        assert!(kinds.contains(&"sticky_overflow_ancestor"));
+        assert!(kinds.contains(&"sticky_never_stuck"));
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_scroll_harvest() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("feed.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <body style="margin: 0">
+            <div id="feed"></div>
+            <script>
+                // Loads 5 more items near the bottom, 20 in all
+                const feed = document.getElementById('feed');
+                const more = () => {
+                    for (let i = 0; i < 5 && feed.children.length < 20; i++) {
+                        const item = document.createElement('div');
+                        item.className = 'feed-item';
+                        item.style.height = '300px';
+                        feed.appendChild(item);
+                    }
+                };
+                more();
+                window.addEventListener('scroll', () => {
+                    if (innerHeight + scrollY >= document.body.scrollHeight - 50) setTimeout(more, 100);
+                });
+            </script>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let result = run_command(&[
+        "scroll",
+        &url,
+        "--until-selector-count",
+        ".feed-item:12",
+        "--browser",
+        "chrome",
+    ])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["reason"].is_string() {
+        assert_eq!(result["reason"], "count");
+        assert_eq!(result["start"]["count"], 5);
+        assert!(result["count"].as_u64().unwrap() >= 12);
+        let steps = result["steps"].as_array().unwrap();
+        assert!(steps.len() >= 2 && steps.len() < 50);
+        assert_eq!(steps[0]["grew"], true);
+
+        // Without a target it runs until the feed stops growing
+        let result = run_command(&[
+            "scroll",
+            &url,
+            "--until-stable",
+            "500",
+            "--browser",
+            "chrome",
+        ])?;
+        assert_eq!(result["reason"], "stable");
+        assert_eq!(result["height"], 6000);
+        let steps = result["steps"].as_array().unwrap();
+        assert_eq!(steps.last().unwrap()["grew"], false);
+
+        let result = run_command(&[
+            "scroll",
+            &url,
+            "--until-stable",
+            "500",
+            "--max-steps",
+            "1",
+            "--browser",
+            "chrome",
+        ])?;
+        assert_eq!(result["reason"], "max_steps");
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_fill_form() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let _ = run_command(&["profile", "destroy", "test-fill", "--force"]);
+    let _ = run_command(&["profile", "create", "test-fill", "--browser", "chrome"]);
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("signup.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <body>
+            <form id="newsletter">
+                <input name="email" type="email">
+                <button>Subscribe</button>
+            </form>
+            <form id="signup" action="done.html">
+                <label for="email">Email address *</label>
+                <input id="email" name="contact" type="email" required>
+                <input name="login" placeholder="Username" required>
+                <input id="password" type="password">
+                <label>Plan
+                    <select name="plan">
+                        <option value="free">Free</option>
+                        <option value="pro">Pro</option>
+                    </select>
+                </label>
+                <fieldset>
+                    <legend>Billing</legend>
+                    <label><input type="radio" name="billing" value="m"> Monthly</label>
+                    <label><input type="radio" name="billing" value="y"> Yearly</label>
+                </fieldset>
+                <label><input type="checkbox" name="terms"> I accept the terms</label>
+                <input type="date" name="start">
+                <label>Phone <input name="phone" required></label>
+                <button type="submit" id="create">Create account</button>
+            </form>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let data = json!({
+        "Email address": "not-an-email",
+        "Username": "ada",
+        "password": "secret",
+        "Plan": "Pro",
+        "Billing": "Yearly",
+        "I accept the terms": true,
+        "start": "2026-01-31",
+        "nickname": "ada99"
+    })
+    .to_string();
+
+    let result = run_command(&[
+        "fill-form",
+        &url,
+        "--data",
+        &data,
+        "--submit",
+        "--profile",
+        "test-fill",
+        "--tab",
+        "main",
+    ])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["form"].is_string() {
+        // The form the keys describe best, not the first one
+        assert_eq!(result["form"], "#signup");
+        assert_eq!(result["unmatched"], json!(["nickname"]));
+
+        let filled = result["filled"].as_array().unwrap();
+        let by_key = |key: &str| filled.iter().find(|f| f["key"] == key).unwrap().clone();
+        assert_eq!(by_key("Email address")["matched_by"], "label");
+        assert_eq!(by_key("Username")["matched_by"], "placeholder");
+        assert_eq!(by_key("password")["value"], "[hidden]");
+        assert_eq!(by_key("Plan")["value"], json!(["pro"]));
+        // The radio labelled Yearly
+        assert!(
+            by_key("Billing")["selector"]
+                .as_str()
+                .unwrap()
+                .ends_with("label:nth-of-type(2) > input")
+        );
+        assert_eq!(by_key("start")["value"], "2026-01-31");
+
+        let missing = result["missing_required"].as_array().unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0]["name"], "phone");
+
+        // The invalid email and empty phone keep the form from submitting
+        assert_eq!(result["submitted"], false);
+        assert_eq!(result["navigated"], false);
+        let validation = result["validation"].as_array().unwrap();
+        assert!(validation.iter().any(|v| v["name"] == "contact"));
+        assert!(validation.iter().any(|v| v["name"] == "phone"));
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_form_controls() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let _ = run_command(&["profile", "destroy", "test-forms", "--force"]);
+    let _ = run_command(&["profile", "create", "test-forms", "--browser", "chrome"]);
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("form.html");
+    let upload = temp_dir.path().join("cv.txt");
+    fs::write(&upload, "hello")?;
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <body>
+            <select id="size">
+                <option value="sm">Small</option>
+                <option value="lg">Large</option>
+                <option value="xl" disabled>Extra large</option>
+            </select>
+            <label><input type="checkbox" id="terms" style="opacity: 0; position: absolute"> Terms</label>
+            <input type="radio" name="plan" id="monthly" checked>
+            <input type="range" id="volume" min="0" max="100" step="5" value="50">
+            <input type="file" id="cv">
+            <div id="log"></div>
+            <script>
+                const log = document.getElementById('log');
+                document.addEventListener('change', (e) => { log.textContent += e.target.id + ' '; });
+            </script>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let tab_args = ["--profile", "test-forms", "--tab", "main"];
+    let with_tab = |args: &[&str]| -> Vec<String> {
+        args.iter()
+            .chain(tab_args.iter())
+            .map(|a| a.to_string())
+            .collect()
+    };
+    let run = |args: &[&str]| -> Result<serde_json::Value> {
+        let args = with_tab(args);
+        run_command(&args.iter().map(|a| a.as_str()).collect::<Vec<_>>())
+    };
+
+    let result = run(&["select", "#size", "--label", "Large", "--url", &url])?;
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["selector"].is_string() {
+        assert_eq!(result["values"], json!(["lg"]));
+        assert_eq!(result["changed"], true);
+
+        // Disabled options can't be chosen, as in the native control
+        let output = Command::new(env!("CARGO_BIN_EXE_webprobe"))
+            .args(with_tab(&["select", "#size", "--value", "xl"]))
+            .output()?;
+        assert!(!output.status.success());
+
+        // The visually hidden checkbox is clicked from script instead
+        let result = run(&["check", "#terms"])?;
+        assert_eq!(result["checked"], true);
+        assert_eq!(result["changed"], true);
+        let result = run(&["check", "#terms"])?;
+        assert_eq!(result["changed"], false);
+
+        let output = Command::new(env!("CARGO_BIN_EXE_webprobe"))
+            .args(with_tab(&["uncheck", "#monthly"]))
+            .output()?;
+        assert!(!output.status.success());
+
+        // Snapped to the step
+        let result = run(&["set-range", "#volume", "42"])?;
+        assert_eq!(result["value"], 40.0);
+        assert_eq!(result["requested"], 42.0);
+
+        let upload = upload.display().to_string();
+        let result = run(&["upload", "#cv", &upload])?;
+        assert_eq!(result["files"][0]["name"], "cv.txt");
+        assert_eq!(result["files"][0]["size"], 5);
+
+        let result = run(&["inspect", "", "#log"])?;
+        assert_eq!(result["text_content"], "size terms volume cv");
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_keyboard_input() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let _ = run_command(&["profile", "destroy", "test-keys", "--force"]);
+    let _ = run_command(&["profile", "create", "test-keys", "--browser", "chrome"]);
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("keys.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <body>
+            <form id="palette" style="display: none">
+                <input id="q">
+            </form>
+            <div id="result"></div>
+            <script>
+                const palette = document.getElementById('palette');
+                const q = document.getElementById('q');
+                document.addEventListener('keydown', (e) => {
+                    if (e.ctrlKey && e.key === 'k') {
+                        e.preventDefault();
+                        palette.style.display = 'block';
+                        q.focus();
+                    }
+                    if (e.key === 'Escape') palette.style.display = 'none';
+                });
+                let keys = 0;
+                q.addEventListener('keydown', () => keys++);
+                palette.addEventListener('submit', (e) => {
+                    e.preventDefault();
+                    document.getElementById('result').textContent = q.value + ':' + keys;
+                });
+            </script>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let tab_args = ["--profile", "test-keys", "--tab", "main"];
+    let mut press = vec!["press", "Control+K", "--url", url.as_str()];
+    press.extend(tab_args);
+    let output = Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(&press)
+        .output()?;
+
+    if output.status.success() {
+        let mut inspect = vec!["inspect", "", "#palette"];
+        inspect.extend(tab_args);
+        let result = run_command(&inspect)?;
+        assert_eq!(result["computed_styles"]["display"], "block");
+
+        // Three keystrokes and Enter, which submits the form
+        let mut type_args = vec!["type", "", "#q", "abc", "--key-delay", "20", "--submit"];
+        type_args.extend(tab_args);
+        Command::new(env!("CARGO_BIN_EXE_webprobe"))
+            .args(&type_args)
+            .output()?;
+        let mut inspect = vec!["inspect", "", "#result"];
+        inspect.extend(tab_args);
+        let result = run_command(&inspect)?;
+        assert_eq!(result["text_content"], "abc:4");
+
+        let mut press = vec!["press", "Escape"];
+        press.extend(tab_args);
+        Command::new(env!("CARGO_BIN_EXE_webprobe"))
+            .args(&press)
+            .output()?;
+        let mut inspect = vec!["inspect", "", "#palette"];
+        inspect.extend(tab_args);
+        let result = run_command(&inspect)?;
+        assert_eq!(result["computed_styles"]["display"], "none");
+    }
+
+    // Unknown key names are rejected before anything is sent
+    let output = Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["press", "Control+Hyper"])
+        .output()?;
+    assert!(!output.status.success());
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_forced_states() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("states.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <head>
+            <style>
+                .menu { display: none; width: 300px; }
+                .nav:hover .menu { display: block; }
+                button:focus-visible { outline: 3px solid blue; }
+            </style>
+        </head>
+        <body>
+            <nav class="nav">
+                Products
+                <ul class="menu"><li>Item</li></ul>
+            </nav>
+            <button id="save">Save</button>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let result = run_command(&["inspect", &url, ".menu"])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["selector"].is_string() {
+        assert_eq!(result["computed_styles"]["display"], "none");
+        assert!(result.get("forced_states").is_none());
+
+        // Hovering the menu hovers the nav it's in, which shows it
+        let result = run_command(&["inspect", &url, ".menu", "--state", "hover"])?;
+        assert_eq!(result["computed_styles"]["display"], "block");
+        assert_eq!(result["size"]["width"], 300.0);
+        assert_eq!(result["forced_states"], json!(["hover"]));
+
+        let result = run_command(&["analyze", &url, "#save", "--state", "focus-visible"])?;
+        assert_eq!(
+            result["raw_data"]["forced_states"],
+            json!(["focus-visible"])
+        );
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_drag() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let _ = run_command(&["profile", "destroy", "test-drag", "--force"]);
+    let _ = run_command(&["profile", "create", "test-drag", "--browser", "chrome"]);
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("drag.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <body style="margin: 0">
+            <div id="handle" style="position: absolute; left: 0; top: 0; width: 50px; height: 50px; background: #ccc"></div>
+            <div id="card" draggable="true" style="width: 100px; height: 30px">Card</div>
+            <div id="done" style="position: absolute; left: 300px; top: 200px; width: 200px; height: 100px">Done</div>
+            <script>
+                // Pointer-event dragging, as sortable and kanban libraries do it
+                const handle = document.getElementById('handle');
+                let start = null;
+                handle.addEventListener('pointerdown', (e) => { start = [e.clientX, e.clientY]; });
+                document.addEventListener('pointermove', (e) => {
+                    if (!start) return;
+                    handle.style.left = (e.clientX - 25) + 'px';
+                    handle.style.top = (e.clientY - 25) + 'px';
+                });
+                document.addEventListener('pointerup', () => { start = null; });
+
+                // HTML5 drag and drop into the done column
+                const done = document.getElementById('done');
+                done.addEventListener('dragover', (e) => e.preventDefault());
+                done.addEventListener('drop', () => done.appendChild(document.getElementById('card')));
+            </script>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let tab_args = ["--profile", "test-drag", "--tab", "main"];
+    let mut drag = vec!["drag", "#handle", "200,150", "--url", url.as_str()];
+    drag.extend(tab_args);
+    let result = run_command(&drag)?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["method"].is_string() {
+        assert_eq!(result["method"], "pointer");
+        assert_eq!(result["before"]["x"], 0.0);
+        assert_eq!(result["after"]["x"], 175.0);
+        assert_eq!(result["moved"], true);
+
+        let mut drag = vec!["drag", "#card", "#done"];
+        drag.extend(tab_args);
+        let result = run_command(&drag)?;
+        assert_eq!(result["method"], "html5");
+        assert_eq!(result["dropped"], true);
+        assert_eq!(result["after"]["x"], 300.0);
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_pointer_actions() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let _ = run_command(&["profile", "destroy", "test-pointer", "--force"]);
+    let _ = run_command(&["profile", "create", "test-pointer", "--browser", "chrome"]);
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("pointer.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <head>
+            <style>
+                #submenu { display: none; }
+                #menu:hover #submenu { display: block; }
+            </style>
+        </head>
+        <body>
+            <div id="menu">Menu<div id="submenu">Settings</div></div>
+            <div id="row" style="width: 200px; height: 40px">Row</div>
+            <p id="log"></p>
+            <script>
+                const log = (text) => document.getElementById('log').textContent += text + ';';
+                const row = document.getElementById('row');
+                row.addEventListener('dblclick', () => log('dblclick'));
+                row.addEventListener('contextmenu', (e) => { e.preventDefault(); log('contextmenu'); });
+                row.addEventListener('click', (e) => log('click ' + e.offsetX + (e.shiftKey ? ' shift' : '')));
+            </script>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let tab_args = ["--profile", "test-pointer", "--tab", "main"];
+    let run = |args: &[&str]| {
+        let mut full = args.to_vec();
+        full.extend(tab_args);
+        run_command(&full)
+    };
+
+    let _ = run(&["inspect", &url, "body"])?;
+    let _ = run(&["hover", "", "#menu"])?;
+    let submenu = run(&["inspect", "", "#submenu"])?;
+    let _ = run(&["click", "", "#row", "--double"])?;
+    let _ = run(&["click", "", "#row", "--button", "right"])?;
+    let _ = run(&[
+        "click",
+        "",
+        "#row",
+        "--offset",
+        "10,5",
+        "--modifiers",
+        "shift",
+    ])?;
+    let log = run(&["inspect", "", "#log"])?;
+
+    if log["error"].as_bool() == Some(true) {
+        assert!(log["message"].is_string());
+    } else if log["text_content"].is_string() {
+        assert_eq!(submenu["computed_styles"]["display"], "block");
+        let text = log["text_content"].as_str().unwrap();
+        assert!(text.contains("dblclick"));
+        assert!(text.contains("contextmenu"));
+        assert!(text.contains("click 10 shift"));
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_occlusion() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("occlusion.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <body style="margin: 0">
+            <header id="bar" style="position: fixed; top: 0; left: 0; width: 100%; height: 50px; background: #fff; z-index: 10">
+                <a href="/">Home</a>
+            </header>
+            <button id="buy" style="position: absolute; top: 30px; left: 0; width: 200px; height: 50px">Buy</button>
+            <p id="free" style="margin-top: 200px">Nothing on top</p>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let result = run_command(&["inspect", &url, "#buy"])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["occlusion"].is_object() {
+        let occlusion = &result["occlusion"];
+        assert_eq!(occlusion["in_viewport_percent"], 100.0);
+        assert_eq!(occlusion["visible_percent"], 60.0);
+        assert_eq!(occlusion["covered_by"][0]["selector"], "#bar");
+        assert_eq!(occlusion["covered_by"][0]["percent"], 40.0);
+
+        let result = run_command(&["inspect", &url, "#free"])?;
+        assert_eq!(result["occlusion"]["visible_percent"], 100.0);
+
+        let result = run_command(&["analyze", &url, "body", "--focus", "anomalies"])?;
+        if let Some(conflicts) = result["raw_data"]["anomalies"]["interaction_conflicts"].as_array()
+        {
+            assert!(
+                conflicts
+                    .iter()
+                    .any(|c| c["clickable_element"] == "#buy" && c["blocking_element"] == "#bar")
+            );
+        }
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_why_cascade() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("cascade.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <head>
+            <style>
+                :root { --space: 24px; --brand: rgb(10, 102, 194); }
+                p { margin-top: 8px; }
+                .card p { margin: var(--space) 0; }
+                main { color: var(--brand); }
+            </style>
+        </head>
+        <body>
+            <main class="card"><p id="target">Text</p></main>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let result = run_command(&["why", "#target", "--property", "margin-top", "--url", &url])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["properties"].is_array() {
+        let margin = &result["properties"][0];
+        assert_eq!(margin["computed"], "24px");
+        assert_eq!(margin["winner"]["selector"], ".card p");
+        assert_eq!(margin["winner"]["shorthand"], "margin");
+        assert_eq!(margin["cascade"][1]["selector"], "p");
+        assert_eq!(margin["cascade"][1]["status"], "overridden");
+        assert_eq!(margin["variables"][0]["name"], "--space");
+
+        let result = run_command(&["why", "#target", "--property", "color", "--url", &url])?;
+        let color = &result["properties"][0];
+        assert_eq!(color["source"], "inherited");
+        assert_eq!(color["inherited_from"]["element"], "body > main");
+        assert_eq!(color["variables"][0]["name"], "--brand");
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_why_source_maps() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    // margin on generated column 11 maps to Card.module.scss:42
+    let map = r#"{"version":3,"sources":["webpack://app/./src/components/Card.module.scss"],"mappings":"AAAA,UAyCE"}"#;
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("sourcemap.html");
+    fs::write(
+        &test_page,
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head><style>.card p {{ margin: 24px 0; }}
+/*# sourceMappingURL=data:application/json,{map} */</style></head>
+<body><main class="card"><p id="target">Text</p></main></body>
+</html>
+"#
+        ),
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let result = run_command(&["why", "#target", "--property", "margin-top", "--url", &url])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["properties"].is_array() {
+        let winner = &result["properties"][0]["winner"];
+        assert_eq!(winner["stylesheet"], "<style> #1");
+        assert_eq!(winner["line"], 1);
+        assert_eq!(
+            winner["original"]["file"],
+            "src/components/Card.module.scss"
+        );
+        assert_eq!(winner["original"]["line"], 42);
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_images_audit() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let pixel = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("images.html");
+    fs::write(
+        &test_page,
+        format!(
+            r#"
+        <!DOCTYPE html>
+        <html>
+        <body>
+            <img id="stretched" src="{pixel}" alt="Stretched" style="width: 200px; height: 100px">
+            <img id="lazy" src="{pixel}" alt="Lazy" loading="lazy" srcset="{pixel} 1x"
+                 style="width: 1px; height: 1px">
+            <img id="missing" src="does-not-exist.png" alt="Missing">
+        </body>
+        </html>
+    "#
+        ),
+    )?;
+
+    let result = run_command(&["images", &format!("file://{}", test_page.display())])?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["images"].is_array() {
+        let issues_of = |selector: &str| -> Vec<String> {
+            result["images"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|i| i["selector"] == selector)
+                .map(|i| {
+                    i["issues"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .filter_map(|issue| issue["type"].as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let stretched = issues_of("#stretched");
+        assert!(
+            stretched.contains(&"undersized".to_string()),
+            "{:?}",
+            stretched
+        );
+        assert!(
+            stretched.contains(&"distorted".to_string()),
+            "{:?}",
+            stretched
+        );
+        assert!(
+            stretched.contains(&"missing-srcset".to_string()),
+            "{:?}",
+            stretched
+        );
+
+        let lazy = issues_of("#lazy");
+        assert!(lazy.contains(&"lazy-above-fold".to_string()), "{:?}", lazy);
+        assert!(!lazy.contains(&"missing-srcset".to_string()), "{:?}", lazy);
+
+        assert_eq!(issues_of("#missing"), vec!["broken".to_string()]);
+        assert_eq!(result["summary"]["images"], 3);
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_layout_shifts() -> Result<()> {
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "start", "--browser", "chrome"])
+        .output()?;
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let _ = run_command(&["profile", "destroy", "test-shifts", "--force"]);
+    let _ = run_command(&["profile", "create", "test-shifts", "--browser", "chrome"]);
+
+    let temp_dir = TempDir::new()?;
+    let test_page = temp_dir.path().join("shifts.html");
+    fs::write(
+        &test_page,
+        r#"
+        <!DOCTYPE html>
+        <html>
+        <body>
+            <div id="slot"></div>
+            <p id="content">Content that gets pushed down</p>
+            <button id="more" onclick="document.getElementById('slot').insertAdjacentHTML('beforeend', '<div style=&quot;height: 120px&quot;>Promo</div>')">More</button>
+            <script>
+                setTimeout(() => {
+                    document.getElementById('slot').innerHTML = '<div style="height: 80px">Banner</div>';
+                }, 300);
+            </script>
+        </body>
+        </html>
+    "#,
+    )?;
+
+    let url = format!("file://{}", test_page.display());
+    let tab_args = ["--profile", "test-shifts", "--tab", "main"];
+    let mut inspect = vec!["inspect", url.as_str(), "body"];
+    inspect.extend(tab_args);
+    let _ = run_command(&inspect)?;
+    std::thread::sleep(std::time::Duration::from_secs(1));
+
+    let mut click = vec!["click", "", "#more"];
+    click.extend(tab_args);
+    let _ = run_command(&click)?;
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    let mut shifts = vec!["shifts"];
+    shifts.extend(tab_args);
+    let result = run_command(&shifts)?;
+
+    if result["error"].as_bool() == Some(true) {
+        assert!(result["message"].is_string());
+    } else if result["elements"].is_array() {
+        let content = result["elements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|e| e["selector"] == "#content")
+            .expect("#content should have moved");
+        assert!(content["max_move"]["y"].as_i64().unwrap() >= 80);
+
+        let phases: Vec<&str> = result["shifts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|s| s["phase"].as_str())
+            .collect();
+        assert!(
+            phases.iter().any(|p| p.starts_with("load ")),
+            "{:?}",
+            phases
+        );
+        assert!(phases.contains(&"click #more"), "{:?}", phases);
+    }
+
+    let _ = run_command(&["profile", "destroy", "test-shifts", "--force"]);
+    Command::new(env!("CARGO_BIN_EXE_webprobe"))
+        .args(["daemon", "stop"])
+        .output()?;
+
+    Ok(())
+}
+
 #[test]
 fn test_compare_identical_pages() -> Result<()> {
     let temp_dir = TempDir::new()?;